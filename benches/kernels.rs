@@ -0,0 +1,77 @@
+//! Compares the throughput of the vector kernels on every instruction set the CPU
+//! supports, through `dotc` and `axpy` on Complex32 and Complex64 vectors and
+//! `matrix_mult`, whose row updates run on the same kernels.
+//! Run with `cargo bench --bench kernels --features simd,std`, without `simd` only
+//! the scalar loops are measured.
+
+#[macro_use]
+extern crate criterion;
+extern crate lin_alg;
+extern crate num_complex;
+
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+use lin_alg::kernels::{is_supported, set_isa, Isa};
+use lin_alg::{axpy, dotc, matrix_mult, Matrix};
+use num_complex::{Complex, Complex32, Complex64};
+
+const LEVELS: [(Isa, &str); 4] = [(Isa::Scalar, "scalar"), (Isa::Sse2, "sse2"), (Isa::Avx2Fma, "avx2_fma"), (Isa::Neon, "neon")];
+
+fn filled(len: usize, seed: u64) -> Vec<Complex64> {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 11) as f64) / ((1u64 << 52) as f64) - 1.0
+    };
+
+    (0..len).map(|_| Complex64::new(next(), next())).collect()
+}
+
+fn narrow(x: &[Complex64]) -> Vec<Complex32> {
+    x.iter().map(|x| Complex32::new(x.re as f32, x.im as f32)).collect()
+}
+
+fn bench_vectors(c: &mut Criterion) {
+    for &len in [256, 4096].iter() {
+        let (x64, y64) = (filled(len, 1), filled(len, 2));
+        let (x32, y32) = (narrow(&x64), narrow(&y64));
+
+        let mut group = c.benchmark_group(format!("kernels_{}", len));
+        group.throughput(Throughput::Elements(len as u64));
+        for &(isa, name) in LEVELS.iter().filter(|&&(isa, _)| is_supported(isa)) {
+            set_isa(isa);
+
+            group.bench_function(BenchmarkId::new("dotc_c64", name), |b| b.iter(|| dotc(black_box(&x64), black_box(&y64)).unwrap()));
+            group.bench_function(BenchmarkId::new("dotc_c32", name), |b| b.iter(|| dotc(black_box(&x32), black_box(&y32)).unwrap()));
+
+            let mut y = y64.clone();
+            group.bench_function(BenchmarkId::new("axpy_c64", name), |b| {
+                b.iter(|| axpy(Complex::new(1e-3, -1e-3), black_box(&x64), &mut y).unwrap())
+            });
+            let mut y = y32.clone();
+            group.bench_function(BenchmarkId::new("axpy_c32", name), |b| {
+                b.iter(|| axpy(Complex::new(1e-3, -1e-3), black_box(&x32), &mut y).unwrap())
+            });
+        }
+        group.finish();
+    }
+}
+
+fn bench_matrix_mult(c: &mut Criterion) {
+    let n = 128;
+    let a = Matrix::from_vec(narrow(&filled(n * n, 3)), n, n).unwrap();
+    let b = Matrix::from_vec(narrow(&filled(n * n, 4)), n, n).unwrap();
+    let mut prod = Matrix::zeros(n, n);
+
+    let mut group = c.benchmark_group("kernels_matrix_mult_c32");
+    group.sample_size(10);
+    for &(isa, name) in LEVELS.iter().filter(|&&(isa, _)| is_supported(isa)) {
+        set_isa(isa);
+        group.bench_with_input(BenchmarkId::new(name, n), &n, |bencher, _| {
+            bencher.iter(|| matrix_mult(black_box(&a), black_box(&b), &mut prod).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_vectors, bench_matrix_mult);
+criterion_main!(benches);
@@ -0,0 +1,63 @@
+//! Compares `matrix_mult` with the textbook i-j-k triple loop, which strides down
+//! the columns of B, and with `gemm_3m`, which needs three real products instead of
+//! four, for square Complex64 matrices of 64 x 64 and larger.
+//! Run with `cargo bench --bench matrix_mult`.
+
+#[macro_use]
+extern crate criterion;
+extern crate lin_alg;
+extern crate num_complex;
+
+use criterion::{black_box, BenchmarkId, Criterion};
+use lin_alg::{gemm_3m, matrix_mult, Matrix, Op};
+use num_complex::Complex64;
+
+fn filled(n: usize, seed: u64) -> Matrix<Complex64> {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 11) as f64) / ((1u64 << 52) as f64) - 1.0
+    };
+
+    let a = (0..n * n).map(|_| Complex64::new(next(), next())).collect();
+    Matrix::from_vec(a, n, n).unwrap()
+}
+
+fn naive_mult(a: &Matrix<Complex64>, b: &Matrix<Complex64>, c: &mut Matrix<Complex64>) {
+    for i in 0..a.rows() {
+        for j in 0..b.cols() {
+            let mut sum = c[(i, j)];
+            for k in 0..a.cols() {
+                sum += a[(i, k)] * b[(k, j)];
+            }
+            c[(i, j)] = sum;
+        }
+    }
+}
+
+fn bench_matrix_mult(c: &mut Criterion) {
+    let mut group = c.benchmark_group("matrix_mult");
+    group.sample_size(10);
+
+    for &n in [64, 128, 256, 512].iter() {
+        let a = filled(n, 1);
+        let b = filled(n, 2);
+        let mut prod = Matrix::zeros(n, n);
+
+        group.bench_with_input(BenchmarkId::new("blocked", n), &n, |bencher, _| {
+            bencher.iter(|| matrix_mult(black_box(&a), black_box(&b), &mut prod).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("three_mult", n), &n, |bencher, _| {
+            let one = Complex64::new(1.0, 0.0);
+            bencher.iter(|| gemm_3m(one, Op::None, black_box(&a), Op::None, black_box(&b), one, &mut prod).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("naive", n), &n, |bencher, _| {
+            bencher.iter(|| naive_mult(black_box(&a), black_box(&b), &mut prod))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_matrix_mult);
+criterion_main!(benches);
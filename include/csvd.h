@@ -0,0 +1,69 @@
+/*
+ * C interface of lin_alg, see src/ffi.rs.
+ *
+ * Build the static library with
+ *     cargo rustc --release --features ffi,std --crate-type staticlib
+ *
+ * Matrices are m x n arrays in row-major order. Complex numbers are a real part followed
+ * by an imaginary part, so float _Complex and double _Complex arrays can be passed as well.
+ * All functions return CSVD_OK or one of the negative error codes.
+ */
+#ifndef CSVD_H
+#define CSVD_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct { float re; float im; } csvd_complex32;
+typedef struct { double re; double im; } csvd_complex64;
+
+#define CSVD_OK                              0
+#define CSVD_ERR_NULL_POINTER               -1
+#define CSVD_ERR_MISALIGNED                 -2
+#define CSVD_ERR_OVERLAP                    -3
+#define CSVD_ERR_TOO_LARGE                  -4
+#define CSVD_ERR_PANIC                      -5
+#define CSVD_ERR_EMPTY_MATRIX              -10
+#define CSVD_ERR_WIDE_MATRIX               -11
+#define CSVD_ERR_DIMENSION_MISMATCH        -12
+#define CSVD_ERR_BUFFER_TOO_SMALL          -13
+#define CSVD_ERR_INVALID_ARGUMENT          -14
+#define CSVD_ERR_INVALID_LEADING_DIMENSION -15
+#define CSVD_ERR_INVALID_ALIGNMENT         -16
+#define CSVD_ERR_NOT_CONVERGED             -17
+#define CSVD_ERR_SINGULAR                  -18
+#define CSVD_ERR_WORKSPACE_TOO_SMALL       -19
+#define CSVD_ERR_INVALID_DATA              -20
+#define CSVD_ERR_ALLOC_FAILED              -21
+#define CSVD_ERR_NON_FINITE_INPUT          -22
+#define CSVD_ERR_CANCELLED                 -23
+
+/* static, NUL-terminated description of an error code */
+const char *csvd_error_message(int32_t code);
+
+/* number of reals the scratch buffer of csvd_svd_c32/c64 needs, 3 min(m, n) */
+size_t csvd_scratch_len(size_t m, size_t n);
+
+/*
+ * A = U S V*, without allocating. a holds m n elements and is overwritten, s receives
+ * min(m, n) singular values, u the m x m and v the n x n singular vectors. No output may
+ * overlap another buffer.
+ */
+int32_t csvd_svd_c32(csvd_complex32 *a, size_t m, size_t n, float *s,
+                     csvd_complex32 *u, csvd_complex32 *v, float *scratch);
+int32_t csvd_svd_c64(csvd_complex64 *a, size_t m, size_t n, double *s,
+                     csvd_complex64 *u, csvd_complex64 *v, double *scratch);
+
+/* the n x m pseudo-inverse inv of the m x n matrix a, which is left unchanged */
+int32_t csvd_pinv_c32(const csvd_complex32 *a, size_t m, size_t n, csvd_complex32 *inv);
+int32_t csvd_pinv_c64(const csvd_complex64 *a, size_t m, size_t n, csvd_complex64 *inv);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,138 @@
+#!/usr/bin/env python3
+"""Writes the golden reference decompositions of src/test/golden_data.rs.
+
+    python3 scripts/golden.py             # references from LAPACK zgesvd, needs numpy and scipy
+    python3 scripts/golden.py --mpmath    # the same matrices as zgesvd, from a 50 digit SVD
+    python3 scripts/golden.py --analytic  # only the constructed cases, from their exact factors
+
+Every case is a matrix A with thin factors U (m x k), S (k) and V (n x k), k = min(m, n),
+stored row-major as (re, im) pairs. With --analytic the matrices are built as A = U S V*
+from unitary factors that are known exactly, so the references are the constructed factors.
+Otherwise the same matrices plus random ones are decomposed by zgesvd through
+scipy.linalg.svd(lapack_driver='gesvd'), or with --mpmath by mpmath.svd_c in 50 digit
+arithmetic and rounded to double, which needs nothing beyond mpmath.
+"""
+
+import cmath
+import math
+import random
+import sys
+
+OUT = "src/test/golden_data.rs"
+
+
+def matmul(a, b):
+    return [[sum(a[i][l] * b[l][j] for l in range(len(b))) for j in range(len(b[0]))] for i in range(len(a))]
+
+
+def conj_t(a):
+    return [[a[i][j].conjugate() for i in range(len(a))] for j in range(len(a[0]))]
+
+
+def columns(a, k):
+    return [row[:k] for row in a]
+
+
+def compose(u, s, v):
+    k = len(s)
+    us = [[u[i][j] * s[j] for j in range(k)] for i in range(len(u))]
+    return matmul(us, conj_t(v))
+
+
+r2 = 1 / math.sqrt(2)
+# exactly unitary factors
+ROT = [[0.6, 0.8j], [0.8j, 0.6]]
+HAD = [[r2, r2], [-r2, r2]]
+H4 = [[0.5, 0.5, 0.5, 0.5], [0.5, -0.5, 0.5, -0.5], [0.5, 0.5, -0.5, -0.5], [0.5, -0.5, -0.5, 0.5]]
+PH3 = [[0, 1j, 0], [0, 0, -1], [1, 0, 0]]
+F3 = [[cmath.exp(-2j * math.pi * j * k / 3) / math.sqrt(3) for k in range(3)] for j in range(3)]
+
+
+def analytic_cases():
+    """(name, U, S, V) with unitary U and V, only the first k columns are used"""
+    return [
+        ("permuted_phases_3x3", PH3, [5.0, 2.0, 1.0], [[1, 0, 0], [0, 1, 0], [0, 0, 1]]),
+        ("rotations_2x2", ROT, [3.0, 1.0], HAD),
+        ("tall_4x2", H4, [2.0, 0.5], ROT),
+        ("wide_2x4", ROT, [4.0, 0.25], H4),
+        ("rank_one_3x3", F3, [4.0, 0.0, 0.0], PH3),
+        ("repeated_3x3", F3, [2.0, 2.0, 1.0], [[1, 0, 0], [0, 1, 0], [0, 0, 1]]),
+    ]
+
+
+def constructed():
+    cases = []
+    for name, u, s, v in analytic_cases():
+        k = len(s)
+        u = [[complex(x) for x in row] for row in columns(u, k)]
+        v = [[complex(x) for x in row] for row in columns(v, k)]
+        cases.append((name, compose(u, s, v), u, s, v))
+    return cases
+
+
+def matrices():
+    """the constructed matrices plus random square, tall and wide ones"""
+    rng = random.Random(54)
+    cases = [(name, a) for name, a, _, _, _ in constructed()]
+    for m, n in [(5, 5), (8, 3), (3, 8), (12, 12)]:
+        cases.append(("random_%dx%d" % (m, n),
+                      [[complex(rng.uniform(-1, 1), rng.uniform(-1, 1)) for _ in range(n)] for _ in range(m)]))
+    return cases
+
+
+def lapack():
+    import numpy as np
+    from scipy.linalg import svd
+
+    cases = []
+    for name, a in matrices():
+        u, s, vh = svd(np.array(a, dtype=np.complex128), full_matrices=False, lapack_driver="gesvd")
+        cases.append((name, a, u.tolist(), s.tolist(), vh.conj().T.tolist()))
+    return cases
+
+
+def multiprecision():
+    import mpmath
+
+    mpmath.mp.dps = 50
+    cases = []
+    for name, a in matrices():
+        u, s, vh = mpmath.svd_c(mpmath.matrix(a), full_matrices=False)
+        k = len(s)
+        cases.append((name, a,
+                      [[complex(u[i, j]) for j in range(k)] for i in range(u.rows)],
+                      [float(s[j]) for j in range(k)],
+                      [[complex(mpmath.conj(vh[j, i])) for j in range(k)] for i in range(vh.cols)]))
+    return cases
+
+
+def fmt_complex(rows):
+    return ", ".join("[%r, %r]" % (complex(x).real, complex(x).imag) for row in rows for x in row)
+
+
+def main():
+    if "--analytic" in sys.argv:
+        flag, cases, source = " --analytic", constructed(), "analytic factors"
+    elif "--mpmath" in sys.argv:
+        flag, cases, source = " --mpmath", multiprecision(), "mpmath svd_c in 50 digits"
+    else:
+        flag, cases, source = "", lapack(), "LAPACK zgesvd"
+
+    with open(OUT, "w") as f:
+        f.write("// Generated by scripts/golden.py%s from %s, do not edit.\n\n" % (flag, source))
+        f.write("use super::golden::Case;\n\n")
+        f.write("pub(super) static CASES: &[Case] = &[\n")
+        for name, a, u, s, v in cases:
+            f.write("    Case {\n")
+            f.write("        name: \"%s\",\n" % name)
+            f.write("        m: %d,\n        n: %d,\n" % (len(a), len(a[0])))
+            f.write("        a: &[%s],\n" % fmt_complex(a))
+            f.write("        s: &[%s],\n" % ", ".join(repr(float(x)) for x in s))
+            f.write("        u: &[%s],\n" % fmt_complex(u))
+            f.write("        v: &[%s],\n" % fmt_complex(v))
+            f.write("    },\n")
+        f.write("];\n")
+
+
+if __name__ == "__main__":
+    main()
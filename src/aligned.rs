@@ -0,0 +1,323 @@
+//! Over-aligned buffers, e.g. for matrices whose rows SIMD kernels load with aligned
+//! instructions or that a DMA engine reads.
+//!
+//! A `Vec` always frees its buffer with the alignment of its element type, so it cannot
+//! own memory allocated with a larger alignment. `AlignedVec` keeps the layout it was
+//! allocated with and frees the buffer with exactly that layout.
+//!
+//! The buffers come from an `Alloc`, by default the global allocator. An OS can implement
+//! `Alloc` to place them, and so the matrices of a `BufferPool`, in DMA-capable or per-core
+//! memory. Together with `csvd_in_slices`, which decomposes matrices in caller-provided
+//! slices, a whole decomposition then runs in memory of its choice.
+
+use alloc::alloc::{alloc, alloc_zeroed, dealloc, Layout};
+use core::fmt;
+use core::mem::{self, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+use num_complex::Complex;
+use core::slice;
+use num_traits::Zero;
+use super::error::CsvdError;
+
+/// A source of memory blocks, a minimal stand-in for the unstable `core::alloc::Allocator`
+///
+/// # Safety
+///
+/// A block returned by `alloc` or `alloc_zeroed` has to be valid for reads and writes of
+/// layout.size() bytes, aligned to layout.align() and not be handed out again before it
+/// is passed to `dealloc`. `alloc_zeroed` has to return zeroed memory.
+pub unsafe trait Alloc {
+    /// A block for layout, whose size is never zero, or None if the memory is exhausted
+    fn alloc(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Same as `alloc`, with all bytes of the block set to zero
+    fn alloc_zeroed(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let ptr = self.alloc(layout)?;
+        unsafe { ptr::write_bytes(ptr.as_ptr(), 0, layout.size()) };
+        Some(ptr)
+    }
+
+    /// Frees a block that `alloc` or `alloc_zeroed` of this allocator returned for layout
+    ///
+    /// # Safety
+    ///
+    /// ptr has to come from this allocator with the same layout and must not be used afterwards.
+    unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+/// The global allocator of the program
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Global;
+
+unsafe impl Alloc for Global {
+    fn alloc(&self, layout: Layout) -> Option<NonNull<u8>> {
+        NonNull::new(unsafe { alloc(layout) })
+    }
+
+    fn alloc_zeroed(&self, layout: Layout) -> Option<NonNull<u8>> {
+        NonNull::new(unsafe { alloc_zeroed(layout) })
+    }
+
+    unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
+        dealloc(ptr.as_ptr(), layout)
+    }
+}
+
+/// Plain old data: Copy types without padding or pointers, for which all zero bytes are a
+/// valid value, so that buffers of them can come zeroed straight from the allocator.
+///
+/// # Safety
+///
+/// The all-zero bit pattern has to be a valid value of the type.
+pub unsafe trait Pod: Copy + 'static {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for u64 {}
+unsafe impl Pod for usize {}
+unsafe impl Pod for i8 {}
+unsafe impl Pod for i16 {}
+unsafe impl Pod for i32 {}
+unsafe impl Pod for i64 {}
+unsafe impl Pod for isize {}
+unsafe impl Pod for f32 {}
+unsafe impl Pod for f64 {}
+unsafe impl<T: Pod> Pod for Complex<T> {}
+
+/// len zeros aligned to `alignment` bytes, which can be any power of two such as 16 for
+/// SSE, 64 for a cache line or 4096 for a page. Like all constructors of `AlignedVec`,
+/// it returns `AllocFailed` instead of aborting when the heap is exhausted.
+pub fn aligned_vec<T: Pod>(len: usize, alignment: usize) -> Result<AlignedVec<T>, CsvdError> {
+    aligned_vec_in(len, alignment, Global)
+}
+
+/// Same as `aligned_vec`, with the memory from alloc
+pub fn aligned_vec_in<T: Pod, A: Alloc>(len: usize, alignment: usize, alloc: A) -> Result<AlignedVec<T, A>, CsvdError> {
+    let layout = layout::<T>(len, alignment)?;
+    let ptr = allocate(&alloc, layout, true)?;
+    // the zeroed memory already holds len valid elements
+    Ok(AlignedVec { ptr, len, layout, alloc })
+}
+
+/// Size and alignment of a memory page, the unit DMA engines map buffers in
+pub const PAGE_SIZE: usize = 4096;
+
+/// len zeros in page-aligned memory whose size is padded to a whole number of pages, for
+/// channel matrices that a NIC or SDR DMA engine writes and csvd reads without a copy.
+/// No other allocation shares the pages, and `capacity` counts the elements that fit into
+/// them, which are zero past len as well.
+pub fn dma_buffer<T: Pod>(len: usize) -> Result<AlignedVec<T>, CsvdError> {
+    dma_buffer_in(len, Global)
+}
+
+/// Same as `dma_buffer`, with the pages from alloc, e.g. an allocator of physically
+/// contiguous memory
+pub fn dma_buffer_in<T: Pod, A: Alloc>(len: usize, alloc: A) -> Result<AlignedVec<T, A>, CsvdError> {
+    let layout = layout::<T>(len, PAGE_SIZE)?.pad_to_align();
+    let ptr = allocate(&alloc, layout, true)?;
+    Ok(AlignedVec { ptr, len, layout, alloc })
+}
+
+/// len uninitialized elements aligned to `alignment` bytes, for buffers such as U, V or
+/// batched outputs that are overwritten right away, which saves the memory pass of zeroing.
+/// `AlignedVec::init_with` fills it safely, `AlignedVec::assume_init` unsafely.
+pub fn aligned_vec_uninit<T>(len: usize, alignment: usize) -> Result<AlignedVec<MaybeUninit<T>>, CsvdError> {
+    aligned_vec_uninit_in(len, alignment, Global)
+}
+
+/// Same as `aligned_vec_uninit`, with the memory from alloc
+pub fn aligned_vec_uninit_in<T, A: Alloc>(len: usize, alignment: usize, alloc: A)
+        -> Result<AlignedVec<MaybeUninit<T>, A>, CsvdError> {
+    let layout = layout::<T>(len, alignment)?;
+    let ptr = allocate(&alloc, layout, false)?;
+    // uninitialized memory is a valid MaybeUninit
+    Ok(AlignedVec { ptr, len, layout, alloc })
+}
+
+/// The layout of len elements with the given alignment, which has to be a power of two
+fn layout<T>(len: usize, alignment: usize) -> Result<Layout, CsvdError> {
+    if !alignment.is_power_of_two() {
+        return Err(CsvdError::InvalidAlignment { alignment });
+    }
+    let max = isize::MAX as usize / mem::size_of::<T>().max(1);
+    Layout::array::<T>(len)
+        .map_err(|_| CsvdError::InvalidArgument { name: "len", value: len, max })?
+        .align_to(alignment)
+        .map_err(|_| CsvdError::InvalidAlignment { alignment })
+}
+
+fn allocate<T, A: Alloc>(alloc: &A, layout: Layout, zeroed: bool) -> Result<NonNull<T>, CsvdError> {
+    if layout.size() == 0 {
+        // nothing to allocate, any non-null pointer with the alignment is valid
+        return NonNull::new(layout.align() as *mut T).ok_or(CsvdError::AllocFailed);
+    }
+    let ptr = if zeroed { alloc.alloc_zeroed(layout) } else { alloc.alloc(layout) };
+    ptr.map(NonNull::cast).ok_or(CsvdError::AllocFailed)
+}
+
+/// A fixed-length buffer of `len` elements whose first element is aligned to `alignment`
+/// bytes, in memory from the allocator A
+pub struct AlignedVec<T, A: Alloc = Global> {
+    ptr: NonNull<T>,
+    len: usize,
+    layout: Layout,
+    alloc: A,
+}
+
+// AlignedVec owns its elements like a Vec does
+unsafe impl<T: Send, A: Alloc + Send> Send for AlignedVec<T, A> {}
+unsafe impl<T: Sync, A: Alloc + Sync> Sync for AlignedVec<T, A> {}
+
+impl<T: Clone> AlignedVec<T> {
+    /// len copies of value, aligned to `alignment` bytes. The alignment has to be a power
+    /// of two, and alignments below the one of T are raised to it.
+    pub fn from_elem(value: T, len: usize, alignment: usize) -> Result<AlignedVec<T>, CsvdError> {
+        AlignedVec::from_elem_in(value, len, alignment, Global)
+    }
+}
+
+impl<T: Clone + Zero> AlignedVec<T> {
+    /// len zeros aligned to `alignment` bytes, see `from_elem`
+    pub fn zeroed(len: usize, alignment: usize) -> Result<AlignedVec<T>, CsvdError> {
+        AlignedVec::from_elem(T::zero(), len, alignment)
+    }
+}
+
+impl<T: Clone, A: Alloc> AlignedVec<T, A> {
+    /// Same as `from_elem`, with the memory from alloc
+    pub fn from_elem_in(value: T, len: usize, alignment: usize, alloc: A) -> Result<AlignedVec<T, A>, CsvdError> {
+        let layout = layout::<T>(len, alignment)?;
+        let ptr = allocate::<T, A>(&alloc, layout, false)?;
+
+        let mut v = AlignedVec { ptr, len: 0, layout, alloc };
+        for i in 0..len {
+            unsafe { ptr::write(ptr.as_ptr().add(i), value.clone()) };
+            // counted one at a time, so a panicking clone drops exactly the written elements
+            v.len = i + 1;
+        }
+        Ok(v)
+    }
+}
+
+impl<T: Clone + Zero, A: Alloc> AlignedVec<T, A> {
+    /// Same as `zeroed`, with the memory from alloc
+    pub fn zeroed_in(len: usize, alignment: usize, alloc: A) -> Result<AlignedVec<T, A>, CsvdError> {
+        AlignedVec::from_elem_in(T::zero(), len, alignment, alloc)
+    }
+}
+
+impl<T: Clone, A: Alloc + Clone> AlignedVec<T, A> {
+    /// Same as `clone`, but returns `AllocFailed` instead of aborting when the heap is exhausted
+    pub fn try_clone(&self) -> Result<AlignedVec<T, A>, CsvdError> {
+        let alloc = self.alloc.clone();
+        let ptr = allocate::<T, A>(&alloc, self.layout, false)?;
+        let mut v = AlignedVec { ptr, len: 0, layout: self.layout, alloc };
+        for (i, x) in self.iter().enumerate() {
+            unsafe { ptr::write(v.ptr.as_ptr().add(i), x.clone()) };
+            v.len = i + 1;
+        }
+        Ok(v)
+    }
+}
+
+impl<T, A: Alloc> AlignedVec<T, A> {
+    /// The alignment of the buffer in bytes
+    pub fn alignment(&self) -> usize {
+        self.layout.align()
+    }
+
+    /// Number of elements the allocation has room for, at least len. Only page-padded
+    /// buffers from `dma_buffer` have room for more.
+    pub fn capacity(&self) -> usize {
+        match mem::size_of::<T>() {
+            0 => usize::MAX,
+            size => self.len.max(self.layout.size() / size),
+        }
+    }
+
+    /// Size of the allocation in bytes
+    pub fn byte_capacity(&self) -> usize {
+        self.layout.size()
+    }
+
+    /// The allocator the buffer came from
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, A: Alloc> AlignedVec<MaybeUninit<T>, A> {
+    /// Writes f(i) to every element i and returns the initialized buffer
+    pub fn init_with<F: FnMut(usize) -> T>(mut self, mut f: F) -> AlignedVec<T, A> {
+        for (i, x) in self.iter_mut().enumerate() {
+            *x = MaybeUninit::new(f(i));
+        }
+        unsafe { self.assume_init() }
+    }
+
+    /// Reinterprets the buffer as initialized, without touching the elements
+    ///
+    /// # Safety
+    ///
+    /// Every element has to be initialized, e.g. through `as_mut_ptr` or `MaybeUninit::write`.
+    pub unsafe fn assume_init(self) -> AlignedVec<T, A> {
+        // MaybeUninit<T> has the layout of T, and the buffer and allocator now belong to the result
+        let this = mem::ManuallyDrop::new(self);
+        AlignedVec { ptr: this.ptr.cast::<T>(), len: this.len, layout: this.layout, alloc: ptr::read(&this.alloc) }
+    }
+}
+
+impl<T, A: Alloc> Deref for AlignedVec<T, A> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T, A: Alloc> DerefMut for AlignedVec<T, A> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T, A: Alloc> Drop for AlignedVec<T, A> {
+    fn drop(&mut self) {
+        unsafe {
+            if mem::needs_drop::<T>() {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len));
+            }
+            if self.layout.size() != 0 {
+                self.alloc.dealloc(self.ptr.cast(), self.layout);
+            }
+        }
+    }
+}
+
+impl<T: Clone, A: Alloc + Clone> Clone for AlignedVec<T, A> {
+    fn clone(&self) -> AlignedVec<T, A> {
+        self.try_clone().unwrap_or_else(|_| alloc::alloc::handle_alloc_error(self.layout))
+    }
+}
+
+impl<T: fmt::Debug, A: Alloc> fmt::Debug for AlignedVec<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq, A: Alloc, B: Alloc> PartialEq<AlignedVec<T, B>> for AlignedVec<T, A> {
+    fn eq(&self, other: &AlignedVec<T, B>) -> bool {
+        **self == **other
+    }
+}
@@ -0,0 +1,297 @@
+use alloc::vec::Vec;
+use num_complex::Complex;
+use super::csvd::csvd_in_slices;
+use super::error::CsvdError;
+use super::matrix::{try_filled, Matrix};
+use super::scalar::Real;
+use super::svd::SvdResult;
+use super::PinvOptions;
+
+/// Memory layout of a batch of equally sized matrices packed into one slice
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchLayout {
+    /// the row-major matrices follow each other, element (i, j) of matrix b
+    /// is at `b * rows * cols + i * cols + j`
+    Contiguous,
+    /// the same element of all matrices is stored next to each other, element (i, j)
+    /// of matrix b is at `(i * cols + j) * batch + b`, so one vector load fetches
+    /// that element of consecutive matrices
+    Interleaved,
+}
+
+impl BatchLayout {
+    /// Index of element e of the row-major matrix b of a batch of matrices with len elements each
+    #[inline]
+    fn index(self, b: usize, e: usize, len: usize, batch: usize) -> usize {
+        match self {
+            BatchLayout::Contiguous => b * len + e,
+            BatchLayout::Interleaved => e * batch + b,
+        }
+    }
+}
+
+/// Singular value decompositions of a batch of m x n matrices, e.g. the channel matrices
+/// of all subcarriers of a MIMO-OFDM slot, together with the scratch space they share.
+///
+/// The factors are packed in the layout of the input: the min(m, n) singular values,
+/// the m x m matrices U and the n x n matrices V of all matrices.
+/// Once created, `decompose` can be called for every slot without any allocation.
+/// With the `parallel` feature the matrices of a contiguous batch are decomposed on
+/// a rayon thread pool, which allocates scratch space once per thread.
+#[derive(Clone, Debug)]
+pub struct BatchedSvd<T> {
+    m: usize,
+    n: usize,
+    batch: usize,
+    layout: BatchLayout,
+    s: Vec<T>,
+    u: Vec<Complex<T>>,
+    v: Vec<Complex<T>>,
+    /// the work arrays B, C and T of csvd, shared by all matrices
+    scratch: Vec<T>,
+    /// one matrix gathered from an interleaved batch and its factors
+    a_tmp: Vec<Complex<T>>,
+    s_tmp: Vec<T>,
+    u_tmp: Vec<Complex<T>>,
+    v_tmp: Vec<Complex<T>>,
+}
+
+/// Decomposes the batch of m x n matrices packed into a with the given layout.
+/// a is overwritten.
+pub fn batched_svd<T: Real>(a: &mut [Complex<T>], m: usize, n: usize, batch: usize, layout: BatchLayout)
+        -> Result<BatchedSvd<T>, CsvdError> {
+    let mut res = BatchedSvd::new(m, n, batch, layout);
+    res.decompose(a)?;
+    Ok(res)
+}
+
+/// Computes the pseudo-inverses of the batch of m x n matrices packed into a with the given layout,
+/// writing the n x m inverses packed in the same layout into out. a is overwritten.
+/// The cut-off for zero singular values is the same as for `pinv`.
+pub fn batched_pinv<T: Real>(a: &mut [Complex<T>], out: &mut [Complex<T>], m: usize, n: usize, batch: usize,
+        layout: BatchLayout) -> Result<(), CsvdError> {
+    let res = batched_svd(a, m, n, batch, layout)?;
+    res.pinv_into(out, &PinvOptions::absolute(T::from(0.0001).unwrap()))
+}
+
+impl<T: Real> BatchedSvd<T> {
+    /// Allocates the factors and the scratch space for a batch of m x n matrices.
+    /// Panics if the heap is exhausted, see `try_new`.
+    pub fn new(m: usize, n: usize, batch: usize, layout: BatchLayout) -> BatchedSvd<T> {
+        match BatchedSvd::try_new(m, n, batch, layout) {
+            Ok(res) => res,
+            Err(e) => panic!("cannot allocate a batch of {} {} x {} decompositions: {}", batch, m, n, e),
+        }
+    }
+
+    /// Same as `new`, but returns `AllocFailed` instead of panicking when the heap is exhausted
+    pub fn try_new(m: usize, n: usize, batch: usize, layout: BatchLayout) -> Result<BatchedSvd<T>, CsvdError> {
+        let min = m.min(n);
+        let zero = Complex::new(T::zero(), T::zero());
+
+        // only an interleaved batch has to be gathered into contiguous buffers
+        let tmp = if layout == BatchLayout::Interleaved { 1 } else { 0 };
+
+        Ok(BatchedSvd {
+            m,
+            n,
+            batch,
+            layout,
+            s: try_filled(len(&[batch, min])?, T::zero())?,
+            u: try_filled(len(&[batch, m, m])?, zero)?,
+            v: try_filled(len(&[batch, n, n])?, zero)?,
+            scratch: try_filled(len(&[3, min])?, T::zero())?,
+            a_tmp: try_filled(tmp * m * n, zero)?,
+            s_tmp: try_filled(tmp * min, T::zero())?,
+            u_tmp: try_filled(tmp * m * m, zero)?,
+            v_tmp: try_filled(tmp * n * n, zero)?,
+        })
+    }
+
+    /// Number of rows of every matrix
+    pub fn m(&self) -> usize {
+        self.m
+    }
+
+    /// Number of columns of every matrix
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Number of matrices
+    pub fn batch(&self) -> usize {
+        self.batch
+    }
+
+    /// Layout of the input and of the packed factors
+    pub fn layout(&self) -> BatchLayout {
+        self.layout
+    }
+
+    /// The packed singular values, min(m, n) per matrix
+    pub fn s(&self) -> &[T] {
+        &self.s
+    }
+
+    /// The packed m x m matrices U
+    pub fn u(&self) -> &[Complex<T>] {
+        &self.u
+    }
+
+    /// The packed n x n matrices V
+    pub fn v(&self) -> &[Complex<T>] {
+        &self.v
+    }
+
+    /// Decomposes every matrix of the batch packed into a, overwriting a.
+    /// If a decomposition fails, the error is returned and the matrices after it are not decomposed.
+    pub fn decompose(&mut self, a: &mut [Complex<T>]) -> Result<(), CsvdError> {
+        let (m, n, batch) = (self.m, self.n, self.batch);
+        let (mn, mm, nn, min) = (m * n, m * m, n * n, m.min(n));
+
+        if a.len() < batch * mn {
+            return Err(CsvdError::BufferTooSmall { operand: "a", required: batch * mn, len: a.len() });
+        }
+
+        // the matrices of a contiguous batch are decomposed on a rayon thread pool,
+        // every thread with scratch space of its own
+        #[cfg(feature = "parallel")]
+        {
+            if self.layout == BatchLayout::Contiguous && batch > 1 && min > 0 {
+                use rayon::prelude::*;
+
+                return a[..batch * mn].par_chunks_mut(mn)
+                    .zip(self.s.par_chunks_mut(min))
+                    .zip(self.u.par_chunks_mut(mm))
+                    .zip(self.v.par_chunks_mut(nn))
+                    .try_for_each_init(|| alloc::vec![T::zero(); 3 * min], |scratch, (((a, s), u), v)| {
+                        csvd_in_slices(a, s, u, v, scratch, m, n)
+                    });
+            }
+        }
+
+        for b in 0..batch {
+            match self.layout {
+                BatchLayout::Contiguous => {
+                    csvd_in_slices(&mut a[b * mn..(b + 1) * mn], &mut self.s[b * min..(b + 1) * min],
+                        &mut self.u[b * mm..(b + 1) * mm], &mut self.v[b * nn..(b + 1) * nn],
+                        &mut self.scratch, m, n)?;
+                }
+                BatchLayout::Interleaved => {
+                    for e in 0..mn {
+                        self.a_tmp[e] = a[e * batch + b];
+                    }
+
+                    csvd_in_slices(&mut self.a_tmp, &mut self.s_tmp, &mut self.u_tmp, &mut self.v_tmp,
+                        &mut self.scratch, m, n)?;
+
+                    for k in 0..min {
+                        self.s[k * batch + b] = self.s_tmp[k];
+                    }
+                    for e in 0..mm {
+                        self.u[e * batch + b] = self.u_tmp[e];
+                    }
+                    for e in 0..nn {
+                        self.v[e * batch + b] = self.v_tmp[e];
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the pseudo-inverses INV = V x S+ x U* of all decomposed matrices into out,
+    /// as n x m matrices packed in the layout of the batch, without any allocation.
+    /// options decide which singular values are treated as zero.
+    pub fn pinv_into(&self, out: &mut [Complex<T>], options: &PinvOptions<T>) -> Result<(), CsvdError> {
+        let (m, n, batch) = (self.m, self.n, self.batch);
+        let layout = self.layout;
+
+        if out.len() < batch * n * m {
+            return Err(CsvdError::BufferTooSmall { operand: "out", required: batch * n * m, len: out.len() });
+        }
+
+        #[cfg(feature = "parallel")]
+        {
+            if layout == BatchLayout::Contiguous && batch > 1 && n * m > 0 {
+                use rayon::prelude::*;
+
+                out[..batch * n * m].par_chunks_mut(n * m).enumerate().for_each(|(b, out)| {
+                    let cutoff = self.pinv_cutoff(b, options);
+                    for i in 0..n {
+                        for j in 0..m {
+                            out[i * m + j] = self.pinv_entry(b, i, j, cutoff);
+                        }
+                    }
+                });
+
+                return Ok(());
+            }
+        }
+
+        for b in 0..batch {
+            let cutoff = self.pinv_cutoff(b, options);
+            for i in 0..n {
+                for j in 0..m {
+                    out[layout.index(b, i * m + j, n * m, batch)] = self.pinv_entry(b, i, j, cutoff);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The largest singular value of matrix b that options treat as zero
+    fn pinv_cutoff(&self, b: usize, options: &PinvOptions<T>) -> T {
+        let (m, n) = (self.m, self.n);
+        let min = m.min(n);
+
+        if min > 0 {
+            options.cutoff(self.s[self.layout.index(b, 0, min, self.batch)], m, n)
+        }
+        else {
+            T::zero()
+        }
+    }
+
+    /// Entry (i, j) of the pseudo-inverse of matrix b
+    fn pinv_entry(&self, b: usize, i: usize, j: usize, cutoff: T) -> Complex<T> {
+        let (m, n, batch) = (self.m, self.n, self.batch);
+        let min = m.min(n);
+        let layout = self.layout;
+
+        let mut sum = Complex::new(T::zero(), T::zero());
+        for k in 0..min {
+            let sk = self.s[layout.index(b, k, min, batch)];
+            if sk > cutoff {
+                let v = self.v[layout.index(b, i * n + k, n * n, batch)];
+                let u = self.u[layout.index(b, j * m + k, m * m, batch)];
+                sum = sum + v * (T::one() / sk) * u.conj();
+            }
+        }
+
+        sum
+    }
+
+    /// Unpacks the decomposition of matrix b of the batch
+    pub fn result(&self, b: usize) -> SvdResult<T> {
+        let (m, n, batch) = (self.m, self.n, self.batch);
+        let layout = self.layout;
+
+        let s = (0..m.min(n)).map(|k| self.s[layout.index(b, k, m.min(n), batch)]).collect();
+        let u = (0..m * m).map(|e| self.u[layout.index(b, e, m * m, batch)]).collect();
+        let v = (0..n * n).map(|e| self.v[layout.index(b, e, n * n, batch)]).collect();
+
+        SvdResult {
+            u: Matrix::from_vec(u, m, m).unwrap(),
+            s,
+            v: Matrix::from_vec(v, n, n).unwrap(),
+        }
+    }
+}
+
+/// The product of the factors, or `AllocFailed` if it overflows
+fn len(factors: &[usize]) -> Result<usize, CsvdError> {
+    factors.iter().try_fold(1usize, |len, &f| len.checked_mul(f)).ok_or(CsvdError::AllocFailed)
+}
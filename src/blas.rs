@@ -0,0 +1,413 @@
+use alloc::vec::Vec;
+use core::ops::{Index, IndexMut};
+use num_complex::Complex;
+use super::csvd::norm2;
+use super::error::CsvdError;
+use super::kernels;
+use super::matrix::{Layout, Matrix};
+use super::view::{MatrixView, MatrixViewMut};
+use super::scalar::Real;
+
+/// Rows and columns of the panels of B that `gemm` packs, 256 KiB of Complex64
+const GEMM_KC: usize = 128;
+const GEMM_NC: usize = 128;
+
+/// How `gemm` and `gemv` use an operand, as the TRANS arguments of BLAS
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    /// The matrix as it is
+    None,
+    /// The transpose
+    Transpose,
+    /// The conjugate transpose
+    ConjugateTranspose,
+}
+
+impl Op {
+    /// Shape of op(a) for an a of the given (rows, cols)
+    fn shape(self, (rows, cols): (usize, usize)) -> (usize, usize) {
+        match self {
+            Op::None => (rows, cols),
+            _ => (cols, rows),
+        }
+    }
+
+    /// Element (i, j) of op(a), for a matrix or a view
+    #[inline]
+    fn entry<T: Real, A: Index<(usize, usize), Output = Complex<T>>>(self, a: &A, i: usize, j: usize) -> Complex<T> {
+        match self {
+            Op::None => a[(i, j)],
+            Op::Transpose => a[(j, i)],
+            Op::ConjugateTranspose => a[(j, i)].conj(),
+        }
+    }
+}
+
+/// Computes C = alpha op_a(A) op_b(B) + beta C, where op(A) is m x k, op(B) is k x n and C is m x n.
+///
+/// The transposes are never formed, e.g. `gemm(one, Op::ConjugateTranspose, h, Op::None, h, zero, c)`
+/// computes the Gram matrix H* H straight from H. As in BLAS, C is not read when beta is zero,
+/// so it may start out holding anything.
+pub fn gemm<T: Real>(alpha: Complex<T>, op_a: Op, a: &Matrix<Complex<T>>, op_b: Op, b: &Matrix<Complex<T>>,
+                     beta: Complex<T>, c: &mut Matrix<Complex<T>>) -> Result<(), CsvdError> {
+    let (m, k, n) = gemm_shape(op_a, (a.rows(), a.cols()), op_b, (b.rows(), b.cols()), (c.rows(), c.cols()))?;
+    scale_c(beta, c, m, n);
+
+    if alpha == Complex::new(T::zero(), T::zero()) || k == 0 {
+        return Ok(());
+    }
+
+    // the blocked paths walk the rows of B and C, A is only read through Op::entry
+    if b.layout() != Layout::RowMajor || c.layout() != Layout::RowMajor {
+        gemm_entries(alpha, op_a, a, op_b, b, c);
+        return Ok(());
+    }
+    gemm_blocked(alpha, op_a, a, m, k, op_b, b.view(), c.view_mut());
+
+    Ok(())
+}
+
+/// Same as `gemm` on views, so that blocked algorithms can multiply blocks of larger
+/// matrices in place, e.g. C(0..4, 4..8) -= A(0..4, 0..2) B(0..2, 4..8)
+pub fn gemm_view<T: Real>(alpha: Complex<T>, op_a: Op, a: MatrixView<Complex<T>>, op_b: Op, b: MatrixView<Complex<T>>,
+                          beta: Complex<T>, mut c: MatrixViewMut<Complex<T>>) -> Result<(), CsvdError> {
+    let (m, k, n) = gemm_shape(op_a, (a.rows(), a.cols()), op_b, (b.rows(), b.cols()), (c.rows(), c.cols()))?;
+    scale_c(beta, &mut c, m, n);
+
+    if alpha == Complex::new(T::zero(), T::zero()) || k == 0 {
+        return Ok(());
+    }
+    gemm_blocked(alpha, op_a, &a, m, k, op_b, b, c);
+
+    Ok(())
+}
+
+/// Same as `gemm`, with the complex product formed from three real matrix products instead
+/// of four, as the gemm3m routines of vendor BLAS libraries do: with A = Ar + i Ai and
+/// B = Br + i Bi, Re(A B) = Ar Br - Ai Bi and Im(A B) = (Ar + Ai)(Br + Bi) - Ar Br - Ai Bi.
+///
+/// This saves a quarter of the multiplications, which pays off for large matrices, where the
+/// O(m k + k n) work of splitting the operands into their parts is negligible. The real part
+/// is as accurate as with `gemm`, but the imaginary part comes out of a cancellation, so its
+/// error is bounded by eps (|Ar| + |Ai|)(|Br| + |Bi|) rather than eps (|Ar| |Bi| + |Ai| |Br|).
+/// That only shows when the imaginary parts of the product are much smaller than the entries
+/// of the factors, e.g. for nearly real matrices; use `gemm` when those last bits matter.
+pub fn gemm_3m<T: Real>(alpha: Complex<T>, op_a: Op, a: &Matrix<Complex<T>>, op_b: Op, b: &Matrix<Complex<T>>,
+                        beta: Complex<T>, c: &mut Matrix<Complex<T>>) -> Result<(), CsvdError> {
+    let (m, k, n) = gemm_shape(op_a, (a.rows(), a.cols()), op_b, (b.rows(), b.cols()), (c.rows(), c.cols()))?;
+    scale_c(beta, c, m, n);
+
+    if alpha == Complex::new(T::zero(), T::zero()) || k == 0 {
+        return Ok(());
+    }
+
+    // the real part, the imaginary part and their sum of every entry of op(A) and op(B)
+    let split = |op: Op, x: &Matrix<Complex<T>>, rows: usize, cols: usize| {
+        let mut parts = [Vec::with_capacity(rows * cols), Vec::with_capacity(rows * cols), Vec::with_capacity(rows * cols)];
+        for i in 0..rows {
+            for j in 0..cols {
+                let e = op.entry(x, i, j);
+                parts[0].push(e.re);
+                parts[1].push(e.im);
+                parts[2].push(e.re + e.im);
+            }
+        }
+        parts
+    };
+    let a_parts = split(op_a, a, m, k);
+    let b_parts = split(op_b, b, k, n);
+
+    let mut t = [alloc::vec![T::zero(); m * n], alloc::vec![T::zero(); m * n], alloc::vec![T::zero(); m * n]];
+    for (t, (a, b)) in t.iter_mut().zip(a_parts.iter().zip(b_parts.iter())) {
+        gemm_real(k, n, a, b, t);
+    }
+
+    for i in 0..m {
+        for j in 0..n {
+            let (rr, ii, ss) = (t[0][i * n + j], t[1][i * n + j], t[2][i * n + j]);
+            c[(i, j)] = c[(i, j)] + alpha * Complex::new(rr - ii, ss - rr - ii);
+        }
+    }
+
+    Ok(())
+}
+
+/// C = A B for the tightly packed row-major real m x k A, k x n B and m x n C, where C starts
+/// out as zero. Every row of C is updated with whole rows of B, which the compiler vectorizes.
+fn gemm_real<T: Real>(k: usize, n: usize, a: &[T], b: &[T], c: &mut [T]) {
+    for (a, c) in a.chunks_exact(k).zip(c.chunks_exact_mut(n)) {
+        for (&x, b) in a.iter().zip(b.chunks_exact(n)) {
+            if x != T::zero() {
+                for (c, &b) in c.iter_mut().zip(b) {
+                    *c = *c + x * b;
+                }
+            }
+        }
+    }
+}
+
+/// The (m, k, n) of a product of op_a(A) and op_b(B) into C, given the shapes of A, B and C
+pub(crate) fn gemm_shape(op_a: Op, a: (usize, usize), op_b: Op, b: (usize, usize), c: (usize, usize)) -> Result<(usize, usize, usize), CsvdError> {
+    let (m, k) = op_a.shape(a);
+    let (k_b, n) = op_b.shape(b);
+
+    if k_b != k {
+        let expected = if op_b == Op::None { (k, n) } else { (n, k) };
+        return Err(CsvdError::DimensionMismatch { operand: "b", expected, found: b });
+    }
+    if c != (m, n) {
+        return Err(CsvdError::DimensionMismatch { operand: "c", expected: (m, n), found: c });
+    }
+    Ok((m, k, n))
+}
+
+/// C = beta C for the m x n matrix C, which is not read when beta is zero
+fn scale_c<T: Real, C: IndexMut<(usize, usize), Output = Complex<T>>>(beta: Complex<T>, c: &mut C, m: usize, n: usize) {
+    let zero = Complex::new(T::zero(), T::zero());
+    let one = Complex::new(T::one(), T::zero());
+
+    if beta == zero {
+        for i in 0..m {
+            for j in 0..n {
+                c[(i, j)] = zero;
+            }
+        }
+    }
+    else if beta != one {
+        for i in 0..m {
+            for j in 0..n {
+                c[(i, j)] = c[(i, j)] * beta;
+            }
+        }
+    }
+}
+
+/// C += alpha op_a(A) op_b(B) element by element, for column-major B or C
+fn gemm_entries<T: Real>(alpha: Complex<T>, op_a: Op, a: &Matrix<Complex<T>>, op_b: Op, b: &Matrix<Complex<T>>, c: &mut Matrix<Complex<T>>) {
+    let (m, k) = op_a.shape((a.rows(), a.cols()));
+    let n = c.cols();
+
+    for j in 0..n {
+        for i in 0..m {
+            let mut sum = Complex::new(T::zero(), T::zero());
+            for l in 0..k {
+                sum = sum + op_a.entry(a, i, l) * op_b.entry(b, l, j);
+            }
+            c[(i, j)] = c[(i, j)] + alpha * sum;
+        }
+    }
+}
+
+/// C += alpha op_a(A) op_b(B) for the m x k op_a(A) and row-major B and C
+fn gemm_blocked<T: Real, A: Index<(usize, usize), Output = Complex<T>> + Sync>(alpha: Complex<T>, op_a: Op, a: &A, m: usize, k: usize,
+        op_b: Op, b: MatrixView<Complex<T>>, c: MatrixViewMut<Complex<T>>) {
+    match op_b {
+        Op::None => gemm_panels(alpha, op_a, a, m, k, b, c),
+        _ => gemm_dots(alpha, op_a, a, m, k, op_b, b, c),
+    }
+}
+
+/// C += alpha op_a(A) B.
+/// C(i, j0..j1) += alpha op_a(A)(i, k) B(k, j0..j1) walks the rows of B and C contiguously.
+/// B is processed in panels of GEMM_KC x GEMM_NC, packed into one contiguous buffer that stays
+/// in cache while every row of op_a(A) is applied to it. The panels of k are taken in order,
+/// so every C(i, j) still sums over k in order and blocking doesn't change the result.
+fn gemm_panels<T: Real, A: Index<(usize, usize), Output = Complex<T>> + Sync>(alpha: Complex<T>, op_a: Op, a: &A, m: usize, k: usize,
+        b: MatrixView<Complex<T>>, mut c: MatrixViewMut<Complex<T>>) {
+    let n = b.cols();
+
+    let zero = Complex::new(T::zero(), T::zero());
+    let mut panel = Vec::with_capacity(GEMM_KC.min(k) * GEMM_NC.min(n));
+
+    for j0 in (0..n).step_by(GEMM_NC) {
+        let nc = GEMM_NC.min(n - j0);
+
+        for k0 in (0..k).step_by(GEMM_KC) {
+            let kc = GEMM_KC.min(k - k0);
+
+            panel.clear();
+            panel.resize(kc * nc, zero);
+            for l in 0..kc {
+                panel[l * nc..(l + 1) * nc].copy_from_slice(&b.row(k0 + l)[j0..j0 + nc]);
+            }
+
+            let mult_row = |i: usize, row: &mut [Complex<T>]| {
+                for l in 0..kc {
+                    kernels::axpy(alpha * op_a.entry(a, i, k0 + l), &panel[l * nc..(l + 1) * nc], &mut row[j0..j0 + nc]);
+                }
+            };
+
+            // the rows of C are independent, so large products are split over threads by rows
+            #[cfg(feature = "parallel")]
+            {
+                if m * n >= super::PARALLEL_MIN_ELEMENTS {
+                    use rayon::prelude::*;
+
+                    let ldc = c.row_stride();
+                    c.storage_mut().par_chunks_mut(ldc).take(m).enumerate().for_each(|(i, row)| mult_row(i, row));
+                    continue;
+                }
+            }
+
+            for i in 0..m {
+                mult_row(i, c.row_mut(i));
+            }
+        }
+    }
+}
+
+/// C += alpha op_a(A) op_b(B) for a transposed B, whose columns are the contiguous rows of B.
+/// Row i of op_a(A) is gathered once, then every C(i, j) is an inner product with row j of B.
+fn gemm_dots<T: Real, A: Index<(usize, usize), Output = Complex<T>> + Sync>(alpha: Complex<T>, op_a: Op, a: &A, m: usize, k: usize,
+        op_b: Op, b: MatrixView<Complex<T>>, mut c: MatrixViewMut<Complex<T>>) {
+    let n = b.rows();
+
+    // sum_l x(l) B(j, l) = dot_conj(conj(x), B(j, :)) and sum_l x(l) conj(B(j, l)) = conj(dot_conj(x, B(j, :)))
+    let conj_b = op_b == Op::ConjugateTranspose;
+
+    let mult_row = |i: usize, x: &mut Vec<Complex<T>>, row: &mut [Complex<T>]| {
+        x.clear();
+        x.extend((0..k).map(|l| {
+            let e = op_a.entry(a, i, l);
+            if conj_b { e } else { e.conj() }
+        }));
+
+        for j in 0..n {
+            let d = kernels::dot_conj(x, b.row(j));
+            row[j] = row[j] + alpha * if conj_b { d.conj() } else { d };
+        }
+    };
+
+    #[cfg(feature = "parallel")]
+    {
+        if m * n >= super::PARALLEL_MIN_ELEMENTS {
+            use rayon::prelude::*;
+
+            let ldc = c.row_stride();
+            c.storage_mut().par_chunks_mut(ldc).take(m).enumerate()
+                .for_each_init(|| Vec::with_capacity(k), |x, (i, row)| mult_row(i, x, row));
+            return;
+        }
+    }
+
+    let mut x = Vec::with_capacity(k);
+    for i in 0..m {
+        mult_row(i, &mut x, c.row_mut(i));
+    }
+}
+
+/// Computes y = alpha op_a(A) x + beta y, where op(A) is m x n, x has n and y has m elements.
+/// As in BLAS, y is not read when beta is zero.
+pub fn gemv<T: Real>(alpha: Complex<T>, op_a: Op, a: &Matrix<Complex<T>>, x: &[Complex<T>],
+                     beta: Complex<T>, y: &mut [Complex<T>]) -> Result<(), CsvdError> {
+    let zero = Complex::new(T::zero(), T::zero());
+    let (m, n) = op_a.shape((a.rows(), a.cols()));
+
+    if x.len() != n {
+        return Err(CsvdError::DimensionMismatch { operand: "x", expected: (n, 1), found: (x.len(), 1) });
+    }
+    if y.len() != m {
+        return Err(CsvdError::DimensionMismatch { operand: "y", expected: (m, 1), found: (y.len(), 1) });
+    }
+
+    if beta == zero {
+        for y in y.iter_mut() {
+            *y = zero;
+        }
+    }
+    else {
+        scal(beta, y);
+    }
+
+    if alpha == zero {
+        return Ok(());
+    }
+
+    if a.layout() != Layout::RowMajor {
+        for (i, y) in y.iter_mut().enumerate() {
+            let mut sum = Complex::new(T::zero(), T::zero());
+            for (l, &x) in x.iter().enumerate() {
+                sum = sum + op_a.entry(a, i, l) * x;
+            }
+            *y = *y + alpha * sum;
+        }
+        return Ok(());
+    }
+    match op_a {
+        Op::None => {
+            for (i, y) in y.iter_mut().enumerate() {
+                *y = *y + alpha * dotu_unchecked(a.row(i), x);
+            }
+        }
+        Op::Transpose => {
+            // y += sum_i (alpha x(i)) A(i, :) runs along the rows of A
+            for (i, &x) in x.iter().enumerate() {
+                kernels::axpy(alpha * x, a.row(i), y);
+            }
+        }
+        Op::ConjugateTranspose => {
+            // conj(y) += sum_i conj(alpha x(i)) A(i, :), so the rows of A are used as they are
+            conjugate(y);
+            for (i, &x) in x.iter().enumerate() {
+                kernels::axpy((alpha * x).conj(), a.row(i), y);
+            }
+            conjugate(y);
+        }
+    }
+
+    Ok(())
+}
+
+/// Computes y = y + alpha x
+pub fn axpy<T: Real>(alpha: Complex<T>, x: &[Complex<T>], y: &mut [Complex<T>]) -> Result<(), CsvdError> {
+    if x.len() != y.len() {
+        return Err(CsvdError::DimensionMismatch { operand: "y", expected: (x.len(), 1), found: (y.len(), 1) });
+    }
+
+    kernels::axpy(alpha, x, y);
+    Ok(())
+}
+
+/// The inner product x* y = sum(conj(x[i]) * y[i]), conjugating the first vector
+pub fn dotc<T: Real>(x: &[Complex<T>], y: &[Complex<T>]) -> Result<Complex<T>, CsvdError> {
+    if x.len() != y.len() {
+        return Err(CsvdError::DimensionMismatch { operand: "y", expected: (x.len(), 1), found: (y.len(), 1) });
+    }
+
+    Ok(kernels::dot_conj(x, y))
+}
+
+/// The unconjugated product x^T y = sum(x[i] * y[i])
+pub fn dotu<T: Real>(x: &[Complex<T>], y: &[Complex<T>]) -> Result<Complex<T>, CsvdError> {
+    if x.len() != y.len() {
+        return Err(CsvdError::DimensionMismatch { operand: "y", expected: (x.len(), 1), found: (y.len(), 1) });
+    }
+
+    Ok(dotu_unchecked(x, y))
+}
+
+fn dotu_unchecked<T: Real>(x: &[Complex<T>], y: &[Complex<T>]) -> Complex<T> {
+    let mut sum = Complex::new(T::zero(), T::zero());
+    for (x, y) in x.iter().zip(y.iter()) {
+        sum = sum + x * y;
+    }
+    sum
+}
+
+/// Computes x = alpha x
+pub fn scal<T: Real>(alpha: Complex<T>, x: &mut [Complex<T>]) {
+    for x in x.iter_mut() {
+        *x = *x * alpha;
+    }
+}
+
+/// The Euclidean norm of x, computed without overflow or underflow as LAPACK's dznrm2
+pub fn nrm2<T: Real>(x: &[Complex<T>]) -> T {
+    norm2(x.iter().cloned())
+}
+
+fn conjugate<T: Real>(x: &mut [Complex<T>]) {
+    for x in x.iter_mut() {
+        *x = x.conj();
+    }
+}
@@ -0,0 +1,227 @@
+//! A compact binary encoding of matrices and decompositions that works without `std`,
+//! e.g. to send equalizer weights from an SDR node to another core over a byte pipe.
+//!
+//! Every message starts with an 8 byte header: the magic `CSVD`, the format version,
+//! the kind of the payload, the width in bytes of a real number (4 for f32, 8 for f64)
+//! and a reserved zero byte. All integers are u32 and all numbers little-endian.
+//! A matrix is its row and column count followed by its elements row by row, every
+//! element as the real part followed by the imaginary part. A decomposition is the
+//! number of singular values and the values themselves, followed by the matrices U and V.
+//! Encoding writes into a caller-provided buffer, whose required size `encoded_len_*` returns.
+//! The sizes are checked, so a matrix too large to encode is reported instead of wrapping
+//! the length on a 32-bit target.
+
+use alloc::vec::Vec;
+use num_complex::Complex;
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::scalar::Real;
+use super::svd::SvdResult;
+
+const MAGIC: &[u8; 4] = b"CSVD";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 8;
+
+const KIND_MATRIX: u8 = 1;
+const KIND_SVD: u8 = 2;
+
+/// Number of bytes `encode_matrix` writes for a, None if that does not fit into a usize
+pub fn encoded_len_matrix<T: Real>(a: &Matrix<Complex<T>>) -> Option<usize> {
+    matrix_len::<T>(a)?.checked_add(HEADER_LEN)
+}
+
+/// Number of bytes `encode_svd` writes for res, None if that does not fit into a usize
+pub fn encoded_len_svd<T: Real>(res: &SvdResult<T>) -> Option<usize> {
+    res.s.len().checked_mul(width::<T>())?
+        .checked_add(matrix_len::<T>(&res.u)?)?
+        .checked_add(matrix_len::<T>(&res.v)?)?
+        .checked_add(HEADER_LEN + 4)
+}
+
+/// Encodes a, e.g. a pseudo-inverse, into out and returns the number of bytes written
+pub fn encode_matrix<T: Real>(a: &Matrix<Complex<T>>, out: &mut [u8]) -> Result<usize, CsvdError> {
+    let mut w = Writer::new(out, encoded_len_matrix(a))?;
+    w.header::<T>(KIND_MATRIX);
+    w.matrix(a)?;
+    Ok(w.pos)
+}
+
+/// Encodes the factors S, U and V of res into out and returns the number of bytes written
+pub fn encode_svd<T: Real>(res: &SvdResult<T>, out: &mut [u8]) -> Result<usize, CsvdError> {
+    let mut w = Writer::new(out, encoded_len_svd(res))?;
+    w.header::<T>(KIND_SVD);
+    w.u32(res.s.len())?;
+    for &s in &res.s {
+        w.real(s);
+    }
+    w.matrix(&res.u)?;
+    w.matrix(&res.v)?;
+    Ok(w.pos)
+}
+
+/// Decodes a matrix from the start of bytes, returning it and the number of bytes read.
+/// Numbers encoded with a different width than T are converted.
+pub fn decode_matrix<T: Real>(bytes: &[u8]) -> Result<(Matrix<Complex<T>>, usize), CsvdError> {
+    let mut r = Reader::new(bytes, KIND_MATRIX)?;
+    let a = r.matrix()?;
+    Ok((a, r.pos))
+}
+
+/// Decodes a decomposition from the start of bytes, returning it and the number of bytes read.
+/// Only full decompositions, as `svd` returns them, are accepted: square U and V and
+/// min(m, n) singular values.
+pub fn decode_svd<T: Real>(bytes: &[u8]) -> Result<(SvdResult<T>, usize), CsvdError> {
+    let mut r = Reader::new(bytes, KIND_SVD)?;
+
+    let k = r.u32()?;
+    r.check(k.checked_mul(r.width).ok_or(CsvdError::InvalidData { reason: "too many singular values" })?)?;
+    let s: Vec<T> = (0..k).map(|_| r.real()).collect();
+    let u = r.matrix()?;
+    let v = r.matrix()?;
+
+    // the methods of SvdResult index U and V for every singular value
+    if u.rows() != u.cols() || v.rows() != v.cols() || s.len() != u.rows().min(v.rows()) {
+        return Err(CsvdError::InvalidData { reason: "inconsistent factor shapes" });
+    }
+
+    Ok((SvdResult { u, s, v }, r.pos))
+}
+
+fn width<T>() -> usize {
+    core::mem::size_of::<T>()
+}
+
+fn matrix_len<T>(a: &Matrix<Complex<T>>) -> Option<usize> {
+    a.rows().checked_mul(a.cols())?.checked_mul(2 * width::<T>())?.checked_add(8)
+}
+
+struct Writer<'a> {
+    out: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> Writer<'a> {
+    /// A writer into out, which has to hold the required bytes, None for more than a usize
+    fn new(out: &'a mut [u8], required: Option<usize>) -> Result<Writer<'a>, CsvdError> {
+        let required = required.unwrap_or(usize::MAX);
+        if out.len() < required {
+            return Err(CsvdError::BufferTooSmall { operand: "out", required, len: out.len() });
+        }
+        Ok(Writer { out, pos: 0 })
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.out[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+    }
+
+    fn header<T>(&mut self, kind: u8) {
+        self.bytes(MAGIC);
+        self.bytes(&[VERSION, kind, width::<T>() as u8, 0]);
+    }
+
+    fn u32(&mut self, x: usize) -> Result<(), CsvdError> {
+        if x > u32::MAX as usize {
+            return Err(CsvdError::InvalidArgument { name: "dimension", value: x, max: u32::MAX as usize });
+        }
+        self.bytes(&(x as u32).to_le_bytes());
+        Ok(())
+    }
+
+    fn real<T: Real>(&mut self, x: T) {
+        if width::<T>() == 4 {
+            self.bytes(&x.to_f32().unwrap().to_le_bytes());
+        }
+        else {
+            self.bytes(&x.to_f64().unwrap().to_le_bytes());
+        }
+    }
+
+    fn matrix<T: Real>(&mut self, a: &Matrix<Complex<T>>) -> Result<(), CsvdError> {
+        self.u32(a.rows())?;
+        self.u32(a.cols())?;
+        for i in 0..a.rows() {
+            for j in 0..a.cols() {
+                let x = a[(i, j)];
+                self.real(x.re);
+                self.real(x.im);
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    width: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], kind: u8) -> Result<Reader<'a>, CsvdError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(CsvdError::InvalidData { reason: "truncated header" });
+        }
+        if &bytes[..4] != MAGIC {
+            return Err(CsvdError::InvalidData { reason: "missing magic" });
+        }
+        if bytes[4] != VERSION {
+            return Err(CsvdError::InvalidData { reason: "unknown version" });
+        }
+        if bytes[5] != kind {
+            return Err(CsvdError::InvalidData { reason: "unexpected kind of payload" });
+        }
+        let width = bytes[6] as usize;
+        if width != 4 && width != 8 {
+            return Err(CsvdError::InvalidData { reason: "invalid number width" });
+        }
+
+        Ok(Reader { bytes, pos: HEADER_LEN, width })
+    }
+
+    /// Makes sure that len more bytes are available
+    fn check(&self, len: usize) -> Result<(), CsvdError> {
+        if self.bytes.len() - self.pos < len {
+            return Err(CsvdError::InvalidData { reason: "truncated payload" });
+        }
+        Ok(())
+    }
+
+    fn u32(&mut self) -> Result<usize, CsvdError> {
+        self.check(4)?;
+        let b = &self.bytes[self.pos..self.pos + 4];
+        self.pos += 4;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    }
+
+    /// Reads one number, check() has to make sure it is there
+    fn real<T: Real>(&mut self) -> T {
+        let b = &self.bytes[self.pos..self.pos + self.width];
+        self.pos += self.width;
+
+        let x = if self.width == 4 {
+            f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64
+        }
+        else {
+            f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+        };
+        T::from(x).unwrap()
+    }
+
+    fn matrix<T: Real>(&mut self) -> Result<Matrix<Complex<T>>, CsvdError> {
+        let rows = self.u32()?;
+        let cols = self.u32()?;
+        // checked before allocating, so a corrupted size can't request huge amounts of memory
+        let count = rows.checked_mul(cols).ok_or(CsvdError::InvalidData { reason: "matrix too large" })?;
+        self.check(count.checked_mul(2 * self.width).ok_or(CsvdError::InvalidData { reason: "matrix too large" })?)?;
+
+        let mut data = Vec::with_capacity(count);
+        for _ in 0..count {
+            let re = self.real();
+            let im = self.real();
+            data.push(Complex::new(re, im));
+        }
+
+        Ok(Matrix::from_vec(data, rows, cols).unwrap())
+    }
+}
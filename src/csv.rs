@@ -0,0 +1,179 @@
+//! Reading and writing complex matrices as comma-separated text, one matrix row per line.
+//!
+//! Elements are either written as complex literals such as `1.5-2e-3j`, as Python and
+//! numpy.savetxt print them, or split over two columns holding the real and imaginary part.
+//! Empty lines and lines starting with `#` are skipped when reading.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use num_complex::Complex;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use super::matrix::Matrix;
+use super::scalar::Real;
+
+/// How the elements of a row are laid out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvFormat {
+    /// One column per element, e.g. `1+2j,-0.5j,3`. Parentheses and `i` instead of `j`
+    /// are accepted when reading.
+    Complex,
+    /// Two columns per element, the real part followed by the imaginary part
+    Pairs,
+}
+
+/// Errors of reading and writing CSV files, lines and columns count from 1
+#[derive(Debug)]
+pub enum CsvError {
+    /// The underlying reader or writer failed
+    Io(io::Error),
+    /// A field is not a number or complex literal
+    Parse { line: usize, column: usize },
+    /// A row has a different number of elements than the first one
+    RaggedRow { line: usize, expected: usize, found: usize },
+    /// A row of the `Pairs` format has an odd number of columns
+    OddColumns { line: usize },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CsvError::Io(ref e) => write!(f, "{}", e),
+            CsvError::Parse { line, column } => write!(f, "line {}, column {} is not a number", line, column),
+            CsvError::RaggedRow { line, expected, found } =>
+                write!(f, "line {} has {} elements, expected {}", line, found, expected),
+            CsvError::OddColumns { line } => write!(f, "line {} has an odd number of columns", line),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl From<io::Error> for CsvError {
+    fn from(e: io::Error) -> CsvError {
+        CsvError::Io(e)
+    }
+}
+
+/// Reads a complex matrix from the CSV file at path
+pub fn load_csv<T: Real, P: AsRef<Path>>(path: P, format: CsvFormat) -> Result<Matrix<Complex<T>>, CsvError> {
+    from_csv(BufReader::new(File::open(path)?), format)
+}
+
+/// Writes a to the CSV file at path
+pub fn save_csv<T: Real, P: AsRef<Path>>(path: P, a: &Matrix<Complex<T>>, format: CsvFormat) -> Result<(), CsvError> {
+    let mut w = BufWriter::new(File::create(path)?);
+    to_csv(&mut w, a, format)?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Reads a complex matrix in CSV format from r, every row needs the same number of elements
+pub fn from_csv<T: Real, R: BufRead>(r: R, format: CsvFormat) -> Result<Matrix<Complex<T>>, CsvError> {
+    let mut data = Vec::new();
+    let mut rows = 0;
+    let mut cols = 0;
+
+    for (idx, line) in r.lines().enumerate() {
+        let line = line?;
+        let line_no = idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        let start = data.len();
+        match format {
+            CsvFormat::Complex => {
+                for (col, field) in fields.iter().enumerate() {
+                    data.push(parse_complex(field).ok_or(CsvError::Parse { line: line_no, column: col + 1 })?);
+                }
+            }
+            CsvFormat::Pairs => {
+                if !fields.len().is_multiple_of(2) {
+                    return Err(CsvError::OddColumns { line: line_no });
+                }
+                for (col, pair) in fields.chunks(2).enumerate() {
+                    let re = parse_real(pair[0]).ok_or(CsvError::Parse { line: line_no, column: 2 * col + 1 })?;
+                    let im = parse_real(pair[1]).ok_or(CsvError::Parse { line: line_no, column: 2 * col + 2 })?;
+                    data.push(Complex::new(re, im));
+                }
+            }
+        }
+
+        let found = data.len() - start;
+        if rows == 0 {
+            cols = found;
+        }
+        else if found != cols {
+            return Err(CsvError::RaggedRow { line: line_no, expected: cols, found });
+        }
+        rows += 1;
+    }
+
+    Ok(Matrix::from_vec(data, rows, cols).unwrap())
+}
+
+/// Writes a in CSV format to w, with the shortest decimal representation that reads back exactly
+pub fn to_csv<T: Real, W: Write>(mut w: W, a: &Matrix<Complex<T>>, format: CsvFormat) -> Result<(), CsvError> {
+    let mut line = String::new();
+
+    for i in 0..a.rows() {
+        line.clear();
+        for j in 0..a.cols() {
+            let x = &a[(i, j)];
+            if j > 0 {
+                line.push(',');
+            }
+            match format {
+                CsvFormat::Complex => {
+                    let sign = if x.im.is_sign_negative() { '-' } else { '+' };
+                    line.push_str(&alloc::format!("{:?}{}{:?}j", x.re, sign, x.im.abs()));
+                }
+                CsvFormat::Pairs => line.push_str(&alloc::format!("{:?},{:?}", x.re, x.im)),
+            }
+        }
+        line.push('\n');
+        w.write_all(line.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn parse_real<T: Real>(field: &str) -> Option<T> {
+    field.parse::<f64>().ok().and_then(T::from)
+}
+
+/// Parses a complex literal such as `1`, `-2.5j`, `1e-3+4j` or `(3-4i)`
+fn parse_complex<T: Real>(field: &str) -> Option<Complex<T>> {
+    let s = field.trim_start_matches('(').trim_end_matches(')').trim();
+    let zero = T::zero();
+
+    if !(s.ends_with('j') || s.ends_with('i')) {
+        return parse_real(s).map(|re| Complex::new(re, zero));
+    }
+    let s = &s[..s.len() - 1];
+
+    // the sign that separates the parts is the last one that neither starts the
+    // literal nor belongs to an exponent
+    let bytes = s.as_bytes();
+    let split = (1..bytes.len()).rev()
+        .find(|&k| (bytes[k] == b'+' || bytes[k] == b'-') && bytes[k - 1] != b'e' && bytes[k - 1] != b'E');
+
+    match split {
+        Some(k) => Some(Complex::new(parse_real(&s[..k])?, parse_imaginary(&s[k..])?)),
+        None => Some(Complex::new(zero, parse_imaginary(s)?)),
+    }
+}
+
+/// The coefficient of an imaginary part, where a lone sign as in `1-j` means one
+fn parse_imaginary<T: Real>(s: &str) -> Option<T> {
+    match s {
+        "" | "+" => Some(T::one()),
+        "-" => Some(-T::one()),
+        _ => parse_real(s),
+    }
+}
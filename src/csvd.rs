@@ -1,182 +1,812 @@
 use alloc::vec::Vec;
+use core::ops::{ControlFlow, Neg};
+use num_complex::Complex;
+use super::error::CsvdError;
+use super::kernels::{axpy, dot_conj, dot_conj_compensated, CompensatedSum};
+use super::matrix::{Layout, Matrix};
+use super::view::MatrixViewMut;
+use super::scalar::Real;
+use super::svd4::svd4_in_slices;
+
+fn sqrt<T: Real>(input: T) -> T {
+    input.sqrt()
+}
+
+fn abs<T: Real>(input: T) -> T {
+    input.abs()
+}
+
+/// |re + i im| computed as hypot(re, im), so the intermediate squares
+/// neither overflow for huge entries nor underflow for tiny ones
+pub(crate) fn cabs<T: Real>(input: &Complex<T>) -> T {
+    input.re.hypot(input.im)
+}
+
+/// Euclidean norm of a sequence of complex numbers.
+/// The sum of squares is accumulated as scale^2 * ssq with the largest magnitude
+/// seen so far as scale (as in LAPACK's classq), so it neither overflows nor underflows.
+pub(crate) fn norm2<T: Real, I: Iterator<Item = Complex<T>>>(xs: I) -> T {
+    norm2_real(xs.flat_map(|x| [x.re, x.im]))
+}
+
+/// Euclidean norm of a sequence of real numbers, computed as `norm2`
+pub(crate) fn norm2_real<T: Real, I: Iterator<Item = T>>(xs: I) -> T {
+    let zero = T::zero();
+    let one = T::one();
+    let mut scale = zero;
+    let mut ssq = one;
+
+    for x in xs {
+        if x != zero {
+            let absxi = abs(x);
+            if scale < absxi {
+                ssq = one + ssq * (scale / absxi) * (scale / absxi);
+                scale = absxi;
+            }
+            else {
+                ssq = ssq + (absxi / scale) * (absxi / scale);
+            }
+        }
+    }
+
+    scale * sqrt(ssq)
+}
+
+/// `norm2` with the sum of squares accumulated with compensated summation
+pub(crate) fn norm2_compensated<T: Real, I: Iterator<Item = Complex<T>>>(xs: I) -> T {
+    let zero = T::zero();
+    let mut scale = zero;
+    let mut ssq = CompensatedSum::new();
+    ssq.add(T::one());
+
+    for x in xs.flat_map(|x| [x.re, x.im]) {
+        if x != zero {
+            let absxi = abs(x);
+            if scale < absxi {
+                ssq.scale((scale / absxi) * (scale / absxi));
+                ssq.add(T::one());
+                scale = absxi;
+            }
+            else {
+                ssq.add((absxi / scale) * (absxi / scale));
+            }
+        }
+    }
+
+    scale * sqrt(ssq.value())
+}
+
+/// The inner product of rows K, ..., M-1 of columns K and J, sum(conj(A(I,K)) * A(I,J))
+fn column_dot<T: Real>(a: &[Complex<T>], lda: usize, k: usize, m: usize, j: usize, summation: Summation) -> Complex<T> {
+    match summation {
+        Summation::Plain => {
+            let mut q = Complex::new(T::zero(), T::zero());
+            for i in k..m {
+                q = q + a[i*lda + k].conj() * a[i*lda + j];
+            }
+            q
+        }
+        Summation::Compensated => dot_conj_compensated((k..m).map(|i| (a[i*lda + k], a[i*lda + j]))),
+    }
+}
+
+/// Applies the Householder reflection that eliminates A(I,K), I = K+1, ..., M
+/// to the columns K+1, ..., COLS-1 of A, rows K, ..., M-1.
+/// The reflection vector is column K with norm z, w is the magnitude of its first entry.
+fn reflect_columns<T: Real>(a: &mut [Complex<T>], lda: usize, k: usize, m: usize, cols: usize, z: T, w: T,
+        summation: Summation) {
+    #[cfg(feature = "parallel")]
+    {
+        if (m - k) * (cols - k - 1) >= super::PARALLEL_MIN_ELEMENTS {
+            return reflect_columns_parallel(a, lda, k, m, cols, z, w, summation);
+        }
+    }
+
+    for j in k + 1..cols {
+        let mut q = column_dot(a, lda, k, m, j, summation);
+        q = q / z / ( z + w );
+
+        for i in k..m {
+            a[i*lda + j] = a[i*lda + j] - q * a[i*lda + k];
+        }
+    }
+}
+
+/// `reflect_columns` with the inner products computed per column and the update done per row,
+/// both spread over threads. The results are the same as those of the serial loop.
+#[cfg(feature = "parallel")]
+fn reflect_columns_parallel<T: Real>(a: &mut [Complex<T>], lda: usize, k: usize, m: usize, cols: usize, z: T, w: T,
+        summation: Summation) {
+    use rayon::prelude::*;
+    use std::vec::Vec;
+
+    let q: Vec<Complex<T>> = {
+        let a = &*a;
+        (k + 1..cols).into_par_iter().map(|j| {
+            column_dot(a, lda, k, m, j, summation) / z / ( z + w )
+        }).collect()
+    };
+
+    a[k*lda..].par_chunks_mut(lda).take(m - k).for_each(|row| {
+        let aik = row[k];
+        for j in k + 1..cols {
+            row[j] = row[j] - q[j - k - 1] * aik;
+        }
+    });
+}
+
+/// Applies the Householder reflection that eliminates A(K,J), J = K+2, ..., N
+/// to the rows K+1, ..., M-1 of A, columns K+1, ..., N-1.
+/// The reflection vector is row K from column K+1 on with norm z, w is the magnitude of its first entry.
+fn reflect_rows<T: Real>(a: &mut [Complex<T>], lda: usize, k: usize, m: usize, n: usize, z: T, w: T,
+        summation: Summation) {
+    let k1 = k + 1;
+    let (head, tail) = a.split_at_mut(k1 * lda);
+    let row_k = &head[k*lda..];
+
+    let reflect = |row: &mut [Complex<T>]| {
+        let q = summation.dot_conj(&row_k[k1..n], &row[k1..n]) / z / (z + w);
+
+        axpy(-q, &row_k[k1..n], &mut row[k1..n]);
+    };
+
+    // every row is updated independently
+    #[cfg(feature = "parallel")]
+    {
+        if (m - k1) * (n - k1) >= super::PARALLEL_MIN_ELEMENTS {
+            use rayon::prelude::*;
+            tail.par_chunks_mut(lda).take(m - k1).for_each(reflect);
+            return;
+        }
+    }
+
+    for row in tail.chunks_mut(lda).take(m - k1) {
+        reflect(row);
+    }
+}
+
+/// How the norms and inner products of the Householder reduction and the back
+/// transformation are accumulated
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Summation {
+    /// Plain floating-point sums, vectorized with the `simd` feature
+    #[default]
+    Plain,
+    /// Neumaier's compensated summation, as accurate as a sum in twice the precision.
+    /// The reductions run about four times slower and are never vectorized, which makes
+    /// the whole decomposition roughly twice as slow. Worth it for `Complex32` matrices
+    /// of large dimension or condition number, where the plain sums of the reflections
+    /// lose several of the 7 digits of `f32`.
+    Compensated,
+}
+
+impl Summation {
+    /// sum(conj(x[i]) * y[i]) over the common length of x and y
+    fn dot_conj<T: Real>(self, x: &[Complex<T>], y: &[Complex<T>]) -> Complex<T> {
+        match self {
+            Summation::Plain => dot_conj(x, y),
+            Summation::Compensated => dot_conj_compensated(x.iter().cloned().zip(y.iter().cloned())),
+        }
+    }
+
+    fn norm2<T: Real, I: Iterator<Item = Complex<T>>>(self, xs: I) -> T {
+        match self {
+            Summation::Plain => norm2(xs),
+            Summation::Compensated => norm2_compensated(xs),
+        }
+    }
+}
+
+/// Tuning parameters of the decomposition
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SvdOptions {
+    /// Maximum number of QR sweeps spent on a single singular value
+    /// before the decomposition gives up with `CsvdError::NotConverged`
+    pub max_iterations: usize,
+    /// Accumulation of the reductions, `Summation::Plain` by default
+    pub summation: Summation,
+    /// Scan A for NaN and infinite entries first and return `CsvdError::NonFiniteInput`,
+    /// true by default. A non-finite entry spreads through the whole decomposition and
+    /// keeps the QR sweeps from converging, so turn the scan off only for input that is
+    /// known to be finite.
+    pub check_finite: bool,
+}
+
+impl Default for SvdOptions {
+    fn default() -> SvdOptions {
+        SvdOptions { max_iterations: 75, summation: Summation::Plain, check_finite: true }
+    }
+}
+
+/// What the QR diagonalization of a decomposition went through, from `csvd_with_stats`
+/// or `svd_with_stats`. The same events are logged at the `trace` level as they happen
+/// and summed up at the `debug` level, under the `lin_alg::csvd` target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SvdStats<T> {
+    /// QR sweeps over all singular values
+    pub sweeps: usize,
+    /// the most QR sweeps spent on a single singular value, which
+    /// `SvdOptions::max_iterations` limits
+    pub max_sweeps: usize,
+    /// how often the bidiagonal split into independent blocks at a negligible
+    /// superdiagonal element before the singular values of a block had converged
+    pub splits: usize,
+    /// how often a superdiagonal element was cancelled next to a negligible diagonal
+    /// element, once for every zero singular value in exact arithmetic
+    pub cancellations: usize,
+    /// the magnitude below which elements of the bidiagonal counted as zero,
+    /// in the units of A
+    pub tolerance: T,
+    /// the relative residual ||A - U S V*|| / ||A|| in the Frobenius norm, only
+    /// computed by `svd_with_stats`, which has all of A, U and V at hand
+    pub residual: Option<T>,
+}
+
+impl<T: Real> SvdStats<T> {
+    fn new() -> SvdStats<T> {
+        SvdStats { sweeps: 0, max_sweeps: 0, splits: 0, cancellations: 0, tolerance: T::zero(), residual: None }
+    }
+}
+
+/// Where a decomposition stands, passed to the callback of `csvd_with_progress` before
+/// every QR sweep
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// position in the bidiagonal of the singular value the sweep works on, counting down
+    pub index: usize,
+    /// number of singular values that have converged
+    pub converged: usize,
+    /// number of singular values in total, min(m, n)
+    pub total: usize,
+    /// QR sweeps done so far
+    pub sweeps: usize,
+}
+
+/// The callback the QR sweeps report their progress to
+pub(crate) type ProgressFn<'a> = dyn FnMut(Progress) -> ControlFlow<()> + 'a;
+
+/// The callback of all decompositions that do not ask for progress
+pub(crate) fn no_progress(_: Progress) -> ControlFlow<()> {
+    ControlFlow::Continue(())
+}
 
 /// CSVD computes the singular value decomposition of an M by N complex matrix.
 ///
 /// Discussion:
 ///
-///    This routine requires that N <= M.
+///  Matrices with N > M are handled by decomposing the conjugate transpose
+///  A* = U' S V'*, so that U = V' and V = U'.  In that case A is left untouched
+///  and P must be 0.
 ///
-///    The singular value decomposition of a complex M by N matrix A
-///    has the form
+///  A, U and V can each be stored row-major or column-major (see `Layout`).
+///  A wide column-major A is conjugated in place, which turns its storage into the
+///  tall row-major A*, and decomposed without a copy. A tall one is copied once,
+///  as a wide row-major one is. Column-major U and V are filled from row-major copies.
 ///
-///      A = U S V*
+///  The singular value decomposition of a complex M by N matrix A
+///  has the form
 ///
-///    where 
+/// ```text
+///   A = U S V*
+/// ```
 ///
-///      U is an M by M unitary matrix,
-///      S is an M by N diagonal matrix,
-///      V is an N by N unitary matrix.
+///  where 
 ///
-///    Moreover, the entries of S are nonnegative and occur on the diagonal
-///    in descending order.
+///  U is an M by M unitary matrix,
+///  S is an M by N diagonal matrix,
+///  V is an N by N unitary matrix.
 ///
-///    Several internal arrays are dimensioned under the assumption
-///    that N <= 100.
-
+///  Moreover, the entries of S are nonnegative and occur on the diagonal
+///  in descending order.
 ///
-///  Reference:
+///  The phases of the singular vectors follow a fixed convention: the first entry
+///  of every column of U whose magnitude exceeds the machine epsilon is real and
+///  positive, and the matching column of V carries the same phase. Without U the
+///  convention applies to the columns of V. So the factors are reproducible across
+///  runs and, for distinct singular values, unique.
 ///
-///    Peter Businger, Gene Golub,
-///    Algorithm 358:
-///    Singular Value Decomposition of a Complex Matrix,
-///    Communications of the ACM,
-///    Volume 12, Number 10, October 1969, pages 564-565.
+///  The work arrays B, C and T are allocated on the heap with N elements
+///  each, so there is no limit on N other than available memory.
+///  `SvdWorkspace` and `csvd_with_workspace` avoid these allocations.
 ///
-///  Parameters:
+///  The routine is generic over the real scalar type `T` (see `Real`),
+///  so `Complex<f32>` and `Complex<f64>` matrices share one implementation.
 ///
-///   Input/output, complex A(MMAX,*), the M by N matrix, which may be
-///    augmented by P extra columns to which the transformation U*
-///    is to be applied.  On output, A has been overwritten, and
-///    if 0 < P, columns N+1 through N+P have been premultiplied by U*.
+/// Reference:
 ///
-///    Input, integer MMAX, the leading dimension of the arrays A
-///    and U.
+///  Peter Businger, Gene Golub,
+///  Algorithm 358:
+///  Singular Value Decomposition of a Complex Matrix,
+///  Communications of the ACM,
+///  Volume 12, Number 10, October 1969, pages 564-565.
 ///
-///    Input, integer NMAX, the leading dimension of V, and perhaps
-///    the second dimension of A and U.
+/// Parameters:
 ///
-///    Input, integer M, N, the number of rows and columns in A.
-///    It must be the case that 1 <= N <= M.  Several internal arrays are
-///    dimensioned under the assumption that N <= NBIG, where NBIG
-///    is an internal parameter, currently set to 100.
+///  Input/output, complex A, the M by N matrix, which may be
+///  augmented by P extra columns to which the transformation U*
+///  is to be applied.  On output, A has been overwritten, and
+///  if 0 < P, columns N+1 through N+P have been premultiplied by U*.
+///  M and N are taken from the dimensions of A, so A has M rows and N+P columns.
+///  It must be the case that 1 <= N and 1 <= M.
 ///
-///    Input, integer P, the number of vectors, stored in A(*,N+1:N+P),
+///  Input, integer P, the number of vectors, stored in A(*,N+1:N+P),
 ///  to which the transformation U* should be applied.
 ///
-///    Input, integer NU, the number of columns of U to compute.
+///  Input, integer NU, the number of columns of U to compute.
 ///
-///    Input, integer NV, the number of columns of V to compute.
+///  Input, integer NV, the number of columns of V to compute.
 ///
-///    Output, real S(N), the computed singular values.
+///  Output, real S(MIN(M,N)), the computed singular values, S must hold at least MIN(M,N) elements.
 ///
-///    Output, complex U(MMAX,NU), the first NU columns of U.
+///  Output, complex U(M,NU), the first NU columns of U.
+///  U must have M rows and at least max(NU, MIN(M,N)) columns if 0 < NU.
 ///
-///    Output, complex V(NMAX,NV), the first NV columns of V.
+///  Output, complex V(N,NV), the first NV columns of V.
+///  V must have N rows and at least max(NV, MIN(M,N)) columns if 0 < NV.
 ///
-///  Local Parameters:
+/// Local Parameters:
 ///
-///    Local, real ETA, the relative machine precision.
-///    The original text uses ETA = 1.5E-8.
+///  Local, real ETA, the relative machine precision.
+///  The original text uses ETA = 1.5E-8, here it is `T::epsilon()`.
 ///
-///    Local, integer NBIG, is a parameter used to dimension work arrays.
-///    The size of NBIG limits the maximum possible size of N that can
-///    be handled.  If you want to work with values of N that are larger,
-///    simply increase the value assigned to NBIG below.
+///  Local, real TOL, the smallest normalized positive number, divided by ETA.
+///  The original test uses TOL = 1.E-31.
 ///
-///    Local, real TOL, the smallest normalized positive number, divided by ETA.
-///    The original test uses TOL = 1.E-31.
+///  The QR diagonalization spends at most `SvdOptions::max_iterations` sweeps
+///  on each singular value and returns `CsvdError::NotConverged` after that.
+///  A NaN or infinite entry of A is reported as `CsvdError::NonFiniteInput`
+///  before any work is done, see `csvd_unchecked` to skip that scan.
+pub fn csvd<T: Real>(a: &mut Matrix<Complex<T>>, p: usize, nu: usize, nv: usize,
+        s: &mut [T], u: &mut Matrix<Complex<T>>, v: &mut Matrix<Complex<T>>) 
+        -> Result<(), CsvdError> {
+    csvd_with_options(a, p, nu, nv, s, u, v, &SvdOptions::default())
+}
 
+/// Same as `csvd`, without the scan of A for NaN and infinite entries, for callers that
+/// guarantee finite input. A non-finite entry gives meaningless results or
+/// `CsvdError::NotConverged` instead of `CsvdError::NonFiniteInput`.
+pub fn csvd_unchecked<T: Real>(a: &mut Matrix<Complex<T>>, p: usize, nu: usize, nv: usize,
+        s: &mut [T], u: &mut Matrix<Complex<T>>, v: &mut Matrix<Complex<T>>)
+        -> Result<(), CsvdError> {
+    csvd_with_options(a, p, nu, nv, s, u, v, &SvdOptions { check_finite: false, ..SvdOptions::default() })
+}
 
-use num_complex::Complex32;
-use libm::F32Ext;
+/// Same as `csvd`, with the tuning parameters given explicitly.
+/// A 4 x 4 matrix with both factors requested in full and no extra columns takes the
+/// unrolled kernel of `svd4` instead, which leaves a untouched and always checks it for
+/// NaN and infinite entries.
+pub fn csvd_with_options<T: Real>(a: &mut Matrix<Complex<T>>, p: usize, nu: usize, nv: usize,
+        s: &mut [T], u: &mut Matrix<Complex<T>>, v: &mut Matrix<Complex<T>>, options: &SvdOptions) 
+        -> Result<(), CsvdError> {
+    let square4 = |x: &Matrix<Complex<T>>| x.rows() == 4 && x.cols() == 4;
+    if p == 0 && nu == 4 && nv == 4 && square4(a) && square4(u) && square4(v) && s.len() >= 4 {
+        return csvd4(a, s, u, v);
+    }
+    csvd_with_stats(a, p, nu, nv, s, u, v, options).map(|_| ())
+}
 
-const NBIG: usize = 150;
+/// The decomposition of a 4 x 4 `Matrix` in any layout with `svd4_in_slices`
+fn csvd4<T: Real>(a: &Matrix<Complex<T>>, s: &mut [T], u: &mut Matrix<Complex<T>>, v: &mut Matrix<Complex<T>>)
+        -> Result<(), CsvdError> {
+    let zero = Complex::new(T::zero(), T::zero());
+    let (mut a4, mut u4, mut v4) = ([zero; 16], [zero; 16], [zero; 16]);
+    for i in 0..4 {
+        for j in 0..4 {
+            a4[i*4 + j] = a[(i, j)];
+        }
+    }
+
+    svd4_in_slices(&a4, s, &mut u4, &mut v4)?;
+
+    for i in 0..4 {
+        for j in 0..4 {
+            u[(i, j)] = u4[i*4 + j];
+            v[(i, j)] = v4[i*4 + j];
+        }
+    }
+    Ok(())
+}
+
+/// Same as `csvd_with_options`, returning what the QR diagonalization went through
+pub fn csvd_with_stats<T: Real>(a: &mut Matrix<Complex<T>>, p: usize, nu: usize, nv: usize,
+        s: &mut [T], u: &mut Matrix<Complex<T>>, v: &mut Matrix<Complex<T>>, options: &SvdOptions)
+        -> Result<SvdStats<T>, CsvdError> {
+    csvd_matrix(a, p, nu, nv, s, u, v, options, &mut no_progress)
+}
+
+/// Same as `csvd_with_options`, calling progress before every QR sweep. Returning
+/// `ControlFlow::Break` from it stops the decomposition with `CsvdError::Cancelled`,
+/// e.g. when a real-time task is about to miss its deadline. The sweeps are the only
+/// part of the decomposition whose length depends on the values of A, the Householder
+/// reduction before them always takes O(m n^2) operations.
+pub fn csvd_with_progress<T: Real, F: FnMut(Progress) -> ControlFlow<()>>(a: &mut Matrix<Complex<T>>, p: usize, nu: usize,
+        nv: usize, s: &mut [T], u: &mut Matrix<Complex<T>>, v: &mut Matrix<Complex<T>>, options: &SvdOptions,
+        mut progress: F) -> Result<(), CsvdError> {
+    csvd_matrix(a, p, nu, nv, s, u, v, options, &mut progress).map(|_| ())
+}
+
+/// The decomposition of a `Matrix` in any layout, see `csvd_with_options`
+fn csvd_matrix<T: Real>(a: &mut Matrix<Complex<T>>, p: usize, nu: usize, nv: usize,
+        s: &mut [T], u: &mut Matrix<Complex<T>>, v: &mut Matrix<Complex<T>>, options: &SvdOptions,
+        progress: &mut ProgressFn) -> Result<SvdStats<T>, CsvdError> {
+    // U and V are computed row-major and copied into column-major outputs
+    let mut u_rows = (u.layout() == Layout::ColMajor).then(|| Matrix::zeros(u.rows(), u.cols()));
+    let mut v_rows = (v.layout() == Layout::ColMajor).then(|| Matrix::zeros(v.rows(), v.cols()));
+    let stats;
+    {
+        let u_view = match u_rows { Some(ref mut u_rows) => u_rows.view_mut(), None => u.view_mut() };
+        let v_view = match v_rows { Some(ref mut v_rows) => v_rows.view_mut(), None => v.view_mut() };
+        let (m, cols) = (a.rows(), a.cols());
+
+        stats = if a.layout() == Layout::RowMajor {
+            csvd_view_stats(a.view_mut(), p, nu, nv, s, u_view, v_view, options, progress)?
+        }
+        else if p == 0 && m < cols {
+            // read row by row, the storage of A is A^T, and after conjugating it A*,
+            // which is tall and decomposes as V S U*, so the roles of U and V are swapped
+            for j in 0..cols {
+                for x in a.col_mut(j) {
+                    *x = x.conj();
+                }
+            }
+            let ld = a.ld();
+            let a_h = MatrixViewMut::new(a.as_mut_slice(), cols, m, ld)?;
+            let (mut u_view, mut v_view) = (u_view, v_view);
+            let stats = csvd_view_stats(a_h, 0, nv, nu, s, v_view.reborrow(), u_view.reborrow(), options, progress).map_err(swap_uv)?;
+
+            // the phases were normalized for the U of A*, which is the V of A
+            let (ldu, ldv) = (u_view.row_stride(), v_view.row_stride());
+            normalize_phases(u_view.storage_mut(), ldu, m, nu, v_view.storage_mut(), ldv, cols, nv);
+            stats
+        }
+        else {
+            let mut a_rows = a.to_layout(Layout::RowMajor);
+            let stats = csvd_view_stats(a_rows.view_mut(), p, nu, nv, s, u_view, v_view, options, progress)?;
+            // U* B goes back into the extra columns of A
+            for i in 0..m {
+                for j in cols - p..cols {
+                    a[(i, j)] = a_rows[(i, j)];
+                }
+            }
+            stats
+        };
+    }
 
-fn sqrt(input: f32) -> f32 {
-    F32Ext::sqrt(input)
+    for (out, rows) in [(u, u_rows), (v, v_rows)] {
+        if let Some(rows) = rows {
+            for i in 0..rows.rows() {
+                for j in 0..rows.cols() {
+                    out[(i, j)] = rows[(i, j)];
+                }
+            }
+        }
+    }
+    Ok(stats)
 }
 
-fn powf(input: f32, power: f32) -> f32 {
-    F32Ext::powf(input, power)
+/// The first entry of the M by COLS row-major matrix A that is NaN or infinite, as an error
+pub(crate) fn check_finite<T: Real>(a: &[Complex<T>], lda: usize, m: usize, cols: usize) -> Result<(), CsvdError> {
+    for i in 0..m {
+        if let Some(j) = a[i*lda..i*lda + cols].iter().position(|x| !(x.re.is_finite() && x.im.is_finite())) {
+            return Err(CsvdError::NonFiniteInput { row: i, col: j });
+        }
+    }
+    Ok(())
 }
 
-fn abs(input: f32) -> f32 {
-    F32Ext::abs(input)
+/// Names the errors of a decomposition of A* after the U and V of A
+pub(crate) fn swap_uv(e: CsvdError) -> CsvdError {
+    let swap = |name: &'static str| match name {
+        "U" => "V",
+        "V" => "U",
+        "NU" => "NV",
+        "NV" => "NU",
+        name => name,
+    };
+    match e {
+        CsvdError::DimensionMismatch { operand, expected, found } =>
+            CsvdError::DimensionMismatch { operand: swap(operand), expected, found },
+        CsvdError::InvalidArgument { name, value, max } => CsvdError::InvalidArgument { name: swap(name), value, max },
+        CsvdError::NonFiniteInput { row, col } => CsvdError::NonFiniteInput { row: col, col: row },
+        e => e,
+    }
 }
 
-fn cabs(input: &Complex32) -> f32{
-    let a = powf(input.re, 2.0);
-    let b = powf(input.im, 2.0); 
-    sqrt(a + b)
+/// Same as `csvd_with_options` on borrowed, strided matrices, e.g. a block of a larger
+/// matrix or a DMA buffer with padded rows. Only the elements inside the views of A, U
+/// and V are read or written.
+pub fn csvd_view<T: Real>(a: MatrixViewMut<Complex<T>>, p: usize, nu: usize, nv: usize,
+        s: &mut [T], u: MatrixViewMut<Complex<T>>, v: MatrixViewMut<Complex<T>>, options: &SvdOptions)
+        -> Result<(), CsvdError> {
+    csvd_view_stats(a, p, nu, nv, s, u, v, options, &mut no_progress).map(|_| ())
 }
 
-pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize, p: usize, nu: usize, nv: usize, 
-        s: &mut Vec<f32>, u: &mut Vec<Complex32>, v: &mut Vec<Complex32>) 
-        -> Result<(), &'static str> {
+/// `csvd_view` returning the statistics of the QR diagonalization
+fn csvd_view_stats<T: Real>(mut a: MatrixViewMut<Complex<T>>, p: usize, nu: usize, nv: usize,
+        s: &mut [T], mut u: MatrixViewMut<Complex<T>>, mut v: MatrixViewMut<Complex<T>>, options: &SvdOptions,
+        progress: &mut ProgressFn) -> Result<SvdStats<T>, CsvdError> {
     
     // debug!("In csvd");
 
-    //check n
-    if n < 1 {
-        return Err("Fatal Error = Input N < 1");
+    let m = a.rows();
+    if a.cols() < p {
+        return Err(CsvdError::InvalidArgument { name: "P", value: p, max: a.cols() });
     }
-    else if NBIG < n{
-        return Err("Fatal Error: NBIG < N");
+    let n = a.cols() - p;
+    let min = m.min(n);
+
+    //check n and m
+    if n < 1 || m < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    else if m < n && 0 < p {
+        // the transformation U* can only be applied to extra columns of a tall matrix
+        return Err(CsvdError::WideMatrix { rows: m, cols: n });
     }
 
-    //check m
-    if m < 1 {
-        return Err("Fatal Error: Input M < 1");
-    } 
-    else if m < n {
-        return Err("Fatal Error: M < N");
+    //check outputs
+    if s.len() < min {
+        return Err(CsvdError::BufferTooSmall { operand: "S", required: min, len: s.len() });
     }
+    if 0 < nu {
+        if nu > m {
+            return Err(CsvdError::InvalidArgument { name: "NU", value: nu, max: m });
+        }
+        if u.rows() != m || u.cols() < nu.max(min) {
+            return Err(CsvdError::DimensionMismatch { operand: "U", expected: (m, nu.max(min)), found: (u.rows(), u.cols()) });
+        }
+    }
+    if 0 < nv {
+        if nv > n {
+            return Err(CsvdError::InvalidArgument { name: "NV", value: nv, max: n });
+        }
+        if v.rows() != n || v.cols() < nv.max(min) {
+            return Err(CsvdError::DimensionMismatch { operand: "V", expected: (n, nv.max(min)), found: (v.rows(), v.cols()) });
+        }
+    }
+
+    // work arrays B, C and T
+    let mut work: Vec<T> = Vec::with_capacity(3 * min);
+    work.resize(3 * min, T::zero());
+
+    let stats = if m < n {
+        // A* = U' S V'* is tall, so A = V' S U'*:
+        // decompose the conjugate transpose and swap the roles of U and V
+        let mut a_h = a.as_view().to_matrix().conj_transpose();
+
+        let (ldu, ldv) = (u.row_stride(), v.row_stride());
+        csvd_tall(a_h.as_mut_slice(), m, n, m, 0, nv, nu, s, v.storage_mut(), ldv, u.storage_mut(), ldu, &mut work, options, progress)
+            .map_err(swap_uv)?
+    }
+    else {
+        let (lda, ldu, ldv) = (a.row_stride(), u.row_stride(), v.row_stride());
+        csvd_tall(a.storage_mut(), lda, m, n, p, nu, nv, s, u.storage_mut(), ldu, v.storage_mut(), ldv, &mut work, options, progress)?
+    };
+
+    // column K of U times q turns row K of U* B into conj(q) times itself,
+    // B only exists for a tall matrix, whose U has a row of U* B for every K
+    let (lda, ldu, ldv) = (a.row_stride(), u.row_stride(), v.row_stride());
+    let b = a.storage_mut();
+    normalize_phases_with(u.storage_mut(), ldu, m, nu, v.storage_mut(), ldv, n, nv, |k, q| {
+        for x in b.iter_mut().skip(k*lda + n).take(p) {
+            *x = *x * q.conj();
+        }
+    });
+    Ok(stats)
+}
+
+/// Same as `csvd`, but working purely on caller-provided slices so that the allocator
+/// is never touched, e.g. in interrupt context or before the heap is set up.
+///
+/// a holds the tightly packed, row-major M by N matrix and is overwritten.
+/// s receives the MIN(M,N) singular values, u the full M by M matrix U and v the
+/// full N by N matrix V, all tightly packed and row-major.
+/// scratch must hold at least 3 * MIN(M,N) elements for the work arrays B, C and T.
+///
+/// Wide matrices (N > M) are conjugate-transposed in place before the decomposition,
+/// which costs up to O((M N)^2) index computations for the cycle-following transposition.
+/// 4 x 4 matrices take the unrolled kernel of `svd4` and leave a and scratch untouched,
+/// as they do in `csvd_with_options`.
+pub fn csvd_in_slices<T: Real>(a: &mut [Complex<T>], s: &mut [T], u: &mut [Complex<T>], v: &mut [Complex<T>],
+        scratch: &mut [T], m: usize, n: usize) -> Result<(), CsvdError> {
+    let min = m.min(n);
+
+    if m < 1 || n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+
+    let checks = [("A", a.len(), m * n), ("U", u.len(), m * m), ("V", v.len(), n * n)];
+    for &(operand, len, required) in &checks {
+        if len < required {
+            return Err(CsvdError::BufferTooSmall { operand, required, len });
+        }
+    }
+    if s.len() < min {
+        return Err(CsvdError::BufferTooSmall { operand: "S", required: min, len: s.len() });
+    }
+    if scratch.len() < 3 * min {
+        return Err(CsvdError::BufferTooSmall { operand: "scratch", required: 3 * min, len: scratch.len() });
+    }
+
+    if m == 4 && n == 4 {
+        return svd4_in_slices(a, s, u, v);
+    }
+    if m < n {
+        // decompose the conjugate transpose and swap the roles of U and V, as csvd does
+        conj_transpose_in_place(&mut a[..m * n], m, n);
+        csvd_tall(a, m, n, m, 0, n, m, s, v, n, u, m, scratch, &SvdOptions::default(), &mut no_progress).map_err(swap_uv)?;
+    }
+    else {
+        csvd_tall(a, n, m, n, 0, m, n, s, u, m, v, n, scratch, &SvdOptions::default(), &mut no_progress)?;
+    }
+
+    normalize_phases(u, m, m, m, v, n, n, n);
+    Ok(())
+}
+
+/// Enforces the phase convention of all decompositions: the first entry of column K of U
+/// whose magnitude exceeds the machine epsilon is made real and positive, and column K of V
+/// is multiplied by the same phase, which leaves U S V* unchanged. Columns of V without a
+/// computed counterpart in U (NU < NV) follow the same rule on their own.
+/// Together with the descending singular values this makes the factors unique for distinct
+/// singular values, and comparable with those of other libraries after the same normalization.
+pub(crate) fn normalize_phases<T: Real>(u: &mut [Complex<T>], ldu: usize, m: usize, nu: usize,
+        v: &mut [Complex<T>], ldv: usize, n: usize, nv: usize) {
+    normalize_phases_with(u, ldu, m, nu, v, ldv, n, nv, |_, _| {});
+}
+
+/// Same as `normalize_phases`, calling rotated with K and the phase q of every column K
+/// it multiplies, whether U holds that column or not. U* B then needs row K scaled by conj(q).
+fn normalize_phases_with<T: Real, F: FnMut(usize, Complex<T>)>(u: &mut [Complex<T>], ldu: usize, m: usize, nu: usize,
+        v: &mut [Complex<T>], ldv: usize, n: usize, nv: usize, mut rotated: F) {
+    let eps = T::epsilon();
+    let phase = |x: &[Complex<T>], ld: usize, rows: usize, k: usize| {
+        (0..rows).map(|i| x[i*ld + k]).find(|x| cabs(x) > eps).map(|x| x.conj() / cabs(&x))
+    };
+
+    for k in 0..nu.max(nv) {
+        let q = if k < nu { phase(u, ldu, m, k) } else { phase(v, ldv, n, k) };
+        let q = match q {
+            Some(q) if q != Complex::new(T::one(), T::zero()) => q,
+            _ => continue,
+        };
+
+        if k < nu {
+            for i in 0..m {
+                u[i*ldu + k] = u[i*ldu + k] * q;
+            }
+        }
+        if k < nv {
+            for i in 0..n {
+                v[i*ldv + k] = v[i*ldv + k] * q;
+            }
+        }
+        rotated(k, q);
+    }
+}
+
+/// Replaces the tightly packed M by N row-major matrix in a with its
+/// N by M conjugate transpose, without any extra storage
+fn conj_transpose_in_place<T: Real>(a: &mut [Complex<T>], m: usize, n: usize) {
+    transpose_in_place(a, m, n, |x| x.conj());
+}
+
+/// Replaces the tightly packed M by N row-major matrix in a with its N by M transpose,
+/// applying f to every element, without any extra storage
+pub(crate) fn transpose_in_place<E: Copy, F: Fn(E) -> E>(a: &mut [E], m: usize, n: usize, f: F) {
+    // element (i, j) at index i*n + j moves to index j*m + i
+    let dest = |idx: usize| (idx % n) * m + idx / n;
+
+    for start in 0..m * n {
+        // every cycle of the permutation is rotated once, starting from its smallest index
+        let mut next = dest(start);
+        while next > start {
+            next = dest(next);
+        }
+        if next < start {
+            continue;
+        }
+
+        let mut cur = start;
+        let mut val = a[start];
+        loop {
+            let to = dest(cur);
+            let tmp = a[to];
+            a[to] = f(val);
+            val = tmp;
+            cur = to;
+            if cur == start {
+                break;
+            }
+        }
+    }
+}
+
+/// The decomposition itself, for an M by N+P row-major matrix A with N <= M
+/// whose arguments have already been checked by the caller.
+/// lda, ldu and ldv are the leading dimensions of A, U and V,
+/// work holds the 3N elements of the work arrays B, C and T.
+pub(crate) fn csvd_tall<T: Real>(a: &mut [Complex<T>], lda: usize, m: usize, n: usize, p: usize, nu: usize, nv: usize,
+        s: &mut [T], u: &mut [Complex<T>], ldu: usize, v: &mut [Complex<T>], ldv: usize,
+        work: &mut [T], options: &SvdOptions, progress: &mut ProgressFn) -> Result<SvdStats<T>, CsvdError> {
     
     // Householder reduction.
-    let mut c: [f32; NBIG] = [0.0; NBIG];
-    c[1] = 0.0;
-    let mut k = 0;
-    let mut b: [f32; NBIG] = [0.0; NBIG];
+    let zero = T::zero();
+    let one = T::one();
+
+    let (b, work) = work[..3 * n].split_at_mut(n);
+    let (c, t) = work.split_at_mut(n);
+    for x in b.iter_mut().chain(c.iter_mut()) {
+        *x = zero;
+    }
+    let mut k;
     let mut k1;
-    let tol = 1.5 * powf(10.0, -31.0);
+    let tol = T::min_positive_value() / T::epsilon();
+
+    if options.check_finite {
+        check_finite(a, lda, m, n + p)?;
+    }
+
+    // Scale A so that its largest entry lies in [SMLNUM, BIGNUM], which keeps the
+    // products in the QR sweeps from overflowing or underflowing (as LAPACK's xGESVD does).
+    // Only the first N columns are scaled, the transformation U* does not depend on the scale.
+    let smlnum = sqrt(T::min_positive_value()) / T::epsilon();
+    let bignum = one / smlnum;
+    let mut anrm = zero;
+    for i in 0..m {
+        for j in 0..n {
+            anrm = anrm.max(cabs(&a[i*lda + j]));
+        }
+    }
+    let scale = if zero < anrm && anrm < smlnum {
+        smlnum / anrm
+    }
+    else if bignum < anrm {
+        bignum / anrm
+    }
+    else {
+        one
+    };
+    if scale != one {
+        for i in 0..m {
+            for j in 0..n {
+                a[i*lda + j] = a[i*lda + j] * scale;
+            }
+        }
+    }
 
     //10 continue for k in 0..n
     for k in 0..n {
         k1 = k + 1;
 
         // Elimination of A(I,K), I = K+1, ..., M.
-        let mut z: f32 = 0.0;
-        for i in k..m {
-            z = z + powf(a[i*m + k].re, 2.0) + powf(a[i*m + k].im, 2.0);
-        }
+        let mut z: T = options.summation.norm2((k..m).map(|i| a[i*lda + k]));
 
-        b[k] = 0.0;
+        b[k] = zero;
 
         let (mut w, mut q);
-        if tol < z {
+        if tol < z * z {
 
-            z = sqrt(z);
             b[k] = z;
-            w = cabs(&a[k*m + k]);
+            w = cabs(&a[k*lda + k]);
 
-            if w == 0.0 {
-                q = Complex32{ re: 1.0, im: 0.0};
+            if w == zero {
+                q = Complex::new(one, zero);
             }
             else {
-                q = a[k*m + k]/w;
+                q = a[k*lda + k]/w;
             }
 
-            a[k*m + k] = q * ( z + w );
+            a[k*lda + k] = q * ( z + w );
 
             if k != (n - 1 + p) {
-                for j in k1..(n + p){
-                    q = Complex32{ re: 0.0, im: 0.0};
-                    
-                    for i in k..m {
-                        q = q + a[i*m + k].conj() * a[i*m + j];
-                    }
-                    q = q / ( z * ( z + w ) );
-
-                    for i in k..m {
-                        a[i*m + j] = a[i*m + j] - q * a[i*m + k];
-                    }
-                }
+                reflect_columns(a, lda, k, m, n + p, z, w, options.summation);
 
                 // Phase transformation.
-                q = -a[k*m + k].conj() / cabs(&a[k*m + k]);
+                q = -a[k*lda + k].conj() / cabs(&a[k*lda + k]);
 
                 for j in k1..(n + p) {
-                    a[k*m + j] = q * a[k*m + j];
+                    a[k*lda + j] = q * a[k*lda + j];
                 }
             }
         }
@@ -187,53 +817,36 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
             break;
         }
 
-        z = 0.0;
-        for j in k1..n {
-            z = z + powf(a[k*m + j].re, 2.0) + powf(a[k*m + j].im, 2.0);
-        }
-        c[k1] = 0.0;
+        z = options.summation.norm2((k1..n).map(|j| a[k*lda + j]));
+        c[k1] = zero;
 
-        if tol < z {
-            z = sqrt(z);
+        if tol < z * z {
             c[k1] = z;
-            w = cabs(&a[k*m + k1]);
+            w = cabs(&a[k*lda + k1]);
 
-            if w == 0.0 {
-                q = Complex32{ re: 1.0, im: 0.0};
+            if w == zero {
+                q = Complex::new(one, zero);
             }
             else {
-                q = a[k*m + k1] / w;
+                q = a[k*lda + k1] / w;
             }
 
-            a[k*m + k1] = q * (z + w);
-
-            for i in k1..m {
-                q = Complex32{ re: 0.0, im: 0.0};
+            a[k*lda + k1] = q * (z + w);
 
-                for j in k1..n {
-                    q = q + a[k*m + j].conj()  * a[i*m + j];
-                }
-
-                q = q / (z * (z + w));
-
-                for j in k1..n {
-                    a[i*m + j] = a[i*m + j] - q * a[k*m + j];
-                }
-            }
+            reflect_rows(a, lda, k, m, n, z, w, options.summation);
     
             // Phase transformation.
-            q = -a[k*m + k1].conj() / cabs(&a[k*m + k1]);
+            q = -a[k*lda + k1].conj() / cabs(&a[k*lda + k1]);
             for i in k1..m {
-                a[i*m + k1] = a[i* m + k1] * q;
+                a[i*lda + k1] = a[i*lda + k1] * q;
             }
         }
     }
 
     // Tolerance for negligible elements.
     //140 continue
-    let mut eps: f32 = 0.0;
-    let eta: f32 = 1.1920929 * powf(10.0, -7.0);
-    let mut t: [f32; NBIG] = [0.0; NBIG];
+    let mut eps: T = zero;
+    let eta: T = T::epsilon();
 
     for k in 0..n {
        s[k] = b[k];
@@ -244,21 +857,23 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
     eps = eps * eta;
 
     // Initialization of U and V.
+    // The rotations of the QR sweeps touch the first N columns, so those are
+    // initialized even if fewer are requested, only NU and NV are back-transformed.
     if 0 < nu {
-        for j in 0..nu {
+        for j in 0..nu.max(n) {
             for i in 0..m {
-                u[i*m + j] = Complex32{re: 0.0, im: 0.0};
+                u[i*ldu + j] = Complex::new(zero, zero);
             }
-            u[j*m + j] = Complex32{re: 1.0, im: 0.0};
+            u[j*ldu + j] = Complex::new(one, zero);
         }
     }
 
     if 0 < nv {
-        for j in 0..nv {
+        for j in 0..nv.max(n) {
             for i in 0..n {
-                v[i*n + j] = Complex32{re: 0.0, im: 0.0};
+                v[i*ldv + j] = Complex::new(zero, zero);
             }
-            v[j*n + j] = Complex32{re: 1.0, im: 0.0};
+            v[j*ldv + j] = Complex::new(one, zero);
         }
     }
 
@@ -275,6 +890,141 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
 
     // println!("****************");
 
+    // the extra columns of A, which the rotations of U* apply to as well
+    let mut stats = qr_sweeps(s, t, n, nu, nv, u, ldu, v, ldv, &mut a[n..], lda, p, eps, options.max_iterations, progress)?;
+
+    let mut q;
+
+    // Undo the scaling of A.
+    if scale != one {
+        for k in 0..n {
+            s[k] = s[k] / scale;
+        }
+    }
+    stats.tolerance = eps / scale;
+    debug!(target: "lin_alg::csvd", "{} x {}: {} sweeps, at most {} on one singular value, {} splits, {} cancellations",
+        m, n, stats.sweeps, stats.max_sweeps, stats.splits, stats.cancellations);
+
+    // Back transformation.
+    if 0 < nu {
+        for kk in 0..n {
+            k = n - 1 - kk;
+
+            if b[k] != zero {
+                q = -a[k*lda + k] / cabs(&a[k*lda + k]);
+
+                for j in 0..nu {
+                    u[k*ldu + j] = q * u[k*ldu + j];
+                }
+
+                for j in 0..nu {
+
+                    q = match options.summation {
+                        Summation::Plain => {
+                            let mut q = Complex::new(zero, zero);
+                            for i in k..m {
+                                q = q + a[i*lda + k].conj() * u[i*ldu + j];
+                            }
+                            q
+                        }
+                        Summation::Compensated => dot_conj_compensated((k..m).map(|i| (a[i*lda + k], u[i*ldu + j]))),
+                    };
+
+                    q = q / cabs(&a[k*lda + k]) / b[k];
+
+                    for i in k..m {
+                        u[i*ldu + j] = u[i*ldu + j] - q * a[i*lda + k];
+                    }
+
+                }
+
+            }
+
+        }
+
+    }
+
+    if 0 < nv && 1 < n {
+
+        for kk in 1..n {
+            k = n - 1 - kk;
+            k1 = k + 1;
+
+            if c[k1] != zero { 
+                q = -(a[k*lda + k1].conj()) / cabs(&a[k*lda + k1]);
+
+                for j in 0..nv {
+                    v[k1*ldv + j] = q * v[k1*ldv + j];
+                }
+
+                for j in 0..nv {
+                    q = match options.summation {
+                        Summation::Plain => {
+                            let mut q = Complex::new(zero, zero);
+                            for i in k1..n {
+                                q = q + a[k*lda + i] * v[i*ldv + j];
+                            }
+                            q
+                        }
+                        Summation::Compensated => dot_conj_compensated((k1..n).map(|i| (a[k*lda + i].conj(), v[i*ldv + j]))),
+                    };
+                    q = q / cabs(&a[k*lda + k1]) / c[k1];
+
+                    for i in k1..n {
+                        v[i*ldv + j] = v[i*ldv + j] - q * a[k*lda + i].conj();
+                    }
+                }
+            }
+        }
+    }     
+
+    Ok(stats)
+}
+
+/// A real number or a complex number with zero imaginary part, the elements U and V hold
+/// while the QR sweeps rotate them
+pub(crate) trait SweepScalar<T>: Copy + Neg<Output = Self> {
+    fn re(self) -> T;
+    fn from_re(x: T) -> Self;
+}
+
+impl<T: Real> SweepScalar<T> for T {
+    fn re(self) -> T {
+        self
+    }
+
+    fn from_re(x: T) -> T {
+        x
+    }
+}
+
+impl<T: Real> SweepScalar<T> for Complex<T> {
+    fn re(self) -> T {
+        self.re
+    }
+
+    fn from_re(x: T) -> Complex<T> {
+        Complex::new(x, T::zero())
+    }
+}
+
+/// QR diagonalization of the bidiagonal matrix with diagonal s and superdiagonal t,
+/// followed by sorting the singular values in descending order. The rotations are
+/// accumulated into the first N rows of U and V, which have to start out as the identity,
+/// and applied to the first N rows of the P columns of b, whose rows are ldb elements apart.
+pub(crate) fn qr_sweeps<T: Real, E: SweepScalar<T>>(s: &mut [T], t: &mut [T], n: usize, nu: usize, nv: usize,
+        u: &mut [E], ldu: usize, v: &mut [E], ldv: usize, b: &mut [Complex<T>], ldb: usize, p: usize,
+        eps: T, max_iterations: usize, progress: &mut ProgressFn)
+        -> Result<SvdStats<T>, CsvdError> {
+    let zero = T::zero();
+    let one = T::one();
+    let two = one + one;
+    let mut k;
+    let mut stats = SvdStats::new();
+    stats.tolerance = eps;
+    // the start of the block the last sweep worked on, a new one means a split
+    let mut block = 0;
+
     let mut l = 0;
     let mut cs;
     let mut sn;
@@ -284,20 +1034,22 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
     let mut w;
     let mut x;
     let mut y;
-    let mut q;
+    let mut q: E;
     // let mut r;
     let mut g;
 
     // QR diagonalization.
     for kk in 0..n {
         k = n - 1 - kk;
+        let mut iterations = 0;
 
         //Test for split.
         //220 continue
         loop {
             for ll in 0..=k {
                 l = k - ll;
-                if abs(t[l]) <= eps {
+                // t[0] is always zero, checking l also keeps s[l-1] in bounds on NaN input
+                if l == 0 || abs(t[l]) <= eps {
                     //go to 290
                     break;
                 }
@@ -309,15 +1061,17 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
 
             }
 
-            if abs(t[l]) <= eps {
+            if l == 0 || abs(t[l]) <= eps {
                 //go to 290
             }
 
             //Cancellation of E(L).
             // 240 continue
             else if abs(s[l-1]) <= eps {
-                cs = 0.0;
-                sn = 1.0;
+                stats.cancellations += 1;
+                trace!(target: "lin_alg::csvd", "cancelled superdiagonal {} next to negligible diagonal {}", l, l - 1);
+                cs = zero;
+                sn = one;
                 l1 = l - 1;
 
                 for i in l..=k {
@@ -330,28 +1084,25 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
                     }
 
                     h = s[i];
-                    w = sqrt(f * f + h * h);
+                    w = f.hypot(h);
                     s[i] = w;
                     cs = h / w;
                     sn = - f / w;
 
                     if 0 < nu {
                         for j in 0..n {
-                            x = u[j*m + l1].re;
-                            y = u[j*m + i].re;
-                            u[j*m + l1] = Complex32{re: x * cs + y * sn, im: 0.0};
-                            u[j*m + i] = Complex32{re: y * cs - x * sn, im: 0.0};
+                            x = u[j*ldu + l1].re();
+                            y = u[j*ldu + i].re();
+                            u[j*ldu + l1] = E::from_re(x * cs + y * sn);
+                            u[j*ldu + i] = E::from_re(y * cs - x * sn);
                         }
                     }
 
-                    // if p != 0 {
-                    //     for j in (n + 1)..=(n + p) {
-                    //         q = a[l1][j];
-                    //         r = a[i][j];
-                    //         a[l1][j] = q * cs + r * sn;
-                    //         a[i][j] = r * cs - q * sn;
-                    //     }
-                    // }
+                    for j in 0..p {
+                        let (q, r) = (b[l1*ldb + j], b[i*ldb + j]);
+                        b[l1*ldb + j] = q * cs + r * sn;
+                        b[i*ldb + j] = r * cs - q * sn;
+                    }
                 }
             }
 
@@ -361,24 +1112,47 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
 
             if l == k {
                 //go to 360
+                trace!(target: "lin_alg::csvd", "singular value {} converged after {} sweeps", k, iterations);
                 break;
             }
 
+            let status = Progress { index: k, converged: n - 1 - k, total: n, sweeps: stats.sweeps };
+            if progress(status).is_break() {
+                debug!(target: "lin_alg::csvd", "cancelled at singular value {} after {} sweeps", k, stats.sweeps);
+                return Err(CsvdError::Cancelled { index: k, sweeps: stats.sweeps });
+            }
+
+            // give up instead of spinning forever on pathological input
+            iterations += 1;
+            if iterations > max_iterations {
+                debug!(target: "lin_alg::csvd", "singular value {} did not converge after {} sweeps", k, max_iterations);
+                return Err(CsvdError::NotConverged { index: k, iterations: max_iterations });
+            }
+            stats.sweeps += 1;
+            stats.max_sweeps = stats.max_sweeps.max(iterations);
+            if l != block {
+                if l > 0 {
+                    stats.splits += 1;
+                    trace!(target: "lin_alg::csvd", "bidiagonal split at {}, sweeping rows {} to {}", l, l, k);
+                }
+                block = l;
+            }
+
             // Origin shift.
             x = s[l];
             y = s[k-1];
             g = t[k-1];
             h = t[k];
-            f = ( ( y - w ) * ( y + w ) + ( g - h ) * ( g + h ) ) / ( 2.0 * h * y );
-            g = sqrt(f * f + 1.0);
-            if f < 0.0 {
+            f = ( ( y - w ) * ( y + w ) + ( g - h ) * ( g + h ) ) / ( two * h * y );
+            g = f.hypot(one);
+            if f < zero {
                 g = -g;
             }
             f = ( ( x - w ) * ( x + w ) + ( y / ( f + g ) - h ) * h ) / x;
 
             // QR Step
-            cs = 1.0;
-            sn = 1.0;
+            cs = one;
+            sn = one;
             l1 = l + 1;
 
             for i in l1..=k {
@@ -387,10 +1161,17 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
                 y = s[i];
                 h = sn * g;
                 g = cs * g;
-                w = sqrt(h * h + f * f);
+                w = h.hypot(f);
                 t[i-1] = w;
-                cs = f / w;
-                sn = h / w;
+                // an exact shift can annihilate both entries, the rotation is then the identity
+                if w == zero {
+                    cs = one;
+                    sn = zero;
+                }
+                else {
+                    cs = f / w;
+                    sn = h / w;
+                }
                 f = x * cs + g * sn;
                 g = g * cs - x * sn;
                 h = y * sn;
@@ -398,40 +1179,43 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
 
                 if 0 < nv {
                     for j in 0..n {
-                        x = v[j*n + i-1].re;
-                        w = v[j*n + i].re;
-                        v[j*n + i-1] = Complex32{re: x * cs + w * sn, im: 0.0};
-                        v[j*n + i] = Complex32{re: w * cs - x * sn, im: 0.0};
+                        x = v[j*ldv + i-1].re();
+                        w = v[j*ldv + i].re();
+                        v[j*ldv + i-1] = E::from_re(x * cs + w * sn);
+                        v[j*ldv + i] = E::from_re(w * cs - x * sn);
                     }
                 }
 
-                w = sqrt(h * h + f * f);
+                w = h.hypot(f);
                 s[i-1] = w;
-                cs = f / w;
-                sn = h / w;
+                if w == zero {
+                    cs = one;
+                    sn = zero;
+                }
+                else {
+                    cs = f / w;
+                    sn = h / w;
+                }
                 f = cs * g + sn * y;
                 x = cs * y - sn * g;
 
                 if 0 < nu {
                     for j in 0..n {
-                        y = u[j*m + i-1].re;
-                        w = u[j*m + i].re;
-                        u[j*m + i-1] = Complex32{re: y * cs + w * sn, im: 0.0};
-                        u[j*m + i] = Complex32{re: w * cs - y * sn, im: 0.0};
+                        y = u[j*ldu + i-1].re();
+                        w = u[j*ldu + i].re();
+                        u[j*ldu + i-1] = E::from_re(y * cs + w * sn);
+                        u[j*ldu + i] = E::from_re(w * cs - y * sn);
                     }
                 }
 
-                // if p != 0 {
-                //     for j in (n + 1)..=(n + p) {
-                //         q = a[i-1][j];
-                //         r = a[i][j];
-                //         a[i-1][j] = q * cs + r * sn;
-                //         a[i][j] = r * cs - q * sn;
-                //     }
-                // }
+                for j in 0..p {
+                    let (q, r) = (b[(i-1)*ldb + j], b[i*ldb + j]);
+                    b[(i-1)*ldb + j] = q * cs + r * sn;
+                    b[i*ldb + j] = r * cs - q * sn;
+                }
             }
 
-            t[l] = 0.0;
+            t[l] = zero;
             t[k] = f;
             s[k] = x;
             //go to 220
@@ -440,12 +1224,12 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
         // Convergence
         // 360 continue
 
-        if w < 0.0 {
+        if w < zero {
             s[k] = -w;
 
             if 0 < nv {
                 for j in 0..n {
-                    v[j*n + k] = -v[j*n + k];
+                    v[j*ldv + k] = -v[j*ldv + k];
                 }
             }
         }
@@ -455,7 +1239,7 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
     
     // Sort the singular values.
     for k in 0..n {
-        g = -1.0;
+        g = -one;
         j = k;
 
         for i in k..n {
@@ -472,97 +1256,27 @@ pub fn csvd(a: &mut Vec<Complex32>, mmax: usize, nmax: usize, n: usize, m: usize
             //Interchange V(1:N,J) and V(1:N,K).
             if 0 < nv {
                for i in 0..n {
-                    q = v[i*n + j];
-                    v[i*n + j] = v[i*n + k];
-                    v[i*n + k] = q;
+                    q = v[i*ldv + j];
+                    v[i*ldv + j] = v[i*ldv + k];
+                    v[i*ldv + k] = q;
                }
             }
 
             // Interchange U(1:N,J) and U(1:N,K).
             if 0 < nu {
                 for i in 0..n {
-                    q = u[i*m + j];
-                    u[i*m + j] = u[i*m + k];
-                    u[i*m + k] = q;
+                    q = u[i*ldu + j];
+                    u[i*ldu + j] = u[i*ldu + k];
+                    u[i*ldu + k] = q;
                 }
             }
 
-            // Interchange A(J,N1:NP) and A(K,N1:NP).
-            // if p != 0 {
-            //     for i in (n + 1)..=(n + p) {
-            //         q = a[j][i];
-            //         a[j][i] = a[k][i];
-            //         a[k][i] = q;
-            //     }
-            // }
-        }
-    }
-
-    // Back transformation.
-    if 0 < nu {
-        for kk in 0..n {
-            k = n - 1 - kk;
-
-            if b[k] != 0.0 {
-                q = -a[k*m + k] / cabs(&a[k*m + k]);
-
-                for j in 0..nu {
-                    u[k*m + j] = q * u[k*m + j];
-                }
-
-                for j in 0..nu {
-
-                    q = Complex32{re: 0.0, im: 0.0};
-
-                    for i in k..m {
-                        q = q + a[i*m + k].conj() * u[i*m + j];
-                    }
-
-                    q = q / (cabs(&a[k*m + k]) * b[k]);
-
-                    for i in k..m {
-                        u[i*m + j] = u[i*m + j] - q * a[i*m + k];
-                    }
-
-                }
-
+            // Interchange B(J,1:P) and B(K,1:P).
+            for i in 0..p {
+                b.swap(j*ldb + i, k*ldb + i);
             }
-
         }
-
     }
 
-    if 0 < nv {
-
-        if 1 < n {
-
-            for kk in 1..n {
-                k = n - 1 - kk;
-                k1 = k + 1;
-
-                if c[k1] != 0.0 { 
-                    q = -(a[k*m + k1].conj()) / cabs(&a[k*m + k1]);
-
-                    for j in 0..nv {
-                        v[k1*n + j] = q * v[k1*n + j];
-                    }
-
-                    for j in 0..nv {
-                        q = Complex32{re: 0.0, im: 0.0};
-
-                        for i in k1..n {
-                            q = q + a[k*m + i] * v[i*n + j];
-                        }
-                        q = q / (cabs(&a[k*m + k1]) * c[k1]);
-
-                        for i in k1..n {
-                            v[i*n + j] = v[i*n + j] - q * a[k*m + i].conj();
-                        }
-                    }
-                }
-            }
-        }
-    }     
-
-    Ok(())   
+    Ok(stats)
 }
@@ -0,0 +1,126 @@
+//! Eigendecomposition of Hermitian matrices.
+//!
+//! A Hermitian matrix A = A* has real eigenvalues and a unitary matrix of eigenvectors,
+//! and its singular values are the absolute values of its eigenvalues, which is how
+//! `svd_auto` decomposes Hermitian input without the bidiagonalization of `csvd`.
+
+use alloc::vec::Vec;
+use num_complex::Complex;
+use super::csvd::{cabs, norm2};
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::scalar::Real;
+
+/// Sweeps over all off-diagonal pairs before `eigh` gives up, Jacobi's method
+/// converges quadratically and needs well under 20 for any matrix in practice
+const MAX_SWEEPS: usize = 50;
+
+/// The eigendecomposition A = Q diag(values) Q* of a Hermitian matrix A
+#[derive(Clone, Debug, PartialEq)]
+pub struct HermitianEig<T> {
+    /// the n real eigenvalues in ascending order
+    pub values: Vec<T>,
+    /// n x n unitary matrix whose column k is the eigenvector of values[k]
+    pub vectors: Matrix<Complex<T>>,
+}
+
+/// Computes the eigenvalues and eigenvectors of the Hermitian matrix a with the cyclic
+/// Jacobi method, which is accurate to the last bits even for tiny eigenvalues and fast
+/// for the small matrices of MIMO processing, e.g. covariance or Gram matrices H* H.
+///
+/// Only the upper triangle and the real part of the diagonal of a are read, its lower
+/// triangle is taken to be the conjugate of the upper one.
+pub fn eigh<T: Real>(a: &Matrix<Complex<T>>) -> Result<HermitianEig<T>, CsvdError> {
+    let n = a.rows();
+    if n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    if a.cols() != n {
+        return Err(CsvdError::DimensionMismatch { operand: "a", expected: (n, n), found: (n, a.cols()) });
+    }
+
+    let zero = T::zero();
+    let one = T::one();
+    let two = one + one;
+
+    // work on the Hermitian matrix given by the upper triangle
+    let mut h = Matrix::zeros(n, n);
+    for i in 0..n {
+        h[(i, i)] = Complex::new(a[(i, i)].re, zero);
+        for j in i + 1..n {
+            h[(i, j)] = a[(i, j)];
+            h[(j, i)] = a[(i, j)].conj();
+        }
+    }
+    let mut q = Matrix::identity(n);
+
+    let off = |h: &Matrix<Complex<T>>| norm2((0..n).flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j))).map(|(i, j)| h[(i, j)]));
+    let scale = norm2(h.as_slice().iter().copied());
+    let tol = T::epsilon() * scale;
+
+    let mut sweeps = 0;
+    while off(&h) > tol {
+        sweeps += 1;
+        if sweeps > MAX_SWEEPS {
+            return Err(CsvdError::NotConverged { index: 0, iterations: MAX_SWEEPS });
+        }
+
+        for p in 0..n {
+            for r in p + 1..n {
+                let apr = h[(p, r)];
+                let g = cabs(&apr);
+                if g == zero {
+                    continue;
+                }
+
+                // with D = diag(1, e^(i phi)) the 2 x 2 block is D [[a, g], [g, b]] D*, which the
+                // real rotation [[c, s], [-s, c]] diagonalizes, so J = D R D* for the full matrix
+                let phase = apr / g;
+                let theta = (h[(r, r)].re - h[(p, p)].re) / (two * g);
+                let t = {
+                    let t = one / (theta.abs() + theta.hypot(one));
+                    if theta < zero { -t } else { t }
+                };
+                let c = one / t.hypot(one);
+                let s = t * c;
+                let (jpr, jrp) = (phase * s, -phase.conj() * s);
+
+                // H J, then J* (H J), and Q J
+                for k in 0..n {
+                    let (hp, hr) = (h[(k, p)], h[(k, r)]);
+                    h[(k, p)] = hp * c + hr * jrp;
+                    h[(k, r)] = hp * jpr + hr * c;
+                }
+                for k in 0..n {
+                    let (hp, hr) = (h[(p, k)], h[(r, k)]);
+                    h[(p, k)] = hp * c + hr * jrp.conj();
+                    h[(r, k)] = hp * jpr.conj() + hr * c;
+                }
+                for k in 0..n {
+                    let (qp, qr) = (q[(k, p)], q[(k, r)]);
+                    q[(k, p)] = qp * c + qr * jrp;
+                    q[(k, r)] = qp * jpr + qr * c;
+                }
+
+                h[(p, r)] = Complex::new(zero, zero);
+                h[(r, p)] = Complex::new(zero, zero);
+                h[(p, p)] = Complex::new(h[(p, p)].re, zero);
+                h[(r, r)] = Complex::new(h[(r, r)].re, zero);
+            }
+        }
+    }
+
+    // ascending eigenvalues, with the eigenvectors in the same order
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| h[(i, i)].re.partial_cmp(&h[(j, j)].re).unwrap_or(core::cmp::Ordering::Equal));
+
+    let values = order.iter().map(|&k| h[(k, k)].re).collect();
+    let mut vectors = Matrix::zeros(n, n);
+    for (col, &k) in order.iter().enumerate() {
+        for i in 0..n {
+            vectors[(i, col)] = q[(i, k)];
+        }
+    }
+
+    Ok(HermitianEig { values, vectors })
+}
@@ -0,0 +1,69 @@
+use core::fmt;
+
+/// Errors returned by the decompositions and helpers of this crate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvdError {
+    /// A matrix has no rows or no columns
+    EmptyMatrix,
+    /// The matrix has more columns than rows, which the routine does not support
+    WideMatrix { rows: usize, cols: usize },
+    /// An operand does not have the (rows, cols) dimensions the operation requires
+    DimensionMismatch { operand: &'static str, expected: (usize, usize), found: (usize, usize) },
+    /// A buffer holds fewer elements than the operation requires
+    BufferTooSmall { operand: &'static str, required: usize, len: usize },
+    /// A count argument such as NU, NV or P is larger than allowed
+    InvalidArgument { name: &'static str, value: usize, max: usize },
+    /// The leading dimension of a matrix is smaller than its number of columns,
+    /// or of rows for a column-major matrix
+    InvalidLeadingDimension { ld: usize, cols: usize },
+    /// The requested alignment is not supported
+    InvalidAlignment { alignment: usize },
+    /// The QR iteration for the singular value at position `index` of the bidiagonal
+    /// did not converge within the given number of iterations
+    NotConverged { index: usize, iterations: usize },
+    /// The progress callback stopped the decomposition at the singular value at position
+    /// `index` of the bidiagonal, after `sweeps` QR sweeps
+    Cancelled { index: usize, sweeps: usize },
+    /// The matrix is singular, the pivot at position `index` is zero
+    Singular { index: usize },
+    /// The matrix is larger than the (rows, cols) the workspace was created for
+    WorkspaceTooSmall { required: (usize, usize), capacity: (usize, usize) },
+    /// The input matrix holds a NaN or an infinite entry at (row, col)
+    NonFiniteInput { row: usize, col: usize },
+    /// Encoded bytes could not be decoded, the string names what is wrong
+    InvalidData { reason: &'static str },
+    /// A memory allocation failed
+    AllocFailed,
+}
+
+impl fmt::Display for CsvdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CsvdError::EmptyMatrix => write!(f, "matrix has no rows or no columns"),
+            CsvdError::WideMatrix { rows, cols } =>
+                write!(f, "matrix has more columns than rows ({} x {})", rows, cols),
+            CsvdError::DimensionMismatch { operand, expected, found } =>
+                write!(f, "{} must be {} x {}, found {} x {}", operand, expected.0, expected.1, found.0, found.1),
+            CsvdError::BufferTooSmall { operand, required, len } =>
+                write!(f, "{} must hold at least {} elements, found {}", operand, required, len),
+            CsvdError::InvalidArgument { name, value, max } =>
+                write!(f, "{} is {}, at most {} is allowed", name, value, max),
+            CsvdError::InvalidLeadingDimension { ld, cols } =>
+                write!(f, "leading dimension {} is smaller than the number of columns {}", ld, cols),
+            CsvdError::InvalidAlignment { alignment } =>
+                write!(f, "invalid alignment of {} bytes", alignment),
+            CsvdError::NotConverged { index, iterations } =>
+                write!(f, "QR iteration for singular value {} did not converge after {} iterations", index, iterations),
+            CsvdError::Cancelled { index, sweeps } =>
+                write!(f, "decomposition cancelled at singular value {} after {} sweeps", index, sweeps),
+            CsvdError::Singular { index } =>
+                write!(f, "matrix is singular, pivot {} is zero", index),
+            CsvdError::WorkspaceTooSmall { required, capacity } =>
+                write!(f, "workspace for {} x {} matrices cannot hold a {} x {} matrix", capacity.0, capacity.1, required.0, required.1),
+            CsvdError::NonFiniteInput { row, col } =>
+                write!(f, "matrix entry ({}, {}) is NaN or infinite", row, col),
+            CsvdError::InvalidData { reason } => write!(f, "invalid encoded data: {}", reason),
+            CsvdError::AllocFailed => write!(f, "memory allocation failed"),
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! C interface, so that C firmware and other languages can link the crate as a static
+//! library. The declarations for C are in include/csvd.h.
+//!
+//! Matrices are passed as a pointer to their m x n elements in row-major order, with the
+//! sizes of all other buffers following from m and n. A complex number is two consecutive
+//! reals, the real part first, which is the layout of num-complex's `Complex` (repr(C)) as
+//! well as of C99's `float _Complex` and `double _Complex`. Before anything is read or
+//! written, every pointer is checked to be non-null and aligned, every output not to
+//! overlap another buffer, and the buffer sizes to fit into the address space.
+//! Functions return `CSVD_OK` or one of the negative error codes below.
+//!
+//! Build the library with `cargo rustc --release --features ffi,std --crate-type staticlib`.
+//! Without `std`, the firmware's own crate has to provide the panic handler and allocator.
+
+use core::mem;
+use core::slice;
+use num_complex::{Complex, Complex32, Complex64};
+use super::csvd::csvd_in_slices;
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::scalar::Real;
+
+/// The call succeeded
+pub const CSVD_OK: i32 = 0;
+/// A pointer is null
+pub const CSVD_ERR_NULL_POINTER: i32 = -1;
+/// A pointer is not aligned for its element type
+pub const CSVD_ERR_MISALIGNED: i32 = -2;
+/// An output buffer overlaps another buffer
+pub const CSVD_ERR_OVERLAP: i32 = -3;
+/// The buffer sizes implied by the dimensions do not fit into the address space
+pub const CSVD_ERR_TOO_LARGE: i32 = -4;
+/// The computation panicked, only reported with the `std` feature, otherwise a panic aborts
+pub const CSVD_ERR_PANIC: i32 = -5;
+/// `CsvdError::EmptyMatrix`
+pub const CSVD_ERR_EMPTY_MATRIX: i32 = -10;
+/// `CsvdError::WideMatrix`
+pub const CSVD_ERR_WIDE_MATRIX: i32 = -11;
+/// `CsvdError::DimensionMismatch`
+pub const CSVD_ERR_DIMENSION_MISMATCH: i32 = -12;
+/// `CsvdError::BufferTooSmall`
+pub const CSVD_ERR_BUFFER_TOO_SMALL: i32 = -13;
+/// `CsvdError::InvalidArgument`
+pub const CSVD_ERR_INVALID_ARGUMENT: i32 = -14;
+/// `CsvdError::InvalidLeadingDimension`
+pub const CSVD_ERR_INVALID_LEADING_DIMENSION: i32 = -15;
+/// `CsvdError::InvalidAlignment`
+pub const CSVD_ERR_INVALID_ALIGNMENT: i32 = -16;
+/// `CsvdError::NotConverged`
+pub const CSVD_ERR_NOT_CONVERGED: i32 = -17;
+/// `CsvdError::Singular`
+pub const CSVD_ERR_SINGULAR: i32 = -18;
+/// `CsvdError::WorkspaceTooSmall`
+pub const CSVD_ERR_WORKSPACE_TOO_SMALL: i32 = -19;
+/// `CsvdError::InvalidData`
+pub const CSVD_ERR_INVALID_DATA: i32 = -20;
+/// `CsvdError::AllocFailed`
+pub const CSVD_ERR_ALLOC_FAILED: i32 = -21;
+/// `CsvdError::NonFiniteInput`
+pub const CSVD_ERR_NON_FINITE_INPUT: i32 = -22;
+/// `CsvdError::Cancelled`
+pub const CSVD_ERR_CANCELLED: i32 = -23;
+
+/// The code the C functions return for e
+pub fn error_code(e: &CsvdError) -> i32 {
+    match *e {
+        CsvdError::EmptyMatrix => CSVD_ERR_EMPTY_MATRIX,
+        CsvdError::WideMatrix { .. } => CSVD_ERR_WIDE_MATRIX,
+        CsvdError::DimensionMismatch { .. } => CSVD_ERR_DIMENSION_MISMATCH,
+        CsvdError::BufferTooSmall { .. } => CSVD_ERR_BUFFER_TOO_SMALL,
+        CsvdError::InvalidArgument { .. } => CSVD_ERR_INVALID_ARGUMENT,
+        CsvdError::InvalidLeadingDimension { .. } => CSVD_ERR_INVALID_LEADING_DIMENSION,
+        CsvdError::InvalidAlignment { .. } => CSVD_ERR_INVALID_ALIGNMENT,
+        CsvdError::NotConverged { .. } => CSVD_ERR_NOT_CONVERGED,
+        CsvdError::Singular { .. } => CSVD_ERR_SINGULAR,
+        CsvdError::WorkspaceTooSmall { .. } => CSVD_ERR_WORKSPACE_TOO_SMALL,
+        CsvdError::InvalidData { .. } => CSVD_ERR_INVALID_DATA,
+        CsvdError::AllocFailed => CSVD_ERR_ALLOC_FAILED,
+        CsvdError::NonFiniteInput { .. } => CSVD_ERR_NON_FINITE_INPUT,
+        CsvdError::Cancelled { .. } => CSVD_ERR_CANCELLED,
+    }
+}
+
+/// A static, NUL-terminated description of an error code
+#[no_mangle]
+pub extern "C" fn csvd_error_message(code: i32) -> *const u8 {
+    let message: &'static [u8] = match code {
+        CSVD_OK => b"success\0",
+        CSVD_ERR_NULL_POINTER => b"null pointer\0",
+        CSVD_ERR_MISALIGNED => b"misaligned pointer\0",
+        CSVD_ERR_OVERLAP => b"output buffer overlaps another buffer\0",
+        CSVD_ERR_TOO_LARGE => b"dimensions too large\0",
+        CSVD_ERR_PANIC => b"internal error\0",
+        CSVD_ERR_EMPTY_MATRIX => b"matrix has no rows or no columns\0",
+        CSVD_ERR_WIDE_MATRIX => b"matrix has more columns than rows\0",
+        CSVD_ERR_DIMENSION_MISMATCH => b"dimension mismatch\0",
+        CSVD_ERR_BUFFER_TOO_SMALL => b"buffer too small\0",
+        CSVD_ERR_INVALID_ARGUMENT => b"invalid argument\0",
+        CSVD_ERR_INVALID_LEADING_DIMENSION => b"invalid leading dimension\0",
+        CSVD_ERR_INVALID_ALIGNMENT => b"invalid alignment\0",
+        CSVD_ERR_NOT_CONVERGED => b"QR iteration did not converge\0",
+        CSVD_ERR_SINGULAR => b"matrix is singular\0",
+        CSVD_ERR_WORKSPACE_TOO_SMALL => b"workspace too small\0",
+        CSVD_ERR_INVALID_DATA => b"invalid encoded data\0",
+        CSVD_ERR_ALLOC_FAILED => b"memory allocation failed\0",
+        CSVD_ERR_NON_FINITE_INPUT => b"matrix holds a NaN or infinite entry\0",
+        CSVD_ERR_CANCELLED => b"decomposition cancelled\0",
+        _ => b"unknown error code\0",
+    };
+    message.as_ptr()
+}
+
+/// Number of reals the scratch buffer of `csvd_svd_c32` and `csvd_svd_c64` needs
+#[no_mangle]
+pub extern "C" fn csvd_scratch_len(m: usize, n: usize) -> usize {
+    m.min(n).saturating_mul(3)
+}
+
+/// Decomposes the m x n matrix a into U S V*, without allocating.
+///
+/// # Safety
+///
+/// a has to point to m n elements, s to min(m, n), u to m m and v to n n elements, and
+/// scratch to `csvd_scratch_len(m, n)` reals, all valid for reads and writes. a is overwritten.
+#[no_mangle]
+pub unsafe extern "C" fn csvd_svd_c32(a: *mut Complex32, m: usize, n: usize, s: *mut f32,
+        u: *mut Complex32, v: *mut Complex32, scratch: *mut f32) -> i32 {
+    guard(|| svd_raw(a, m, n, s, u, v, scratch))
+}
+
+/// Same as `csvd_svd_c32` in double precision
+///
+/// # Safety
+///
+/// The same as for `csvd_svd_c32`.
+#[no_mangle]
+pub unsafe extern "C" fn csvd_svd_c64(a: *mut Complex64, m: usize, n: usize, s: *mut f64,
+        u: *mut Complex64, v: *mut Complex64, scratch: *mut f64) -> i32 {
+    guard(|| svd_raw(a, m, n, s, u, v, scratch))
+}
+
+/// Writes the n x m pseudo-inverse of the m x n matrix a to inv, like `pinv`.
+/// a is left unchanged, the decomposition works on an allocated copy.
+///
+/// # Safety
+///
+/// a has to point to m n elements valid for reads and inv to n m elements valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csvd_pinv_c32(a: *const Complex32, m: usize, n: usize, inv: *mut Complex32) -> i32 {
+    guard(|| pinv_raw(a, m, n, inv))
+}
+
+/// Same as `csvd_pinv_c32` in double precision
+///
+/// # Safety
+///
+/// The same as for `csvd_pinv_c32`.
+#[no_mangle]
+pub unsafe extern "C" fn csvd_pinv_c64(a: *const Complex64, m: usize, n: usize, inv: *mut Complex64) -> i32 {
+    guard(|| pinv_raw(a, m, n, inv))
+}
+
+/// Runs f, turning a panic into `CSVD_ERR_PANIC` where unwinding is available, as it
+/// must not cross the C boundary
+fn guard<F: FnOnce() -> i32>(f: F) -> i32 {
+    #[cfg(feature = "std")]
+    {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or(CSVD_ERR_PANIC)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        f()
+    }
+}
+
+/// The byte range of a buffer of len elements at ptr, after checking that ptr is non-null
+/// and aligned and that the buffer fits into the address space
+fn region<T>(ptr: *const T, len: usize) -> Result<(usize, usize), i32> {
+    if ptr.is_null() {
+        return Err(CSVD_ERR_NULL_POINTER);
+    }
+    let start = ptr as usize;
+    if !ptr.is_aligned() {
+        return Err(CSVD_ERR_MISALIGNED);
+    }
+    let bytes = len.checked_mul(mem::size_of::<T>()).ok_or(CSVD_ERR_TOO_LARGE)?;
+    if bytes > isize::MAX as usize {
+        return Err(CSVD_ERR_TOO_LARGE);
+    }
+    let end = start.checked_add(bytes).ok_or(CSVD_ERR_TOO_LARGE)?;
+    Ok((start, end))
+}
+
+/// Checks that none of the outputs overlaps another buffer, inputs may overlap each other
+fn disjoint(outputs: &[(usize, usize)], inputs: &[(usize, usize)]) -> Result<(), i32> {
+    for (i, out) in outputs.iter().enumerate() {
+        for other in outputs[i + 1..].iter().chain(inputs) {
+            if out.0 < other.1 && other.0 < out.1 {
+                return Err(CSVD_ERR_OVERLAP);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// m n, or an error if a dimension is zero or the product overflows
+fn elements(m: usize, n: usize) -> Result<usize, i32> {
+    if m == 0 || n == 0 {
+        return Err(CSVD_ERR_EMPTY_MATRIX);
+    }
+    m.checked_mul(n).ok_or(CSVD_ERR_TOO_LARGE)
+}
+
+unsafe fn svd_raw<T: Real>(a: *mut Complex<T>, m: usize, n: usize, s: *mut T,
+        u: *mut Complex<T>, v: *mut Complex<T>, scratch: *mut T) -> i32 {
+    let checked = || -> Result<(), i32> {
+        let a_len = elements(m, n)?;
+        let u_len = elements(m, m)?;
+        let v_len = elements(n, n)?;
+        let s_len = m.min(n);
+        let scratch_len = csvd_scratch_len(m, n);
+
+        disjoint(&[
+            region(a, a_len)?,
+            region(s, s_len)?,
+            region(u, u_len)?,
+            region(v, v_len)?,
+            region(scratch, scratch_len)?,
+        ], &[])?;
+
+        csvd_in_slices(slice::from_raw_parts_mut(a, a_len), slice::from_raw_parts_mut(s, s_len),
+            slice::from_raw_parts_mut(u, u_len), slice::from_raw_parts_mut(v, v_len),
+            slice::from_raw_parts_mut(scratch, scratch_len), m, n)
+            .map_err(|e| error_code(&e))
+    };
+    checked().err().unwrap_or(CSVD_OK)
+}
+
+unsafe fn pinv_raw<T: Real>(a: *const Complex<T>, m: usize, n: usize, inv: *mut Complex<T>) -> i32 {
+    let checked = || -> Result<(), i32> {
+        let len = elements(m, n)?;
+        disjoint(&[region(inv, len)?], &[region(a, len)?])?;
+
+        let mut input = Matrix::from_vec(slice::from_raw_parts(a, len).to_vec(), m, n).map_err(|e| error_code(&e))?;
+        let mut inverse = Matrix::zeros(n, m);
+        super::pinv(&mut input, &mut inverse).map_err(|e| error_code(&e))?;
+
+        slice::from_raw_parts_mut(inv, len).copy_from_slice(inverse.as_slice());
+        Ok(())
+    };
+    checked().err().unwrap_or(CSVD_OK)
+}
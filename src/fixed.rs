@@ -0,0 +1,146 @@
+use num_complex::Complex;
+use super::csvd::{cabs, csvd_in_slices};
+use super::error::CsvdError;
+use super::scalar::Real;
+use super::PinvOptions;
+
+/// The singular value decomposition A = U S V* of an M x N matrix A whose size is known
+/// at compile time, stored entirely in arrays so that it can live on the stack
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedSvd<T, const M: usize, const N: usize> {
+    /// M x M unitary matrix whose columns are the left singular vectors, row-major
+    pub u: [[Complex<T>; M]; M],
+    /// the singular values in descending order, only the first min(M, N) are meaningful
+    /// and the rest are zero
+    pub s: [T; N],
+    /// N x N unitary matrix whose columns are the right singular vectors, row-major
+    pub v: [[Complex<T>; N]; N],
+}
+
+/// Computes the full singular value decomposition of a fixed-size M x N matrix,
+/// e.g. a 2x2 to 8x8 MIMO channel matrix, without touching the heap.
+/// a is overwritten.
+pub fn svd_fixed<T: Real, const M: usize, const N: usize>(a: &mut [[Complex<T>; N]; M])
+        -> Result<FixedSvd<T, M, N>, CsvdError> {
+    let zero = Complex::new(T::zero(), T::zero());
+
+    let mut res = FixedSvd {
+        u: [[zero; M]; M],
+        s: [T::zero(); N],
+        v: [[zero; N]; N],
+    };
+    // the work arrays B, C and T, min(M, N) <= N elements each
+    let mut scratch = [[T::zero(); N]; 3];
+
+    csvd_in_slices(a.as_flattened_mut(), &mut res.s, res.u.as_flattened_mut(), res.v.as_flattened_mut(),
+        scratch.as_flattened_mut(), M, N)?;
+
+    Ok(res)
+}
+
+/// Inverse of a 2 x 2 matrix from the adjugate formula A^-1 = adj(A) / det(A), which takes a
+/// handful of operations instead of a decomposition, e.g. for the channel matrix of a 2 x 2
+/// MIMO subcarrier. A singular or ill-conditioned matrix, whose determinant loses too many
+/// digits to cancellation, gets the pseudo-inverse from `svd_fixed` instead, see `inv3`.
+pub fn inv2<T: Real>(a: &[[Complex<T>; 2]; 2]) -> Result<[[Complex<T>; 2]; 2], CsvdError> {
+    let (scaled, amax) = scale_fixed(a)?;
+    let [[p, q], [r, t]] = scaled;
+    let adj = [[t, -q], [-r, p]];
+    let det = p * t - q * r;
+
+    finish_inverse(a, &scaled, adj, det, amax)
+}
+
+/// Inverse of a 3 x 3 matrix from the adjugate formula A^-1 = adj(A) / det(A).
+///
+/// The result is checked with the 1-norm condition number ||A||_1 ||A^-1||_1, which costs
+/// nothing once the inverse is known. If it exceeds 1 / sqrt(eps), about 7e7 for f64 and
+/// 3e3 for f32, or A is singular, the inverse is replaced by the pseudo-inverse from the
+/// singular value decomposition with the default `PinvOptions`, which stays accurate
+/// and finite. The entries are scaled before the determinant is formed, so it neither
+/// overflows nor underflows. a is left untouched.
+pub fn inv3<T: Real>(a: &[[Complex<T>; 3]; 3]) -> Result<[[Complex<T>; 3]; 3], CsvdError> {
+    let (scaled, amax) = scale_fixed(a)?;
+    let zero = Complex::new(T::zero(), T::zero());
+
+    // cofactor (i, j) from the cyclic 2 x 2 minor, which carries its sign, is entry (j, i) of adj(A)
+    let mut adj = [[zero; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            let (i1, i2, j1, j2) = ((i + 1) % 3, (i + 2) % 3, (j + 1) % 3, (j + 2) % 3);
+            adj[j][i] = scaled[i1][j1] * scaled[i2][j2] - scaled[i1][j2] * scaled[i2][j1];
+        }
+    }
+    let det = (0..3).fold(zero, |acc, j| acc + scaled[0][j] * adj[j][0]);
+
+    finish_inverse(a, &scaled, adj, det, amax)
+}
+
+/// A row-major N x N matrix in an array
+type Square<T, const N: usize> = [[Complex<T>; N]; N];
+
+/// a divided by its largest real or imaginary part, and that part, which is one for a zero a
+fn scale_fixed<T: Real, const N: usize>(a: &Square<T, N>) -> Result<(Square<T, N>, T), CsvdError> {
+    let mut amax = T::zero();
+    for i in 0..N {
+        for j in 0..N {
+            let x = a[i][j];
+            if !(x.re.is_finite() && x.im.is_finite()) {
+                return Err(CsvdError::NonFiniteInput { row: i, col: j });
+            }
+            amax = amax.max(x.re.abs()).max(x.im.abs());
+        }
+    }
+    if amax == T::zero() {
+        amax = T::one();
+    }
+
+    let mut scaled = *a;
+    for row in scaled.iter_mut() {
+        for x in row.iter_mut() {
+            *x = *x / amax;
+        }
+    }
+    Ok((scaled, amax))
+}
+
+/// adj / det, scaled back, or the pseudo-inverse of a if the condition check of `inv3` fails
+fn finish_inverse<T: Real, const N: usize>(a: &Square<T, N>, scaled: &Square<T, N>, adj: Square<T, N>,
+        det: Complex<T>, amax: T) -> Result<Square<T, N>, CsvdError> {
+    let norm1 = |x: &Square<T, N>| (0..N).map(|j| (0..N).fold(T::zero(), |acc, i| acc + cabs(&x[i][j]))).fold(T::zero(), T::max);
+
+    if det != Complex::new(T::zero(), T::zero()) {
+        let mut inv = adj;
+        for row in inv.iter_mut() {
+            for x in row.iter_mut() {
+                *x = *x / det;
+            }
+        }
+        let cond = norm1(scaled) * norm1(&inv);
+        if cond.is_finite() && cond * T::epsilon().sqrt() <= T::one() {
+            for row in inv.iter_mut() {
+                for x in row.iter_mut() {
+                    *x = *x / amax;
+                }
+            }
+            return Ok(inv);
+        }
+    }
+
+    trace!("adjugate inverse of a {} x {} matrix is ill-conditioned, falling back to the pseudo-inverse", N, N);
+    let mut work = *a;
+    let res = svd_fixed(&mut work)?;
+    let cutoff = PinvOptions::default().cutoff(res.s[0], N, N);
+    let mut inv = [[Complex::new(T::zero(), T::zero()); N]; N];
+    for k in 0..N {
+        if res.s[k] > cutoff {
+            let s_inv = T::one() / res.s[k];
+            for i in 0..N {
+                for j in 0..N {
+                    inv[i][j] = inv[i][j] + res.v[i][k] * s_inv * res.u[j][k].conj();
+                }
+            }
+        }
+    }
+    Ok(inv)
+}
@@ -0,0 +1,109 @@
+//! Test matrices whose singular values are known analytically, so that a decomposition
+//! can be checked against the exact spectrum instead of only against its own round trip.
+
+use alloc::vec::Vec;
+use num_complex::Complex;
+use super::blas::{gemm, Op};
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::random::random_unitary;
+use super::scalar::Real;
+
+/// The n x n Hilbert matrix H(i, j) = 1 / (i + j + 1), the classic ill-conditioned matrix.
+///
+/// Its singular values have no closed form, but as H is positive definite they are its
+/// eigenvalues, and their product is det(H) = c(n)^4 / c(2n) with c(k) = 0! 1! ... (k-1)!.
+/// The condition number grows like e^(3.5 n), so beyond n = 12 even f64 loses all digits.
+pub fn hilbert<T: Real>(n: usize) -> Matrix<Complex<T>> {
+    let mut a = Matrix::zeros(n, n);
+    for i in 0..n {
+        for j in 0..n {
+            a[(i, j)] = Complex::new(T::one() / T::from(i + j + 1).unwrap(), T::zero());
+        }
+    }
+    a
+}
+
+/// The m x n Vandermonde matrix V(i, j) = nodes[i]^j of the m = nodes.len() nodes.
+///
+/// For the m-th roots of unity as nodes and n <= m, the columns are orthogonal with norm
+/// sqrt(m), so all n singular values are sqrt(m). For n = m this is `dft(m)` up to the
+/// order of the nodes.
+pub fn vandermonde<T: Real>(nodes: &[Complex<T>], n: usize) -> Matrix<Complex<T>> {
+    let mut a = Matrix::zeros(nodes.len(), n);
+    for (i, &x) in nodes.iter().enumerate() {
+        let mut power = Complex::new(T::one(), T::zero());
+        for j in 0..n {
+            a[(i, j)] = power;
+            power = power * x;
+        }
+    }
+    a
+}
+
+/// The n x n matrix of the unnormalized discrete Fourier transform, F(j, k) = w^(j k) with
+/// w = exp(-2 pi i / n). F* F = n I, so all n singular values are sqrt(n).
+pub fn dft<T: Real>(n: usize) -> Matrix<Complex<T>> {
+    let mut a = Matrix::zeros(n, n);
+    let step = -T::PI() * T::from(2).unwrap() / T::from(n.max(1)).unwrap();
+
+    for j in 0..n {
+        for k in 0..n {
+            // reducing j k modulo n keeps the angle, and so its rounding error, small
+            let angle = step * T::from((j * k) % n).unwrap();
+            a[(j, k)] = Complex::new(angle.cos(), angle.sin());
+        }
+    }
+    a
+}
+
+/// An m x n matrix with exactly the singular values s, which may be in any order and
+/// contain zeros, and random singular vectors, A = U(:, 0..r) diag(s) V(:, 0..r)*
+/// with r = s.len() <= min(m, n) and Haar-distributed unitary U and V.
+pub fn with_singular_values<T: Real>(m: usize, n: usize, s: &[T], seed: u64) -> Result<Matrix<Complex<T>>, CsvdError> {
+    let r = s.len();
+    if r > m.min(n) {
+        return Err(CsvdError::InvalidArgument { name: "s", value: r, max: m.min(n) });
+    }
+
+    let u = random_unitary::<T>(m, seed);
+    let v = random_unitary::<T>(n, seed.wrapping_add(1));
+
+    let mut us = Matrix::zeros(m, r);
+    let mut v_r = Matrix::zeros(n, r);
+    for k in 0..r {
+        for i in 0..m {
+            us[(i, k)] = u[(i, k)] * s[k];
+        }
+        for i in 0..n {
+            v_r[(i, k)] = v[(i, k)];
+        }
+    }
+
+    let mut a = Matrix::zeros(m, n);
+    let one = Complex::new(T::one(), T::zero());
+    gemm(one, Op::None, &us, Op::ConjugateTranspose, &v_r, Complex::new(T::zero(), T::zero()), &mut a)?;
+    Ok(a)
+}
+
+/// An m x n matrix of rank `rank`, whose nonzero singular values are rank, rank - 1, ..., 1
+pub fn rank_deficient<T: Real>(m: usize, n: usize, rank: usize, seed: u64) -> Result<Matrix<Complex<T>>, CsvdError> {
+    let s: Vec<T> = (0..rank).map(|k| T::from(rank - k).unwrap()).collect();
+    with_singular_values(m, n, &s, seed)
+}
+
+/// The n x n graded matrix A = diag(1, g, g^2, ..., g^(n-1)) Q with a random unitary Q,
+/// whose rows shrink geometrically for |g| < 1. A A* = diag(|g|^(2i)), so the singular
+/// values are exactly 1, |g|, ..., |g|^(n-1), spanning many orders of magnitude.
+pub fn graded<T: Real>(n: usize, g: T, seed: u64) -> Matrix<Complex<T>> {
+    let mut a = random_unitary::<T>(n, seed);
+
+    let mut scale = T::one();
+    for i in 0..n {
+        for x in a.row_mut(i) {
+            *x = *x * scale;
+        }
+        scale = scale * g;
+    }
+    a
+}
@@ -0,0 +1,195 @@
+//! Streaming singular value decomposition of a matrix that arrives one row at a time.
+//!
+//! With the top-k factorization A = U S V* of the rows seen so far, a new row r splits into
+//! its part r V V* in the tracked subspace and the remainder e = r - r V V* with norm rho,
+//! and with p = e* / rho the extended matrix is
+//!
+//! ```text
+//! [A]   [U 0] [S    0  ]
+//! [r] = [0 1] [r V  rho] [V p]*
+//! ```
+//!
+//! whose (k + 1) x (k + 1) core is diagonal plus rank one and is decomposed by the same
+//! secular equations as `SvdResult::rank_one_update`, see Brand, "Incremental singular
+//! value decomposition of uncertain data with missing values". Afterwards only the k
+//! largest singular values are kept, so for data of rank above k the result is an
+//! approximation whose error grows with the discarded singular values.
+
+use alloc::vec::Vec;
+use num_complex::Complex;
+use super::csvd::{norm2, normalize_phases};
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::scalar::Real;
+use super::update::core_svd;
+
+/// The top-k singular value decomposition of a growing m x n matrix, updated row by row,
+/// e.g. for tracking the signal subspace of an antenna array snapshot by snapshot.
+/// Every row costs O((m + n) k^2) with the left singular vectors and O(n k^2) without.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IncrementalSvd<T> {
+    /// number of columns of every row
+    cols: usize,
+    /// number of singular values kept at most
+    k: usize,
+    /// number of rows ingested
+    rows: usize,
+    /// rows x rank left singular vectors, None if they are not tracked
+    u: Option<Matrix<Complex<T>>>,
+    /// the rank <= k largest singular values in descending order
+    s: Vec<T>,
+    /// cols x rank right singular vectors
+    v: Matrix<Complex<T>>,
+}
+
+impl<T: Real> IncrementalSvd<T> {
+    /// Creates an empty decomposition of rows with cols elements that keeps at most k
+    /// singular values. Without `keep_u` the left singular vectors, which grow by a row
+    /// with every row, are not tracked, which is all subspace tracking needs.
+    pub fn new(cols: usize, k: usize, keep_u: bool) -> Result<IncrementalSvd<T>, CsvdError> {
+        if cols < 1 {
+            return Err(CsvdError::EmptyMatrix);
+        }
+        if k < 1 || k > cols {
+            return Err(CsvdError::InvalidArgument { name: "k", value: k, max: cols });
+        }
+
+        Ok(IncrementalSvd {
+            cols,
+            k,
+            rows: 0,
+            u: if keep_u { Some(Matrix::zeros(0, 0)) } else { None },
+            s: Vec::new(),
+            v: Matrix::zeros(cols, 0),
+        })
+    }
+
+    /// Number of rows ingested so far
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns of every row
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Number of singular values kept, at most k and at most the rank of the rows so far
+    pub fn rank(&self) -> usize {
+        self.s.len()
+    }
+
+    /// The tracked singular values in descending order
+    pub fn singular_values(&self) -> &[T] {
+        &self.s
+    }
+
+    /// The rows x rank left singular vectors, None if they are not tracked
+    pub fn u(&self) -> Option<&Matrix<Complex<T>>> {
+        self.u.as_ref()
+    }
+
+    /// The cols x rank right singular vectors, an orthonormal basis of the dominant
+    /// subspace of the rows
+    pub fn v(&self) -> &Matrix<Complex<T>> {
+        &self.v
+    }
+
+    /// Appends the row to the decomposed matrix
+    pub fn push_row(&mut self, row: &[Complex<T>]) -> Result<(), CsvdError> {
+        let n = self.cols;
+        let r = self.s.len();
+        let zero = Complex::new(T::zero(), T::zero());
+
+        if row.len() != n {
+            return Err(CsvdError::DimensionMismatch { operand: "row", expected: (1, n), found: (1, row.len()) });
+        }
+        if let Some(j) = row.iter().position(|x| !(x.re.is_finite() && x.im.is_finite())) {
+            return Err(CsvdError::NonFiniteInput { row: self.rows, col: j });
+        }
+
+        // c = row V and e = row - c V*, projected twice for orthogonality to working precision
+        let mut c = alloc::vec![zero; r];
+        let mut e = row.to_vec();
+        for _ in 0..2 {
+            for j in 0..r {
+                let cj = (0..n).fold(zero, |acc, i| acc + e[i] * self.v[(i, j)]);
+                for i in 0..n {
+                    e[i] = e[i] - cj * self.v[(i, j)].conj();
+                }
+                c[j] = c[j] + cj;
+            }
+        }
+        let rho = norm2(e.iter().copied());
+
+        // a row inside the subspace, up to rounding errors, does not extend it
+        let grow = rho > T::epsilon() * T::from(n).unwrap() * norm2(row.iter().copied());
+        let mut b: Vec<Complex<T>> = c.iter().map(|c| c.conj()).collect();
+        if grow {
+            b.push(Complex::new(rho, T::zero()));
+        }
+        if b.is_empty() {
+            // a zero row before any other
+            self.rows += 1;
+            if let Some(u) = self.u.as_mut() {
+                *u = Matrix::zeros(self.rows, 0);
+            }
+            return Ok(());
+        }
+
+        // the core is S with the row c (rho) appended, S + e_r b* with e_r the last unit vector
+        let mut a = alloc::vec![zero; r + 1];
+        a[r] = Complex::new(T::one(), T::zero());
+        let (uk, sigma, vk) = core_svd(&self.s, &a, &b);
+        let rank = b.len().min(self.k);
+
+        let mut v = Matrix::zeros(n, rank);
+        for i in 0..n {
+            // the new direction is e* / rho
+            let p = if grow { e[i].conj() / rho } else { zero };
+            for j in 0..rank {
+                let sum = (0..r).fold(zero, |acc, l| acc + self.v[(i, l)] * vk[(l, j)]);
+                v[(i, j)] = if grow { sum + p * vk[(r, j)] } else { sum };
+            }
+        }
+
+        let m = self.rows;
+        if let Some(u) = self.u.as_mut() {
+            let mut next = Matrix::zeros(m + 1, rank);
+            for i in 0..m {
+                for j in 0..rank {
+                    next[(i, j)] = (0..r).fold(zero, |acc, l| acc + u[(i, l)] * uk[(l, j)]);
+                }
+            }
+            for j in 0..rank {
+                next[(m, j)] = uk[(r, j)];
+            }
+            *u = next;
+        }
+
+        self.rows += 1;
+        self.s = sigma[..rank].to_vec();
+        self.v = v;
+
+        match self.u.as_mut() {
+            Some(u) => normalize_phases(u.as_mut_slice(), rank, m + 1, rank, self.v.as_mut_slice(), rank, n, rank),
+            None => normalize_phases(&mut [], 0, 0, 0, self.v.as_mut_slice(), rank, n, rank),
+        }
+        Ok(())
+    }
+
+    /// Appends all rows of the matrix, in order
+    pub fn push_rows(&mut self, rows: &Matrix<Complex<T>>) -> Result<(), CsvdError> {
+        if rows.cols() != self.cols {
+            return Err(CsvdError::DimensionMismatch { operand: "rows", expected: (rows.rows(), self.cols), found: (rows.rows(), rows.cols()) });
+        }
+
+        let mut row = Vec::with_capacity(self.cols);
+        for i in 0..rows.rows() {
+            row.clear();
+            row.extend((0..self.cols).map(|j| rows[(i, j)]));
+            self.push_row(&row)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! Ingestion of raw IQ samples from SDR front-ends.
+//!
+//! Radios deliver baseband samples as interleaved in-phase and quadrature values: signed
+//! 16 bit pairs from most ADCs, 8 bit pairs from low-cost receivers, or f32 pairs from
+//! software that already converted them. These helpers turn such buffers into `Complex32`
+//! samples in one pass, scaled on the way, either into a caller-provided slice, a `Matrix`
+//! or an `AlignedVec` for `csvd_in_slices` and `MatrixViewMut`. f32 pairs in the layout of
+//! `Complex32` need no conversion at all, `pairs_as_complex` reinterprets them in place.
+//! Channel estimates in Q15 fixed point convert with `q15_into`, or decompose directly
+//! with `svd_q15`.
+
+use alloc::vec::Vec;
+use core::slice;
+use num_complex::{Complex, Complex32};
+use super::aligned::{aligned_vec_uninit, AlignedVec};
+use super::error::CsvdError;
+use super::matrix::Matrix;
+
+/// A raw sample format of a radio front-end
+pub trait IqSample: Copy {
+    /// The magnitude of a full-scale sample, so that a scale of `1.0 / FULL_SCALE` maps the
+    /// samples to [-1, 1)
+    const FULL_SCALE: f32;
+
+    /// The sample as an f32, without scaling
+    fn to_f32(self) -> f32;
+}
+
+impl IqSample for i8 {
+    const FULL_SCALE: f32 = 128.0;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+}
+
+impl IqSample for i16 {
+    const FULL_SCALE: f32 = 32768.0;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+}
+
+impl IqSample for f32 {
+    const FULL_SCALE: f32 = 1.0;
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+}
+
+/// The number of complex samples in raw, which has to hold whole I/Q pairs
+fn sample_count<S: IqSample>(raw: &[S]) -> Result<usize, CsvdError> {
+    if !raw.len().is_multiple_of(2) {
+        return Err(CsvdError::InvalidData { reason: "interleaved IQ buffer holds an odd number of values" });
+    }
+    Ok(raw.len() / 2)
+}
+
+/// Converts the interleaved I/Q values raw, I first, into the samples (I + jQ) * scale at the
+/// start of out and returns their number, raw.len() / 2. For `[f32; 2]` pairs, pass
+/// `pairs.as_flattened()`.
+pub fn iq_into<S: IqSample>(raw: &[S], scale: f32, out: &mut [Complex32]) -> Result<usize, CsvdError> {
+    let len = sample_count(raw)?;
+    if out.len() < len {
+        return Err(CsvdError::BufferTooSmall { operand: "out", required: len, len: out.len() });
+    }
+
+    for (x, iq) in out.iter_mut().zip(raw.chunks_exact(2)) {
+        *x = Complex32::new(iq[0].to_f32() * scale, iq[1].to_f32() * scale);
+    }
+    Ok(len)
+}
+
+/// Converts the Q15 fixed-point samples a, whose parts count in units of 2^-15, into the
+/// samples a / 2^15 * scale at the start of out and returns their number, a.len()
+pub fn q15_into(a: &[Complex<i16>], scale: f32, out: &mut [Complex32]) -> Result<usize, CsvdError> {
+    if out.len() < a.len() {
+        return Err(CsvdError::BufferTooSmall { operand: "out", required: a.len(), len: out.len() });
+    }
+
+    let scale = scale / i16::FULL_SCALE;
+    for (x, q) in out.iter_mut().zip(a) {
+        *x = Complex32::new(q.re as f32 * scale, q.im as f32 * scale);
+    }
+    Ok(a.len())
+}
+
+/// The tightly packed, row-major rows x cols matrix of the first rows * cols samples of
+/// raw, e.g. one snapshot per row from an array of cols antennas, see `iq_into`
+pub fn iq_matrix<S: IqSample>(raw: &[S], rows: usize, cols: usize, scale: f32) -> Result<Matrix<Complex32>, CsvdError> {
+    // a shape whose sample count overflows needs more than any buffer holds
+    let required = rows.checked_mul(cols).and_then(|n| n.checked_mul(2))
+        .ok_or(CsvdError::BufferTooSmall { operand: "raw", required: usize::MAX, len: raw.len() })?;
+    if raw.len() < required {
+        return Err(CsvdError::BufferTooSmall { operand: "raw", required, len: raw.len() });
+    }
+    let len = required / 2;
+
+    let mut data = Vec::new();
+    data.try_reserve_exact(len).map_err(|_| CsvdError::AllocFailed)?;
+    data.extend(raw[..2 * len].chunks_exact(2).map(|iq| Complex32::new(iq[0].to_f32() * scale, iq[1].to_f32() * scale)));
+    Matrix::from_vec(data, rows, cols)
+}
+
+/// The samples of raw in a buffer aligned to alignment bytes, converted without zeroing
+/// the buffer first, see `iq_into` and `aligned_vec`
+pub fn iq_aligned<S: IqSample>(raw: &[S], alignment: usize, scale: f32) -> Result<AlignedVec<Complex32>, CsvdError> {
+    let len = sample_count(raw)?;
+    let buffer = aligned_vec_uninit::<Complex32>(len, alignment)?;
+    Ok(buffer.init_with(|k| Complex32::new(raw[2 * k].to_f32() * scale, raw[2 * k + 1].to_f32() * scale)))
+}
+
+/// f32 I/Q pairs as complex samples, without copying
+pub fn pairs_as_complex(pairs: &[[f32; 2]]) -> &[Complex32] {
+    // Complex32 is repr(C) with re followed by im, the layout of [f32; 2]
+    unsafe { slice::from_raw_parts(pairs.as_ptr() as *const Complex32, pairs.len()) }
+}
+
+/// `pairs_as_complex` for a mutable buffer, e.g. to scale the samples in place with `scal`
+pub fn pairs_as_complex_mut(pairs: &mut [[f32; 2]]) -> &mut [Complex32] {
+    unsafe { slice::from_raw_parts_mut(pairs.as_mut_ptr() as *mut Complex32, pairs.len()) }
+}
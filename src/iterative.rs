@@ -0,0 +1,1102 @@
+//! Singular values from matrix-vector products alone.
+//!
+//! These methods touch A only through the products A x and A* y, so they cost O(m n) per
+//! step for a dense A and never factorize it, which makes them the cheap choice when only
+//! the largest singular values are needed, e.g. the norm of A for a normalization or a
+//! step size, and the only choice for operators too large to store, see `LinearOperator`.
+//! `cg`, `gmres`, `bicgstab`, `cgls` and `lsqr` solve linear systems and least-squares
+//! problems with the same products, for dense matrices, a `CsrMatrix` or closures in an
+//! `FnOperator` alike. `sigma_min` is the exception that factorizes A once, as the
+//! smallest singular value is out of reach of products with A alone.
+
+use alloc::vec::Vec;
+use core::ops::ControlFlow;
+use num_complex::Complex;
+use super::csvd::{cabs, check_finite, norm2, normalize_phases};
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::random::Xoshiro256;
+use super::scalar::Real;
+use super::qr::qr;
+use super::svd::{singular_values, svd, SvdResult};
+
+/// Seed of the start vector of the iterations, fixed so that the results are reproducible
+const START_SEED: u64 = 0x5eed;
+
+/// Inverse iterations of `sigma_min` before it falls back to the full decomposition
+const SIGMA_MIN_ITERATIONS: usize = 50;
+
+/// An m x n matrix A that is only ever applied to vectors, e.g. a sparse matrix, a
+/// product of factors or a fast transform that would be too large to store densely
+pub trait LinearOperator<T: Real> {
+    /// Number of rows m, the length of A x
+    fn rows(&self) -> usize;
+
+    /// Number of columns n, the length of x
+    fn cols(&self) -> usize;
+
+    /// y = A x for x with n and y with m elements
+    fn apply(&self, x: &[Complex<T>], y: &mut [Complex<T>]);
+
+    /// x = A* y for y with m and x with n elements
+    fn apply_adjoint(&self, y: &[Complex<T>], x: &mut [Complex<T>]);
+
+    /// The dimensions (m, n)
+    fn dims(&self) -> (usize, usize) {
+        (self.rows(), self.cols())
+    }
+}
+
+impl<T: Real> LinearOperator<T> for Matrix<Complex<T>> {
+    fn rows(&self) -> usize {
+        Matrix::rows(self)
+    }
+
+    fn cols(&self) -> usize {
+        Matrix::cols(self)
+    }
+
+    fn apply(&self, x: &[Complex<T>], y: &mut [Complex<T>]) {
+        for (i, y) in y.iter_mut().enumerate() {
+            *y = x.iter().enumerate().fold(Complex::new(T::zero(), T::zero()), |acc, (j, x)| acc + self[(i, j)] * x);
+        }
+    }
+
+    fn apply_adjoint(&self, y: &[Complex<T>], x: &mut [Complex<T>]) {
+        for (j, x) in x.iter_mut().enumerate() {
+            *x = y.iter().enumerate().fold(Complex::new(T::zero(), T::zero()), |acc, (i, y)| acc + self[(i, j)].conj() * y);
+        }
+    }
+}
+
+/// An operator given by two closures, one computing y = A x and one x = A* y, e.g. for a
+/// fast transform or a product of factors that is never multiplied out. `cg` and `gmres`
+/// only ever call the first.
+pub struct FnOperator<F, G> {
+    rows: usize,
+    cols: usize,
+    apply: F,
+    apply_adjoint: G,
+}
+
+impl<F, G> FnOperator<F, G> {
+    /// The rows x cols operator whose products `apply(x, y)` and `apply_adjoint(y, x)`
+    /// overwrite their second argument
+    pub fn new(rows: usize, cols: usize, apply: F, apply_adjoint: G) -> FnOperator<F, G> {
+        FnOperator { rows, cols, apply, apply_adjoint }
+    }
+}
+
+impl<T, F, G> LinearOperator<T> for FnOperator<F, G>
+where
+    T: Real,
+    F: Fn(&[Complex<T>], &mut [Complex<T>]),
+    G: Fn(&[Complex<T>], &mut [Complex<T>]),
+{
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn apply(&self, x: &[Complex<T>], y: &mut [Complex<T>]) {
+        (self.apply)(x, y);
+    }
+
+    fn apply_adjoint(&self, y: &[Complex<T>], x: &mut [Complex<T>]) {
+        (self.apply_adjoint)(y, x);
+    }
+}
+
+/// A preconditioner M, an approximation of A whose systems M z = r are cheap to solve, for
+/// `gmres_preconditioned` and `bicgstab_preconditioned`. Closures `|r, z| ...` that
+/// overwrite z are preconditioners as well, e.g. a few sweeps of an incomplete factorization.
+pub trait Preconditioner<T: Real> {
+    /// z = M^-1 r for r and z with n elements
+    fn solve(&self, r: &[Complex<T>], z: &mut [Complex<T>]);
+}
+
+impl<T: Real, F: Fn(&[Complex<T>], &mut [Complex<T>])> Preconditioner<T> for F {
+    fn solve(&self, r: &[Complex<T>], z: &mut [Complex<T>]) {
+        self(r, z);
+    }
+}
+
+/// The Jacobi preconditioner M = diag(A), which evens out the scaling of the rows of A
+#[derive(Clone, Debug, PartialEq)]
+pub struct JacobiPreconditioner<T> {
+    /// the reciprocals of the diagonal
+    inverse: Vec<Complex<T>>,
+}
+
+impl<T: Real> JacobiPreconditioner<T> {
+    /// The preconditioner for the diagonal of A, e.g. from `CsrMatrix::diagonal`,
+    /// which fails with `CsvdError::Singular` at its first zero
+    pub fn new(diagonal: &[Complex<T>]) -> Result<JacobiPreconditioner<T>, CsvdError> {
+        let zero = Complex::new(T::zero(), T::zero());
+        if let Some(index) = diagonal.iter().position(|&d| d == zero) {
+            return Err(CsvdError::Singular { index });
+        }
+        Ok(JacobiPreconditioner { inverse: diagonal.iter().map(|d| d.inv()).collect() })
+    }
+}
+
+impl<T: Real> Preconditioner<T> for JacobiPreconditioner<T> {
+    fn solve(&self, r: &[Complex<T>], z: &mut [Complex<T>]) {
+        for ((z, r), d) in z.iter_mut().zip(r).zip(&self.inverse) {
+            *z = r * d;
+        }
+    }
+}
+
+/// The identity M = I, for the solvers without a preconditioner
+fn unpreconditioned<T: Real>(r: &[Complex<T>], z: &mut [Complex<T>]) {
+    z.copy_from_slice(r);
+}
+
+/// A tightly packed, row-major m x n matrix in a slice
+struct RowMajor<'a, T> {
+    a: &'a [Complex<T>],
+    m: usize,
+    n: usize,
+}
+
+impl<T: Real> LinearOperator<T> for RowMajor<'_, T> {
+    fn rows(&self) -> usize {
+        self.m
+    }
+
+    fn cols(&self) -> usize {
+        self.n
+    }
+
+    fn apply(&self, x: &[Complex<T>], y: &mut [Complex<T>]) {
+        for (y, row) in y.iter_mut().zip(self.a.chunks_exact(self.n)) {
+            *y = row.iter().zip(x).fold(Complex::new(T::zero(), T::zero()), |acc, (a, x)| acc + a * x);
+        }
+    }
+
+    fn apply_adjoint(&self, y: &[Complex<T>], x: &mut [Complex<T>]) {
+        x.fill(Complex::new(T::zero(), T::zero()));
+        for (y, row) in y.iter().zip(self.a.chunks_exact(self.n)) {
+            for (x, a) in x.iter_mut().zip(row) {
+                *x = *x + a.conj() * y;
+            }
+        }
+    }
+}
+
+/// The adjoint A* of an operator, for running the Lanczos process on the taller side
+struct Adjoint<'a, O>(&'a O);
+
+impl<T: Real, O: LinearOperator<T>> LinearOperator<T> for Adjoint<'_, O> {
+    fn rows(&self) -> usize {
+        self.0.cols()
+    }
+
+    fn cols(&self) -> usize {
+        self.0.rows()
+    }
+
+    fn apply(&self, x: &[Complex<T>], y: &mut [Complex<T>]) {
+        self.0.apply_adjoint(x, y);
+    }
+
+    fn apply_adjoint(&self, y: &[Complex<T>], x: &mut [Complex<T>]) {
+        self.0.apply(y, x);
+    }
+}
+
+/// A singular value with its singular vectors, A v = sigma u
+#[derive(Clone, Debug, PartialEq)]
+pub struct SingularTriplet<T> {
+    /// the singular value
+    pub sigma: T,
+    /// the left singular vector, m elements of norm one
+    pub u: Vec<Complex<T>>,
+    /// the right singular vector, n elements of norm one
+    pub v: Vec<Complex<T>>,
+    /// the iterations that were run
+    pub iterations: usize,
+    /// whether sigma settled to the requested tolerance within the iterations
+    pub converged: bool,
+}
+
+/// Estimates the largest singular value of the tightly packed, row-major M by N matrix a
+/// and its singular vectors by power iteration on A* A, stopping after `iters` iterations
+/// or once an iteration changes sigma by at most `tol` relative to it.
+///
+/// Every iteration costs one product with A and one with A*, and the error of sigma shrinks
+/// by (sigma_2 / sigma_1)^2 per iteration, so a few iterations give sigma_max to a couple of
+/// digits for normalizations or step sizes, while the vectors converge slowly when the two
+/// largest singular values are close. An estimate that has not reached tol is still returned,
+/// with `converged` false. The vectors follow the phase convention of `csvd`.
+pub fn dominant_singular_triplet<T: Real>(a: &[Complex<T>], m: usize, n: usize, iters: usize, tol: T)
+        -> Result<SingularTriplet<T>, CsvdError> {
+    if m < 1 || n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    if a.len() < m * n {
+        return Err(CsvdError::BufferTooSmall { operand: "A", required: m * n, len: a.len() });
+    }
+    check_finite(a, n, m, n)?;
+
+    Ok(power_iteration(&RowMajor { a: &a[..m * n], m, n }, iters, tol))
+}
+
+/// `dominant_singular_triplet` for an operator, e.g. a `CsrMatrix`, which it touches only
+/// through `apply` and `apply_adjoint`
+pub fn dominant_singular_triplet_of<T: Real, O: LinearOperator<T>>(op: &O, iters: usize, tol: T)
+        -> Result<SingularTriplet<T>, CsvdError> {
+    if op.rows() < 1 || op.cols() < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+
+    Ok(power_iteration(op, iters, tol))
+}
+
+/// The power iteration of `dominant_singular_triplet` for a nonempty operator
+fn power_iteration<T: Real, O: LinearOperator<T>>(op: &O, iters: usize, tol: T) -> SingularTriplet<T> {
+    let m = op.rows();
+    let n = op.cols();
+    let zero = Complex::new(T::zero(), T::zero());
+
+    let mut rng = Xoshiro256::new(START_SEED);
+    let mut v: Vec<Complex<T>> = (0..n).map(|_| rng.next_complex_normal()).collect();
+    scale_to_unit(&mut v);
+    let mut u = alloc::vec![zero; m];
+
+    let mut sigma = T::zero();
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < iters {
+        op.apply(&v, &mut u);
+        if norm2(u.iter().copied()) == T::zero() {
+            // v is in the null space, which for a random v means A = 0
+            converged = true;
+            break;
+        }
+        scale_to_unit(&mut u);
+        op.apply_adjoint(&u, &mut v);
+        let next = scale_to_unit(&mut v);
+
+        iterations += 1;
+        let change = (next - sigma).abs();
+        sigma = next;
+        if change <= tol * sigma {
+            converged = true;
+            break;
+        }
+    }
+
+    // A v = sigma u exactly for the final v
+    op.apply(&v, &mut u);
+    sigma = scale_to_unit(&mut u);
+    if sigma == T::zero() {
+        u.fill(zero);
+        u[0] = Complex::new(T::one(), T::zero());
+    }
+    normalize_phases(&mut u, 1, m, 1, &mut v, 1, n, 1);
+
+    SingularTriplet { sigma, u, v, iterations, converged }
+}
+
+/// The smallest singular value, the min(m, n)-th one, of the tightly packed, row-major
+/// m x n matrix a, for rank and conditioning checks that need nothing else of the spectrum.
+///
+/// A = Q R, or A* = Q R for a wide a, leaves R with the singular values of A, and inverse
+/// iteration on R* R finds the smallest one without the squared condition number of
+/// forming A* A. Every iteration costs two triangular solves, O(min(m, n)^2), after the
+/// O(m n min(m, n)) of the QR decomposition, and shrinks the error by the squared ratio of
+/// the two smallest singular values. The result is accurate to about eps ||A||, as that of
+/// `singular_values`, which takes over if the two smallest are so close that the residual
+/// of the iteration has not dropped to that level after 50 iterations. A zero on the
+/// diagonal of R gives exactly zero.
+pub fn sigma_min<T: Real>(a: &[Complex<T>], m: usize, n: usize) -> Result<T, CsvdError> {
+    if m < 1 || n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    if a.len() < m * n {
+        return Err(CsvdError::BufferTooSmall { operand: "A", required: m * n, len: a.len() });
+    }
+    check_finite(a, n, m, n)?;
+
+    let a = Matrix::from_vec(a[..m * n].to_vec(), m, n)?;
+    let tall = if m >= n { qr(&a)? } else { qr(&a.conj_transpose())? };
+    let r = tall.r();
+    let k = m.min(n);
+    let zero = Complex::new(T::zero(), T::zero());
+
+    let r_max = (0..k).map(|i| cabs(&r[(i, i)])).fold(T::zero(), T::max);
+    if (0..k).any(|i| r[(i, i)] == zero) {
+        return Ok(T::zero());
+    }
+
+    let mut rng = Xoshiro256::new(START_SEED);
+    let mut x: Vec<Complex<T>> = (0..k).map(|_| rng.next_complex_normal()).collect();
+    scale_to_unit(&mut x);
+    let tol = T::epsilon() * T::from(k).unwrap() * r_max;
+
+    for _ in 0..SIGMA_MIN_ITERATIONS {
+        // x = (R* R)^-1 x, forward substitution with R* and back substitution with R
+        for i in 0..k {
+            let sum = (0..i).fold(x[i], |acc, l| acc - r[(l, i)].conj() * x[l]);
+            x[i] = sum / r[(i, i)].conj();
+        }
+        for i in (0..k).rev() {
+            let sum = (i + 1..k).fold(x[i], |acc, l| acc - r[(i, l)] * x[l]);
+            x[i] = sum / r[(i, i)];
+        }
+        scale_to_unit(&mut x);
+
+        // sigma = ||R x|| for the unit x bounds sigma_min from above, and is within
+        // ||R* u - sigma x|| of it for u = R x / sigma, which stays large as long as x
+        // mixes the vectors of two close singular values
+        let mut u: Vec<Complex<T>> = (0..k).map(|i| (i..k).fold(zero, |acc, l| acc + r[(i, l)] * x[l])).collect();
+        let sigma = scale_to_unit(&mut u);
+        let residual = norm2((0..k).map(|i| (0..i + 1).fold(zero, |acc, l| acc + r[(l, i)].conj() * u[l]) - x[i] * sigma));
+        if residual <= tol || sigma <= tol {
+            return Ok(sigma);
+        }
+    }
+
+    debug!(target: "lin_alg::iterative", "inverse iteration for sigma_min did not settle, computing all singular values");
+    let s = singular_values(&a)?;
+    Ok(s[k - 1])
+}
+
+/// Tuning parameters of `partial_svd`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LanczosOptions<T> {
+    /// A singular triplet counts as converged once ||A* u - sigma v|| <= tol sigma_max,
+    /// sqrt(eps) by default, which leaves the singular values accurate to about eps
+    /// relative to sigma_max because their error is quadratic in the residual. A new
+    /// Krylov direction below tol sigma_max ends the Krylov space and restarts it.
+    pub tol: T,
+    /// Maximum number of Lanczos steps, each one product with A and one with A*, before
+    /// `partial_svd` gives up with `CsvdError::NotConverged`. At most min(m, n) steps are
+    /// taken in any case, after which the Krylov space holds the whole row or column space.
+    /// Asking for more singular values than steps is an `InvalidArgument` for k.
+    pub max_steps: usize,
+}
+
+impl<T: Real> Default for LanczosOptions<T> {
+    fn default() -> LanczosOptions<T> {
+        LanczosOptions { tol: T::epsilon().sqrt(), max_steps: usize::MAX }
+    }
+}
+
+/// Computes the k largest singular values of op and their singular vectors with the
+/// Golub-Kahan-Lanczos bidiagonalization, which needs nothing but products with A and A*.
+///
+/// Step j extends orthonormal bases U and V of Krylov spaces of A A* and A* A with
+/// A V = U B for a j x j upper bidiagonal B, whose singular triplets give the
+/// approximations U x and V y. The largest ones converge first, typically within a few
+/// times k steps for well separated singular values. Both bases are kept and
+/// reorthogonalized in full, which costs O((m + n) j) memory and time per step, and a
+/// start vector orthogonal to V is drawn whenever the Krylov space is exhausted, so
+/// that multiple singular values are found with their full multiplicity.
+///
+/// The result holds an m x k matrix u, k singular values and an n x k matrix v, in the
+/// layout of `svd_truncated` and with the phase convention of `csvd`.
+pub fn partial_svd<T: Real, O: LinearOperator<T>>(op: &O, k: usize, options: &LanczosOptions<T>) -> Result<SvdResult<T>, CsvdError> {
+    let m = op.rows();
+    let n = op.cols();
+    if m < 1 || n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    if k > m.min(n) {
+        return Err(CsvdError::InvalidArgument { name: "k", value: k, max: m.min(n) });
+    }
+    // every step adds one singular value to B, so fewer steps cannot find k of them
+    if k > options.max_steps {
+        return Err(CsvdError::InvalidArgument { name: "k", value: k, max: options.max_steps });
+    }
+
+    // with m >= n the process ends with V spanning all of C^n, which makes B exact
+    let mut res = if m >= n {
+        lanczos(op, k, options)?
+    }
+    else {
+        let res = lanczos(&Adjoint(op), k, options)?;
+        SvdResult { u: res.v, s: res.s, v: res.u }
+    };
+    normalize_phases(res.u.as_mut_slice(), k, m, k, res.v.as_mut_slice(), k, n, k);
+    Ok(res)
+}
+
+/// The Lanczos bidiagonalization of an operator with m >= n, see `partial_svd`
+fn lanczos<T: Real, O: LinearOperator<T>>(op: &O, k: usize, options: &LanczosOptions<T>)
+        -> Result<SvdResult<T>, CsvdError> {
+    let m = op.rows();
+    let n = op.cols();
+    let zero = T::zero();
+    let czero = Complex::new(zero, zero);
+    let max_steps = options.max_steps.min(n);
+    if k == 0 {
+        return Ok(SvdResult { u: Matrix::zeros(m, 0), s: Vec::new(), v: Matrix::zeros(n, 0) });
+    }
+
+    let mut rng = Xoshiro256::new(START_SEED);
+    let mut us: Vec<Vec<Complex<T>>> = Vec::new();
+    let mut vs: Vec<Vec<Complex<T>>> = Vec::new();
+    let mut alphas: Vec<T> = Vec::new();
+    let mut betas: Vec<T> = Vec::new();
+
+    let mut v: Vec<Complex<T>> = (0..n).map(|_| rng.next_complex_normal()).collect();
+    scale_to_unit(&mut v);
+    let mut scale = zero;
+    // v was drawn at random after a breakdown, and the rest of C^n is in the null space of A
+    let mut restarted = false;
+    let mut exhausted = false;
+
+    loop {
+        let j = vs.len();
+
+        // u_j = A v_j - beta_(j-1) u_(j-1), orthogonal to all earlier u
+        let mut u = alloc::vec![czero; m];
+        op.apply(&v, &mut u);
+        if restarted && norm2(u.iter().copied()) <= options.tol * scale {
+            // a random vector orthogonal to V is in the null space only if all of them are
+            exhausted = true;
+        }
+        orthogonalize(&mut u, &us);
+        let mut alpha = scale_to_unit(&mut u);
+        scale = scale.max(alpha);
+        if alpha <= T::epsilon() * scale {
+            // A v_j lies in the span of the earlier u, continue with any orthogonal u_j
+            alpha = zero;
+            u = random_orthogonal(&mut rng, m, &us);
+        }
+        vs.push(v);
+        us.push(u);
+        alphas.push(alpha);
+
+        // v_(j+1) = A* u_j - alpha_j v_j, orthogonal to all earlier v
+        let mut next = alloc::vec![czero; n];
+        op.apply_adjoint(&us[j], &mut next);
+        orthogonalize(&mut next, &vs);
+        let mut beta = scale_to_unit(&mut next);
+        scale = scale.max(beta);
+        let breakdown = beta <= options.tol * scale && j + 1 < n;
+        if breakdown {
+            // V spans an invariant subspace up to the tolerance, restart with a vector
+            // orthogonal to it, which holds the second copy of a repeated singular value
+            beta = zero;
+            next = random_orthogonal(&mut rng, n, &vs);
+        }
+        v = next;
+        restarted = breakdown;
+
+        let steps = j + 1;
+        if steps < k {
+            betas.push(beta);
+            continue;
+        }
+
+        // the singular triplets of B, with A* U x - sigma V y = beta_j v_(j+1) x_j
+        let mut b = Matrix::zeros(steps, steps);
+        for i in 0..steps {
+            b[(i, i)] = Complex::new(alphas[i], zero);
+            if i + 1 < steps {
+                b[(i, i + 1)] = Complex::new(betas[i], zero);
+            }
+        }
+        let res = svd(&b)?;
+        let tol = options.tol * res.s[0].max(scale * T::epsilon());
+        // after a breakdown the singular values of the rest of C^n are unknown until the
+        // next step, unless they are all zero
+        let converged = steps == n || ((!breakdown || exhausted)
+            && (0..k).all(|i| beta * cabs(&res.u[(steps - 1, i)]) <= tol));
+
+        if converged || steps >= max_steps {
+            if !converged {
+                let index = (0..k).find(|&i| beta * cabs(&res.u[(steps - 1, i)]) > tol).unwrap_or(0);
+                debug!(target: "lin_alg::iterative", "singular value {} did not converge in {} Lanczos steps", index, steps);
+                return Err(CsvdError::NotConverged { index, iterations: steps });
+            }
+            trace!(target: "lin_alg::iterative", "{} singular values converged in {} Lanczos steps", k, steps);
+
+            let mut left = Matrix::zeros(m, k);
+            let mut right = Matrix::zeros(n, k);
+            for c in 0..k {
+                for (l, (u, v)) in us.iter().zip(&vs).enumerate() {
+                    let (x, y) = (res.u[(l, c)], res.v[(l, c)]);
+                    for i in 0..m {
+                        left[(i, c)] = left[(i, c)] + u[i] * x;
+                    }
+                    for i in 0..n {
+                        right[(i, c)] = right[(i, c)] + v[i] * y;
+                    }
+                }
+            }
+            return Ok(SvdResult { u: left, s: res.s[..k].to_vec(), v: right });
+        }
+        betas.push(beta);
+    }
+}
+
+/// Tuning parameters of the iterative solvers `cg`, `gmres`, `bicgstab`, `cgls` and `lsqr`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolverOptions<T> {
+    /// The iteration stops once ||b - A x|| <= tol ||b||, sqrt(eps) by default. `lsqr`
+    /// and `cgls` also stop once ||A* (b - A x)|| <= tol ||A|| ||b - A x||, which marks the
+    /// least-squares solution of an inconsistent system.
+    pub tol: T,
+    /// Maximum number of iterations, each one product with A, or two for `bicgstab`, and
+    /// for `lsqr` and `cgls` one with A* as well. At most 4 max(m, n) iterations are taken in any case,
+    /// as rounding errors delay but rarely prevent the convergence after min(m, n)
+    /// iterations of exact arithmetic.
+    pub max_iterations: usize,
+}
+
+impl<T: Real> Default for SolverOptions<T> {
+    fn default() -> SolverOptions<T> {
+        SolverOptions { tol: T::epsilon().sqrt(), max_iterations: usize::MAX }
+    }
+}
+
+/// The approximate solution of A x = b, or of min ||A x - b||, from an iterative solver
+#[derive(Clone, Debug, PartialEq)]
+pub struct IterativeSolution<T> {
+    /// the n elements of the solution
+    pub x: Vec<Complex<T>>,
+    /// the residual norm ||A x - b||
+    pub residual: T,
+    /// the iterations that were run
+    pub iterations: usize,
+    /// whether one of the stopping criteria of `SolverOptions::tol` was met
+    pub converged: bool,
+}
+
+/// Solves min ||A x - b|| for the m elements of b with LSQR of Paige and Saunders, which
+/// runs the Golub-Kahan bidiagonalization of `partial_svd` started from b and needs nothing
+/// but products with A and A*.
+///
+/// Unlike `partial_svd` it keeps no bases, so every iteration costs one product with A,
+/// one with A* and O(m + n) work and memory, which suits large sparse systems such as a
+/// `CsrMatrix`. The iteration count grows with the condition number of A. Started from
+/// x = 0, it converges to the minimum norm solution also for rank deficient A. An
+/// estimate that has not reached the tolerance is still returned, with `converged` false.
+pub fn lsqr<T: Real, O: LinearOperator<T>>(op: &O, b: &[Complex<T>], options: &SolverOptions<T>)
+        -> Result<IterativeSolution<T>, CsvdError> {
+    check_rhs(op, b)?;
+    let (m, n) = op.dims();
+    let czero = Complex::new(T::zero(), T::zero());
+
+    let mut x = alloc::vec![czero; n];
+    // beta_1 u_1 = b and alpha_1 v_1 = A* u_1
+    let mut u = b.to_vec();
+    let bnorm = scale_to_unit(&mut u);
+    let mut v = alloc::vec![czero; n];
+    op.apply_adjoint(&u, &mut v);
+    let mut alpha = scale_to_unit(&mut v);
+    if bnorm == T::zero() || alpha == T::zero() {
+        // A* b = 0, so x = 0 is the minimum norm solution
+        return Ok(IterativeSolution { x, residual: bnorm, iterations: 0, converged: true });
+    }
+
+    let max_iterations = options.max_iterations.min(4 * m.max(n));
+    let mut w = v.clone();
+    let mut phibar = bnorm;
+    let mut rhobar = alpha;
+    let mut anorm2 = T::zero();
+    let mut av = alloc::vec![czero; m];
+    let mut au = alloc::vec![czero; n];
+
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < max_iterations {
+        // beta u = A v - alpha u and alpha v = A* u - beta v
+        op.apply(&v, &mut av);
+        for (u, av) in u.iter_mut().zip(&av) {
+            *u = *av - *u * alpha;
+        }
+        let beta = scale_to_unit(&mut u);
+        anorm2 = anorm2 + alpha * alpha + beta * beta;
+        op.apply_adjoint(&u, &mut au);
+        for (v, au) in v.iter_mut().zip(&au) {
+            *v = *au - *v * beta;
+        }
+        alpha = scale_to_unit(&mut v);
+
+        // a Givens rotation eliminates beta from the bidiagonal, its QR factorization
+        // updates x along the search direction w
+        let rho = rhobar.hypot(beta);
+        let c = rhobar / rho;
+        let s = beta / rho;
+        let theta = s * alpha;
+        rhobar = -c * alpha;
+        let phi = c * phibar;
+        phibar = s * phibar;
+        for (x, w) in x.iter_mut().zip(w.iter_mut()) {
+            *x = *x + *w * (phi / rho);
+            *w = -*w * (theta / rho);
+        }
+        for (w, v) in w.iter_mut().zip(&v) {
+            *w = *w + v;
+        }
+
+        iterations += 1;
+        // phibar is ||b - A x|| and phibar alpha |c| is ||A* (b - A x)||
+        if phibar <= options.tol * bnorm || phibar * alpha * c.abs() <= options.tol * anorm2.sqrt() * phibar {
+            converged = true;
+            break;
+        }
+    }
+    if !converged {
+        debug!(target: "lin_alg::iterative", "LSQR did not converge in {} iterations", iterations);
+    }
+
+    op.apply(&x, &mut av);
+    let residual = norm2(av.iter().zip(b).map(|(ax, b)| ax - b));
+    Ok(IterativeSolution { x, residual, iterations, converged })
+}
+
+/// Checks that op is nonempty and b is a finite vector of op.rows() elements
+fn check_rhs<T: Real, O: LinearOperator<T>>(op: &O, b: &[Complex<T>]) -> Result<(), CsvdError> {
+    let (m, n) = op.dims();
+    if m < 1 || n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    if b.len() != m {
+        return Err(CsvdError::DimensionMismatch { operand: "b", expected: (m, 1), found: (b.len(), 1) });
+    }
+    if let Some(i) = b.iter().position(|x| !(x.re.is_finite() && x.im.is_finite())) {
+        return Err(CsvdError::NonFiniteInput { row: i, col: 0 });
+    }
+    Ok(())
+}
+
+/// Checks that op is square and b fits it, for the solvers of A x = b
+fn check_system<T: Real, O: LinearOperator<T>>(op: &O, b: &[Complex<T>]) -> Result<(), CsvdError> {
+    check_rhs(op, b)?;
+    let (m, n) = op.dims();
+    if m != n {
+        return Err(CsvdError::DimensionMismatch { operand: "A", expected: (m, m), found: (m, n) });
+    }
+    Ok(())
+}
+
+/// The state of `cg_with_progress` or `cgls_with_progress` after an iteration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolverProgress<T> {
+    /// the iterations run so far, from one on
+    pub iteration: usize,
+    /// the residual norm ||b - A x|| of the current x, as updated by the recurrence
+    pub residual: T,
+}
+
+/// Solves A x = b for a Hermitian positive definite n x n operator with the method of
+/// conjugate gradients, e.g. for the normal equations of a regularized estimator.
+///
+/// Every iteration costs one product with A and O(n) work, and the error shrinks at least
+/// by (sqrt(k) - 1) / (sqrt(k) + 1) per iteration for the condition number k of A. Besides
+/// x it keeps three vectors of n elements, allocated once. The iteration stops without
+/// converging once p* A p <= 0 for a search direction p, which shows that A is not
+/// positive definite. An estimate that has not reached the tolerance is still returned,
+/// with `converged` false.
+pub fn cg<T: Real, O: LinearOperator<T>>(op: &O, b: &[Complex<T>], options: &SolverOptions<T>)
+        -> Result<IterativeSolution<T>, CsvdError> {
+    cg_with_progress(op, b, options, |_| ControlFlow::Continue(()))
+}
+
+/// `cg` that reports the residual after every iteration to `progress`, e.g. to record the
+/// convergence history into a fixed buffer. Returning `ControlFlow::Break` stops the
+/// iteration, and the current x is returned with `converged` false.
+pub fn cg_with_progress<T, O, F>(op: &O, b: &[Complex<T>], options: &SolverOptions<T>, mut progress: F)
+        -> Result<IterativeSolution<T>, CsvdError>
+where
+    T: Real,
+    O: LinearOperator<T>,
+    F: FnMut(SolverProgress<T>) -> ControlFlow<()>,
+{
+    check_system(op, b)?;
+    let n = op.cols();
+    let czero = Complex::new(T::zero(), T::zero());
+
+    let mut x = alloc::vec![czero; n];
+    let mut r = b.to_vec();
+    let mut p = r.clone();
+    let mut ap = alloc::vec![czero; n];
+    let bnorm = norm2(b.iter().copied());
+    let mut rr = bnorm * bnorm;
+
+    let max_iterations = options.max_iterations.min(4 * n);
+    let mut iterations = 0;
+    let mut converged = bnorm == T::zero();
+    while !converged && iterations < max_iterations {
+        op.apply(&p, &mut ap);
+        let pap = p.iter().zip(&ap).fold(T::zero(), |acc, (p, ap)| acc + (p.conj() * ap).re);
+        if pap <= T::zero() || pap.is_nan() {
+            debug!(target: "lin_alg::iterative", "CG stopped, the operator is not positive definite");
+            break;
+        }
+
+        let alpha = rr / pap;
+        for ((x, r), (p, ap)) in x.iter_mut().zip(r.iter_mut()).zip(p.iter().zip(&ap)) {
+            *x = *x + p * alpha;
+            *r = *r - ap * alpha;
+        }
+        let next = r.iter().fold(T::zero(), |acc, r| acc + r.norm_sqr());
+        iterations += 1;
+        converged = next.sqrt() <= options.tol * bnorm;
+        let flow = progress(SolverProgress { iteration: iterations, residual: next.sqrt() });
+        if !converged && flow.is_break() {
+            debug!(target: "lin_alg::iterative", "CG cancelled after {} iterations", iterations);
+            break;
+        }
+
+        let beta = next / rr;
+        rr = next;
+        for (p, r) in p.iter_mut().zip(&r) {
+            *p = r + *p * beta;
+        }
+    }
+    if !converged {
+        debug!(target: "lin_alg::iterative", "CG did not converge in {} iterations", iterations);
+    }
+
+    // the updated r drifts from b - A x over many iterations
+    op.apply(&x, &mut ap);
+    let residual = norm2(ap.iter().zip(b).map(|(ax, b)| ax - b));
+    Ok(IterativeSolution { x, residual, iterations, converged })
+}
+
+/// Solves min ||A x - b|| for the m elements of b with CGLS, the method of conjugate
+/// gradients on the normal equations A* A x = A* b without forming A* A.
+///
+/// Every iteration costs one product with A, one with A* and O(m + n) work, the same as
+/// `lsqr`, which computes the same iterates in exact arithmetic and is the more robust
+/// choice for ill-conditioned A, while CGLS keeps one vector less. Started from x = 0, it
+/// converges to the minimum norm solution also for rank deficient A. An estimate that has
+/// not reached the tolerance is still returned, with `converged` false.
+pub fn cgls<T: Real, O: LinearOperator<T>>(op: &O, b: &[Complex<T>], options: &SolverOptions<T>)
+        -> Result<IterativeSolution<T>, CsvdError> {
+    cgls_with_progress(op, b, options, |_| ControlFlow::Continue(()))
+}
+
+/// `cgls` that reports the residual after every iteration, see `cg_with_progress`
+pub fn cgls_with_progress<T, O, F>(op: &O, b: &[Complex<T>], options: &SolverOptions<T>, mut progress: F)
+        -> Result<IterativeSolution<T>, CsvdError>
+where
+    T: Real,
+    O: LinearOperator<T>,
+    F: FnMut(SolverProgress<T>) -> ControlFlow<()>,
+{
+    check_rhs(op, b)?;
+    let (m, n) = op.dims();
+    let czero = Complex::new(T::zero(), T::zero());
+
+    let mut x = alloc::vec![czero; n];
+    let mut r = b.to_vec();
+    // s = A* r is the residual of the normal equations
+    let mut s = alloc::vec![czero; n];
+    op.apply_adjoint(&r, &mut s);
+    let mut p = s.clone();
+    let mut q = alloc::vec![czero; m];
+    let bnorm = norm2(b.iter().copied());
+    let mut gamma = s.iter().fold(T::zero(), |acc, s| acc + s.norm_sqr());
+    // ||A p|| / ||p|| of all directions, a lower bound of ||A||
+    let mut anorm = T::zero();
+
+    let max_iterations = options.max_iterations.min(4 * m.max(n));
+    let mut iterations = 0;
+    // A* b = 0 makes x = 0 the minimum norm solution
+    let mut converged = gamma == T::zero();
+    while !converged && iterations < max_iterations {
+        op.apply(&p, &mut q);
+        let qq = q.iter().fold(T::zero(), |acc, q| acc + q.norm_sqr());
+        if qq == T::zero() {
+            // p lies in the range of A*, so only rounding errors put it in the null space of A
+            break;
+        }
+        anorm = anorm.max((qq / p.iter().fold(T::zero(), |acc, p| acc + p.norm_sqr())).sqrt());
+
+        let alpha = gamma / qq;
+        for (x, p) in x.iter_mut().zip(&p) {
+            *x = *x + p * alpha;
+        }
+        for (r, q) in r.iter_mut().zip(&q) {
+            *r = *r - q * alpha;
+        }
+        op.apply_adjoint(&r, &mut s);
+        let next = s.iter().fold(T::zero(), |acc, s| acc + s.norm_sqr());
+        let rnorm = norm2(r.iter().copied());
+        iterations += 1;
+        converged = rnorm <= options.tol * bnorm || next.sqrt() <= options.tol * anorm * rnorm;
+        let flow = progress(SolverProgress { iteration: iterations, residual: rnorm });
+        if !converged && flow.is_break() {
+            debug!(target: "lin_alg::iterative", "CGLS cancelled after {} iterations", iterations);
+            break;
+        }
+
+        let beta = next / gamma;
+        gamma = next;
+        for (p, s) in p.iter_mut().zip(&s) {
+            *p = s + *p * beta;
+        }
+    }
+    if !converged {
+        debug!(target: "lin_alg::iterative", "CGLS did not converge in {} iterations", iterations);
+    }
+
+    op.apply(&x, &mut q);
+    let residual = norm2(q.iter().zip(b).map(|(ax, b)| ax - b));
+    Ok(IterativeSolution { x, residual, iterations, converged })
+}
+
+/// Solves A x = b for a square operator with GMRES of Saad and Schultz, restarted after
+/// `restart` iterations.
+///
+/// Each cycle builds an orthonormal basis of the Krylov space of A and the residual with
+/// the Arnoldi process and takes the x that minimizes ||b - A x|| over it. Iteration j of
+/// a cycle costs one product with A and O(n j) work, and the cycle keeps restart + 1
+/// vectors of n elements, so a larger restart converges in fewer iterations at a higher
+/// cost per iteration. Unlike `cg` it needs neither a Hermitian nor a positive definite A.
+/// An estimate that has not reached the tolerance is still returned, with `converged` false.
+pub fn gmres<T: Real, O: LinearOperator<T>>(op: &O, b: &[Complex<T>], restart: usize, options: &SolverOptions<T>)
+        -> Result<IterativeSolution<T>, CsvdError> {
+    gmres_preconditioned(op, b, &unpreconditioned, restart, options)
+}
+
+/// `gmres` with the right preconditioner M, which solves A M^-1 u = b for x = M^-1 u. The
+/// Krylov space is built from A M^-1, which converges in fewer iterations the closer M is
+/// to A, while the residual, and so the stopping criterion, stays that of A x = b.
+pub fn gmres_preconditioned<T: Real, O: LinearOperator<T>, P: Preconditioner<T>>(op: &O, b: &[Complex<T>], m: &P,
+        restart: usize, options: &SolverOptions<T>) -> Result<IterativeSolution<T>, CsvdError> {
+    check_system(op, b)?;
+    let n = op.cols();
+    if restart < 1 {
+        return Err(CsvdError::InvalidArgument { name: "restart", value: restart, max: n });
+    }
+    let restart = restart.min(n);
+    let zero = T::zero();
+    let czero = Complex::new(zero, zero);
+
+    let mut x = alloc::vec![czero; n];
+    let mut r = b.to_vec();
+    let mut w = alloc::vec![czero; n];
+    let mut z = alloc::vec![czero; n];
+    let bnorm = norm2(b.iter().copied());
+
+    let max_iterations = options.max_iterations.min(4 * n);
+    let mut iterations = 0;
+    let mut residual = bnorm;
+    let mut converged = residual <= options.tol * bnorm;
+    while !converged && iterations < max_iterations {
+        let mut basis = Vec::with_capacity(restart + 1);
+        let mut v = r.clone();
+        scale_to_unit(&mut v);
+        basis.push(v);
+        // the columns of the Hessenberg matrix, reduced to triangular form by the rotations
+        // (c, s), which also carry ||r|| e_1 into g
+        let mut h: Vec<Vec<Complex<T>>> = Vec::with_capacity(restart);
+        let mut rotations: Vec<(T, Complex<T>)> = Vec::with_capacity(restart);
+        let mut g = alloc::vec![czero; restart + 1];
+        g[0] = Complex::new(residual, zero);
+
+        for j in 0..restart {
+            m.solve(&basis[j], &mut z);
+            op.apply(&z, &mut w);
+            iterations += 1;
+
+            // modified Gram-Schmidt, twice as in `orthogonalize`
+            let mut column = alloc::vec![czero; j + 2];
+            for _ in 0..2 {
+                for (c, v) in column.iter_mut().zip(&basis) {
+                    let p = v.iter().zip(w.iter()).fold(czero, |acc, (v, w)| acc + v.conj() * w);
+                    for (w, v) in w.iter_mut().zip(v) {
+                        *w = *w - *v * p;
+                    }
+                    *c = *c + p;
+                }
+            }
+            let next = scale_to_unit(&mut w);
+
+            for (i, &(c, s)) in rotations.iter().enumerate() {
+                let (a, b) = (column[i], column[i + 1]);
+                column[i] = a * c + s * b;
+                column[i + 1] = b * c - s.conj() * a;
+            }
+            let a = column[j];
+            let norm = cabs(&a).hypot(next);
+            let (c, s) = if norm == zero {
+                (T::one(), czero)
+            }
+            else if cabs(&a) == zero {
+                (zero, Complex::new(T::one(), zero))
+            }
+            else {
+                (cabs(&a) / norm, a / cabs(&a) * (next / norm))
+            };
+            column[j] = a * c + s * next;
+            column[j + 1] = czero;
+            g[j + 1] = -s.conj() * g[j];
+            g[j] = g[j] * c;
+            rotations.push((c, s));
+            h.push(column);
+
+            // a zero next vector means the Krylov space is invariant and holds the solution
+            if cabs(&g[j + 1]) <= options.tol * bnorm || next == zero || iterations >= max_iterations {
+                break;
+            }
+            basis.push(w.clone());
+        }
+
+        // x += M^-1 V y for the triangular system H y = g
+        let k = h.len();
+        let mut y = alloc::vec![czero; k];
+        for i in (0..k).rev() {
+            let sum = (i + 1..k).fold(g[i], |acc, l| acc - h[l][i] * y[l]);
+            y[i] = if h[i][i] == czero { czero } else { sum / h[i][i] };
+        }
+        w.fill(czero);
+        for (v, y) in basis.iter().zip(&y) {
+            for (w, v) in w.iter_mut().zip(v) {
+                *w = *w + v * y;
+            }
+        }
+        m.solve(&w, &mut z);
+        for (x, z) in x.iter_mut().zip(&z) {
+            *x = *x + z;
+        }
+
+        op.apply(&x, &mut w);
+        for ((r, w), b) in r.iter_mut().zip(&w).zip(b) {
+            *r = b - w;
+        }
+        residual = norm2(r.iter().copied());
+        converged = residual <= options.tol * bnorm;
+    }
+    if !converged {
+        debug!(target: "lin_alg::iterative", "GMRES did not converge in {} iterations", iterations);
+    }
+
+    Ok(IterativeSolution { x, residual, iterations, converged })
+}
+
+/// Solves A x = b for a square operator with BiCGSTAB of van der Vorst.
+///
+/// Every iteration costs two products with A and O(n) work and memory, independent of
+/// the iteration count, which makes it the lighter alternative to `gmres` for general
+/// systems, at the price of an irregular convergence that may break down. The iteration
+/// then stops without converging, and `gmres` is the fallback. An estimate that has not
+/// reached the tolerance is still returned, with `converged` false.
+pub fn bicgstab<T: Real, O: LinearOperator<T>>(op: &O, b: &[Complex<T>], options: &SolverOptions<T>)
+        -> Result<IterativeSolution<T>, CsvdError> {
+    bicgstab_preconditioned(op, b, &unpreconditioned, options)
+}
+
+/// `bicgstab` with the right preconditioner M, see `gmres_preconditioned`
+pub fn bicgstab_preconditioned<T: Real, O: LinearOperator<T>, P: Preconditioner<T>>(op: &O, b: &[Complex<T>], m: &P,
+        options: &SolverOptions<T>) -> Result<IterativeSolution<T>, CsvdError> {
+    check_system(op, b)?;
+    let n = op.cols();
+    let one = Complex::new(T::one(), T::zero());
+    let czero = Complex::new(T::zero(), T::zero());
+    let dot = |x: &[Complex<T>], y: &[Complex<T>]| x.iter().zip(y).fold(czero, |acc, (x, y)| acc + x.conj() * y);
+
+    let mut x = alloc::vec![czero; n];
+    let mut r = b.to_vec();
+    // the shadow residual, fixed at r_0
+    let shadow = r.clone();
+    let mut p = alloc::vec![czero; n];
+    let mut v = alloc::vec![czero; n];
+    let mut t = alloc::vec![czero; n];
+    let mut z = alloc::vec![czero; n];
+    let mut zs = alloc::vec![czero; n];
+    let (mut rho, mut alpha, mut omega) = (one, one, one);
+    let bnorm = norm2(b.iter().copied());
+
+    let max_iterations = options.max_iterations.min(4 * n);
+    let mut iterations = 0;
+    let mut converged = bnorm == T::zero();
+    while !converged && iterations < max_iterations {
+        let next = dot(&shadow, &r);
+        if next == czero || omega == czero {
+            debug!(target: "lin_alg::iterative", "BiCGSTAB broke down after {} iterations", iterations);
+            break;
+        }
+        let beta = next / rho * (alpha / omega);
+        rho = next;
+        for ((p, r), v) in p.iter_mut().zip(&r).zip(&v) {
+            *p = r + (*p - v * omega) * beta;
+        }
+
+        m.solve(&p, &mut z);
+        op.apply(&z, &mut v);
+        let sv = dot(&shadow, &v);
+        if sv == czero {
+            debug!(target: "lin_alg::iterative", "BiCGSTAB broke down after {} iterations", iterations);
+            break;
+        }
+        alpha = rho / sv;
+        // r becomes s = r - alpha v
+        for ((x, r), (z, v)) in x.iter_mut().zip(r.iter_mut()).zip(z.iter().zip(&v)) {
+            *x = *x + z * alpha;
+            *r = *r - v * alpha;
+        }
+        iterations += 1;
+        if norm2(r.iter().copied()) <= options.tol * bnorm {
+            converged = true;
+            break;
+        }
+
+        m.solve(&r, &mut zs);
+        op.apply(&zs, &mut t);
+        let tt = dot(&t, &t);
+        omega = if tt == czero { czero } else { dot(&t, &r) / tt };
+        for ((x, r), (zs, t)) in x.iter_mut().zip(r.iter_mut()).zip(zs.iter().zip(&t)) {
+            *x = *x + zs * omega;
+            *r = *r - t * omega;
+        }
+        converged = norm2(r.iter().copied()) <= options.tol * bnorm;
+    }
+    if !converged {
+        debug!(target: "lin_alg::iterative", "BiCGSTAB did not converge in {} iterations", iterations);
+    }
+
+    op.apply(&x, &mut t);
+    let residual = norm2(t.iter().zip(b).map(|(ax, b)| ax - b));
+    Ok(IterativeSolution { x, residual, iterations, converged })
+}
+
+/// Removes the components along the orthonormal basis from x, twice, as a single
+/// Gram-Schmidt pass loses orthogonality once x is mostly in the span of the basis
+fn orthogonalize<T: Real>(x: &mut [Complex<T>], basis: &[Vec<Complex<T>>]) {
+    for _ in 0..2 {
+        for b in basis {
+            let p = b.iter().zip(x.iter()).fold(Complex::new(T::zero(), T::zero()), |acc, (b, x)| acc + b.conj() * x);
+            for (x, b) in x.iter_mut().zip(b) {
+                *x = *x - *b * p;
+            }
+        }
+    }
+}
+
+/// A random unit vector of len elements orthogonal to the basis, which has fewer than len
+fn random_orthogonal<T: Real>(rng: &mut Xoshiro256, len: usize, basis: &[Vec<Complex<T>>]) -> Vec<Complex<T>> {
+    loop {
+        let mut x: Vec<Complex<T>> = (0..len).map(|_| rng.next_complex_normal()).collect();
+        let before = norm2(x.iter().copied());
+        orthogonalize(&mut x, basis);
+        // a random vector has only a tiny part outside the basis with negligible probability
+        if scale_to_unit(&mut x) > before / T::from(16.0).unwrap() / T::from(len).unwrap().sqrt() {
+            return x;
+        }
+    }
+}
+
+/// Scales x to unit length and returns its former norm, leaving a zero x as it is
+fn scale_to_unit<T: Real>(x: &mut [Complex<T>]) -> T {
+    let norm = norm2(x.iter().copied());
+    if norm > T::zero() {
+        for x in x.iter_mut() {
+            *x = *x / norm;
+        }
+    }
+    norm
+}
@@ -0,0 +1,740 @@
+//! The complex vector kernels of the hot loops: the inner products and updates of the
+//! Householder eliminations, the row updates of `matrix_mult` and the plane rotations of
+//! the Jacobi sweeps.
+//!
+//! With the `simd` feature, `Complex32` and `Complex64` slices run on the best
+//! instruction set the CPU offers, every other case runs the scalar loops.
+//! On aarch64 the NEON kernels use fused multiply-adds; the FCMLA complex multiply of
+//! Armv8.3 would save the swaps, but its intrinsics are not available on stable Rust. The level is
+//! detected once, at runtime with `std` and from the compile-time target features
+//! without it, and can be inspected with `isa()` or overridden with `set_isa()`.
+//! A level without kernels of its own runs those of the next lower level.
+//! The vectorized inner products add the terms in a different order, so their results
+//! can differ in the last bits.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+use num_complex::Complex;
+use super::scalar::Real;
+
+/// The instruction set levels the kernels can be dispatched to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Isa {
+    /// The portable scalar loops
+    Scalar = 1,
+    /// 128-bit SSE2, part of the x86_64 baseline
+    Sse2 = 2,
+    /// 256-bit AVX2 with fused multiply-add
+    Avx2Fma = 3,
+    /// 128-bit NEON, part of the aarch64 baseline
+    Neon = 4,
+}
+
+const UNDETECTED: u8 = 0;
+
+static SELECTED: AtomicU8 = AtomicU8::new(UNDETECTED);
+
+/// The instruction set the kernels currently run on.
+/// Unless `set_isa` chose one, this is the best level supported, detected on the first call.
+pub fn isa() -> Isa {
+    match SELECTED.load(Ordering::Relaxed) {
+        2 => Isa::Sse2,
+        3 => Isa::Avx2Fma,
+        4 => Isa::Neon,
+        1 => Isa::Scalar,
+        _ => {
+            let best = best_isa();
+            SELECTED.store(best as u8, Ordering::Relaxed);
+            best
+        }
+    }
+}
+
+/// Makes the kernels run on isa, e.g. to compare against the scalar loops.
+/// Returns false and keeps the current level if isa is not supported.
+pub fn set_isa(isa: Isa) -> bool {
+    if !is_supported(isa) {
+        return false;
+    }
+
+    SELECTED.store(isa as u8, Ordering::Relaxed);
+    true
+}
+
+/// Whether the kernels can run on isa with this build on this CPU
+pub fn is_supported(isa: Isa) -> bool {
+    match isa {
+        Isa::Scalar => true,
+        Isa::Sse2 => cfg!(all(feature = "simd", target_arch = "x86_64")),
+        Isa::Avx2Fma => avx2_fma_detected(),
+        Isa::Neon => cfg!(all(feature = "simd", target_arch = "aarch64")),
+    }
+}
+
+fn best_isa() -> Isa {
+    [Isa::Avx2Fma, Isa::Neon, Isa::Sse2].iter()
+        .cloned()
+        .find(|&isa| is_supported(isa))
+        .unwrap_or(Isa::Scalar)
+}
+
+#[cfg(all(feature = "simd", feature = "std", target_arch = "x86_64"))]
+fn avx2_fma_detected() -> bool {
+    std::is_x86_feature_detected!("avx2") && std::is_x86_feature_detected!("fma")
+}
+
+#[cfg(not(all(feature = "simd", feature = "std", target_arch = "x86_64")))]
+fn avx2_fma_detected() -> bool {
+    cfg!(all(feature = "simd", target_arch = "x86_64", target_feature = "avx2", target_feature = "fma"))
+}
+
+/// Computes sum(conj(x[i]) * y[i]) over the common length of x and y
+#[inline]
+pub(crate) fn dot_conj<T: Real>(x: &[Complex<T>], y: &[Complex<T>]) -> Complex<T> {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        match isa() {
+            Isa::Avx2Fma => {
+                if let Some((x, y)) = cast2::<T, f64>(x, y) {
+                    return recast(unsafe { avx2::dot_conj_f64(x, y) });
+                }
+                if let Some((x, y)) = cast2::<T, f32>(x, y) {
+                    return recast(unsafe { avx2::dot_conj_f32(x, y) });
+                }
+            }
+            Isa::Sse2 => {
+                if let Some((x, y)) = cast2::<T, f64>(x, y) {
+                    return recast(unsafe { sse2::dot_conj_f64(x, y) });
+                }
+                if let Some((x, y)) = cast2::<T, f32>(x, y) {
+                    return recast(unsafe { sse2::dot_conj_f32(x, y) });
+                }
+            }
+            _ => {}
+        }
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    {
+        if let Isa::Neon = isa() {
+            if let Some((x, y)) = cast2::<T, f64>(x, y) {
+                return recast(unsafe { neon::dot_conj_f64(x, y) });
+            }
+            if let Some((x, y)) = cast2::<T, f32>(x, y) {
+                return recast(unsafe { neon::dot_conj_f32(x, y) });
+            }
+        }
+    }
+
+    dot_conj_scalar(x, y)
+}
+
+/// Computes y[i] = y[i] + alpha * x[i] over the common length of x and y
+#[inline]
+pub(crate) fn axpy<T: Real>(alpha: Complex<T>, x: &[Complex<T>], y: &mut [Complex<T>]) {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        match isa() {
+            Isa::Avx2Fma => {
+                if let (Some(x), Some(alpha)) = (cast::<T, f64>(x), cast_scalar::<T, f64>(alpha)) {
+                    let y = cast_mut::<T, f64>(y).unwrap();
+                    return unsafe { avx2::axpy_f64(alpha, x, y) };
+                }
+                if let (Some(x), Some(alpha)) = (cast::<T, f32>(x), cast_scalar::<T, f32>(alpha)) {
+                    let y = cast_mut::<T, f32>(y).unwrap();
+                    return unsafe { avx2::axpy_f32(alpha, x, y) };
+                }
+            }
+            Isa::Sse2 => {
+                if let (Some(x), Some(alpha)) = (cast::<T, f64>(x), cast_scalar::<T, f64>(alpha)) {
+                    let y = cast_mut::<T, f64>(y).unwrap();
+                    return unsafe { sse2::axpy_f64(alpha, x, y) };
+                }
+                if let (Some(x), Some(alpha)) = (cast::<T, f32>(x), cast_scalar::<T, f32>(alpha)) {
+                    let y = cast_mut::<T, f32>(y).unwrap();
+                    return unsafe { sse2::axpy_f32(alpha, x, y) };
+                }
+            }
+            _ => {}
+        }
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    {
+        if let Isa::Neon = isa() {
+            if let (Some(x), Some(alpha)) = (cast::<T, f64>(x), cast_scalar::<T, f64>(alpha)) {
+                let y = cast_mut::<T, f64>(y).unwrap();
+                return unsafe { neon::axpy_f64(alpha, x, y) };
+            }
+            if let (Some(x), Some(alpha)) = (cast::<T, f32>(x), cast_scalar::<T, f32>(alpha)) {
+                let y = cast_mut::<T, f32>(y).unwrap();
+                return unsafe { neon::axpy_f32(alpha, x, y) };
+            }
+        }
+    }
+
+    axpy_scalar(alpha, x, y)
+}
+
+/// Applies the plane rotation with real cosine c and complex sine s to the pairs
+/// (x[i], y[i]) over the common length of x and y, as LAPACK's zrot:
+/// x[i] = c x[i] + s y[i] and y[i] = c y[i] - conj(s) x[i]
+#[inline]
+pub(crate) fn rot<T: Real>(c: T, s: Complex<T>, x: &mut [Complex<T>], y: &mut [Complex<T>]) {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        if let Isa::Avx2Fma = isa() {
+            if let Some(s) = cast_scalar::<T, f64>(s) {
+                let (x, y) = (cast_mut::<T, f64>(x).unwrap(), cast_mut::<T, f64>(y).unwrap());
+                return unsafe { avx2::rot_f64(c.to_f64().unwrap(), s, x, y) };
+            }
+            if let Some(s) = cast_scalar::<T, f32>(s) {
+                let (x, y) = (cast_mut::<T, f32>(x).unwrap(), cast_mut::<T, f32>(y).unwrap());
+                return unsafe { avx2::rot_f32(c.to_f32().unwrap(), s, x, y) };
+            }
+        }
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    {
+        if let Isa::Neon = isa() {
+            if let Some(s) = cast_scalar::<T, f64>(s) {
+                let (x, y) = (cast_mut::<T, f64>(x).unwrap(), cast_mut::<T, f64>(y).unwrap());
+                return unsafe { neon::rot_f64(c.to_f64().unwrap(), s, x, y) };
+            }
+            if let Some(s) = cast_scalar::<T, f32>(s) {
+                let (x, y) = (cast_mut::<T, f32>(x).unwrap(), cast_mut::<T, f32>(y).unwrap());
+                return unsafe { neon::rot_f32(c.to_f32().unwrap(), s, x, y) };
+            }
+        }
+    }
+
+    rot_scalar(c, s, x, y)
+}
+
+pub(crate) fn dot_conj_scalar<T: Real>(x: &[Complex<T>], y: &[Complex<T>]) -> Complex<T> {
+    let mut sum = Complex::new(T::zero(), T::zero());
+    for (x, y) in x.iter().zip(y.iter()) {
+        sum = sum + x.conj() * y;
+    }
+    sum
+}
+
+/// A running sum with Neumaier's compensation, which carries the rounding error of every
+/// addition along and adds it back at the end, so the result is as accurate as if it had
+/// been accumulated in twice the precision
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CompensatedSum<T> {
+    sum: T,
+    compensation: T,
+}
+
+impl<T: Real> CompensatedSum<T> {
+    pub(crate) fn new() -> CompensatedSum<T> {
+        CompensatedSum { sum: T::zero(), compensation: T::zero() }
+    }
+
+    pub(crate) fn add(&mut self, x: T) {
+        let t = self.sum + x;
+        // the low-order bits of the smaller operand are the ones lost in t
+        if self.sum.abs() >= x.abs() {
+            self.compensation = self.compensation + ((self.sum - t) + x);
+        }
+        else {
+            self.compensation = self.compensation + ((x - t) + self.sum);
+        }
+        self.sum = t;
+    }
+
+    /// Multiplies the sum and its compensation, e.g. to rescale a sum of squares
+    pub(crate) fn scale(&mut self, factor: T) {
+        self.sum = self.sum * factor;
+        self.compensation = self.compensation * factor;
+    }
+
+    pub(crate) fn value(&self) -> T {
+        self.sum + self.compensation
+    }
+}
+
+/// Computes sum(conj(x) * y) over the pairs (x, y), adding up the four real products of
+/// every term with compensated summation. Roughly four times slower than `dot_conj`.
+pub(crate) fn dot_conj_compensated<T: Real, I: Iterator<Item = (Complex<T>, Complex<T>)>>(pairs: I) -> Complex<T> {
+    let mut re = CompensatedSum::new();
+    let mut im = CompensatedSum::new();
+    for (x, y) in pairs {
+        re.add(x.re * y.re);
+        re.add(x.im * y.im);
+        im.add(x.re * y.im);
+        im.add(-(x.im * y.re));
+    }
+    Complex::new(re.value(), im.value())
+}
+
+pub(crate) fn axpy_scalar<T: Real>(alpha: Complex<T>, x: &[Complex<T>], y: &mut [Complex<T>]) {
+    for (x, y) in x.iter().zip(y.iter_mut()) {
+        *y = *y + alpha * x;
+    }
+}
+
+pub(crate) fn rot_scalar<T: Real>(c: T, s: Complex<T>, x: &mut [Complex<T>], y: &mut [Complex<T>]) {
+    for (x, y) in x.iter_mut().zip(y.iter_mut()) {
+        let (xv, yv) = (*x, *y);
+        *x = xv * c + s * yv;
+        *y = yv * c - s.conj() * xv;
+    }
+}
+
+/// Reinterprets a slice of `Complex<T>` as a slice of `Complex<U>` if T and U are the same type
+#[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[inline]
+fn cast<T: Real, U: Real>(x: &[Complex<T>]) -> Option<&[Complex<U>]> {
+    if core::any::TypeId::of::<T>() == core::any::TypeId::of::<U>() {
+        // T and U are the same type, so this only changes the name of the element type
+        Some(unsafe { core::slice::from_raw_parts(x.as_ptr() as *const Complex<U>, x.len()) })
+    }
+    else {
+        None
+    }
+}
+
+#[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[inline]
+fn cast_mut<T: Real, U: Real>(x: &mut [Complex<T>]) -> Option<&mut [Complex<U>]> {
+    if core::any::TypeId::of::<T>() == core::any::TypeId::of::<U>() {
+        Some(unsafe { core::slice::from_raw_parts_mut(x.as_mut_ptr() as *mut Complex<U>, x.len()) })
+    }
+    else {
+        None
+    }
+}
+
+#[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+type SlicePair<'a, U> = (&'a [Complex<U>], &'a [Complex<U>]);
+
+#[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[inline]
+fn cast2<'a, T: Real, U: Real>(x: &'a [Complex<T>], y: &'a [Complex<T>]) -> Option<SlicePair<'a, U>> {
+    match (cast(x), cast(y)) {
+        (Some(x), Some(y)) => Some((x, y)),
+        _ => None,
+    }
+}
+
+#[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[inline]
+fn cast_scalar<T: Real, U: Real>(x: Complex<T>) -> Option<Complex<U>> {
+    cast::<T, U>(core::slice::from_ref(&x)).map(|x| x[0])
+}
+
+/// The inverse of `cast_scalar`, for a U that is known to be T
+#[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[inline]
+fn recast<T: Real, U: Real>(x: Complex<U>) -> Complex<T> {
+    cast_scalar::<U, T>(x).unwrap()
+}
+
+/// SSE2 is part of the x86_64 baseline, so these need no runtime detection
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod sse2 {
+    use core::arch::x86_64::*;
+    use num_complex::Complex;
+
+    /// One Complex64 per register: (re, im)
+    pub(super) unsafe fn dot_conj_f64(x: &[Complex<f64>], y: &[Complex<f64>]) -> Complex<f64> {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f64;
+        let yp = y.as_ptr() as *const f64;
+
+        // conj(x) y = (xr yr + xi yi) + i (xr yi - xi yr)
+        let mut same = _mm_setzero_pd();
+        let mut cross = _mm_setzero_pd();
+        for i in 0..len {
+            let xv = _mm_loadu_pd(xp.add(2 * i));
+            let yv = _mm_loadu_pd(yp.add(2 * i));
+            let ys = _mm_shuffle_pd(yv, yv, 0b01);
+            same = _mm_add_pd(same, _mm_mul_pd(xv, yv));
+            cross = _mm_add_pd(cross, _mm_mul_pd(xv, ys));
+        }
+
+        let mut s = [0.0f64; 2];
+        let mut c = [0.0f64; 2];
+        _mm_storeu_pd(s.as_mut_ptr(), same);
+        _mm_storeu_pd(c.as_mut_ptr(), cross);
+
+        Complex::new(s[0] + s[1], c[0] - c[1])
+    }
+
+    pub(super) unsafe fn axpy_f64(alpha: Complex<f64>, x: &[Complex<f64>], y: &mut [Complex<f64>]) {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f64;
+        let yp = y.as_mut_ptr() as *mut f64;
+
+        // alpha x = (ar xr - ai xi, ar xi + ai xr) = ar (xr, xi) + (-ai, ai) (xi, xr)
+        let ar = _mm_set1_pd(alpha.re);
+        let ai = _mm_set_pd(alpha.im, -alpha.im);
+        for i in 0..len {
+            let xv = _mm_loadu_pd(xp.add(2 * i));
+            let xs = _mm_shuffle_pd(xv, xv, 0b01);
+            let yv = _mm_loadu_pd(yp.add(2 * i));
+            let t = _mm_add_pd(_mm_mul_pd(ar, xv), _mm_mul_pd(ai, xs));
+            _mm_storeu_pd(yp.add(2 * i), _mm_add_pd(yv, t));
+        }
+    }
+
+    /// Two Complex32 per register: (re0, im0, re1, im1)
+    pub(super) unsafe fn dot_conj_f32(x: &[Complex<f32>], y: &[Complex<f32>]) -> Complex<f32> {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f32;
+        let yp = y.as_ptr() as *const f32;
+
+        let mut same = _mm_setzero_ps();
+        let mut cross = _mm_setzero_ps();
+        let pairs = len / 2;
+        for i in 0..pairs {
+            let xv = _mm_loadu_ps(xp.add(4 * i));
+            let yv = _mm_loadu_ps(yp.add(4 * i));
+            let ys = _mm_shuffle_ps(yv, yv, 0b10_11_00_01);
+            same = _mm_add_ps(same, _mm_mul_ps(xv, yv));
+            cross = _mm_add_ps(cross, _mm_mul_ps(xv, ys));
+        }
+
+        let mut s = [0.0f32; 4];
+        let mut c = [0.0f32; 4];
+        _mm_storeu_ps(s.as_mut_ptr(), same);
+        _mm_storeu_ps(c.as_mut_ptr(), cross);
+        let mut sum = Complex::new((s[0] + s[2]) + (s[1] + s[3]), (c[0] + c[2]) - (c[1] + c[3]));
+
+        if len % 2 == 1 {
+            sum += x[len - 1].conj() * y[len - 1];
+        }
+
+        sum
+    }
+
+    pub(super) unsafe fn axpy_f32(alpha: Complex<f32>, x: &[Complex<f32>], y: &mut [Complex<f32>]) {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f32;
+        let yp = y.as_mut_ptr() as *mut f32;
+
+        let ar = _mm_set1_ps(alpha.re);
+        let ai = _mm_set_ps(alpha.im, -alpha.im, alpha.im, -alpha.im);
+        let pairs = len / 2;
+        for i in 0..pairs {
+            let xv = _mm_loadu_ps(xp.add(4 * i));
+            let xs = _mm_shuffle_ps(xv, xv, 0b10_11_00_01);
+            let yv = _mm_loadu_ps(yp.add(4 * i));
+            let t = _mm_add_ps(_mm_mul_ps(ar, xv), _mm_mul_ps(ai, xs));
+            _mm_storeu_ps(yp.add(4 * i), _mm_add_ps(yv, t));
+        }
+
+        if len % 2 == 1 {
+            y[len - 1] += alpha * x[len - 1];
+        }
+    }
+}
+
+/// Only called after `is_supported(Isa::Avx2Fma)` confirmed AVX2 and FMA, at runtime with
+/// `std` and at compile time without it
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod avx2 {
+    use core::arch::x86_64::*;
+    use num_complex::Complex;
+
+    /// Two Complex64 per register: (re0, im0, re1, im1), the permute swaps the parts of both
+    #[target_feature(enable = "avx2,fma")]
+    pub(super) unsafe fn dot_conj_f64(x: &[Complex<f64>], y: &[Complex<f64>]) -> Complex<f64> {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f64;
+        let yp = y.as_ptr() as *const f64;
+
+        // conj(x) y = (xr yr + xi yi) + i (xr yi - xi yr)
+        let mut same = _mm256_setzero_pd();
+        let mut cross = _mm256_setzero_pd();
+        let pairs = len / 2;
+        for i in 0..pairs {
+            let xv = _mm256_loadu_pd(xp.add(4 * i));
+            let yv = _mm256_loadu_pd(yp.add(4 * i));
+            same = _mm256_fmadd_pd(xv, yv, same);
+            cross = _mm256_fmadd_pd(xv, _mm256_permute_pd(yv, 0b0101), cross);
+        }
+
+        let mut s = [0.0f64; 4];
+        let mut c = [0.0f64; 4];
+        _mm256_storeu_pd(s.as_mut_ptr(), same);
+        _mm256_storeu_pd(c.as_mut_ptr(), cross);
+        let mut sum = Complex::new((s[0] + s[2]) + (s[1] + s[3]), (c[0] + c[2]) - (c[1] + c[3]));
+
+        if len % 2 == 1 {
+            sum += x[len - 1].conj() * y[len - 1];
+        }
+
+        sum
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub(super) unsafe fn axpy_f64(alpha: Complex<f64>, x: &[Complex<f64>], y: &mut [Complex<f64>]) {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f64;
+        let yp = y.as_mut_ptr() as *mut f64;
+
+        // alpha x = ar (xr, xi) + (-ai, ai) (xi, xr)
+        let ar = _mm256_set1_pd(alpha.re);
+        let ai = _mm256_set_pd(alpha.im, -alpha.im, alpha.im, -alpha.im);
+        let pairs = len / 2;
+        for i in 0..pairs {
+            let xv = _mm256_loadu_pd(xp.add(4 * i));
+            let yv = _mm256_fmadd_pd(ar, xv, _mm256_loadu_pd(yp.add(4 * i)));
+            _mm256_storeu_pd(yp.add(4 * i), _mm256_fmadd_pd(ai, _mm256_permute_pd(xv, 0b0101), yv));
+        }
+
+        if len % 2 == 1 {
+            y[len - 1] += alpha * x[len - 1];
+        }
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub(super) unsafe fn rot_f64(c: f64, s: Complex<f64>, x: &mut [Complex<f64>], y: &mut [Complex<f64>]) {
+        let len = x.len().min(y.len());
+        let xp = x.as_mut_ptr() as *mut f64;
+        let yp = y.as_mut_ptr() as *mut f64;
+
+        // s y = sr (yr, yi) + (-si, si) (yi, yr) and conj(s) x = sr (xr, xi) - (-si, si) (xi, xr)
+        let cv = _mm256_set1_pd(c);
+        let sr = _mm256_set1_pd(s.re);
+        let si = _mm256_set_pd(s.im, -s.im, s.im, -s.im);
+        let pairs = len / 2;
+        for i in 0..pairs {
+            let xv = _mm256_loadu_pd(xp.add(4 * i));
+            let yv = _mm256_loadu_pd(yp.add(4 * i));
+            let xn = _mm256_fmadd_pd(si, _mm256_permute_pd(yv, 0b0101), _mm256_fmadd_pd(sr, yv, _mm256_mul_pd(cv, xv)));
+            let yn = _mm256_fmadd_pd(si, _mm256_permute_pd(xv, 0b0101), _mm256_fnmadd_pd(sr, xv, _mm256_mul_pd(cv, yv)));
+            _mm256_storeu_pd(xp.add(4 * i), xn);
+            _mm256_storeu_pd(yp.add(4 * i), yn);
+        }
+
+        if len % 2 == 1 {
+            let (xv, yv) = (x[len - 1], y[len - 1]);
+            x[len - 1] = xv * c + s * yv;
+            y[len - 1] = yv * c - s.conj() * xv;
+        }
+    }
+
+    /// Four Complex32 per register, the permute swaps the parts within each 128-bit lane
+    #[target_feature(enable = "avx2,fma")]
+    pub(super) unsafe fn dot_conj_f32(x: &[Complex<f32>], y: &[Complex<f32>]) -> Complex<f32> {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f32;
+        let yp = y.as_ptr() as *const f32;
+
+        let mut same = _mm256_setzero_ps();
+        let mut cross = _mm256_setzero_ps();
+        let quads = len / 4;
+        for i in 0..quads {
+            let xv = _mm256_loadu_ps(xp.add(8 * i));
+            let yv = _mm256_loadu_ps(yp.add(8 * i));
+            same = _mm256_fmadd_ps(xv, yv, same);
+            cross = _mm256_fmadd_ps(xv, _mm256_permute_ps(yv, 0b10_11_00_01), cross);
+        }
+
+        let mut s = [0.0f32; 8];
+        let mut c = [0.0f32; 8];
+        _mm256_storeu_ps(s.as_mut_ptr(), same);
+        _mm256_storeu_ps(c.as_mut_ptr(), cross);
+        let re = ((s[0] + s[4]) + (s[2] + s[6])) + ((s[1] + s[5]) + (s[3] + s[7]));
+        let im = ((c[0] + c[4]) + (c[2] + c[6])) - ((c[1] + c[5]) + (c[3] + c[7]));
+        let mut sum = Complex::new(re, im);
+
+        for i in 4 * quads..len {
+            sum += x[i].conj() * y[i];
+        }
+
+        sum
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub(super) unsafe fn axpy_f32(alpha: Complex<f32>, x: &[Complex<f32>], y: &mut [Complex<f32>]) {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f32;
+        let yp = y.as_mut_ptr() as *mut f32;
+
+        let ar = _mm256_set1_ps(alpha.re);
+        let ai = _mm256_set_ps(alpha.im, -alpha.im, alpha.im, -alpha.im, alpha.im, -alpha.im, alpha.im, -alpha.im);
+        let quads = len / 4;
+        for i in 0..quads {
+            let xv = _mm256_loadu_ps(xp.add(8 * i));
+            let yv = _mm256_fmadd_ps(ar, xv, _mm256_loadu_ps(yp.add(8 * i)));
+            _mm256_storeu_ps(yp.add(8 * i), _mm256_fmadd_ps(ai, _mm256_permute_ps(xv, 0b10_11_00_01), yv));
+        }
+
+        for i in 4 * quads..len {
+            y[i] += alpha * x[i];
+        }
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub(super) unsafe fn rot_f32(c: f32, s: Complex<f32>, x: &mut [Complex<f32>], y: &mut [Complex<f32>]) {
+        let len = x.len().min(y.len());
+        let xp = x.as_mut_ptr() as *mut f32;
+        let yp = y.as_mut_ptr() as *mut f32;
+
+        let cv = _mm256_set1_ps(c);
+        let sr = _mm256_set1_ps(s.re);
+        let si = _mm256_set_ps(s.im, -s.im, s.im, -s.im, s.im, -s.im, s.im, -s.im);
+        let quads = len / 4;
+        for i in 0..quads {
+            let xv = _mm256_loadu_ps(xp.add(8 * i));
+            let yv = _mm256_loadu_ps(yp.add(8 * i));
+            let xn = _mm256_fmadd_ps(si, _mm256_permute_ps(yv, 0b10_11_00_01), _mm256_fmadd_ps(sr, yv, _mm256_mul_ps(cv, xv)));
+            let yn = _mm256_fmadd_ps(si, _mm256_permute_ps(xv, 0b10_11_00_01), _mm256_fnmadd_ps(sr, xv, _mm256_mul_ps(cv, yv)));
+            _mm256_storeu_ps(xp.add(8 * i), xn);
+            _mm256_storeu_ps(yp.add(8 * i), yn);
+        }
+
+        for i in 4 * quads..len {
+            let (xv, yv) = (x[i], y[i]);
+            x[i] = xv * c + s * yv;
+            y[i] = yv * c - s.conj() * xv;
+        }
+    }
+}
+
+/// NEON is part of the aarch64 baseline, so these need no runtime detection either
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+mod neon {
+    use core::arch::aarch64::*;
+    use num_complex::Complex;
+
+    /// (re, im) -> (im, re) of one Complex64
+    #[inline(always)]
+    unsafe fn swap_f64(v: float64x2_t) -> float64x2_t {
+        vcombine_f64(vget_high_f64(v), vget_low_f64(v))
+    }
+
+    /// One Complex64 per register: (re, im)
+    pub(super) unsafe fn dot_conj_f64(x: &[Complex<f64>], y: &[Complex<f64>]) -> Complex<f64> {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f64;
+        let yp = y.as_ptr() as *const f64;
+
+        // conj(x) y = (xr yr + xi yi) + i (xr yi - xi yr)
+        let mut same = vdupq_n_f64(0.0);
+        let mut cross = vdupq_n_f64(0.0);
+        for i in 0..len {
+            let xv = vld1q_f64(xp.add(2 * i));
+            let yv = vld1q_f64(yp.add(2 * i));
+            same = vfmaq_f64(same, xv, yv);
+            cross = vfmaq_f64(cross, xv, swap_f64(yv));
+        }
+
+        let mut s = [0.0f64; 2];
+        let mut c = [0.0f64; 2];
+        vst1q_f64(s.as_mut_ptr(), same);
+        vst1q_f64(c.as_mut_ptr(), cross);
+
+        Complex::new(s[0] + s[1], c[0] - c[1])
+    }
+
+    pub(super) unsafe fn axpy_f64(alpha: Complex<f64>, x: &[Complex<f64>], y: &mut [Complex<f64>]) {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f64;
+        let yp = y.as_mut_ptr() as *mut f64;
+
+        // alpha x = ar (xr, xi) + (-ai, ai) (xi, xr)
+        let ar = vdupq_n_f64(alpha.re);
+        let ai = vld1q_f64([-alpha.im, alpha.im].as_ptr());
+        for i in 0..len {
+            let xv = vld1q_f64(xp.add(2 * i));
+            let yv = vfmaq_f64(vld1q_f64(yp.add(2 * i)), ar, xv);
+            vst1q_f64(yp.add(2 * i), vfmaq_f64(yv, ai, swap_f64(xv)));
+        }
+    }
+
+    pub(super) unsafe fn rot_f64(c: f64, s: Complex<f64>, x: &mut [Complex<f64>], y: &mut [Complex<f64>]) {
+        let len = x.len().min(y.len());
+        let xp = x.as_mut_ptr() as *mut f64;
+        let yp = y.as_mut_ptr() as *mut f64;
+
+        // s y = sr (yr, yi) + (-si, si) (yi, yr) and conj(s) x = sr (xr, xi) - (-si, si) (xi, xr)
+        let cv = vdupq_n_f64(c);
+        let sr = vdupq_n_f64(s.re);
+        let si = vld1q_f64([-s.im, s.im].as_ptr());
+        for i in 0..len {
+            let xv = vld1q_f64(xp.add(2 * i));
+            let yv = vld1q_f64(yp.add(2 * i));
+            let xn = vfmaq_f64(vfmaq_f64(vmulq_f64(cv, xv), sr, yv), si, swap_f64(yv));
+            let yn = vfmaq_f64(vfmsq_f64(vmulq_f64(cv, yv), sr, xv), si, swap_f64(xv));
+            vst1q_f64(xp.add(2 * i), xn);
+            vst1q_f64(yp.add(2 * i), yn);
+        }
+    }
+
+    /// Two Complex32 per register: (re0, im0, re1, im1), vrev64q swaps the parts of both
+    pub(super) unsafe fn dot_conj_f32(x: &[Complex<f32>], y: &[Complex<f32>]) -> Complex<f32> {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f32;
+        let yp = y.as_ptr() as *const f32;
+
+        let mut same = vdupq_n_f32(0.0);
+        let mut cross = vdupq_n_f32(0.0);
+        let pairs = len / 2;
+        for i in 0..pairs {
+            let xv = vld1q_f32(xp.add(4 * i));
+            let yv = vld1q_f32(yp.add(4 * i));
+            same = vfmaq_f32(same, xv, yv);
+            cross = vfmaq_f32(cross, xv, vrev64q_f32(yv));
+        }
+
+        let mut s = [0.0f32; 4];
+        let mut c = [0.0f32; 4];
+        vst1q_f32(s.as_mut_ptr(), same);
+        vst1q_f32(c.as_mut_ptr(), cross);
+        let mut sum = Complex::new((s[0] + s[2]) + (s[1] + s[3]), (c[0] + c[2]) - (c[1] + c[3]));
+
+        if len % 2 == 1 {
+            sum += x[len - 1].conj() * y[len - 1];
+        }
+
+        sum
+    }
+
+    pub(super) unsafe fn axpy_f32(alpha: Complex<f32>, x: &[Complex<f32>], y: &mut [Complex<f32>]) {
+        let len = x.len().min(y.len());
+        let xp = x.as_ptr() as *const f32;
+        let yp = y.as_mut_ptr() as *mut f32;
+
+        let ar = vdupq_n_f32(alpha.re);
+        let ai = vld1q_f32([-alpha.im, alpha.im, -alpha.im, alpha.im].as_ptr());
+        let pairs = len / 2;
+        for i in 0..pairs {
+            let xv = vld1q_f32(xp.add(4 * i));
+            let yv = vfmaq_f32(vld1q_f32(yp.add(4 * i)), ar, xv);
+            vst1q_f32(yp.add(4 * i), vfmaq_f32(yv, ai, vrev64q_f32(xv)));
+        }
+
+        if len % 2 == 1 {
+            y[len - 1] += alpha * x[len - 1];
+        }
+    }
+
+    pub(super) unsafe fn rot_f32(c: f32, s: Complex<f32>, x: &mut [Complex<f32>], y: &mut [Complex<f32>]) {
+        let len = x.len().min(y.len());
+        let xp = x.as_mut_ptr() as *mut f32;
+        let yp = y.as_mut_ptr() as *mut f32;
+
+        let cv = vdupq_n_f32(c);
+        let sr = vdupq_n_f32(s.re);
+        let si = vld1q_f32([-s.im, s.im, -s.im, s.im].as_ptr());
+        let pairs = len / 2;
+        for i in 0..pairs {
+            let xv = vld1q_f32(xp.add(4 * i));
+            let yv = vld1q_f32(yp.add(4 * i));
+            let xn = vfmaq_f32(vfmaq_f32(vmulq_f32(cv, xv), sr, yv), si, vrev64q_f32(yv));
+            let yn = vfmaq_f32(vfmsq_f32(vmulq_f32(cv, yv), sr, xv), si, vrev64q_f32(xv));
+            vst1q_f32(xp.add(4 * i), xn);
+            vst1q_f32(yp.add(4 * i), yn);
+        }
+
+        if len % 2 == 1 {
+            let (xv, yv) = (x[len - 1], y[len - 1]);
+            x[len - 1] = xv * c + s * yv;
+            y[len - 1] = yv * c - s.conj() * xv;
+        }
+    }
+}
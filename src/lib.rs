@@ -1,164 +1,204 @@
 #![no_std]
-#![feature(alloc)]
-#![feature(extern_crate_item_prelude)]
+// the decompositions are ported from Fortran and index their arrays the same way
+#![allow(clippy::needless_range_loop, clippy::too_many_arguments)]
 
 #[macro_use] extern crate log;
 extern crate alloc;
 extern crate num_complex;
+extern crate num_traits;
 extern crate libm;
-// extern crate rand;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "nalgebra")]
+extern crate nalgebra;
+#[cfg(all(test, feature = "proptest"))]
+extern crate proptest;
 // extern crate aligned_vec;
 
+pub mod aligned;
+pub mod batch;
+pub mod blas;
+pub mod codec;
+#[cfg(feature = "std")]
+pub mod csv;
 pub mod csvd;
+pub mod eig;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fixed;
+pub mod gallery;
+pub mod incremental;
+pub mod iq;
+pub mod iterative;
+pub mod kernels;
+pub mod lu;
+pub mod matrix;
+pub mod mimo;
+pub mod mixed;
+#[cfg(feature = "nalgebra")]
+mod nalgebra_interop;
+#[cfg(feature = "std")]
+pub mod npy;
+pub mod pinv_operator;
+pub mod pool;
+#[cfg(feature = "std")]
+pub mod print;
+pub mod qr;
+pub mod random;
+pub mod rsvd;
+pub mod scalar;
+pub mod soa;
+pub mod solve;
+pub mod sparse;
+pub mod svd;
+pub mod svd4;
 pub mod test;
+mod update;
+pub mod verify;
+pub mod view;
+pub mod workspace;
 
-use num_complex::Complex32;
+use num_complex::{Complex, Complex32};
 use alloc::vec::Vec;
 use self::csvd::csvd;
-// use aligned_vec::{aligned_alloc, aligned_alloc_f32_16};
-use core::mem;
-
-#[repr(align(16))]
-struct Align16(u64,u64);
-
-#[repr(align(32))]
-struct Align32(u64,u64,u64,u64);
-
-#[repr(align(64))]
-struct Align64(u64,u64,u64,u64,u64,u64,u64,u64);
-
-/// function to return a vector aligned at "alignment" no of bytes with the only valid inputs being 16, 32 and 64
-/// len is the the number of Complex32 items in the vector
-/// vec is where the resulting vector is stored
-/// function also initializes all elements to '0.0 + 0.0i'
-pub fn aligned_alloc(alignment: u8, len: usize, mut vec: &mut Vec<Complex32>) -> Result<(), &'static str> {
-    
-    if alignment == 16 {
-        aligned_alloc_16(len, &mut vec);
-    }
-    else if alignment == 32 {
-        aligned_alloc_32(len, &mut vec);
-    }
-    else if alignment == 64 {
-        aligned_alloc_64(len, &mut vec);
-    }
-    else{
-        return Err("Invalid alignment input")
+use self::scalar::Real;
+pub use self::aligned::{aligned_vec, aligned_vec_in, aligned_vec_uninit, aligned_vec_uninit_in, dma_buffer, dma_buffer_in, Alloc, AlignedVec, Global, Pod, PAGE_SIZE};
+pub use self::batch::{batched_pinv, batched_svd, BatchLayout, BatchedSvd};
+pub use self::blas::{axpy, dotc, dotu, gemm, gemm_3m, gemm_view, gemv, nrm2, scal, Op};
+pub use self::eig::{eigh, HermitianEig};
+pub use self::error::CsvdError;
+pub use self::fixed::{inv2, inv3, svd_fixed, FixedSvd};
+pub use self::incremental::IncrementalSvd;
+pub use self::iq::{iq_aligned, iq_into, iq_matrix, pairs_as_complex, pairs_as_complex_mut, q15_into, IqSample};
+pub use self::iterative::{bicgstab, bicgstab_preconditioned, cg, cg_with_progress, cgls, cgls_with_progress, dominant_singular_triplet, dominant_singular_triplet_of, gmres, gmres_preconditioned, lsqr, partial_svd, sigma_min, FnOperator, IterativeSolution, JacobiPreconditioner, LanczosOptions, LinearOperator, Preconditioner, SingularTriplet, SolverOptions, SolverProgress};
+pub use self::lu::{cond_estimate, det, inv, logdet, lu, Lu};
+pub use self::matrix::{kron, outer, Layout, Matrix};
+pub use self::mimo::{eigen_beamforming, zf_weights, zf_weights_batched, Beamforming};
+pub use self::mixed::{lstsq_mixed, pinv_mixed, MixedPrecisionSolver};
+pub use self::pinv_operator::PinvOperator;
+pub use self::pool::{BufferPool, PooledMatrix};
+#[cfg(feature = "std")]
+pub use self::print::{print_matrix, print_svd, print_vector};
+pub use self::qr::{orthonormalize, qr, qr_pivoted, Qr};
+pub use self::random::{random_complex_matrix, random_unitary, Xoshiro256};
+pub use self::rsvd::svd_real;
+pub use self::soa::{gemm_soa, qr_soa, SoaMatrix};
+pub use self::solve::{solve_auto, AutoSolution, Solver};
+pub use self::sparse::{spmv, CsrMatrix};
+pub use self::csvd::{csvd_in_slices, csvd_unchecked, csvd_view, csvd_with_progress, csvd_with_stats, Progress, Summation, SvdOptions, SvdStats};
+pub use self::svd::{column_space, cond, low_rank_approx, lstsq, null_space, pinv_regularized, rank, rank_by_gap, refine_singular_vectors, solve_multi, svd, svd_unchecked, svd_with_options, svd_with_progress, svd_with_stats, svd_truncated, svd_q15, singular_values, structure, svd_auto, GapRank, LowRankApprox, LstsqSolution, Reorthogonalization, Structure, StructuredSvd, SvdResult};
+pub use self::svd4::{pinv4, svd4};
+pub use self::verify::{is_hermitian, is_unitary, verify_lstsq, verify_pinv, verify_svd, LstsqReport, PinvReport, SvdReport};
+pub use self::view::{MatrixView, MatrixViewMut};
+pub use self::workspace::{csvd_with_workspace, MatrixRef, SvdWorkspace};
+
+/// Smallest number of elements an update has to touch before the `parallel` feature
+/// spreads it over threads, below that the scheduling overhead dominates
+#[cfg(feature = "parallel")]
+pub(crate) const PARALLEL_MIN_ELEMENTS: usize = 64 * 64;
+
+/// function to return a vector of len elements, all initialized to '0.0 + 0.0i'
+/// the only valid alignments are 16, 32 and 64
+///
+/// A Vec frees its buffer with the alignment of Complex32, so it cannot hold an over-aligned
+/// allocation, and the returned vector is no longer guaranteed to have the requested alignment.
+#[deprecated(note = "a Vec cannot own over-aligned memory, use AlignedVec::zeroed instead")]
+pub fn aligned_alloc(alignment: u8, len: usize, vec: &mut Vec<Complex32>) -> Result<(), CsvdError> {
+    if alignment != 16 && alignment != 32 && alignment != 64 {
+        return Err(CsvdError::InvalidAlignment { alignment: alignment as usize })
     }
-    
- 
+
+    vec.clear();
+    vec.resize(len, Complex32::new(0.0, 0.0));
     Ok(())
 }
 
-fn aligned_alloc_16(len: usize, vec: &mut Vec<Complex32>) {
-    let C32_in_A16 = 2;
-
-    let buffer : Vec<Align16> = Vec::with_capacity(len/C32_in_A16);
-    let buffer_ptr = buffer.as_slice().as_ptr() as usize;
-    // debug!("Aligned pointer: {:#X}", buffer_ptr);
-    let ptr = buffer_ptr as *mut Complex32;
-    mem::forget(buffer);
-    *vec = unsafe {Vec::from_raw_parts(ptr, len, len)};
-
-    for num in vec {
-        num.re = 0.0;
-        num.im = 0.0;
-    }
+/// function to return a vector of len zeros
+/// A Vec cannot own over-aligned memory, so the vector has no particular alignment
+#[deprecated(note = "a Vec cannot own over-aligned memory, use aligned_vec::<u8>(len, 32) instead")]
+pub fn aligned_alloc_u8_32(len: usize, vec: &mut Vec<u8>) {
+    vec.clear();
+    vec.resize(len, 0);
 }
 
-fn aligned_alloc_32(len: usize, vec: &mut Vec<Complex32>) {
-    let C32_in_A32 = 4;
-
-    let buffer : Vec<Align32> = Vec::with_capacity(len/C32_in_A32);
-    let buffer_ptr = buffer.as_slice().as_ptr() as usize;
-    // debug!("Aligned pointer: {:#X}", buffer_ptr);
-    let ptr = buffer_ptr as *mut Complex32;
-    mem::forget(buffer);
-    *vec = unsafe {Vec::from_raw_parts(ptr, len, len)};
-
-    for num in vec {
-        num.re = 0.0;
-        num.im = 0.0;
-    }
+/// function to return a vector of len zeros
+/// A Vec cannot own over-aligned memory, so the vector has no particular alignment
+#[deprecated(note = "a Vec cannot own over-aligned memory, use aligned_vec::<f32>(len, 32) instead")]
+pub fn aligned_alloc_f32_32(len: usize, vec: &mut Vec<f32>) {
+    vec.clear();
+    vec.resize(len, 0.0);
 }
 
-fn aligned_alloc_64(len: usize, vec: &mut Vec<Complex32>) {
-    let C32_in_A64 = 8;
-
-    let buffer : Vec<Align64> = Vec::with_capacity(len/C32_in_A64);
-    let buffer_ptr = buffer.as_slice().as_ptr() as usize;
-    // debug!("Aligned pointer: {:#X}", buffer_ptr);
-    let ptr = buffer_ptr as *mut Complex32;
-    mem::forget(buffer);
-    *vec = unsafe {Vec::from_raw_parts(ptr, len, len)};
-
-    for num in vec {
-        num.re = 0.0;
-        num.im = 0.0;
-    }
-    
+/// Cut-off for the singular values that the pseudo-inverse treats as zero
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PinvOptions<T> {
+    /// singular values not larger than atol are treated as zero
+    pub atol: T,
+    /// singular values not larger than rcond * sigma_max are treated as zero,
+    /// None uses max(m, n) * eps as NumPy and LAPACK do
+    pub rcond: Option<T>,
 }
 
-///TODO: need to generalize this
-pub fn aligned_alloc_u8_32(len: usize, vec: &mut Vec<u8>) {
-    let U8_in_A32 = 32;
-
-    let buffer : Vec<Align16> = Vec::with_capacity(len/U8_in_A32);
-    let buffer_ptr = buffer.as_slice().as_ptr() as usize;
-    // debug!("Aligned pointer: {:#X}", buffer_ptr);
-    let ptr = buffer_ptr as *mut u8;
-    mem::forget(buffer);
-    *vec = unsafe {Vec::from_raw_parts(ptr, len, len)};
-
-    for num in vec {
-        *num = 0;
+impl<T: Real> Default for PinvOptions<T> {
+    fn default() -> PinvOptions<T> {
+        PinvOptions { atol: T::zero(), rcond: None }
     }
 }
 
-///TODO: need to generalize this
-pub fn aligned_alloc_f32_32(len: usize, vec: &mut Vec<f32>) {
-    let F32_in_A32 = 8;
+impl<T: Real> PinvOptions<T> {
+    /// Only the absolute threshold tol, which is what `pinv` and `find_pinv_from_svd` use with tol = 1e-4
+    pub fn absolute(tol: T) -> PinvOptions<T> {
+        PinvOptions { atol: tol, rcond: Some(T::zero()) }
+    }
 
-    let buffer : Vec<Align16> = Vec::with_capacity(len/F32_in_A32);
-    let buffer_ptr = buffer.as_slice().as_ptr() as usize;
-    // debug!("Aligned pointer: {:#X}", buffer_ptr);
-    let ptr = buffer_ptr as *mut f32;
-    mem::forget(buffer);
-    *vec = unsafe {Vec::from_raw_parts(ptr, len, len)};
+    /// The largest singular value that is treated as zero for an m x n matrix
+    /// whose largest singular value is s_max
+    pub fn cutoff(&self, s_max: T, m: usize, n: usize) -> T {
+        let rcond = match self.rcond {
+            Some(rcond) => rcond,
+            None => T::from(m.max(n)).unwrap() * T::epsilon(),
+        };
 
-    for num in vec {
-        *num = 0.0;
+        self.atol.max(rcond * s_max)
     }
 }
 
 /// Finds the pseudo-inverse of matrix using Singular Value Decomposition
 /// Assumes that input_mat has dimensions mxn and inverse_mat has dimension nxm
 /// Stores the return value in inverse_mat, and values of input_mat are modified
-pub fn pinv(mut input_mat: &mut Vec<Complex32>, mut inverse_mat: &mut Vec<Complex32>, input_num_rows: usize, input_num_cols: usize) -> Result< (), &'static str> {
-    let m = input_num_rows;
-    let n = input_num_cols;
+/// Works for both Complex32 and Complex64 matrices, and for any m and n
+pub fn pinv<T: Real>(input_mat: &mut Matrix<Complex<T>>, inverse_mat: &mut Matrix<Complex<T>>) -> Result<(), CsvdError> {
+    pinv_with_options(input_mat, inverse_mat, &PinvOptions::absolute(T::from(0.0001).unwrap()))
+}
+
+/// Same as `pinv`, with the cut-off for zero singular values given explicitly
+pub fn pinv_with_options<T: Real>(input_mat: &mut Matrix<Complex<T>>, inverse_mat: &mut Matrix<Complex<T>>,
+        options: &PinvOptions<T>) -> Result<(), CsvdError> {
+    let m = input_mat.rows();
+    let n = input_mat.cols();
 
-    //create S vector with dimension n
-    let mut s: Vec<f32> = Vec::with_capacity(n);
-    for _ in 0..n {
-        s.push(0.0);
+    if inverse_mat.rows() != n || inverse_mat.cols() != m {
+        return Err(CsvdError::DimensionMismatch { operand: "inverse_mat", expected: (n, m), found: (inverse_mat.rows(), inverse_mat.cols()) });
     }
 
-    //create U matrix dimension mxm
-    let mut u: Vec<Complex32> = Vec::with_capacity(m*m);
-    for _ in 0..m*m {
-        u.push(Complex32{re: 0.0, im: 0.0});
+    //create S vector with dimension min(m, n)
+    let mut s: Vec<T> = Vec::with_capacity(m.min(n));
+    for _ in 0..m.min(n) {
+        s.push(T::zero());
     }
 
+    //create U matrix dimension mxm
+    let mut u = Matrix::zeros(m, m);
+
     //create v matrix with dimension nxn
-    let mut v: Vec<Complex32> = Vec::with_capacity(n*n);
-    for _ in 0..n*n {
-        v.push(Complex32{re: 0.0, im: 0.0});
-    }
+    let mut v = Matrix::zeros(n, n);
 
-    csvd(&mut input_mat, m, n, n, m, 0, m, n, &mut s, &mut u, &mut v)?;
+    csvd(input_mat, 0, m, n, &mut s, &mut u, &mut v)?;
 
-    find_pinv_from_svd(&mut s, &u, &v, m, n, &mut inverse_mat);
+    find_pinv_from_svd_with_options(&mut s, &u, &v, inverse_mat, options);
 
 
     Ok(())
@@ -166,97 +206,52 @@ pub fn pinv(mut input_mat: &mut Vec<Complex32>, mut inverse_mat: &mut Vec<Comple
 
 /// Finds the pseudo-inverse of a matrix from the singular value decompositions
 /// INV = V x S+ x U*
-/// where S+ is found by taking the reciprocal fo all non-zero elements of S and changing the dimension from n to nxm
+/// where S+ is found by taking the reciprocal fo all non-zero elements of S and changing the dimension from min(m, n) to nxm
 /// and U* is the conjugate-transpose of U
-pub fn find_pinv_from_svd(s: &mut Vec<f32>, u: &Vec<Complex32>, v: &Vec<Complex32>, m: usize, n: usize, inv: &mut Vec<Complex32>) {
+/// u has dimension mxm, v has dimension nxn, s has min(m, n) elements and inv has dimension nxm
+/// Singular values up to the absolute threshold 1e-4 are assumed to be 0
+pub fn find_pinv_from_svd<T: Real>(s: &mut [T], u: &Matrix<Complex<T>>, v: &Matrix<Complex<T>>, inv: &mut Matrix<Complex<T>>) {
+    find_pinv_from_svd_with_options(s, u, v, inv, &PinvOptions::absolute(T::from(0.0001).unwrap()))
+}
+
+/// Same as `find_pinv_from_svd`, with the cut-off for zero singular values given explicitly
+pub fn find_pinv_from_svd_with_options<T: Real>(s: &mut [T], u: &Matrix<Complex<T>>, v: &Matrix<Complex<T>>,
+        inv: &mut Matrix<Complex<T>>, options: &PinvOptions<T>) {
+    let m = u.rows();
+    let n = v.rows();
+    // a truncated decomposition only holds s.len() singular triplets
+    let min = m.min(n).min(s.len());
 
     // debug!("In find pinv from svd");
-    // cut-off value for a number to be assumed to be 0
-    let eps = 0.0001;
-    let mut n_ = n;
+    // cut-off value for a number to be assumed to be 0, the singular values are in descending order
+    let s_max = if min > 0 { s[0] } else { T::zero() };
+    let eps = options.cutoff(s_max, m, n);
 
     // take reciprocal of all non-zero elements in S
-    for i in 0..n {
+    for i in 0..min {
         if s[i] > eps {
-            s[i] = 1.0/s[i];
+            s[i] = T::one()/s[i];
         }
         else {
-            s[i] = 0.0;
+            s[i] = T::zero();
         }
     }
 
-    // extend S to be of size m 
-    while n_ < m {
-        s.push(0.0);
-        n_ += 1;
-    }
-
     for i in 0..n {
         for j in 0..m {
-            inv[i*n + j].re = 0.0;
-            inv[i*n + j].im = 0.0;
-            for k in 0..n {
-                inv[i*n + j] = inv[i*n + j] + v[i*n + k] * s[k] * u[j*m + k].conj();
+            inv[(i, j)] = Complex::new(T::zero(), T::zero());
+            for k in 0..min {
+                inv[(i, j)] = inv[(i, j)] + v[(i, k)] * s[k] * u[(j, k)].conj();
             }
         }
     }
 }
 
+/// Computes C = C + A x B
+/// where A is a_rows x a_cols, B is a_cols x b_cols and C is a_rows x b_cols
 #[inline]
-pub fn matrix_mult(a: &[Complex32], a_rows: usize, a_cols: usize, b: &[Complex32], b_rows: usize, b_cols: usize, c: &mut[Complex32]) -> Result< (), &'static str> {
-//     let a = &mat_a[0..a_rows*a_cols];
-//     let b = &mat_b[0..b_rows*b_cols];
-//     let c = &mut mat_c[0..a_rows*b_cols];
-//     if a_cols != b_rows {
-//         return Err("Matrix dimension not compatible!");
-//     }
-
-    // //transpose b
-    // let mut b = Vec::new();
-    // aligned_alloc_32(b_rows*b_cols, &mut b);
-
-    // for i in 0..b_cols {
-    //     for j in 0..b_rows {
-    //         b[i*b_rows + j] = mat_b[j*b_cols +i];
-    //     }
-    // }
-
-    // let b = &b[0..b_cols*b_rows];
-
-
-    // let mut i = 0;
-    // loop {
-    //     if i==a_rows {break;}
-    //     let mut j = 0;
-
-    //     loop {
-    //         if j == a_rows { break;}
-    //         let mut k = 0;
-
-    //         loop {
-    //             if k == a_cols { break;}
-
-    //             c[i * b_cols + j] += a[j*a_cols + k] * b[j*a_cols + k];
-    //             k += 1;
-    //         }
-
-    //         j += 1;
-    //     }
-
-    //     i +=1;
-    // }
-    // const a_r: usize = 8;
-    // const b_c: usize = 8;
-    // const a_c: usize = 8;
-
-    for i in 0..a_rows {
-        for j in 0..b_cols {
-            for k in 0..a_cols{
-                c[i * b_cols + j] += a[i*a_cols + k] * b[k*b_cols + j]; 
-            }
-        }
-    }
-
-    Ok(())
+pub fn matrix_mult<T: Real>(a: &Matrix<Complex<T>>, b: &Matrix<Complex<T>>, c: &mut Matrix<Complex<T>>) -> Result<(), CsvdError> {
+    let one = Complex::new(T::one(), T::zero());
+    gemm(one, Op::None, a, Op::None, b, one, c)
 }
 
@@ -0,0 +1,352 @@
+use alloc::vec::Vec;
+use num_complex::Complex;
+use super::csvd::cabs;
+use super::error::CsvdError;
+use super::matrix::{Layout, Matrix};
+use super::scalar::Real;
+
+/// The LU decomposition P A = L U of a square n x n matrix A with partial pivoting.
+///
+/// As in LAPACK's getrf, the unit lower triangular L (without its diagonal) and
+/// the upper triangular U share one matrix, and row i of P A is row perm()[i] of A.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lu<T> {
+    lu: Matrix<Complex<T>>,
+    perm: Vec<usize>,
+}
+
+/// Computes the LU decomposition of the square matrix a, leaving a untouched.
+/// Returns `CsvdError::Singular` if a pivot is exactly zero.
+pub fn lu<T: Real>(a: &Matrix<Complex<T>>) -> Result<Lu<T>, CsvdError> {
+    let n = a.rows();
+
+    if n < 1 || a.cols() < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    if a.cols() != n {
+        return Err(CsvdError::DimensionMismatch { operand: "a", expected: (n, n), found: (n, a.cols()) });
+    }
+
+    let mut lu = a.to_layout(Layout::RowMajor);
+    let mut perm: Vec<usize> = (0..n).collect();
+
+    for k in 0..n {
+        // the largest remaining entry of column k becomes the pivot
+        let mut p = k;
+        for i in k + 1..n {
+            if cabs(&lu[(i, k)]) > cabs(&lu[(p, k)]) {
+                p = i;
+            }
+        }
+        if lu[(p, k)] == Complex::new(T::zero(), T::zero()) {
+            return Err(CsvdError::Singular { index: k });
+        }
+        if p != k {
+            for j in 0..n {
+                let tmp = lu[(k, j)];
+                lu[(k, j)] = lu[(p, j)];
+                lu[(p, j)] = tmp;
+            }
+            perm.swap(k, p);
+        }
+
+        let pivot = lu[(k, k)];
+        for i in k + 1..n {
+            let l = lu[(i, k)] / pivot;
+            lu[(i, k)] = l;
+            for j in k + 1..n {
+                lu[(i, j)] = lu[(i, j)] - l * lu[(k, j)];
+            }
+        }
+    }
+
+    Ok(Lu { lu, perm })
+}
+
+/// Inverse of the nonsingular square matrix a, through its LU decomposition.
+/// For well-conditioned square matrices this is much cheaper than `pinv`.
+pub fn inv<T: Real>(a: &Matrix<Complex<T>>) -> Result<Matrix<Complex<T>>, CsvdError> {
+    Ok(lu(a)?.inverse())
+}
+
+/// Determinant of the square matrix a, through its LU decomposition.
+/// A singular matrix has determinant zero.
+pub fn det<T: Real>(a: &Matrix<Complex<T>>) -> Result<Complex<T>, CsvdError> {
+    match lu(a) {
+        Ok(f) => Ok(f.det()),
+        Err(CsvdError::Singular { .. }) => Ok(Complex::new(T::zero(), T::zero())),
+        Err(e) => Err(e),
+    }
+}
+
+/// Phase and natural logarithm of the magnitude of the determinant of the square matrix a,
+/// det(a) = phase * exp(logabs), as NumPy's slogdet. Unlike `det` this neither overflows
+/// nor underflows for large matrices, e.g. in likelihood computations.
+/// A singular matrix gives a zero phase and a logabs of minus infinity.
+pub fn logdet<T: Real>(a: &Matrix<Complex<T>>) -> Result<(Complex<T>, T), CsvdError> {
+    match lu(a) {
+        Ok(f) => Ok(f.logdet()),
+        Err(CsvdError::Singular { .. }) => Ok((Complex::new(T::zero(), T::zero()), T::neg_infinity())),
+        Err(e) => Err(e),
+    }
+}
+
+/// Estimates the 1-norm condition number ||A||_1 ||A^-1||_1 of the square matrix a from its
+/// LU decomposition in O(n^2) after the O(n^3) factorization, a fraction of the cost of `cond`.
+/// The estimate is a lower bound that is rarely off by more than a factor of 3, and as the 1-
+/// and 2-norm condition numbers differ by at most a factor of n, it is enough to decide
+/// whether a system is well-conditioned enough for `Lu::solve` or needs the SVD.
+/// A singular matrix has an infinite condition number.
+pub fn cond_estimate<T: Real>(a: &Matrix<Complex<T>>) -> Result<T, CsvdError> {
+    match lu(a) {
+        Ok(f) => {
+            let norm = (0..a.cols()).map(|j| (0..a.rows()).fold(T::zero(), |acc, i| acc + cabs(&a[(i, j)]))).fold(T::zero(), T::max);
+            Ok(norm * f.inverse_norm1_estimate())
+        }
+        Err(CsvdError::Singular { .. }) => Ok(T::infinity()),
+        Err(e) => Err(e),
+    }
+}
+
+impl<T: Real> Lu<T> {
+    /// Order of the decomposed matrix
+    pub fn n(&self) -> usize {
+        self.lu.rows()
+    }
+
+    /// The row permutation, row i of P A is row perm()[i] of A
+    pub fn perm(&self) -> &[usize] {
+        &self.perm
+    }
+
+    /// The unit lower triangular factor L
+    pub fn l(&self) -> Matrix<Complex<T>> {
+        let n = self.n();
+        let mut l = Matrix::zeros(n, n);
+
+        for i in 0..n {
+            for j in 0..i {
+                l[(i, j)] = self.lu[(i, j)];
+            }
+            l[(i, i)] = Complex::new(T::one(), T::zero());
+        }
+
+        l
+    }
+
+    /// The upper triangular factor U
+    pub fn u(&self) -> Matrix<Complex<T>> {
+        let n = self.n();
+        let mut u = Matrix::zeros(n, n);
+
+        for i in 0..n {
+            for j in i..n {
+                u[(i, j)] = self.lu[(i, j)];
+            }
+        }
+
+        u
+    }
+
+    /// Determinant of the decomposed matrix
+    pub fn det(&self) -> Complex<T> {
+        let mut det = Complex::new(self.perm_sign(), T::zero());
+
+        for i in 0..self.n() {
+            det = det * self.lu[(i, i)];
+        }
+
+        det
+    }
+
+    /// Phase and natural logarithm of the magnitude of the determinant, see `logdet`
+    pub fn logdet(&self) -> (Complex<T>, T) {
+        let mut phase = Complex::new(self.perm_sign(), T::zero());
+        let mut logabs = T::zero();
+
+        for i in 0..self.n() {
+            let d = self.lu[(i, i)];
+            let abs = cabs(&d);
+            phase = phase * (d / abs);
+            logabs = logabs + abs.ln();
+        }
+
+        (phase, logabs)
+    }
+
+    /// Sign of the row permutation, -1 if it consists of an odd number of transpositions
+    fn perm_sign(&self) -> T {
+        let n = self.n();
+        let mut sign = T::one();
+        let mut visited: Vec<bool> = (0..n).map(|_| false).collect();
+
+        // a cycle of length l is made of l - 1 transpositions
+        for start in 0..n {
+            let mut len = 0;
+            let mut i = start;
+            while !visited[i] {
+                visited[i] = true;
+                i = self.perm[i];
+                len += 1;
+            }
+            if len > 0 && len % 2 == 0 {
+                sign = -sign;
+            }
+        }
+
+        sign
+    }
+
+    /// Solves A X = B for the n x nrhs matrix b, returning X
+    pub fn solve(&self, b: &Matrix<Complex<T>>) -> Result<Matrix<Complex<T>>, CsvdError> {
+        let n = self.n();
+        let nrhs = b.cols();
+
+        if b.rows() != n {
+            return Err(CsvdError::DimensionMismatch { operand: "b", expected: (n, nrhs), found: (b.rows(), nrhs) });
+        }
+
+        // X = P B
+        let mut x = Matrix::zeros(n, nrhs);
+        for i in 0..n {
+            for j in 0..nrhs {
+                x[(i, j)] = b[(self.perm[i], j)];
+            }
+        }
+
+        for j in 0..nrhs {
+            // forward substitution with L
+            for i in 0..n {
+                let mut sum = x[(i, j)];
+                for k in 0..i {
+                    sum = sum - self.lu[(i, k)] * x[(k, j)];
+                }
+                x[(i, j)] = sum;
+            }
+
+            // back substitution with U
+            for i in (0..n).rev() {
+                let mut sum = x[(i, j)];
+                for k in i + 1..n {
+                    sum = sum - self.lu[(i, k)] * x[(k, j)];
+                }
+                x[(i, j)] = sum / self.lu[(i, i)];
+            }
+        }
+
+        Ok(x)
+    }
+
+    /// Estimates ||A^-1||_1 with Hager's method as refined by Higham, LAPACK's lacn2, from at
+    /// most five solves with A and with A*, without forming the inverse
+    pub fn inverse_norm1_estimate(&self) -> T {
+        let n = self.n();
+        let zero = Complex::new(T::zero(), T::zero());
+        let one = Complex::new(T::one(), T::zero());
+        let norm1 = |x: &[Complex<T>]| x.iter().fold(T::zero(), |acc, x| acc + cabs(x));
+
+        let mut x = alloc::vec![one / T::from(n).unwrap(); n];
+        self.solve_in_place(&mut x);
+        let mut est = norm1(&x);
+        let mut last = n;
+
+        for _ in 0..5 {
+            // the subgradient of ||y||_1 at y = A^-1 x
+            for y in x.iter_mut() {
+                let abs = cabs(y);
+                *y = if abs > T::zero() { *y / abs } else { one };
+            }
+            self.solve_adjoint_in_place(&mut x);
+
+            let mut j = 0;
+            for i in 1..n {
+                if cabs(&x[i]) > cabs(&x[j]) {
+                    j = i;
+                }
+            }
+            // a repeated column is a local maximum
+            if j == last {
+                break;
+            }
+            last = j;
+
+            x.fill(zero);
+            x[j] = one;
+            self.solve_in_place(&mut x);
+            let next = norm1(&x);
+            if next <= est {
+                break;
+            }
+            est = next;
+        }
+
+        // the alternating vector guards against the rare matrices that mislead the iteration
+        if n > 1 {
+            let mut x: Vec<Complex<T>> = (0..n).map(|i| {
+                let v = T::one() + T::from(i).unwrap() / T::from(n - 1).unwrap();
+                Complex::new(if i % 2 == 0 { v } else { -v }, T::zero())
+            }).collect();
+            self.solve_in_place(&mut x);
+            est = est.max(norm1(&x) * T::from(2.0 / (3 * n) as f64).unwrap());
+        }
+
+        est
+    }
+
+    /// Overwrites x with the solution of A y = x
+    fn solve_in_place(&self, x: &mut [Complex<T>]) {
+        let n = self.n();
+
+        let b = x.to_vec();
+        for i in 0..n {
+            x[i] = b[self.perm[i]];
+        }
+        for i in 0..n {
+            let mut sum = x[i];
+            for k in 0..i {
+                sum = sum - self.lu[(i, k)] * x[k];
+            }
+            x[i] = sum;
+        }
+        for i in (0..n).rev() {
+            let mut sum = x[i];
+            for k in i + 1..n {
+                sum = sum - self.lu[(i, k)] * x[k];
+            }
+            x[i] = sum / self.lu[(i, i)];
+        }
+    }
+
+    /// Overwrites x with the solution of A* y = x, with A* = U* L* P
+    fn solve_adjoint_in_place(&self, x: &mut [Complex<T>]) {
+        let n = self.n();
+
+        // forward substitution with U*
+        for i in 0..n {
+            let mut sum = x[i];
+            for k in 0..i {
+                sum = sum - self.lu[(k, i)].conj() * x[k];
+            }
+            x[i] = sum / self.lu[(i, i)].conj();
+        }
+        // back substitution with the unit L*
+        for i in (0..n).rev() {
+            let mut sum = x[i];
+            for k in i + 1..n {
+                sum = sum - self.lu[(k, i)].conj() * x[k];
+            }
+            x[i] = sum;
+        }
+
+        let w = x.to_vec();
+        for i in 0..n {
+            x[self.perm[i]] = w[i];
+        }
+    }
+
+    /// The inverse of the decomposed matrix
+    pub fn inverse(&self) -> Matrix<Complex<T>> {
+        // the identity always has the right number of rows
+        self.solve(&Matrix::identity(self.n())).unwrap()
+    }
+}
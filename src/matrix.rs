@@ -0,0 +1,390 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Index, IndexMut};
+use num_complex::Complex;
+use num_traits::Zero;
+use super::error::CsvdError;
+use super::scalar::Real;
+
+/// Order in which the elements of a matrix are stored
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// Row by row, as in C and NumPy
+    #[default]
+    RowMajor,
+    /// Column by column, as in Fortran, LAPACK and MATLAB
+    ColMajor,
+}
+
+/// A dense matrix stored in row-major or column-major order.
+///
+/// In row-major order element (i, j) lives at `data[i * ld + j]`, where the leading dimension
+/// `ld` is the distance between the starts of two consecutive rows and must be at least `cols`.
+/// A leading dimension larger than `cols` lets rows be padded, e.g. to keep every row aligned.
+/// In column-major order element (i, j) lives at `data[j * ld + i]` with `ld` at least `rows`,
+/// so that Fortran-ordered data can be used without a transpose copy.
+///
+/// `csvd`, `pinv` and `gemm` accept either layout, most other routines expect row-major matrices.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix<T> {
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+    ld: usize,
+    layout: Layout,
+}
+
+impl<T> Matrix<T> {
+    /// Wraps a tightly packed row-major vector of `rows * cols` elements
+    pub fn from_vec(data: Vec<T>, rows: usize, cols: usize) -> Result<Matrix<T>, CsvdError> {
+        Matrix::from_vec_with_ld(data, rows, cols, cols)
+    }
+
+    /// Wraps a row-major vector whose rows are `ld` elements apart
+    pub fn from_vec_with_ld(data: Vec<T>, rows: usize, cols: usize, ld: usize) -> Result<Matrix<T>, CsvdError> {
+        Matrix::from_vec_with_layout(data, rows, cols, ld, Layout::RowMajor)
+    }
+
+    /// Wraps a tightly packed column-major vector of `rows * cols` elements, e.g. from Fortran
+    pub fn from_vec_col_major(data: Vec<T>, rows: usize, cols: usize) -> Result<Matrix<T>, CsvdError> {
+        Matrix::from_vec_with_layout(data, rows, cols, rows, Layout::ColMajor)
+    }
+
+    /// Wraps a vector in the given layout whose rows, or columns for `Layout::ColMajor`,
+    /// are `ld` elements apart
+    pub fn from_vec_with_layout(data: Vec<T>, rows: usize, cols: usize, ld: usize, layout: Layout) -> Result<Matrix<T>, CsvdError> {
+        let (lines, len) = match layout {
+            Layout::RowMajor => (rows, cols),
+            Layout::ColMajor => (cols, rows),
+        };
+        if ld < len {
+            return Err(CsvdError::InvalidLeadingDimension { ld, cols: len });
+        }
+        let required = if lines > 0 { (lines - 1) * ld + len } else { 0 };
+        if data.len() < required {
+            return Err(CsvdError::BufferTooSmall { operand: "data", required, len: data.len() });
+        }
+
+        Ok(Matrix { data, rows, cols, ld, layout })
+    }
+
+    /// Number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Leading dimension, i.e. the distance between the starts of two consecutive rows,
+    /// or columns for a column-major matrix
+    pub fn ld(&self) -> usize {
+        self.ld
+    }
+
+    /// Order in which the elements are stored
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// The underlying storage in the layout of the matrix, including any padding
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// The underlying mutable storage in the layout of the matrix, including any padding
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Returns the underlying storage
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Reinterprets the first rows * cols elements of the storage as a tightly packed
+    /// rows x cols matrix in the same layout, without moving or reallocating anything
+    pub fn reshape(&mut self, rows: usize, cols: usize) -> Result<(), CsvdError> {
+        if self.data.len() < rows * cols {
+            return Err(CsvdError::BufferTooSmall { operand: "data", required: rows * cols, len: self.data.len() });
+        }
+
+        self.rows = rows;
+        self.cols = cols;
+        self.ld = match self.layout {
+            Layout::RowMajor => cols,
+            Layout::ColMajor => rows,
+        };
+        Ok(())
+    }
+
+    /// Row i, without padding. Panics for a column-major matrix, whose rows are not contiguous.
+    pub fn row(&self, i: usize) -> &[T] {
+        assert!(self.layout == Layout::RowMajor, "row of a column-major matrix");
+        &self.data[i * self.ld..i * self.ld + self.cols]
+    }
+
+    /// Mutable row i, without padding. Panics for a column-major matrix.
+    pub fn row_mut(&mut self, i: usize) -> &mut [T] {
+        assert!(self.layout == Layout::RowMajor, "row of a column-major matrix");
+        &mut self.data[i * self.ld..i * self.ld + self.cols]
+    }
+
+    /// Column j, without padding. Panics for a row-major matrix, whose columns are not contiguous.
+    pub fn col(&self, j: usize) -> &[T] {
+        assert!(self.layout == Layout::ColMajor, "column of a row-major matrix");
+        &self.data[j * self.ld..j * self.ld + self.rows]
+    }
+
+    /// Mutable column j, without padding. Panics for a row-major matrix.
+    pub fn col_mut(&mut self, j: usize) -> &mut [T] {
+        assert!(self.layout == Layout::ColMajor, "column of a row-major matrix");
+        &mut self.data[j * self.ld..j * self.ld + self.rows]
+    }
+
+    /// Position of element (i, j) in the storage
+    #[inline]
+    fn offset(&self, i: usize, j: usize) -> usize {
+        assert!(i < self.rows && j < self.cols, "index ({}, {}) out of bounds for a {} x {} matrix", i, j, self.rows, self.cols);
+        match self.layout {
+            Layout::RowMajor => i * self.ld + j,
+            Layout::ColMajor => j * self.ld + i,
+        }
+    }
+}
+
+impl<T: Clone + Zero> Matrix<T> {
+    /// Creates a rows x cols matrix with all elements set to zero
+    pub fn zeros(rows: usize, cols: usize) -> Matrix<T> {
+        Matrix::zeros_with_layout(rows, cols, Layout::RowMajor)
+    }
+
+    /// Creates a rows x cols matrix of zeros stored in the given layout
+    pub fn zeros_with_layout(rows: usize, cols: usize, layout: Layout) -> Matrix<T> {
+        let mut data = Vec::with_capacity(rows * cols);
+        data.resize(rows * cols, T::zero());
+
+        let ld = if layout == Layout::RowMajor { cols } else { rows };
+        Matrix { data, rows, cols, ld, layout }
+    }
+
+    /// Same as `zeros`, but returns `AllocFailed` instead of aborting when the heap is exhausted
+    pub fn try_zeros(rows: usize, cols: usize) -> Result<Matrix<T>, CsvdError> {
+        let len = rows.checked_mul(cols).ok_or(CsvdError::AllocFailed)?;
+        let data = try_filled(len, T::zero())?;
+
+        Ok(Matrix { data, rows, cols, ld: cols, layout: Layout::RowMajor })
+    }
+}
+
+/// A Vec of len copies of value, or `AllocFailed` if the heap cannot provide it
+pub(crate) fn try_filled<T: Clone>(len: usize, value: T) -> Result<Vec<T>, CsvdError> {
+    let mut v = Vec::new();
+    v.try_reserve_exact(len).map_err(|_| CsvdError::AllocFailed)?;
+    v.resize(len, value);
+    Ok(v)
+}
+
+impl<T: Clone> Matrix<T> {
+    /// Builds a matrix from a slice of rows, which all need the same length
+    pub fn from_rows(rows: &[&[T]]) -> Result<Matrix<T>, CsvdError> {
+        let cols = rows.first().map_or(0, |row| row.len());
+        let mut data = Vec::with_capacity(rows.len() * cols);
+
+        for row in rows {
+            if row.len() != cols {
+                return Err(CsvdError::DimensionMismatch { operand: "row", expected: (1, cols), found: (1, row.len()) });
+            }
+            data.extend_from_slice(row);
+        }
+
+        Ok(Matrix { data, rows: rows.len(), cols, ld: cols, layout: Layout::RowMajor })
+    }
+
+    /// A tightly packed copy in the given layout
+    pub fn to_layout(&self, layout: Layout) -> Matrix<T> {
+        let (rows, cols) = (self.rows, self.cols);
+        let mut data = Vec::with_capacity(rows * cols);
+        match layout {
+            Layout::RowMajor => for i in 0..rows {
+                data.extend((0..cols).map(|j| self[(i, j)].clone()));
+            },
+            Layout::ColMajor => for j in 0..cols {
+                data.extend((0..rows).map(|i| self[(i, j)].clone()));
+            },
+        }
+
+        let ld = if layout == Layout::RowMajor { cols } else { rows };
+        Matrix { data, rows, cols, ld, layout }
+    }
+
+    /// The cols x rows transpose, tightly packed
+    pub fn transpose(&self) -> Matrix<T> {
+        self.transposed_map(|x| x.clone())
+    }
+
+    /// Transposes while applying f to every element
+    fn transposed_map<U, F: Fn(&T) -> U>(&self, f: F) -> Matrix<U> {
+        let mut data = Vec::with_capacity(self.rows * self.cols);
+        for j in 0..self.cols {
+            for i in 0..self.rows {
+                data.push(f(&self[(i, j)]));
+            }
+        }
+
+        Matrix { data, rows: self.cols, cols: self.rows, ld: self.rows, layout: Layout::RowMajor }
+    }
+
+    /// Combines the elements of two matrices of the same shape with f
+    fn zip_map<F: Fn(&T, &T) -> T>(&self, other: &Matrix<T>, f: F) -> Result<Matrix<T>, CsvdError> {
+        if other.rows != self.rows || other.cols != self.cols {
+            return Err(CsvdError::DimensionMismatch { operand: "other", expected: (self.rows, self.cols), found: (other.rows, other.cols) });
+        }
+
+        let mut data = Vec::with_capacity(self.rows * self.cols);
+        for i in 0..self.rows {
+            data.extend((0..self.cols).map(|j| f(&self[(i, j)], &other[(i, j)])));
+        }
+
+        Ok(Matrix { data, rows: self.rows, cols: self.cols, ld: self.cols, layout: Layout::RowMajor })
+    }
+}
+
+impl<T: Real> Matrix<Complex<T>> {
+    /// The n x n identity matrix
+    pub fn identity(n: usize) -> Matrix<Complex<T>> {
+        let mut a = Matrix::zeros(n, n);
+        for i in 0..n {
+            a[(i, i)] = Complex::new(T::one(), T::zero());
+        }
+        a
+    }
+
+    /// The square matrix with the real values d on its diagonal, e.g. S from the singular values
+    pub fn from_diag(d: &[T]) -> Matrix<Complex<T>> {
+        let mut a = Matrix::zeros(d.len(), d.len());
+        for (i, &x) in d.iter().enumerate() {
+            a[(i, i)] = Complex::new(x, T::zero());
+        }
+        a
+    }
+
+    /// The rows x cols matrix with the complex values d on its diagonal and zeros elsewhere,
+    /// e.g. S of an m x n decomposition, for d with at most min(rows, cols) values
+    pub fn from_diag_rect(d: &[Complex<T>], rows: usize, cols: usize) -> Result<Matrix<Complex<T>>, CsvdError> {
+        if d.len() > rows.min(cols) {
+            return Err(CsvdError::InvalidArgument { name: "d", value: d.len(), max: rows.min(cols) });
+        }
+
+        let mut a = Matrix::zeros(rows, cols);
+        for (i, &x) in d.iter().enumerate() {
+            a[(i, i)] = x;
+        }
+        Ok(a)
+    }
+
+    /// The min(rows, cols) diagonal entries a(i, i), in any layout
+    pub fn diagonal(&self) -> Vec<Complex<T>> {
+        (0..self.rows.min(self.cols)).map(|i| self[(i, i)]).collect()
+    }
+
+    /// The sum of the diagonal entries, the sum of the eigenvalues for a square matrix
+    pub fn trace(&self) -> Complex<T> {
+        (0..self.rows.min(self.cols)).fold(Complex::new(T::zero(), T::zero()), |acc, i| acc + self[(i, i)])
+    }
+
+    /// The cols x rows conjugate transpose A*, tightly packed
+    pub fn conj_transpose(&self) -> Matrix<Complex<T>> {
+        self.transposed_map(|x| x.conj())
+    }
+
+    /// The elementwise sum A + B
+    pub fn add(&self, other: &Matrix<Complex<T>>) -> Result<Matrix<Complex<T>>, CsvdError> {
+        self.zip_map(other, |x, y| x + y)
+    }
+
+    /// The elementwise difference A - B
+    pub fn sub(&self, other: &Matrix<Complex<T>>) -> Result<Matrix<Complex<T>>, CsvdError> {
+        self.zip_map(other, |x, y| x - y)
+    }
+
+    /// The elementwise (Hadamard) product of A and B
+    pub fn hadamard(&self, other: &Matrix<Complex<T>>) -> Result<Matrix<Complex<T>>, CsvdError> {
+        self.zip_map(other, |x, y| x * y)
+    }
+
+    /// Multiplies every element by alpha in place
+    pub fn scale(&mut self, alpha: Complex<T>) {
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                self[(i, j)] = self[(i, j)] * alpha;
+            }
+        }
+    }
+}
+
+/// The Kronecker product of an m x n matrix a and a p x q matrix b, the mp x nq block
+/// matrix whose block (i, j) is a(i, j) b
+pub fn kron<T: Real>(a: &Matrix<Complex<T>>, b: &Matrix<Complex<T>>) -> Matrix<Complex<T>> {
+    let rows = a.rows * b.rows;
+    let cols = a.cols * b.cols;
+    let mut data = Vec::with_capacity(rows * cols);
+
+    for i in 0..a.rows {
+        for k in 0..b.rows {
+            for j in 0..a.cols {
+                let x = a[(i, j)];
+                data.extend((0..b.cols).map(|l| x * b[(k, l)]));
+            }
+        }
+    }
+
+    Matrix { data, rows, cols, ld: cols, layout: Layout::RowMajor }
+}
+
+/// The outer product x y^T of x with m and y with n elements, an m x n matrix of rank one.
+/// As numpy's outer, y is not conjugated, pass conj(y) for x y*.
+pub fn outer<T: Real>(x: &[Complex<T>], y: &[Complex<T>]) -> Matrix<Complex<T>> {
+    let mut data = Vec::with_capacity(x.len() * y.len());
+    for &x in x {
+        data.extend(y.iter().map(|&y| x * y));
+    }
+
+    Matrix { data, rows: x.len(), cols: y.len(), ld: y.len(), layout: Layout::RowMajor }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        &self.data[self.offset(i, j)]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        let offset = self.offset(i, j);
+        &mut self.data[offset]
+    }
+}
+
+/// One row per line with the elements separated by commas. Width and precision apply to
+/// every element, so `{:.3}` prints complex elements as e.g. `1.000+0.500i`.
+impl<T: fmt::Display> fmt::Display for Matrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.rows {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for j in 0..self.cols {
+                if j > 0 {
+                    write!(f, ", ")?;
+                }
+                fmt::Display::fmt(&self[(i, j)], f)?;
+            }
+        }
+        Ok(())
+    }
+}
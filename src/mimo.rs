@@ -0,0 +1,103 @@
+//! Linear receive filters for MIMO channels.
+//!
+//! With n_tx streams x sent over n_rx antennas, the received vector is y = H x + noise for
+//! the n_rx x n_tx channel matrix H, and a linear equalizer estimates x as W y. With the
+//! channel known at the transmitter as well, `eigen_beamforming` instead sends the streams
+//! along the singular vectors of H, which turns it into parallel channels without any
+//! interference between them.
+
+use alloc::vec::Vec;
+use num_complex::Complex;
+use super::batch::{BatchLayout, BatchedSvd};
+use super::csvd::check_finite;
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::scalar::Real;
+use super::svd::{svd, svd_truncated};
+use super::PinvOptions;
+
+/// The zero-forcing equalizer W = H+ of the tightly packed, row-major n_rx x n_tx channel
+/// matrix h, as an n_tx x n_rx matrix, which cancels the interference between the streams,
+/// W H = I for a channel of full column rank, at the price of amplifying the noise along
+/// weak singular directions.
+///
+/// Singular values up to max(n_rx, n_tx) eps times the largest one are treated as zero,
+/// see `PinvOptions::default`, so a rank deficient channel gives the minimum norm weights
+/// for the streams it can separate. h is left untouched.
+pub fn zf_weights<T: Real>(h: &[Complex<T>], n_rx: usize, n_tx: usize) -> Result<Matrix<Complex<T>>, CsvdError> {
+    if n_rx < 1 || n_tx < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    let len = channel_len(h, n_rx, n_tx, 1)?;
+    if h.len() < len {
+        return Err(CsvdError::BufferTooSmall { operand: "H", required: len, len: h.len() });
+    }
+    check_finite(h, n_tx, n_rx, n_tx)?;
+
+    let h = Matrix::from_vec(h[..len].to_vec(), n_rx, n_tx)?;
+    Ok(svd(&h)?.pinv_with_options(&PinvOptions::default()))
+}
+
+/// Number of elements of count n_rx x n_tx channel matrices, a product that overflows
+/// needs more than any buffer h holds
+fn channel_len<T>(h: &[T], n_rx: usize, n_tx: usize, count: usize) -> Result<usize, CsvdError> {
+    n_rx.checked_mul(n_tx).and_then(|len| len.checked_mul(count))
+        .ok_or(CsvdError::BufferTooSmall { operand: "H", required: usize::MAX, len: h.len() })
+}
+
+/// `zf_weights` for the channel matrices of all subcarriers of a MIMO-OFDM symbol, packed
+/// into h with the given layout, writing the n_tx x n_rx weights of all subcarriers packed
+/// in the same layout into w. h is left untouched. To reuse the scratch space from slot
+/// to slot, see `BatchedSvd::pinv_into`.
+pub fn zf_weights_batched<T: Real>(h: &[Complex<T>], w: &mut [Complex<T>], n_rx: usize, n_tx: usize,
+        subcarriers: usize, layout: BatchLayout) -> Result<(), CsvdError> {
+    if n_rx < 1 || n_tx < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    let len = channel_len(h, n_rx, n_tx, subcarriers)?;
+    if h.len() < len {
+        return Err(CsvdError::BufferTooSmall { operand: "H", required: len, len: h.len() });
+    }
+    if w.len() < len {
+        return Err(CsvdError::BufferTooSmall { operand: "W", required: len, len: w.len() });
+    }
+
+    let mut work: Vec<Complex<T>> = h[..len].to_vec();
+    let mut res = BatchedSvd::try_new(n_rx, n_tx, subcarriers, layout)?;
+    res.decompose(&mut work)?;
+    res.pinv_into(w, &PinvOptions::default())
+}
+
+/// Transmit precoding and receive combining for the k strongest eigenmodes of a channel,
+/// H = U S V*. Sending the k streams x as F x and receiving with G* y gives
+/// G* H F x = diag(gains) x, k parallel channels with gains sigma_1 >= ... >= sigma_k.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Beamforming<T> {
+    /// the n_tx x k precoder F, the first k right singular vectors of H
+    pub precoder: Matrix<Complex<T>>,
+    /// the n_rx x k combiner G, the first k left singular vectors of H
+    pub combiner: Matrix<Complex<T>>,
+    /// the k largest singular values of H, the amplitude gains of the streams
+    pub gains: Vec<T>,
+}
+
+/// The eigen-beamforming precoder and combiner for k streams over the tightly packed,
+/// row-major n_rx x n_tx channel matrix h, 1 <= k <= min(n_rx, n_tx). Both have orthonormal
+/// columns, so the precoder keeps the transmit power and the combiner keeps white noise
+/// white. The vectors follow the phase convention of `csvd`. h is left untouched.
+pub fn eigen_beamforming<T: Real>(h: &[Complex<T>], n_rx: usize, n_tx: usize, k: usize) -> Result<Beamforming<T>, CsvdError> {
+    if n_rx < 1 || n_tx < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    let len = channel_len(h, n_rx, n_tx, 1)?;
+    if h.len() < len {
+        return Err(CsvdError::BufferTooSmall { operand: "H", required: len, len: h.len() });
+    }
+    if k < 1 || k > n_rx.min(n_tx) {
+        return Err(CsvdError::InvalidArgument { name: "k", value: k, max: n_rx.min(n_tx) });
+    }
+
+    let h = Matrix::from_vec(h[..len].to_vec(), n_rx, n_tx)?;
+    let res = svd_truncated(&h, k)?;
+    Ok(Beamforming { precoder: res.v, combiner: res.u, gains: res.s })
+}
@@ -0,0 +1,164 @@
+//! Mixed-precision least squares: the decomposition is computed in single precision and
+//! the solution is refined with residuals computed in double precision.
+//!
+//! The O(m n min(m, n)) work of the decomposition runs on `Complex32`, while each step of
+//! refinement only costs a few O(m n) products. For a full rank A whose condition number is
+//! well below 1 / sqrt(eps) of `f32`, about 3000, every step gains about 7 digits, so two or
+//! three steps give a solution as accurate as a decomposition in `Complex64`.
+//!
+//! The refinement solves the normal equations, A* A x = A* b for tall or square A and
+//! x = A* y with A A* y = b for wide A, with the inverse Gram matrix applied from the
+//! single-precision factors. A correction of A* r for tall A converges to the true
+//! least-squares solution even when the residual b - A x stays large, which a correction
+//! of r alone would not.
+
+use num_complex::{Complex, Complex32, Complex64};
+use super::blas::{gemm, Op};
+use super::csvd::cabs;
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::svd::{svd, SvdResult};
+use super::PinvOptions;
+
+/// Upper bound on the refinement steps of `lstsq_mixed` and `pinv_mixed`, the correction
+/// is usually below the rounding errors of `f64` after two or three
+pub const MAX_REFINEMENTS: usize = 10;
+
+/// The single-precision decomposition of a double-precision matrix, for solving any number
+/// of least-squares problems with it to double precision
+#[derive(Clone, Debug, PartialEq)]
+pub struct MixedPrecisionSolver {
+    /// the matrix itself, for the residuals
+    a: Matrix<Complex64>,
+    /// the decomposition of a rounded to single precision
+    svd: SvdResult<f32>,
+    /// 1 / s^2 for the singular values above the cut-off, zero for the rest
+    s_inv2: alloc::vec::Vec<f32>,
+}
+
+impl MixedPrecisionSolver {
+    /// Rounds a to single precision and decomposes it. Singular values up to the default
+    /// cut-off of `PinvOptions` for `f32` are treated as zero.
+    pub fn new(a: &Matrix<Complex64>) -> Result<MixedPrecisionSolver, CsvdError> {
+        let single = Matrix::from_vec((0..a.rows()).flat_map(|i| (0..a.cols()).map(move |j| (i, j)))
+            .map(|(i, j)| Complex32::new(a[(i, j)].re as f32, a[(i, j)].im as f32)).collect(), a.rows(), a.cols())?;
+        let svd = svd(&single)?;
+
+        let s_max = svd.s.first().copied().unwrap_or(0.0);
+        let cutoff = PinvOptions::default().cutoff(s_max, a.rows(), a.cols());
+        let s_inv2 = svd.s.iter().map(|&s| if s > cutoff { 1.0 / (s * s) } else { 0.0 }).collect();
+
+        Ok(MixedPrecisionSolver { a: a.clone(), svd, s_inv2 })
+    }
+
+    /// Number of rows of the decomposed matrix
+    pub fn rows(&self) -> usize {
+        self.a.rows()
+    }
+
+    /// Number of columns of the decomposed matrix
+    pub fn cols(&self) -> usize {
+        self.a.cols()
+    }
+
+    /// Solves min ||A X - B|| for the m x nrhs matrix b with at most `iterations` steps of
+    /// refinement, stopping early once a step changes X by less than the rounding errors
+    pub fn solve(&self, b: &Matrix<Complex64>, iterations: usize) -> Result<Matrix<Complex64>, CsvdError> {
+        let m = self.rows();
+        let n = self.cols();
+        let nrhs = b.cols();
+        let one = Complex64::new(1.0, 0.0);
+
+        if b.rows() != m {
+            return Err(CsvdError::DimensionMismatch { operand: "b", expected: (m, nrhs), found: (b.rows(), nrhs) });
+        }
+
+        // the single-precision solution is the correction of x = 0
+        let mut dx = Matrix::zeros(n, nrhs);
+        self.correction(b, &mut dx)?;
+        let mut x = dx.clone();
+
+        for _ in 0..iterations {
+            // r = b - A x in double precision
+            let mut r = b.clone();
+            gemm(-one, Op::None, &self.a, Op::None, &x, one, &mut r)?;
+            self.correction(&r, &mut dx)?;
+
+            let x_norm = x.as_slice().iter().fold(0.0f64, |acc, x| acc.max(cabs(x)));
+            let dx_norm = dx.as_slice().iter().fold(0.0f64, |acc, x| acc.max(cabs(x)));
+            x = x.add(&dx)?;
+            if dx_norm <= f64::EPSILON * x_norm {
+                break;
+            }
+        }
+
+        Ok(x)
+    }
+
+    /// The pseudo-inverse of A to double precision, the solution of min ||A X - I||
+    pub fn pinv(&self, iterations: usize) -> Result<Matrix<Complex64>, CsvdError> {
+        self.solve(&Matrix::identity(self.rows()), iterations)
+    }
+
+    /// dx = (A* A)^-1 A* r for m >= n, dx = A* (A A*)^-1 r for m < n, with the
+    /// products with A in double and the inverse Gram matrix in single precision
+    fn correction(&self, r: &Matrix<Complex64>, dx: &mut Matrix<Complex64>) -> Result<(), CsvdError> {
+        let m = self.rows();
+        let n = self.cols();
+        let one = Complex64::new(1.0, 0.0);
+        let zero = Complex64::new(0.0, 0.0);
+
+        if m >= n {
+            let mut g = Matrix::zeros(n, r.cols());
+            gemm(one, Op::ConjugateTranspose, &self.a, Op::None, r, zero, &mut g)?;
+            *dx = self.apply_gram(&self.svd.v, &g);
+        }
+        else {
+            let y = self.apply_gram(&self.svd.u, r);
+            gemm(one, Op::ConjugateTranspose, &self.a, Op::None, &y, zero, dx)?;
+        }
+        Ok(())
+    }
+
+    /// Q S^-2 Q* x for the singular vectors q of the smaller side, in single precision.
+    /// x is scaled by its largest element before it is rounded, so that small residuals
+    /// do not underflow.
+    fn apply_gram(&self, q: &Matrix<Complex32>, x: &Matrix<Complex64>) -> Matrix<Complex64> {
+        let d = x.rows();
+        let nrhs = x.cols();
+        let scale = x.as_slice().iter().fold(0.0f64, |acc, x| acc.max(x.re.abs()).max(x.im.abs()));
+        let mut out = Matrix::zeros(d, nrhs);
+        if scale == 0.0 {
+            return out;
+        }
+
+        let mut c = alloc::vec![Complex32::new(0.0, 0.0); self.s_inv2.len()];
+        for j in 0..nrhs {
+            for (k, c) in c.iter_mut().enumerate() {
+                let mut sum = Complex32::new(0.0, 0.0);
+                for i in 0..d {
+                    let x = x[(i, j)] / scale;
+                    sum += q[(i, k)].conj() * Complex32::new(x.re as f32, x.im as f32);
+                }
+                *c = sum * self.s_inv2[k];
+            }
+            for i in 0..d {
+                let sum = c.iter().enumerate().fold(Complex32::new(0.0, 0.0), |acc, (k, &c)| acc + q[(i, k)] * c);
+                out[(i, j)] = Complex::new(sum.re as f64, sum.im as f64) * scale;
+            }
+        }
+        out
+    }
+}
+
+/// Solves min ||A X - B|| to double precision from a single-precision decomposition of a,
+/// see `MixedPrecisionSolver`. For wide a the solution has the minimum norm.
+pub fn lstsq_mixed(a: &Matrix<Complex64>, b: &Matrix<Complex64>) -> Result<Matrix<Complex64>, CsvdError> {
+    MixedPrecisionSolver::new(a)?.solve(b, MAX_REFINEMENTS)
+}
+
+/// The pseudo-inverse of a to double precision from a single-precision decomposition,
+/// e.g. for a zero-forcing equalizer computed at the speed of `Complex32`
+pub fn pinv_mixed(a: &Matrix<Complex64>) -> Result<Matrix<Complex64>, CsvdError> {
+    MixedPrecisionSolver::new(a)?.pinv(MAX_REFINEMENTS)
+}
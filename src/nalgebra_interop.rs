@@ -0,0 +1,54 @@
+//! Conversions to and from nalgebra, with the `nalgebra` feature, so that nalgebra can be
+//! used for everything else while this crate only provides the complex SVD.
+
+use nalgebra::{ComplexField, DMatrix, DVector, Dynamic, SVD};
+use num_complex::Complex;
+use super::matrix::Matrix;
+use super::scalar::Real;
+use super::svd::SvdResult;
+
+impl<'a, T: Real> From<&'a Matrix<Complex<T>>> for DMatrix<Complex<T>> {
+    fn from(a: &'a Matrix<Complex<T>>) -> DMatrix<Complex<T>> {
+        DMatrix::from_fn(a.rows(), a.cols(), |i, j| a[(i, j)])
+    }
+}
+
+impl<T: Real> From<Matrix<Complex<T>>> for DMatrix<Complex<T>> {
+    fn from(a: Matrix<Complex<T>>) -> DMatrix<Complex<T>> {
+        DMatrix::from(&a)
+    }
+}
+
+impl<'a, T: Real> From<&'a DMatrix<Complex<T>>> for Matrix<Complex<T>> {
+    fn from(a: &'a DMatrix<Complex<T>>) -> Matrix<Complex<T>> {
+        let mut b = Matrix::zeros(a.nrows(), a.ncols());
+        for i in 0..a.nrows() {
+            for j in 0..a.ncols() {
+                b[(i, j)] = a[(i, j)];
+            }
+        }
+        b
+    }
+}
+
+impl<T: Real> From<DMatrix<Complex<T>>> for Matrix<Complex<T>> {
+    fn from(a: DMatrix<Complex<T>>) -> Matrix<Complex<T>> {
+        Matrix::from(&a)
+    }
+}
+
+/// The thin decomposition as nalgebra stores it, the first min(m, n) columns of U,
+/// the singular values and V* with min(m, n) rows
+impl<T: Real> From<SvdResult<T>> for SVD<Complex<T>, Dynamic, Dynamic>
+    where Complex<T>: ComplexField<RealField = T>
+{
+    fn from(res: SvdResult<T>) -> SVD<Complex<T>, Dynamic, Dynamic> {
+        let k = res.s.len();
+
+        SVD {
+            u: Some(DMatrix::from_fn(res.rows(), k, |i, j| res.u[(i, j)])),
+            v_t: Some(DMatrix::from_fn(k, res.cols(), |i, j| res.v[(j, i)].conj())),
+            singular_values: DVector::from_column_slice(&res.s),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Reading and writing complex matrices in NumPy's .npy format, so matrices can be
+//! exchanged with Python test benches and the results compared with numpy.linalg.svd.
+//!
+//! Complex64 (`<c8`) and complex128 (`<c16`) arrays of one or two dimensions are
+//! supported, in C or Fortran order and either byte order. Vectors are read as columns.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use num_complex::Complex;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use super::matrix::Matrix;
+use super::scalar::Real;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Errors of reading and writing .npy files
+#[derive(Debug)]
+pub enum NpyError {
+    /// The underlying reader or writer failed
+    Io(io::Error),
+    /// The file is not a valid .npy file, the string names what is wrong
+    InvalidFormat(&'static str),
+    /// The array holds something other than complex64 or complex128 values
+    UnsupportedDtype(String),
+    /// The array has more than two dimensions
+    UnsupportedShape(usize),
+}
+
+impl fmt::Display for NpyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NpyError::Io(ref e) => write!(f, "{}", e),
+            NpyError::InvalidFormat(what) => write!(f, "invalid .npy file: {}", what),
+            NpyError::UnsupportedDtype(ref descr) => write!(f, "unsupported dtype {}, only complex64 and complex128 are supported", descr),
+            NpyError::UnsupportedShape(dims) => write!(f, "arrays with {} dimensions are not supported", dims),
+        }
+    }
+}
+
+impl std::error::Error for NpyError {}
+
+impl From<io::Error> for NpyError {
+    fn from(e: io::Error) -> NpyError {
+        NpyError::Io(e)
+    }
+}
+
+/// Reads a complex matrix from the .npy file at path
+pub fn load_npy<T: Real, P: AsRef<Path>>(path: P) -> Result<Matrix<Complex<T>>, NpyError> {
+    read_npy(BufReader::new(File::open(path)?))
+}
+
+/// Writes a to the file at path, as complex64 for f32 and complex128 for f64 elements
+pub fn save_npy<T: Real, P: AsRef<Path>>(path: P, a: &Matrix<Complex<T>>) -> Result<(), NpyError> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write_npy(&mut w, a)?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Reads a complex matrix in .npy format from r, converting its values to T
+pub fn read_npy<T: Real, R: Read>(mut r: R) -> Result<Matrix<Complex<T>>, NpyError> {
+    let mut preamble = [0u8; 8];
+    r.read_exact(&mut preamble)?;
+    if &preamble[..6] != MAGIC {
+        return Err(NpyError::InvalidFormat("missing magic string"));
+    }
+
+    let header_len = match preamble[6] {
+        1 => {
+            let mut len = [0u8; 2];
+            r.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        2 | 3 => {
+            let mut len = [0u8; 4];
+            r.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        _ => return Err(NpyError::InvalidFormat("unknown format version")),
+    };
+
+    let mut header = alloc::vec![0; header_len];
+    r.read_exact(&mut header)?;
+    let header = core::str::from_utf8(&header).map_err(|_| NpyError::InvalidFormat("header is not text"))?;
+
+    let descr = dict_value(header, "descr").ok_or(NpyError::InvalidFormat("missing descr"))?;
+    let descr = descr.trim_matches(|c| c == '\'' || c == '"');
+    let (little_endian, width) = match descr {
+        "<c8" => (true, 4),
+        ">c8" => (false, 4),
+        "<c16" => (true, 8),
+        ">c16" => (false, 8),
+        _ => return Err(NpyError::UnsupportedDtype(descr.into())),
+    };
+
+    let fortran_order = match dict_value(header, "fortran_order") {
+        Some("True") => true,
+        Some("False") => false,
+        _ => return Err(NpyError::InvalidFormat("missing fortran_order")),
+    };
+
+    let shape = dict_value(header, "shape").ok_or(NpyError::InvalidFormat("missing shape"))?;
+    let dims = shape.trim_start_matches('(').trim_end_matches(')')
+        .split(',')
+        .map(|d| d.trim())
+        .filter(|d| !d.is_empty())
+        .map(|d| d.parse::<usize>().map_err(|_| NpyError::InvalidFormat("invalid shape")))
+        .collect::<Result<Vec<usize>, NpyError>>()?;
+    let (rows, cols) = match dims.len() {
+        0 => (1, 1),
+        1 => (dims[0], 1),
+        2 => (dims[0], dims[1]),
+        d => return Err(NpyError::UnsupportedShape(d)),
+    };
+
+    // the shape comes from the file, so the buffer only grows with the data actually read
+    let len = rows.checked_mul(cols).and_then(|len| len.checked_mul(2 * width))
+        .ok_or(NpyError::InvalidFormat("shape too large"))?;
+    let mut bytes = Vec::new();
+    r.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(NpyError::Io(io::ErrorKind::UnexpectedEof.into()));
+    }
+
+    let part = |chunk: &[u8]| -> T {
+        let value = match (width, little_endian) {
+            (4, true) => f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as f64,
+            (4, false) => f32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as f64,
+            (_, true) => f64::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7]]),
+            (_, false) => f64::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7]]),
+        };
+        T::from(value).unwrap()
+    };
+
+    let mut a = Matrix::zeros(rows, cols);
+    for (idx, value) in bytes.chunks(2 * width).enumerate() {
+        let (i, j) = if fortran_order { (idx % rows, idx / rows) } else { (idx / cols, idx % cols) };
+        a[(i, j)] = Complex::new(part(&value[..width]), part(&value[width..]));
+    }
+
+    Ok(a)
+}
+
+/// Writes a in .npy format to w, as complex64 for f32 and complex128 for f64 elements
+pub fn write_npy<T: Real, W: Write>(mut w: W, a: &Matrix<Complex<T>>) -> Result<(), NpyError> {
+    let single = core::mem::size_of::<T>() == 4;
+    let descr = if single { "<c8" } else { "<c16" };
+
+    let mut header = alloc::format!("{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}), }}", descr, a.rows(), a.cols());
+    // numpy pads the header with spaces and a newline so that the data is 64-byte aligned
+    let unpadded = MAGIC.len() + 4 + header.len() + 1;
+    for _ in 0..(64 - unpadded % 64) % 64 {
+        header.push(' ');
+    }
+    header.push('\n');
+
+    let mut out = Vec::with_capacity(MAGIC.len() + 4 + header.len() + a.rows() * a.cols() * 16);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&[1, 0]);
+    out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    out.extend_from_slice(header.as_bytes());
+
+    for i in 0..a.rows() {
+        for j in 0..a.cols() {
+            let x = a[(i, j)];
+            for &part in &[x.re, x.im] {
+                if single {
+                    out.extend_from_slice(&part.to_f32().unwrap().to_le_bytes());
+                }
+                else {
+                    out.extend_from_slice(&part.to_f64().unwrap().to_le_bytes());
+                }
+            }
+        }
+    }
+
+    w.write_all(&out)?;
+    Ok(())
+}
+
+/// The text of the value of key in the Python dict literal of a .npy header
+fn dict_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header.find(&alloc::format!("'{}'", key)).or_else(|| header.find(&alloc::format!("\"{}\"", key)))?;
+    let rest = header[start + key.len() + 2..].trim_start().strip_prefix(':')?.trim_start();
+
+    // the shape tuple contains commas itself
+    let end = if rest.starts_with('(') { rest.find(')')? + 1 } else { rest.find([',', '}'])? };
+    Some(rest[..end].trim())
+}
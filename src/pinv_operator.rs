@@ -0,0 +1,115 @@
+use alloc::vec::Vec;
+use num_complex::Complex;
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::scalar::Real;
+use super::svd::{svd, SvdResult};
+use super::PinvOptions;
+
+/// The pseudo-inverse INV = V x S+ x U* of an m x n matrix A, kept in factored form.
+///
+/// Only the singular triplets above the cut-off of the `PinvOptions` are stored,
+/// so applying the operator to a vector costs O((m + n) r) for rank r and
+/// the dense n x m inverse is never formed. One decomposition can be reused
+/// for any number of vectors, e.g. to equalize every received symbol vector of a slot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PinvOperator<T> {
+    /// m x r left singular vectors
+    u: Matrix<Complex<T>>,
+    /// reciprocals of the r nonzero singular values
+    s_inv: Vec<T>,
+    /// n x r right singular vectors
+    v: Matrix<Complex<T>>,
+}
+
+impl<T: Real> PinvOperator<T> {
+    /// Decomposes a, leaving it untouched, with the absolute cut-off 1e-4 of `pinv`,
+    /// see `new_with_options`
+    pub fn new(a: &Matrix<Complex<T>>) -> Result<PinvOperator<T>, CsvdError> {
+        PinvOperator::new_with_options(a, &PinvOptions::absolute(T::from(0.0001).unwrap()))
+    }
+
+    /// Same as `new`, with options deciding which singular values are treated as zero
+    pub fn new_with_options(a: &Matrix<Complex<T>>, options: &PinvOptions<T>) -> Result<PinvOperator<T>, CsvdError> {
+        Ok(PinvOperator::from_svd_with_options(&svd(a)?, options))
+    }
+
+    /// Builds the operator from an existing decomposition with the absolute cut-off 1e-4
+    /// of `pinv`, see `from_svd_with_options`
+    pub fn from_svd(res: &SvdResult<T>) -> PinvOperator<T> {
+        PinvOperator::from_svd_with_options(res, &PinvOptions::absolute(T::from(0.0001).unwrap()))
+    }
+
+    /// Builds the operator from an existing decomposition, keeping the singular values
+    /// above the cut-off of options, the same ones `SvdResult::pinv_with_options` keeps
+    pub fn from_svd_with_options(res: &SvdResult<T>, options: &PinvOptions<T>) -> PinvOperator<T> {
+        let m = res.rows();
+        let n = res.cols();
+
+        // cut-off value for a singular value to be assumed to be 0
+        let s_max = res.s.first().copied().unwrap_or(T::zero());
+        let eps = options.cutoff(s_max, m, n);
+        // the singular values are in descending order
+        let r = res.s.iter().take_while(|&&s| s > eps).count();
+
+        let mut u = Matrix::zeros(m, r);
+        for i in 0..m {
+            u.row_mut(i).copy_from_slice(&res.u.row(i)[..r]);
+        }
+        let mut v = Matrix::zeros(n, r);
+        for i in 0..n {
+            v.row_mut(i).copy_from_slice(&res.v.row(i)[..r]);
+        }
+        let s_inv = res.s[..r].iter().map(|&s| T::one() / s).collect();
+
+        PinvOperator { u, s_inv, v }
+    }
+
+    /// Number of rows of the decomposed matrix, i.e. the length of the input vectors
+    pub fn rows(&self) -> usize {
+        self.u.rows()
+    }
+
+    /// Number of columns of the decomposed matrix, i.e. the length of the output vectors
+    pub fn cols(&self) -> usize {
+        self.v.rows()
+    }
+
+    /// Number of singular values above the cut-off
+    pub fn rank(&self) -> usize {
+        self.s_inv.len()
+    }
+
+    /// Computes out = INV x b for a vector b of m elements into out of n elements,
+    /// without any allocation
+    pub fn apply(&self, b: &[Complex<T>], out: &mut [Complex<T>]) -> Result<(), CsvdError> {
+        let m = self.rows();
+        let n = self.cols();
+
+        if b.len() != m {
+            return Err(CsvdError::DimensionMismatch { operand: "b", expected: (m, 1), found: (b.len(), 1) });
+        }
+        if out.len() != n {
+            return Err(CsvdError::DimensionMismatch { operand: "out", expected: (n, 1), found: (out.len(), 1) });
+        }
+
+        for x in out.iter_mut() {
+            *x = Complex::new(T::zero(), T::zero());
+        }
+
+        for k in 0..self.rank() {
+            // c = u(:, k)* b / s(k)
+            let mut c = Complex::new(T::zero(), T::zero());
+            for i in 0..m {
+                c = c + self.u[(i, k)].conj() * b[i];
+            }
+            c = c * self.s_inv[k];
+
+            for i in 0..n {
+                out[i] = out[i] + self.v[(i, k)] * c;
+            }
+        }
+
+        Ok(())
+    }
+}
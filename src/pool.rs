@@ -0,0 +1,155 @@
+//! A pool of aligned matrices of one shape, for real-time loops that hand a result such as
+//! the pseudo-inverse of every slot to another stage and get the buffer back later.
+//!
+//! Buffers are only allocated while the pool grows. Once it holds as many matrices as are
+//! in flight at the same time, `take` and `give` never touch the global allocator, and
+//! together with an `SvdWorkspace` neither does the decomposition. The matrices come from
+//! the `Alloc` of the pool, e.g. DMA-capable memory, the bookkeeping from the global allocator.
+
+use alloc::vec::Vec;
+use core::ops::{Index, IndexMut};
+use num_traits::Zero;
+use super::aligned::{Alloc, AlignedVec, Global};
+use super::error::CsvdError;
+
+/// A rows x cols matrix in row-major order, borrowed from a `BufferPool`
+#[derive(Debug)]
+pub struct PooledMatrix<T, A: Alloc = Global> {
+    data: AlignedVec<T, A>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T, A: Alloc> PooledMatrix<T, A> {
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// All rows x cols elements, row by row
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> &mut [T] {
+        &mut self.data[i * self.cols..(i + 1) * self.cols]
+    }
+}
+
+impl<T, A: Alloc> Index<(usize, usize)> for PooledMatrix<T, A> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        assert!(i < self.rows && j < self.cols, "index ({}, {}) out of bounds for a {} x {} matrix", i, j, self.rows, self.cols);
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<T, A: Alloc> IndexMut<(usize, usize)> for PooledMatrix<T, A> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        assert!(i < self.rows && j < self.cols, "index ({}, {}) out of bounds for a {} x {} matrix", i, j, self.rows, self.cols);
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+/// Hands out and takes back rows x cols matrices aligned to `alignment` bytes.
+///
+/// New matrices are zeroed, a reclaimed one holds whatever its last user left in it.
+pub struct BufferPool<T, A: Alloc + Clone = Global> {
+    rows: usize,
+    cols: usize,
+    alignment: usize,
+    alloc: A,
+    free: Vec<AlignedVec<T, A>>,
+    /// number of matrices allocated by the pool, whether free or handed out
+    allocated: usize,
+}
+
+impl<T: Clone + Zero> BufferPool<T> {
+    /// A pool of rows x cols matrices that allocates `count` of them right away, so that
+    /// up to count matrices can be in use without any later allocation
+    pub fn new(rows: usize, cols: usize, alignment: usize, count: usize) -> Result<BufferPool<T>, CsvdError> {
+        BufferPool::new_in(rows, cols, alignment, count, Global)
+    }
+}
+
+impl<T: Clone + Zero, A: Alloc + Clone> BufferPool<T, A> {
+    /// Same as `new`, with the matrices allocated from alloc
+    pub fn new_in(rows: usize, cols: usize, alignment: usize, count: usize, alloc: A) -> Result<BufferPool<T, A>, CsvdError> {
+        if !alignment.is_power_of_two() {
+            return Err(CsvdError::InvalidAlignment { alignment });
+        }
+        let mut pool = BufferPool { rows, cols, alignment, alloc, free: Vec::new(), allocated: 0 };
+        pool.reserve(count)?;
+        Ok(pool)
+    }
+
+    /// Allocates matrices until `count` more are free
+    pub fn reserve(&mut self, count: usize) -> Result<(), CsvdError> {
+        let len = self.rows.checked_mul(self.cols).ok_or(CsvdError::AllocFailed)?;
+        // room to take back every matrix, so that give never allocates
+        let total = self.allocated.checked_add(count).ok_or(CsvdError::AllocFailed)?;
+        self.free.try_reserve(total - self.free.len()).map_err(|_| CsvdError::AllocFailed)?;
+
+        for _ in 0..count {
+            self.free.push(AlignedVec::zeroed_in(len, self.alignment, self.alloc.clone())?);
+            self.allocated += 1;
+        }
+        Ok(())
+    }
+
+    /// A matrix from the pool, allocating a new one if none is free
+    pub fn take(&mut self) -> Result<PooledMatrix<T, A>, CsvdError> {
+        if self.free.is_empty() {
+            self.reserve(1)?;
+        }
+        let data = self.free.pop().ok_or(CsvdError::AllocFailed)?;
+        Ok(PooledMatrix { data, rows: self.rows, cols: self.cols })
+    }
+}
+
+impl<T, A: Alloc + Clone> BufferPool<T, A> {
+    /// Returns a matrix to the pool. Matrices of another shape or alignment, which came
+    /// from a different pool, are refused.
+    pub fn give(&mut self, matrix: PooledMatrix<T, A>) -> Result<(), CsvdError> {
+        if matrix.rows != self.rows || matrix.cols != self.cols {
+            return Err(CsvdError::DimensionMismatch { operand: "matrix", expected: (self.rows, self.cols), found: (matrix.rows, matrix.cols) });
+        }
+        if matrix.data.alignment() != self.alignment.max(core::mem::align_of::<T>()) {
+            return Err(CsvdError::InvalidAlignment { alignment: matrix.data.alignment() });
+        }
+        if self.free.len() >= self.allocated {
+            // more matrices returned than the pool allocated
+            return Err(CsvdError::InvalidArgument { name: "matrix", value: self.free.len() + 1, max: self.allocated });
+        }
+
+        self.free.push(matrix.data);
+        Ok(())
+    }
+
+    /// Shape of the matrices as (rows, cols)
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Number of matrices ready to be taken without allocating
+    pub fn available(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Number of matrices the pool allocated so far
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+}
@@ -0,0 +1,30 @@
+//! Printing matrices and vectors to stdout, for host-side test programs and debugging.
+//! The formatting itself is `Matrix`'s `Display` impl, which also works without `std`.
+
+use core::fmt::Display;
+use super::matrix::Matrix;
+use super::scalar::Real;
+use super::svd::SvdResult;
+
+/// Prints a one row per line, followed by an empty line
+pub fn print_matrix<T: Display>(a: &Matrix<T>) {
+    std::println!("{}\n", a);
+}
+
+/// Prints the elements of v one per line, followed by an empty line
+pub fn print_vector<T: Display>(v: &[T]) {
+    for x in v {
+        std::println!("{}", x);
+    }
+    std::println!();
+}
+
+/// Prints the singular values and the factors U and V of res
+pub fn print_svd<T: Real + Display>(res: &SvdResult<T>) {
+    std::println!("S =");
+    print_vector(&res.s);
+    std::println!("U =");
+    print_matrix(&res.u);
+    std::println!("V =");
+    print_matrix(&res.v);
+}
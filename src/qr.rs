@@ -0,0 +1,355 @@
+use alloc::vec::Vec;
+use num_complex::Complex;
+use super::csvd::{cabs, norm2};
+use super::error::CsvdError;
+use super::matrix::{Layout, Matrix};
+use super::scalar::Real;
+
+/// The QR decomposition A = Q R of an m x n matrix A, with k = min(m, n).
+///
+/// As in LAPACK's geqrf, R is stored in the upper triangle of the factored matrix and
+/// Q = H(0) H(1) ... H(k-1) is kept as Householder reflectors H(i) = I - tau(i) v v*,
+/// whose vectors v (with an implicit leading 1) are stored below the diagonal.
+/// Q is only formed explicitly when `q()` is called.
+/// For a decomposition from `qr_pivoted` the factors are those of A P,
+/// with the column permutation P given by `perm()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Qr<T> {
+    qr: Matrix<Complex<T>>,
+    tau: Vec<Complex<T>>,
+    perm: Vec<usize>,
+}
+
+/// Computes the QR decomposition of a, leaving a untouched. a can have any shape.
+/// This is considerably cheaper than the SVD when only a least-squares solution
+/// or an orthonormal basis of the column space is needed.
+pub fn qr<T: Real>(a: &Matrix<Complex<T>>) -> Result<Qr<T>, CsvdError> {
+    factor(a, false)
+}
+
+/// Computes the QR decomposition with column pivoting A P = Q R, as LAPACK's geqp3.
+/// At every step the remaining column with the largest norm is moved to the front,
+/// so the magnitudes of the diagonal of R decrease and reveal the numerical rank
+/// (see `Qr::rank`) much more cheaply than the SVD.
+pub fn qr_pivoted<T: Real>(a: &Matrix<Complex<T>>) -> Result<Qr<T>, CsvdError> {
+    factor(a, true)
+}
+
+fn factor<T: Real>(a: &Matrix<Complex<T>>, pivot: bool) -> Result<Qr<T>, CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+
+    if m < 1 || n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+
+    let mut qr = a.to_layout(Layout::RowMajor);
+
+    let k = m.min(n);
+    let mut tau = Vec::with_capacity(k);
+    let mut perm: Vec<usize> = (0..n).collect();
+
+    // partial column norms vn1 and the norms vn2 they were last computed exactly from
+    let mut vn1: Vec<T> = (0..n).map(|j| norm2((0..m).map(|i| qr[(i, j)]))).collect();
+    let mut vn2 = vn1.clone();
+    let tol3z = T::epsilon().sqrt();
+
+    for i in 0..k {
+        if pivot {
+            let mut pvt = i;
+            for j in i + 1..n {
+                if vn1[j] > vn1[pvt] {
+                    pvt = j;
+                }
+            }
+            if pvt != i {
+                for r in 0..m {
+                    let tmp = qr[(r, i)];
+                    qr[(r, i)] = qr[(r, pvt)];
+                    qr[(r, pvt)] = tmp;
+                }
+                perm.swap(i, pvt);
+                vn1.swap(i, pvt);
+                vn2.swap(i, pvt);
+            }
+        }
+
+        let t = householder(&mut qr, i, i);
+
+        // apply H(i)* to the trailing columns
+        let t_h = t.conj();
+        for j in i + 1..n {
+            let mut w = qr[(i, j)];
+            for r in i + 1..m {
+                w = w + qr[(r, i)].conj() * qr[(r, j)];
+            }
+            w = w * t_h;
+
+            qr[(i, j)] = qr[(i, j)] - w;
+            for r in i + 1..m {
+                qr[(r, j)] = qr[(r, j)] - qr[(r, i)] * w;
+            }
+        }
+
+        if pivot {
+            // downdate the partial column norms, recomputing them once cancellation
+            // would make the downdated value inaccurate
+            for j in i + 1..n {
+                if vn1[j] == T::zero() {
+                    continue;
+                }
+
+                let ratio = cabs(&qr[(i, j)]) / vn1[j];
+                let temp = (T::one() - ratio * ratio).max(T::zero());
+                let temp2 = temp * (vn1[j] / vn2[j]) * (vn1[j] / vn2[j]);
+
+                if temp2 <= tol3z {
+                    vn1[j] = norm2((i + 1..m).map(|r| qr[(r, j)]));
+                    vn2[j] = vn1[j];
+                }
+                else {
+                    vn1[j] = vn1[j] * temp.sqrt();
+                }
+            }
+        }
+
+        tau.push(t);
+    }
+
+    Ok(Qr { qr, tau, perm })
+}
+
+/// Orthonormalizes the k vectors of length m held as the columns of the tightly packed,
+/// row-major m x k matrix vectors in place, each against the ones before it with modified
+/// Gram-Schmidt, and returns the number of linearly independent vectors among them.
+/// A vector in the span of the ones before it, up to rounding, is set to zero.
+///
+/// Every vector is orthogonalized twice, as a single pass leaves errors proportional to
+/// the condition number of the vectors, while two give columns orthonormal to working
+/// precision. Unlike `qr`, which needs the whole matrix, this extends a basis one vector
+/// at a time, e.g. a subspace tracked from snapshot to snapshot.
+pub fn orthonormalize<T: Real>(vectors: &mut [Complex<T>], m: usize, k: usize) -> Result<usize, CsvdError> {
+    if vectors.len() < m * k {
+        return Err(CsvdError::BufferTooSmall { operand: "vectors", required: m * k, len: vectors.len() });
+    }
+
+    Ok(gram_schmidt(vectors, m, k, k, 1))
+}
+
+/// `orthonormalize` on the m x k matrix whose element (i, j) is q[i * row_stride + j * col_stride]
+pub(crate) fn gram_schmidt<T: Real>(q: &mut [Complex<T>], m: usize, k: usize, row_stride: usize, col_stride: usize) -> usize {
+    let zero = Complex::new(T::zero(), T::zero());
+    let at = |i: usize, j: usize| i * row_stride + j * col_stride;
+    let mut rank = 0;
+
+    for j in 0..k {
+        let before = norm2((0..m).map(|i| q[at(i, j)]));
+        for _ in 0..2 {
+            for l in 0..j {
+                let p = (0..m).fold(zero, |acc, i| acc + q[at(i, l)].conj() * q[at(i, j)]);
+                for i in 0..m {
+                    q[at(i, j)] = q[at(i, j)] - q[at(i, l)] * p;
+                }
+            }
+        }
+
+        // what is left of a dependent vector is rounding noise in a random direction
+        let norm = norm2((0..m).map(|i| q[at(i, j)]));
+        if norm > T::epsilon() * T::from(m).unwrap() * before {
+            for i in 0..m {
+                q[at(i, j)] = q[at(i, j)] / norm;
+            }
+            rank += 1;
+        }
+        else {
+            for i in 0..m {
+                q[at(i, j)] = zero;
+            }
+        }
+    }
+
+    rank
+}
+
+/// Generates the reflector H = I - tau v v* with H* x = (beta, 0, ..., 0),
+/// where x is column col of a from row row down.
+/// beta overwrites a(row, col), v(1..) overwrites the entries below it, and tau is returned.
+pub(crate) fn householder<T: Real>(a: &mut Matrix<Complex<T>>, row: usize, col: usize) -> Complex<T> {
+    let m = a.rows();
+    let zero = T::zero();
+
+    let alpha = a[(row, col)];
+    let xnorm = norm2((row + 1..m).map(|i| a[(i, col)]));
+
+    if xnorm == zero && alpha.im == zero {
+        // already of the desired form, H = I
+        return Complex::new(zero, zero);
+    }
+
+    let mut beta = alpha.re.hypot(alpha.im).hypot(xnorm);
+    if alpha.re >= zero {
+        beta = -beta;
+    }
+
+    let tau = Complex::new((beta - alpha.re) / beta, -alpha.im / beta);
+    let scale = Complex::new(T::one(), zero) / (alpha - beta);
+    for i in row + 1..m {
+        a[(i, col)] = a[(i, col)] * scale;
+    }
+    a[(row, col)] = Complex::new(beta, zero);
+
+    tau
+}
+
+/// Applies H = I - tau v v* of the reflector stored in column col of v, starting at row row,
+/// to all columns of c. Pass conj(tau) to apply H* instead.
+fn apply_reflector<T: Real>(v: &Matrix<Complex<T>>, row: usize, col: usize, tau: Complex<T>, c: &mut Matrix<Complex<T>>) {
+    let m = v.rows();
+    let zero = T::zero();
+
+    if tau == Complex::new(zero, zero) {
+        return;
+    }
+
+    for j in 0..c.cols() {
+        // w = v* c(:, j), with the implicit v(row) = 1
+        let mut w = c[(row, j)];
+        for i in row + 1..m {
+            w = w + v[(i, col)].conj() * c[(i, j)];
+        }
+        w = w * tau;
+
+        c[(row, j)] = c[(row, j)] - w;
+        for i in row + 1..m {
+            c[(i, j)] = c[(i, j)] - v[(i, col)] * w;
+        }
+    }
+}
+
+impl<T: Real> Qr<T> {
+    /// A decomposition from its factored matrix, reflector scalars and column permutation
+    pub(crate) fn from_parts(qr: Matrix<Complex<T>>, tau: Vec<Complex<T>>, perm: Vec<usize>) -> Qr<T> {
+        Qr { qr, tau, perm }
+    }
+
+    /// Number of rows of the decomposed matrix
+    pub fn rows(&self) -> usize {
+        self.qr.rows()
+    }
+
+    /// Number of columns of the decomposed matrix
+    pub fn cols(&self) -> usize {
+        self.qr.cols()
+    }
+
+    /// The scalar factors tau of the k Householder reflectors
+    pub fn tau(&self) -> &[Complex<T>] {
+        &self.tau
+    }
+
+    /// The column permutation, column j of A P is column perm()[j] of A.
+    /// This is the identity unless the decomposition came from `qr_pivoted`.
+    pub fn perm(&self) -> &[usize] {
+        &self.perm
+    }
+
+    /// Number of diagonal entries of R larger than tol in magnitude.
+    /// This estimates the numerical rank of A only for a decomposition from `qr_pivoted`.
+    pub fn rank(&self, tol: T) -> usize {
+        (0..self.tau.len()).filter(|&i| cabs(&self.qr[(i, i)]) > tol).count()
+    }
+
+    /// The k x n upper trapezoidal factor R
+    pub fn r(&self) -> Matrix<Complex<T>> {
+        let k = self.tau.len();
+        let mut r = Matrix::zeros(k, self.cols());
+
+        for i in 0..k {
+            for j in i..self.cols() {
+                r[(i, j)] = self.qr[(i, j)];
+            }
+        }
+
+        r
+    }
+
+    /// The m x k matrix Q with orthonormal columns, so that A = Q R
+    pub fn q(&self) -> Matrix<Complex<T>> {
+        let k = self.tau.len();
+        let mut q = Matrix::zeros(self.rows(), k);
+        for i in 0..k {
+            q[(i, i)] = Complex::new(T::one(), T::zero());
+        }
+
+        // Q = H(0) ... H(k-1) applied to the first k columns of the identity
+        for i in (0..k).rev() {
+            apply_reflector(&self.qr, i, i, self.tau[i], &mut q);
+        }
+
+        q
+    }
+
+    /// Overwrites the m x nrhs matrix b with Q* b
+    pub fn apply_qt(&self, b: &mut Matrix<Complex<T>>) -> Result<(), CsvdError> {
+        if b.rows() != self.rows() {
+            return Err(CsvdError::DimensionMismatch { operand: "b", expected: (self.rows(), b.cols()), found: (b.rows(), b.cols()) });
+        }
+
+        // Q* = H(k-1)* ... H(0)*
+        for i in 0..self.tau.len() {
+            apply_reflector(&self.qr, i, i, self.tau[i].conj(), b);
+        }
+
+        Ok(())
+    }
+
+    /// Solves min ||A X - B|| for the m x nrhs matrix b by back substitution with R after
+    /// forming Q* B, which is the least-squares solution for a matrix A of full column rank,
+    /// m >= n. For a rank deficient A use the SVD instead, e.g. `lstsq`.
+    /// Returns `CsvdError::Singular` if a diagonal entry of R is exactly zero.
+    pub fn solve(&self, b: &Matrix<Complex<T>>) -> Result<Matrix<Complex<T>>, CsvdError> {
+        let m = self.rows();
+        let n = self.cols();
+
+        if m < n {
+            return Err(CsvdError::WideMatrix { rows: m, cols: n });
+        }
+
+        let mut y = b.to_layout(Layout::RowMajor);
+        self.apply_qt(&mut y)?;
+
+        // x = P R^-1 (Q* b)(0..n)
+        let mut x = Matrix::zeros(n, b.cols());
+        for j in 0..b.cols() {
+            for i in (0..n).rev() {
+                let d = self.qr[(i, i)];
+                if d == Complex::new(T::zero(), T::zero()) {
+                    return Err(CsvdError::Singular { index: i });
+                }
+                let mut sum = y[(i, j)];
+                for k in i + 1..n {
+                    sum = sum - self.qr[(i, k)] * y[(k, j)];
+                }
+                y[(i, j)] = sum / d;
+            }
+            for i in 0..n {
+                x[(self.perm[i], j)] = y[(i, j)];
+            }
+        }
+
+        Ok(x)
+    }
+
+    /// Overwrites the m x nrhs matrix b with Q b
+    pub fn apply_q(&self, b: &mut Matrix<Complex<T>>) -> Result<(), CsvdError> {
+        if b.rows() != self.rows() {
+            return Err(CsvdError::DimensionMismatch { operand: "b", expected: (self.rows(), b.cols()), found: (b.rows(), b.cols()) });
+        }
+
+        for i in (0..self.tau.len()).rev() {
+            apply_reflector(&self.qr, i, i, self.tau[i], b);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! Deterministic pseudo-random matrices for tests and randomized algorithms.
+//!
+//! The generator is xoshiro256** seeded through splitmix64, which needs neither `std`
+//! nor an allocator, so the same seed gives the same matrices on every target.
+
+use num_complex::Complex;
+use num_traits::Float;
+use super::matrix::Matrix;
+use super::qr::qr;
+use super::scalar::Real;
+
+/// The xoshiro256** generator of Blackman and Vigna. Not suitable for cryptography.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Xoshiro256 {
+    s: [u64; 4],
+}
+
+impl Xoshiro256 {
+    /// Creates a generator whose state is expanded from seed with splitmix64,
+    /// so that similar seeds still give unrelated streams
+    pub fn new(seed: u64) -> Xoshiro256 {
+        let mut x = seed;
+        let mut splitmix = || {
+            x = x.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+
+        Xoshiro256 { s: [splitmix(), splitmix(), splitmix(), splitmix()] }
+    }
+
+    /// The next 64 random bits
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+
+    /// A uniformly distributed value in [0, 1) with 53 random bits
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A circularly-symmetric complex Gaussian value with unit variance,
+    /// i.e. real and imaginary parts are independent with variance 1/2 (Box-Muller)
+    pub fn next_complex_normal<T: Real>(&mut self) -> Complex<T> {
+        // 1 - u lies in (0, 1], so the logarithm is finite
+        let r = Float::sqrt(-Float::ln(1.0 - self.next_f64()));
+        let theta = 2.0 * core::f64::consts::PI * self.next_f64();
+
+        Complex::new(T::from(r * Float::cos(theta)).unwrap(), T::from(r * Float::sin(theta)).unwrap())
+    }
+}
+
+/// An m x n matrix of independent circularly-symmetric complex Gaussian entries with
+/// unit variance, the usual model of a Rayleigh fading channel matrix
+pub fn random_complex_matrix<T: Real>(m: usize, n: usize, seed: u64) -> Matrix<Complex<T>> {
+    let mut rng = Xoshiro256::new(seed);
+    let mut a = Matrix::zeros(m, n);
+    for i in 0..m {
+        for x in a.row_mut(i) {
+            *x = rng.next_complex_normal();
+        }
+    }
+    a
+}
+
+/// An n x n unitary matrix distributed uniformly (by the Haar measure) over the unitary group.
+///
+/// This is Q of the QR decomposition of a complex Gaussian matrix, with the columns
+/// rotated by the phases of the diagonal of R so that the distribution does not depend
+/// on the sign convention of the Householder reflections (Mezzadri, 2007).
+pub fn random_unitary<T: Real>(n: usize, seed: u64) -> Matrix<Complex<T>> {
+    if n == 0 {
+        return Matrix::zeros(0, 0);
+    }
+
+    // a non-empty matrix always has a QR decomposition
+    let f = qr(&random_complex_matrix::<T>(n, n, seed)).unwrap();
+    let r = f.r();
+    let mut q = f.q();
+
+    for j in 0..n {
+        let d = r[(j, j)];
+        let norm = d.re.hypot(d.im);
+        if norm > T::zero() {
+            let phase = d / norm;
+            for i in 0..n {
+                q[(i, j)] = q[(i, j)] * phase;
+            }
+        }
+    }
+
+    q
+}
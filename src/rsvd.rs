@@ -0,0 +1,284 @@
+//! The singular value decomposition of real matrices.
+//!
+//! The same algorithm as `csvd`, with the Householder reflections and back transformations
+//! done in real arithmetic. The phase transformations of the complex version reduce to sign
+//! changes, so a real matrix costs about a quarter of the multiplications and half the
+//! memory of the same matrix stored as complex numbers with zero imaginary parts.
+
+use super::csvd::{no_progress, norm2_real, qr_sweeps, transpose_in_place, SvdOptions};
+use super::error::CsvdError;
+use super::scalar::Real;
+
+/// +1 or -1 with the sign of x, +1 for zero, the phase x / |x| of a real number
+fn sign<T: Real>(x: T) -> T {
+    if x < T::zero() { -T::one() } else { T::one() }
+}
+
+/// Computes the singular value decomposition A = U S V^T of the real M by N matrix A,
+/// working purely on caller-provided slices like `csvd_in_slices`.
+///
+/// a holds the tightly packed, row-major M by N matrix and is overwritten.
+/// s receives the MIN(M,N) singular values in descending order, u the full M by M
+/// orthogonal matrix U and v the full N by N orthogonal matrix V, all tightly packed
+/// and row-major. scratch must hold at least 3 * MIN(M,N) elements.
+///
+/// Wide matrices (N > M) are transposed in place before the decomposition.
+/// A NaN or infinite entry of A is reported as `CsvdError::NonFiniteInput`.
+pub fn svd_real<T: Real>(a: &mut [T], s: &mut [T], u: &mut [T], v: &mut [T], scratch: &mut [T],
+        m: usize, n: usize) -> Result<(), CsvdError> {
+    let min = m.min(n);
+
+    if m < 1 || n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+
+    let checks = [("A", a.len(), m * n), ("U", u.len(), m * m), ("V", v.len(), n * n)];
+    for &(operand, len, required) in &checks {
+        if len < required {
+            return Err(CsvdError::BufferTooSmall { operand, required, len });
+        }
+    }
+    if s.len() < min {
+        return Err(CsvdError::BufferTooSmall { operand: "S", required: min, len: s.len() });
+    }
+    if scratch.len() < 3 * min {
+        return Err(CsvdError::BufferTooSmall { operand: "scratch", required: 3 * min, len: scratch.len() });
+    }
+    if let Some(idx) = a[..m * n].iter().position(|x| !x.is_finite()) {
+        return Err(CsvdError::NonFiniteInput { row: idx / n, col: idx % n });
+    }
+
+    let max_iterations = SvdOptions::default().max_iterations;
+    if m < n {
+        // A^T = U' S V'^T is tall, so A = V' S U'^T
+        transpose_in_place(&mut a[..m * n], m, n, |x| x);
+        rsvd_tall(a, m, n, m, n, m, s, v, n, u, m, scratch, max_iterations)?;
+    }
+    else {
+        rsvd_tall(a, n, m, n, m, n, s, u, m, v, n, scratch, max_iterations)?;
+    }
+
+    normalize_signs(u, m, v, n);
+    Ok(())
+}
+
+/// The real version of `normalize_phases`: the first entry of column K of U above the
+/// machine epsilon is made positive, and column K of V changes its sign along with it.
+/// Columns of V past the M columns of U follow the same rule on their own.
+fn normalize_signs<T: Real>(u: &mut [T], m: usize, v: &mut [T], n: usize) {
+    let negative = |x: &[T], rows: usize, k: usize| {
+        (0..rows).map(|i| x[i*rows + k]).find(|x| x.abs() > T::epsilon()).is_some_and(|x| x < T::zero())
+    };
+
+    for k in 0..m.max(n) {
+        if !(if k < m { negative(u, m, k) } else { negative(v, n, k) }) {
+            continue;
+        }
+        if k < m {
+            for i in 0..m {
+                u[i*m + k] = -u[i*m + k];
+            }
+        }
+        if k < n {
+            for i in 0..n {
+                v[i*n + k] = -v[i*n + k];
+            }
+        }
+    }
+}
+
+/// The decomposition of an M by N row-major matrix A with N <= M, see `csvd_tall`.
+/// U gets NU and V NV columns, work holds the 3N elements of B, C and T.
+pub(crate) fn rsvd_tall<T: Real>(a: &mut [T], lda: usize, m: usize, n: usize, nu: usize, nv: usize,
+        s: &mut [T], u: &mut [T], ldu: usize, v: &mut [T], ldv: usize,
+        work: &mut [T], max_iterations: usize) -> Result<(), CsvdError> {
+    let zero = T::zero();
+    let one = T::one();
+
+    let (b, work) = work[..3 * n].split_at_mut(n);
+    let (c, t) = work.split_at_mut(n);
+    for x in b.iter_mut().chain(c.iter_mut()) {
+        *x = zero;
+    }
+    let tol = T::min_positive_value() / T::epsilon();
+
+    // Scale A into [SMLNUM, BIGNUM] as csvd does.
+    let smlnum = T::min_positive_value().sqrt() / T::epsilon();
+    let bignum = one / smlnum;
+    let mut anrm = zero;
+    for i in 0..m {
+        for j in 0..n {
+            anrm = anrm.max(a[i*lda + j].abs());
+        }
+    }
+    let scale = if zero < anrm && anrm < smlnum {
+        smlnum / anrm
+    }
+    else if bignum < anrm {
+        bignum / anrm
+    }
+    else {
+        one
+    };
+    if scale != one {
+        for i in 0..m {
+            for j in 0..n {
+                a[i*lda + j] = a[i*lda + j] * scale;
+            }
+        }
+    }
+
+    // Householder reduction.
+    for k in 0..n {
+        let k1 = k + 1;
+
+        // Elimination of A(I,K), I = K+1, ..., M.
+        let mut z = norm2_real((k..m).map(|i| a[i*lda + k]));
+        b[k] = zero;
+
+        if tol < z * z {
+            b[k] = z;
+            let w = a[k*lda + k].abs();
+            a[k*lda + k] = sign(a[k*lda + k]) * (z + w);
+
+            if k != n - 1 {
+                for j in k1..n {
+                    let mut q = zero;
+                    for i in k..m {
+                        q = q + a[i*lda + k] * a[i*lda + j];
+                    }
+                    q = q / z / (z + w);
+
+                    for i in k..m {
+                        a[i*lda + j] = a[i*lda + j] - q * a[i*lda + k];
+                    }
+                }
+
+                // Sign change of row K.
+                let q = -sign(a[k*lda + k]);
+                for j in k1..n {
+                    a[k*lda + j] = q * a[k*lda + j];
+                }
+            }
+        }
+
+        // Elimination of A(K,J), J = K+2, ..., N.
+        if k == n - 1 {
+            break;
+        }
+
+        z = norm2_real((k1..n).map(|j| a[k*lda + j]));
+        c[k1] = zero;
+
+        if tol < z * z {
+            c[k1] = z;
+            let w = a[k*lda + k1].abs();
+            a[k*lda + k1] = sign(a[k*lda + k1]) * (z + w);
+
+            for i in k1..m {
+                let mut q = zero;
+                for j in k1..n {
+                    q = q + a[k*lda + j] * a[i*lda + j];
+                }
+                q = q / z / (z + w);
+
+                for j in k1..n {
+                    a[i*lda + j] = a[i*lda + j] - q * a[k*lda + j];
+                }
+            }
+
+            // Sign change of column K+1.
+            let q = -sign(a[k*lda + k1]);
+            for i in k1..m {
+                a[i*lda + k1] = a[i*lda + k1] * q;
+            }
+        }
+    }
+
+    // Tolerance for negligible elements.
+    let mut eps = zero;
+    for k in 0..n {
+        s[k] = b[k];
+        t[k] = c[k];
+        eps = eps.max(s[k] + t[k]);
+    }
+    eps = eps * T::epsilon();
+
+    // Initialization of U and V.
+    if 0 < nu {
+        for j in 0..nu.max(n) {
+            for i in 0..m {
+                u[i*ldu + j] = zero;
+            }
+            u[j*ldu + j] = one;
+        }
+    }
+    if 0 < nv {
+        for j in 0..nv.max(n) {
+            for i in 0..n {
+                v[i*ldv + j] = zero;
+            }
+            v[j*ldv + j] = one;
+        }
+    }
+
+    qr_sweeps(s, t, n, nu, nv, u, ldu, v, ldv, &mut [], 0, 0, eps, max_iterations, &mut no_progress)?;
+
+    // Undo the scaling of A.
+    if scale != one {
+        for k in 0..n {
+            s[k] = s[k] / scale;
+        }
+    }
+
+    // Back transformation.
+    if 0 < nu {
+        for k in (0..n).rev() {
+            if b[k] != zero {
+                let q = -sign(a[k*lda + k]);
+                for j in 0..nu {
+                    u[k*ldu + j] = q * u[k*ldu + j];
+                }
+
+                for j in 0..nu {
+                    let mut q = zero;
+                    for i in k..m {
+                        q = q + a[i*lda + k] * u[i*ldu + j];
+                    }
+                    q = q / a[k*lda + k].abs() / b[k];
+
+                    for i in k..m {
+                        u[i*ldu + j] = u[i*ldu + j] - q * a[i*lda + k];
+                    }
+                }
+            }
+        }
+    }
+
+    if 0 < nv && 1 < n {
+        for k in (0..n - 1).rev() {
+            let k1 = k + 1;
+
+            if c[k1] != zero {
+                let q = -sign(a[k*lda + k1]);
+                for j in 0..nv {
+                    v[k1*ldv + j] = q * v[k1*ldv + j];
+                }
+
+                for j in 0..nv {
+                    let mut q = zero;
+                    for i in k1..n {
+                        q = q + a[k*lda + i] * v[i*ldv + j];
+                    }
+                    q = q / a[k*lda + k1].abs() / c[k1];
+
+                    for i in k1..n {
+                        v[i*ldv + j] = v[i*ldv + j] - q * a[k*lda + i];
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use core::fmt::Debug;
+use num_traits::{Float, FloatConst};
+
+/// The real scalar type underlying the complex matrices of this crate,
+/// i.e. `f32` for `Complex32` matrices and `f64` for `Complex64` matrices.
+///
+/// Any type implementing num-traits' `Float` and `FloatConst` qualifies,
+/// so the decompositions are written once and instantiated per precision.
+/// `Send + Sync` lets the `parallel` feature share matrices between threads.
+pub trait Real: Float + FloatConst + Debug + Send + Sync + 'static {}
+
+impl<T: Float + FloatConst + Debug + Send + Sync + 'static> Real for T {}
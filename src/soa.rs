@@ -0,0 +1,271 @@
+//! Complex matrices stored as structure of arrays (SoA), the real and the imaginary parts
+//! in separate planes.
+//!
+//! With interleaved `Complex<T>` elements every complex product needs shuffles to line up
+//! the real and imaginary parts, which SSE and NEON handle poorly. On split planes the same
+//! product is four real multiplications of whole vectors, so the loops of `gemm_soa` and
+//! the Householder updates of `qr_soa` vectorize without any explicit SIMD code. Opting in
+//! costs one conversion with `SoaMatrix::from_matrix` and one back with `to_matrix`.
+
+use alloc::vec::Vec;
+use num_complex::Complex;
+use super::blas::{gemm_shape, Op};
+use super::csvd::norm2;
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::qr::Qr;
+use super::scalar::Real;
+
+/// An m x n complex matrix whose real and imaginary parts are held in two separate,
+/// tightly packed row-major planes
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoaMatrix<T> {
+    rows: usize,
+    cols: usize,
+    re: Vec<T>,
+    im: Vec<T>,
+}
+
+impl<T: Real> SoaMatrix<T> {
+    /// A rows x cols matrix of zeros
+    pub fn zeros(rows: usize, cols: usize) -> SoaMatrix<T> {
+        SoaMatrix { rows, cols, re: alloc::vec![T::zero(); rows * cols], im: alloc::vec![T::zero(); rows * cols] }
+    }
+
+    /// Takes ownership of the row-major planes of a rows x cols matrix
+    pub fn from_planes(re: Vec<T>, im: Vec<T>, rows: usize, cols: usize) -> Result<SoaMatrix<T>, CsvdError> {
+        if re.len() != rows * cols {
+            return Err(CsvdError::DimensionMismatch { operand: "re", expected: (rows * cols, 1), found: (re.len(), 1) });
+        }
+        if im.len() != rows * cols {
+            return Err(CsvdError::DimensionMismatch { operand: "im", expected: (rows * cols, 1), found: (im.len(), 1) });
+        }
+
+        Ok(SoaMatrix { rows, cols, re, im })
+    }
+
+    /// The split planes of a matrix in any layout
+    pub fn from_matrix(a: &Matrix<Complex<T>>) -> SoaMatrix<T> {
+        let mut res = SoaMatrix::zeros(a.rows(), a.cols());
+        for i in 0..a.rows() {
+            for j in 0..a.cols() {
+                res.set(i, j, a[(i, j)]);
+            }
+        }
+        res
+    }
+
+    /// The row-major matrix of interleaved elements
+    pub fn to_matrix(&self) -> Matrix<Complex<T>> {
+        let data = self.re.iter().zip(&self.im).map(|(&re, &im)| Complex::new(re, im)).collect();
+        Matrix::from_vec(data, self.rows, self.cols).unwrap()
+    }
+
+    /// Number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The real parts, row after row
+    pub fn re(&self) -> &[T] {
+        &self.re
+    }
+
+    /// The imaginary parts, row after row
+    pub fn im(&self) -> &[T] {
+        &self.im
+    }
+
+    /// Both planes, mutably
+    pub fn planes_mut(&mut self) -> (&mut [T], &mut [T]) {
+        (&mut self.re, &mut self.im)
+    }
+
+    /// Element (i, j)
+    pub fn get(&self, i: usize, j: usize) -> Complex<T> {
+        Complex::new(self.re[i * self.cols + j], self.im[i * self.cols + j])
+    }
+
+    /// Overwrites element (i, j) with x
+    pub fn set(&mut self, i: usize, j: usize, x: Complex<T>) {
+        self.re[i * self.cols + j] = x.re;
+        self.im[i * self.cols + j] = x.im;
+    }
+
+    /// Element (i, j) of op(self)
+    fn entry(&self, op: Op, i: usize, j: usize) -> Complex<T> {
+        match op {
+            Op::None => self.get(i, j),
+            Op::Transpose => self.get(j, i),
+            Op::ConjugateTranspose => self.get(j, i).conj(),
+        }
+    }
+
+    /// The planes of row i, mutably
+    fn row_mut(&mut self, i: usize) -> (&mut [T], &mut [T]) {
+        let range = i * self.cols..(i + 1) * self.cols;
+        (&mut self.re[range.clone()], &mut self.im[range])
+    }
+
+    /// Generates the reflector H = I - tau v v* with H* x = (beta, 0, ..., 0), where x is
+    /// column col from row row down, the same way `qr` does.
+    /// beta overwrites (row, col), v(1..) overwrites the entries below it, and tau is returned.
+    fn householder(&mut self, row: usize, col: usize) -> Complex<T> {
+        let zero = T::zero();
+
+        let alpha = self.get(row, col);
+        let xnorm = norm2((row + 1..self.rows).map(|i| self.get(i, col)));
+
+        if xnorm == zero && alpha.im == zero {
+            return Complex::new(zero, zero);
+        }
+
+        let mut beta = alpha.re.hypot(alpha.im).hypot(xnorm);
+        if alpha.re >= zero {
+            beta = -beta;
+        }
+
+        let tau = Complex::new((beta - alpha.re) / beta, -alpha.im / beta);
+        let scale = Complex::new(T::one(), zero) / (alpha - beta);
+        for i in row + 1..self.rows {
+            let x = self.get(i, col) * scale;
+            self.set(i, col, x);
+        }
+        self.set(row, col, Complex::new(beta, zero));
+
+        tau
+    }
+
+    /// Applies H* = I - conj(tau) v v* of the reflector stored in column col from row row
+    /// on to the columns col + 1, ..., n - 1. Row by row, w = v* C accumulates and
+    /// C = C - v (conj(tau) w) subtracts whole rows of the planes, with w in wr and wi.
+    fn reflect_trailing(&mut self, row: usize, col: usize, tau: Complex<T>, wr: &mut [T], wi: &mut [T]) {
+        let j0 = col + 1;
+        let (wr, wi) = (&mut wr[j0..self.cols], &mut wi[j0..self.cols]);
+
+        // the implicit v(row) = 1
+        {
+            let (cr, ci) = self.row_mut(row);
+            wr.copy_from_slice(&cr[j0..]);
+            wi.copy_from_slice(&ci[j0..]);
+        }
+        for i in row + 1..self.rows {
+            let v = self.get(i, col).conj();
+            let (cr, ci) = self.row_mut(i);
+            axpy_planes(v, &cr[j0..], &ci[j0..], wr, wi);
+        }
+
+        let t = tau.conj();
+        for (wr, wi) in wr.iter_mut().zip(wi.iter_mut()) {
+            let w = t * Complex::new(*wr, *wi);
+            *wr = w.re;
+            *wi = w.im;
+        }
+
+        let minus_one = Complex::new(-T::one(), T::zero());
+        {
+            let (cr, ci) = self.row_mut(row);
+            axpy_planes(minus_one, wr, wi, &mut cr[j0..], &mut ci[j0..]);
+        }
+        for i in row + 1..self.rows {
+            let v = -self.get(i, col);
+            let (cr, ci) = self.row_mut(i);
+            axpy_planes(v, wr, wi, &mut cr[j0..], &mut ci[j0..]);
+        }
+    }
+}
+
+/// Computes y = y + alpha x on split planes, the kernel of `gemm_soa` and `qr_soa`.
+/// Both parts of the result are plain sums of real products, which the compiler vectorizes.
+#[inline]
+fn axpy_planes<T: Real>(alpha: Complex<T>, xr: &[T], xi: &[T], yr: &mut [T], yi: &mut [T]) {
+    let (ar, ai) = (alpha.re, alpha.im);
+    for (((yr, yi), &xr), &xi) in yr.iter_mut().zip(yi.iter_mut()).zip(xr).zip(xi) {
+        *yr = *yr + ar * xr - ai * xi;
+        *yi = *yi + ar * xi + ai * xr;
+    }
+}
+
+/// `gemm` on matrices with split planes, C = alpha op_a(A) op_b(B) + beta C, where op(A)
+/// is m x k, op(B) is k x n and C is m x n. Every row of C is updated with whole rows of
+/// op(B), so a transposed B is formed once in split planes. As in BLAS, C is not read when
+/// beta is zero.
+pub fn gemm_soa<T: Real>(alpha: Complex<T>, op_a: Op, a: &SoaMatrix<T>, op_b: Op, b: &SoaMatrix<T>,
+                         beta: Complex<T>, c: &mut SoaMatrix<T>) -> Result<(), CsvdError> {
+    let zero = Complex::new(T::zero(), T::zero());
+    let (m, k, n) = gemm_shape(op_a, (a.rows, a.cols), op_b, (b.rows, b.cols), (c.rows, c.cols))?;
+
+    if beta == zero {
+        c.re.fill(T::zero());
+        c.im.fill(T::zero());
+    }
+    else if beta != Complex::new(T::one(), T::zero()) {
+        for (re, im) in c.re.iter_mut().zip(c.im.iter_mut()) {
+            let x = beta * Complex::new(*re, *im);
+            *re = x.re;
+            *im = x.im;
+        }
+    }
+
+    if alpha == zero || k == 0 {
+        return Ok(());
+    }
+
+    let transposed;
+    let b = match op_b {
+        Op::None => b,
+        _ => {
+            let mut t = SoaMatrix::zeros(k, n);
+            for i in 0..k {
+                for j in 0..n {
+                    t.set(i, j, b.entry(op_b, i, j));
+                }
+            }
+            transposed = t;
+            &transposed
+        }
+    };
+
+    for i in 0..m {
+        let (cr, ci) = c.row_mut(i);
+        for l in 0..k {
+            let x = alpha * a.entry(op_a, i, l);
+            if x != zero {
+                let range = l * n..(l + 1) * n;
+                axpy_planes(x, &b.re[range.clone()], &b.im[range], cr, ci);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `qr` of a matrix with split planes, with the Householder updates running row by row on
+/// the planes. The factors are returned in the usual interleaved form, so R, Q and the
+/// solves of `Qr` are available as for `qr`. a is left untouched.
+pub fn qr_soa<T: Real>(a: &SoaMatrix<T>) -> Result<Qr<T>, CsvdError> {
+    let (m, n) = (a.rows, a.cols);
+    if m < 1 || n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+
+    let mut work = a.clone();
+    let k = m.min(n);
+    let mut tau = Vec::with_capacity(k);
+    let (mut wr, mut wi) = (alloc::vec![T::zero(); n], alloc::vec![T::zero(); n]);
+
+    for i in 0..k {
+        let t = work.householder(i, i);
+        if t != Complex::new(T::zero(), T::zero()) {
+            work.reflect_trailing(i, i, t, &mut wr, &mut wi);
+        }
+        tau.push(t);
+    }
+
+    Ok(Qr::from_parts(work.to_matrix(), tau, (0..n).collect()))
+}
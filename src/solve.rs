@@ -0,0 +1,114 @@
+//! A linear solver that picks the algorithm for the problem at hand.
+//!
+//! The cheapest method that is accurate for A X = B depends on the shape and conditioning
+//! of A: the adjugate formula for the tiny square matrices of 2 x 2 and 3 x 3 MIMO, LU for
+//! other well-conditioned square matrices, QR for well-conditioned overdetermined systems,
+//! and the SVD, which is the only one to handle rank deficient and wide matrices, for the
+//! rest. `solve_auto` makes that choice from the shape and an O(n^2) condition estimate
+//! of the factorization it has already computed, so a well-conditioned system never pays
+//! for a decomposition it does not need.
+
+use num_complex::Complex;
+use super::csvd::cabs;
+use super::error::CsvdError;
+use super::fixed::{inv2, inv3};
+use super::lu::lu;
+use super::matrix::Matrix;
+use super::qr::qr;
+use super::scalar::Real;
+use super::svd::svd;
+use super::PinvOptions;
+
+/// The algorithm `solve_auto` used
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Solver {
+    /// the inverse of a 2 x 2 or 3 x 3 matrix from `inv2` or `inv3`, which fall back to
+    /// the pseudo-inverse themselves for an ill-conditioned matrix
+    Adjugate,
+    /// the LU decomposition of a well-conditioned square matrix
+    Lu,
+    /// the QR decomposition of a well-conditioned tall matrix
+    Qr,
+    /// the SVD with the default `PinvOptions`, as `solve_multi`
+    Svd,
+}
+
+/// A solution from `solve_auto` together with the algorithm that computed it
+#[derive(Clone, Debug, PartialEq)]
+pub struct AutoSolution<T> {
+    /// n x nrhs solution, one column per right-hand side
+    pub x: Matrix<Complex<T>>,
+    pub solver: Solver,
+}
+
+/// Solves A X = B in the least-squares sense for the m x n matrix a and the m x nrhs
+/// matrix b with the cheapest algorithm that is accurate for a, see `Solver`.
+///
+/// Square and tall matrices whose estimated condition number stays below 1 / sqrt(eps),
+/// the limit `inv3` uses as well, are solved by LU or QR, all others by the SVD. The result
+/// agrees with `solve_multi` up to rounding, and is the minimum norm solution for a rank
+/// deficient or wide matrix. a and b are left untouched.
+pub fn solve_auto<T: Real>(a: &Matrix<Complex<T>>, b: &Matrix<Complex<T>>) -> Result<AutoSolution<T>, CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+    let nrhs = b.cols();
+
+    if m < 1 || n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    if b.rows() != m {
+        return Err(CsvdError::DimensionMismatch { operand: "b", expected: (m, nrhs), found: (b.rows(), nrhs) });
+    }
+
+    let limit = T::one() / T::epsilon().sqrt();
+
+    if m == n && (n == 2 || n == 3) {
+        let x = if n == 2 {
+            apply(&inv2(&core::array::from_fn(|i| core::array::from_fn(|j| a[(i, j)])))?, b)
+        }
+        else {
+            apply(&inv3(&core::array::from_fn(|i| core::array::from_fn(|j| a[(i, j)])))?, b)
+        };
+        return Ok(AutoSolution { x, solver: Solver::Adjugate });
+    }
+    else if m == n {
+        match lu(a) {
+            Ok(f) => {
+                let norm = (0..n).map(|j| (0..n).fold(T::zero(), |acc, i| acc + cabs(&a[(i, j)]))).fold(T::zero(), T::max);
+                let cond = norm * f.inverse_norm1_estimate();
+                if cond.is_finite() && cond <= limit {
+                    return Ok(AutoSolution { x: f.solve(b)?, solver: Solver::Lu });
+                }
+                trace!("{} x {} matrix has an estimated condition number of {:?}, solving with the SVD", n, n, cond);
+            }
+            Err(CsvdError::Singular { .. }) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    else if m > n {
+        let f = qr(a)?;
+        let r = f.r();
+        // the spread of the diagonal of R is a lower bound on the condition number
+        let (rmin, rmax) = (0..n).map(|i| cabs(&r[(i, i)])).fold((T::infinity(), T::zero()), |(lo, hi), r| (lo.min(r), hi.max(r)));
+        if rmin > T::zero() && rmax / rmin <= limit {
+            return Ok(AutoSolution { x: f.solve(b)?, solver: Solver::Qr });
+        }
+        trace!("{} x {} matrix has a diagonal of R spread by {:?}, solving with the SVD", m, n, rmax / rmin);
+    }
+
+    let x = svd(a)?.solve(b, &PinvOptions::default())?;
+    Ok(AutoSolution { x, solver: Solver::Svd })
+}
+
+/// The product of the N x N matrix inv and the N x nrhs matrix b
+fn apply<T: Real, const N: usize>(inv: &[[Complex<T>; N]; N], b: &Matrix<Complex<T>>) -> Matrix<Complex<T>> {
+    let mut x = Matrix::zeros(N, b.cols());
+
+    for i in 0..N {
+        for j in 0..b.cols() {
+            x[(i, j)] = (0..N).fold(Complex::new(T::zero(), T::zero()), |acc, k| acc + inv[i][k] * b[(k, j)]);
+        }
+    }
+
+    x
+}
@@ -0,0 +1,251 @@
+//! Sparse matrices in the compressed sparse row (CSR) format.
+//!
+//! A `CsrMatrix` stores only its nonzero entries, row after row, so products with it cost
+//! O(nnz) instead of O(m n). It implements `LinearOperator`, which lets `partial_svd`,
+//! `dominant_singular_triplet_of` and the solvers from `gmres` to `lsqr` work on large
+//! sparse system models without ever forming them densely.
+
+use alloc::vec::Vec;
+use num_complex::Complex;
+use super::blas::{scal, Op};
+use super::error::CsvdError;
+use super::iterative::LinearOperator;
+use super::matrix::Matrix;
+use super::scalar::Real;
+
+/// An m x n complex matrix in the compressed sparse row format: the entries of row i are
+/// `values[row_ptr[i]..row_ptr[i + 1]]`, in the columns `col_idx[row_ptr[i]..row_ptr[i + 1]]`
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsrMatrix<T> {
+    rows: usize,
+    cols: usize,
+    /// rows + 1 offsets into col_idx and values, starting at 0 and ending at nnz
+    row_ptr: Vec<usize>,
+    /// the column of every stored entry
+    col_idx: Vec<usize>,
+    /// the stored entries
+    values: Vec<Complex<T>>,
+}
+
+impl<T: Real> CsrMatrix<T> {
+    /// Takes ownership of the CSR arrays of a rows x cols matrix after checking that
+    /// row_ptr starts at zero, never decreases and ends at the number of stored entries,
+    /// and that every column index is below cols. Columns may be stored in any order
+    /// within a row, and a repeated column adds up.
+    pub fn new(rows: usize, cols: usize, row_ptr: Vec<usize>, col_idx: Vec<usize>, values: Vec<Complex<T>>)
+            -> Result<CsrMatrix<T>, CsvdError> {
+        if row_ptr.len() != rows + 1 {
+            return Err(CsvdError::DimensionMismatch { operand: "row_ptr", expected: (rows + 1, 1), found: (row_ptr.len(), 1) });
+        }
+        if row_ptr[0] != 0 {
+            return Err(CsvdError::InvalidArgument { name: "row_ptr", value: row_ptr[0], max: 0 });
+        }
+        if let Some(i) = (0..rows).find(|&i| row_ptr[i] > row_ptr[i + 1]) {
+            return Err(CsvdError::InvalidArgument { name: "row_ptr", value: row_ptr[i], max: row_ptr[i + 1] });
+        }
+        let nnz = row_ptr[rows];
+        if col_idx.len() != nnz {
+            return Err(CsvdError::DimensionMismatch { operand: "col_idx", expected: (nnz, 1), found: (col_idx.len(), 1) });
+        }
+        if values.len() != nnz {
+            return Err(CsvdError::DimensionMismatch { operand: "values", expected: (nnz, 1), found: (values.len(), 1) });
+        }
+        if let Some(&j) = col_idx.iter().find(|&&j| j >= cols) {
+            return Err(CsvdError::InvalidArgument { name: "col_idx", value: j, max: cols.saturating_sub(1) });
+        }
+
+        Ok(CsrMatrix { rows, cols, row_ptr, col_idx, values })
+    }
+
+    /// Builds a rows x cols matrix from (row, col, value) triplets in any order,
+    /// adding up the values of repeated positions as finite element assembly does
+    pub fn from_triplets(rows: usize, cols: usize, triplets: &[(usize, usize, Complex<T>)]) -> Result<CsrMatrix<T>, CsvdError> {
+        if let Some(&(i, _, _)) = triplets.iter().find(|t| t.0 >= rows) {
+            return Err(CsvdError::InvalidArgument { name: "row", value: i, max: rows.saturating_sub(1) });
+        }
+        if let Some(&(_, j, _)) = triplets.iter().find(|t| t.1 >= cols) {
+            return Err(CsvdError::InvalidArgument { name: "col", value: j, max: cols.saturating_sub(1) });
+        }
+
+        let mut order: Vec<usize> = (0..triplets.len()).collect();
+        order.sort_by_key(|&l| (triplets[l].0, triplets[l].1));
+
+        let mut row_ptr = alloc::vec![0; rows + 1];
+        let mut col_idx = Vec::with_capacity(triplets.len());
+        let mut values: Vec<Complex<T>> = Vec::with_capacity(triplets.len());
+        let mut last = None;
+        for l in order {
+            let (i, j, x) = triplets[l];
+            if last == Some((i, j)) {
+                let sum = values.last_mut().unwrap();
+                *sum = *sum + x;
+                continue;
+            }
+            last = Some((i, j));
+            row_ptr[i + 1] += 1;
+            col_idx.push(j);
+            values.push(x);
+        }
+        for i in 0..rows {
+            row_ptr[i + 1] += row_ptr[i];
+        }
+
+        Ok(CsrMatrix { rows, cols, row_ptr, col_idx, values })
+    }
+
+    /// The nonzero entries of a dense matrix
+    pub fn from_dense(a: &Matrix<Complex<T>>) -> CsrMatrix<T> {
+        let zero = Complex::new(T::zero(), T::zero());
+        let mut row_ptr = Vec::with_capacity(a.rows() + 1);
+        let mut col_idx = Vec::new();
+        let mut values = Vec::new();
+
+        row_ptr.push(0);
+        for i in 0..a.rows() {
+            for j in 0..a.cols() {
+                if a[(i, j)] != zero {
+                    col_idx.push(j);
+                    values.push(a[(i, j)]);
+                }
+            }
+            row_ptr.push(values.len());
+        }
+
+        CsrMatrix { rows: a.rows(), cols: a.cols(), row_ptr, col_idx, values }
+    }
+
+    /// The dense row-major matrix with the same entries
+    pub fn to_dense(&self) -> Matrix<Complex<T>> {
+        let mut a = Matrix::zeros(self.rows, self.cols);
+        for i in 0..self.rows {
+            for l in self.row_ptr[i]..self.row_ptr[i + 1] {
+                a[(i, self.col_idx[l])] = a[(i, self.col_idx[l])] + self.values[l];
+            }
+        }
+        a
+    }
+
+    /// Number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Number of stored entries
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The rows + 1 offsets of the rows into `col_indices` and `values`
+    pub fn row_ptr(&self) -> &[usize] {
+        &self.row_ptr
+    }
+
+    /// The column of every stored entry
+    pub fn col_indices(&self) -> &[usize] {
+        &self.col_idx
+    }
+
+    /// The stored entries, row after row
+    pub fn values(&self) -> &[Complex<T>] {
+        &self.values
+    }
+
+    /// The min(m, n) diagonal entries, zero where none is stored
+    pub fn diagonal(&self) -> Vec<Complex<T>> {
+        (0..self.rows.min(self.cols))
+            .map(|i| self.row_entries(i).filter(|&(j, _)| j == i).fold(Complex::new(T::zero(), T::zero()), |acc, (_, a)| acc + a))
+            .collect()
+    }
+
+    /// The stored entries of row i as (col, value) pairs
+    fn row_entries(&self, i: usize) -> impl Iterator<Item = (usize, Complex<T>)> + '_ {
+        let range = self.row_ptr[i]..self.row_ptr[i + 1];
+        self.col_idx[range.clone()].iter().copied().zip(self.values[range].iter().copied())
+    }
+}
+
+/// Computes y = alpha op_a(A) x + beta y for a sparse A, where op(A) is m x n, x has n and
+/// y has m elements, in O(nnz) operations. As in `gemv`, y is not read when beta is zero.
+pub fn spmv<T: Real>(alpha: Complex<T>, op_a: Op, a: &CsrMatrix<T>, x: &[Complex<T>],
+                     beta: Complex<T>, y: &mut [Complex<T>]) -> Result<(), CsvdError> {
+    let zero = Complex::new(T::zero(), T::zero());
+    let (m, n) = match op_a {
+        Op::None => (a.rows, a.cols),
+        _ => (a.cols, a.rows),
+    };
+
+    if x.len() != n {
+        return Err(CsvdError::DimensionMismatch { operand: "x", expected: (n, 1), found: (x.len(), 1) });
+    }
+    if y.len() != m {
+        return Err(CsvdError::DimensionMismatch { operand: "y", expected: (m, 1), found: (y.len(), 1) });
+    }
+
+    if beta == zero {
+        y.fill(zero);
+    }
+    else {
+        scal(beta, y);
+    }
+
+    if alpha == zero {
+        return Ok(());
+    }
+
+    match op_a {
+        Op::None => {
+            for (i, y) in y.iter_mut().enumerate() {
+                let sum = a.row_entries(i).fold(zero, |acc, (j, a)| acc + a * x[j]);
+                *y = *y + alpha * sum;
+            }
+        }
+        // row i of A scatters alpha x_i into y
+        Op::Transpose => {
+            for (i, &x) in x.iter().enumerate() {
+                let x = alpha * x;
+                for (j, a) in a.row_entries(i) {
+                    y[j] = y[j] + a * x;
+                }
+            }
+        }
+        Op::ConjugateTranspose => {
+            for (i, &x) in x.iter().enumerate() {
+                let x = alpha * x;
+                for (j, a) in a.row_entries(i) {
+                    y[j] = y[j] + a.conj() * x;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl<T: Real> LinearOperator<T> for CsrMatrix<T> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn apply(&self, x: &[Complex<T>], y: &mut [Complex<T>]) {
+        for (i, y) in y.iter_mut().enumerate() {
+            *y = self.row_entries(i).fold(Complex::new(T::zero(), T::zero()), |acc, (j, a)| acc + a * x[j]);
+        }
+    }
+
+    fn apply_adjoint(&self, y: &[Complex<T>], x: &mut [Complex<T>]) {
+        x.fill(Complex::new(T::zero(), T::zero()));
+        for (i, y) in y.iter().enumerate() {
+            for (j, a) in self.row_entries(i) {
+                x[j] = x[j] + a.conj() * y;
+            }
+        }
+    }
+}
@@ -0,0 +1,807 @@
+use alloc::vec::Vec;
+use num_complex::{Complex, Complex32};
+use core::ops::ControlFlow;
+use super::csvd::{cabs, csvd, csvd_with_options, csvd_with_progress, csvd_with_stats, norm2, normalize_phases, Progress, SvdOptions, SvdStats};
+use super::eig::eigh;
+use super::iq::q15_into;
+use super::lu::lu;
+use super::error::CsvdError;
+use super::{find_pinv_from_svd, find_pinv_from_svd_with_options, PinvOptions};
+use super::matrix::{Layout, Matrix};
+use super::qr::gram_schmidt;
+use super::rsvd::svd_real;
+use super::scalar::Real;
+use super::verify::orthogonality;
+
+/// The singular value decomposition A = U S V* of an m x n matrix A,
+/// owning all of its factors.
+/// A truncated decomposition from `svd_truncated` holds only k columns of U and V.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvdResult<T> {
+    /// m x m unitary matrix whose columns are the left singular vectors
+    pub u: Matrix<Complex<T>>,
+    /// the min(m, n) singular values in descending order
+    pub s: Vec<T>,
+    /// n x n unitary matrix whose columns are the right singular vectors
+    pub v: Matrix<Complex<T>>,
+}
+
+/// The loss of orthogonality ||Q* Q - I|| of the singular vectors, in the Frobenius norm,
+/// before and after `SvdResult::reorthogonalize`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reorthogonalization<T> {
+    /// of U before the pass
+    pub u_before: T,
+    /// of U after the pass
+    pub u_after: T,
+    /// of V before the pass
+    pub v_before: T,
+    /// of V after the pass
+    pub v_after: T,
+}
+
+/// The minimum norm solution X of min ||A X - B|| computed by `lstsq`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LstsqSolution<T> {
+    /// n x nrhs solution, one column per right-hand side
+    pub x: Matrix<Complex<T>>,
+    /// the residual norm ||A x - b|| of every column
+    pub residuals: Vec<T>,
+    /// the effective rank of A, i.e. the number of singular values used
+    pub rank: usize,
+}
+
+/// Solves the least-squares problem min ||A X - B|| for the m x nrhs matrix b,
+/// using the singular value decomposition of a without forming the pseudo-inverse.
+/// Singular values not larger than rcond times the largest one are treated as zero,
+/// which gives the minimum norm solution for rank deficient a.
+pub fn lstsq<T: Real>(a: &Matrix<Complex<T>>, b: &Matrix<Complex<T>>, rcond: T) -> Result<LstsqSolution<T>, CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+    let nrhs = b.cols();
+
+    if b.rows() != m {
+        return Err(CsvdError::DimensionMismatch { operand: "b", expected: (m, nrhs), found: (b.rows(), nrhs) });
+    }
+
+    let res = svd(a)?;
+    let zero = Complex::new(T::zero(), T::zero());
+
+    let options = PinvOptions { atol: T::zero(), rcond: Some(rcond) };
+    let rank = res.rank(options.cutoff(res.s.first().map_or(T::zero(), |&s0| s0), m, n));
+    let x = res.solve(b, &options)?;
+
+    let mut residuals = Vec::with_capacity(nrhs);
+    for j in 0..nrhs {
+        let r = (0..m).map(|i| {
+            let mut ax = zero;
+            for k in 0..n {
+                ax = ax + a[(i, k)] * x[(k, j)];
+            }
+            ax - b[(i, j)]
+        });
+        residuals.push(norm2(r));
+    }
+
+    Ok(LstsqSolution { x, residuals, rank })
+}
+
+/// Solves A X = B in the least-squares sense for all nrhs columns of the m x nrhs matrix b
+/// with a single decomposition of a, X = V x S+ x U* x B, e.g. to equalize all received
+/// vectors of an OFDM symbol at once. The pseudo-inverse is never formed and
+/// the default `PinvOptions` decide which singular values are treated as zero.
+pub fn solve_multi<T: Real>(a: &Matrix<Complex<T>>, b: &Matrix<Complex<T>>) -> Result<Matrix<Complex<T>>, CsvdError> {
+    if b.rows() != a.rows() {
+        return Err(CsvdError::DimensionMismatch { operand: "b", expected: (a.rows(), b.cols()), found: (b.rows(), b.cols()) });
+    }
+
+    svd(a)?.solve(b, &PinvOptions::default())
+}
+
+/// Computes the singular value decomposition of a, leaving a untouched
+/// and sizing all of the factors internally. a can have any shape.
+pub fn svd<T: Real>(a: &Matrix<Complex<T>>) -> Result<SvdResult<T>, CsvdError> {
+    svd_with_options(a, &SvdOptions::default())
+}
+
+/// Same as `svd`, without the scan of a for NaN and infinite entries, see `csvd_unchecked`
+pub fn svd_unchecked<T: Real>(a: &Matrix<Complex<T>>) -> Result<SvdResult<T>, CsvdError> {
+    svd_with_options(a, &SvdOptions { check_finite: false, ..SvdOptions::default() })
+}
+
+/// `svd` of the tightly packed, row-major m x n matrix of Q15 fixed-point entries a, which
+/// stands for a / 2^15 * scale, e.g. a channel estimate from a fixed-point front-end.
+/// The entries are converted to an f32 copy up front, in a single pass that takes the place
+/// of the scan for NaN and infinite entries, which fixed-point numbers cannot hold. The
+/// conversion is not fused into the first Householder pass: csvd overwrites its input, so
+/// the copy is needed anyway, and the pass costs O(m n) next to the O(m n^2) reduction.
+/// a is left untouched.
+pub fn svd_q15(a: &[Complex<i16>], m: usize, n: usize, scale: f32) -> Result<SvdResult<f32>, CsvdError> {
+    if m < 1 || n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    if a.len() < m * n {
+        return Err(CsvdError::BufferTooSmall { operand: "A", required: m * n, len: a.len() });
+    }
+
+    let mut work = Matrix::try_zeros(m, n)?;
+    q15_into(&a[..m * n], scale, work.as_mut_slice())?;
+
+    let mut s = alloc::vec![0.0; m.min(n)];
+    let mut u = Matrix::<Complex32>::zeros(m, m);
+    let mut v = Matrix::zeros(n, n);
+
+    // a scale of NaN or infinity still has to be caught
+    let options = SvdOptions { check_finite: !scale.is_finite(), ..SvdOptions::default() };
+    csvd_with_options(&mut work, 0, m, n, &mut s, &mut u, &mut v, &options)?;
+
+    Ok(SvdResult { u, s, v })
+}
+
+/// Same as `svd`, with the tuning parameters given explicitly
+pub fn svd_with_options<T: Real>(a: &Matrix<Complex<T>>, options: &SvdOptions) -> Result<SvdResult<T>, CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+
+    // csvd overwrites its input
+    let mut work = a.clone();
+
+    let mut s: Vec<T> = Vec::with_capacity(m.min(n));
+    s.resize(m.min(n), T::zero());
+    let mut u = Matrix::zeros(m, m);
+    let mut v = Matrix::zeros(n, n);
+
+    csvd_with_options(&mut work, 0, m, n, &mut s, &mut u, &mut v, options)?;
+
+    Ok(SvdResult { u, s, v })
+}
+
+/// Same as `svd_with_options`, together with the statistics of the QR diagonalization and
+/// the relative residual ||A - U S V*|| / ||A|| of the factors, which costs another
+/// O(m n min(m, n)) operations
+pub fn svd_with_stats<T: Real>(a: &Matrix<Complex<T>>, options: &SvdOptions) -> Result<(SvdResult<T>, SvdStats<T>), CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+
+    // csvd overwrites its input
+    let mut work = a.clone();
+
+    let mut s: Vec<T> = Vec::with_capacity(m.min(n));
+    s.resize(m.min(n), T::zero());
+    let mut u = Matrix::zeros(m, m);
+    let mut v = Matrix::zeros(n, n);
+
+    let mut stats = csvd_with_stats(&mut work, 0, m, n, &mut s, &mut u, &mut v, options)?;
+    let res = SvdResult { u, s, v };
+
+    let diff = res.reconstruct();
+    let a_norm = norm2((0..m).flat_map(|i| (0..n).map(move |j| (i, j))).map(|(i, j)| a[(i, j)]));
+    let residual = norm2((0..m).flat_map(|i| (0..n).map(move |j| (i, j))).map(|(i, j)| a[(i, j)] - diff[(i, j)]));
+    stats.residual = Some(if a_norm > T::zero() { residual / a_norm } else { residual });
+    debug!(target: "lin_alg::csvd", "{} x {}: relative residual {:?}", m, n, stats.residual);
+
+    Ok((res, stats))
+}
+
+/// Same as `svd_with_options`, with progress called before every QR sweep, see
+/// `csvd_with_progress`
+pub fn svd_with_progress<T: Real, F: FnMut(Progress) -> ControlFlow<()>>(a: &Matrix<Complex<T>>, options: &SvdOptions,
+        progress: F) -> Result<SvdResult<T>, CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+
+    // csvd overwrites its input
+    let mut work = a.clone();
+
+    let mut s: Vec<T> = Vec::with_capacity(m.min(n));
+    s.resize(m.min(n), T::zero());
+    let mut u = Matrix::zeros(m, m);
+    let mut v = Matrix::zeros(n, n);
+
+    csvd_with_progress(&mut work, 0, m, n, &mut s, &mut u, &mut v, options, progress)?;
+    Ok(SvdResult { u, s, v })
+}
+
+/// The structure of a matrix that `svd_auto` found and exploited
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Structure {
+    /// no structure, decomposed by `csvd`
+    General,
+    /// all imaginary parts are zero, decomposed by `svd_real`
+    Real,
+    /// square with a = a*, decomposed by the eigensolver `eigh`
+    Hermitian,
+}
+
+/// A decomposition from `svd_auto` together with the path that computed it
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructuredSvd<T> {
+    pub svd: SvdResult<T>,
+    pub structure: Structure,
+}
+
+/// Finds the structure of a that `svd_auto` can exploit. The checks are exact, so a matrix
+/// that is Hermitian only up to rounding errors counts as general. A real symmetric matrix
+/// counts as real, the real SVD is faster than the complex eigensolver.
+pub fn structure<T: Real>(a: &Matrix<Complex<T>>) -> Structure {
+    let m = a.rows();
+    let n = a.cols();
+
+    if (0..m).all(|i| (0..n).all(|j| a[(i, j)].im == T::zero())) {
+        Structure::Real
+    }
+    else if m == n && (0..n).all(|i| (i..n).all(|j| a[(i, j)] == a[(j, i)].conj())) {
+        Structure::Hermitian
+    }
+    else {
+        Structure::General
+    }
+}
+
+/// Same as `svd`, after a scan of a for structure that allows a faster decomposition.
+/// Real matrices are decomposed in real arithmetic, Hermitian ones through their
+/// eigendecomposition A = Q L Q*, which gives U = Q, S = |L| and V = Q sign(L).
+/// The result tells which path was taken, the factors agree with `svd` up to the
+/// usual freedom in the phases of the singular vectors.
+pub fn svd_auto<T: Real>(a: &Matrix<Complex<T>>) -> Result<StructuredSvd<T>, CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+    let zero = T::zero();
+    let structure = structure(a);
+
+    let svd = match structure {
+        Structure::General => svd(a)?,
+        Structure::Real => {
+            let mut work: Vec<T> = (0..m).flat_map(|i| (0..n).map(move |j| (i, j))).map(|(i, j)| a[(i, j)].re).collect();
+            let mut s = alloc::vec![zero; m.min(n)];
+            let mut u = alloc::vec![zero; m * m];
+            let mut v = alloc::vec![zero; n * n];
+            let mut scratch = alloc::vec![zero; 3 * m.min(n)];
+
+            svd_real(&mut work, &mut s, &mut u, &mut v, &mut scratch, m, n)?;
+
+            let complex = |x: Vec<T>, k| Matrix::from_vec(x.into_iter().map(|x| Complex::new(x, zero)).collect(), k, k);
+            SvdResult { u: complex(u, m)?, s, v: complex(v, n)? }
+        }
+        Structure::Hermitian => {
+            let eig = eigh(a)?;
+
+            // singular values are the |eigenvalues| in descending order, the sign of a
+            // negative eigenvalue goes into its right singular vector
+            let mut order: Vec<usize> = (0..n).collect();
+            order.sort_by(|&i, &j| eig.values[j].abs().partial_cmp(&eig.values[i].abs()).unwrap_or(core::cmp::Ordering::Equal));
+
+            let mut u = Matrix::zeros(n, n);
+            let mut v = Matrix::zeros(n, n);
+            for (col, &k) in order.iter().enumerate() {
+                let sign = if eig.values[k] < zero { -T::one() } else { T::one() };
+                for i in 0..n {
+                    u[(i, col)] = eig.vectors[(i, k)];
+                    v[(i, col)] = eig.vectors[(i, k)] * sign;
+                }
+            }
+            let s = order.iter().map(|&k| eig.values[k].abs()).collect();
+            normalize_phases(u.as_mut_slice(), n, n, n, v.as_mut_slice(), n, n, n);
+
+            SvdResult { u, s, v }
+        }
+    };
+
+    Ok(StructuredSvd { svd, structure })
+}
+
+/// Computes only the k largest singular values of a and their singular vectors,
+/// e.g. for a low-rank approximation of a channel matrix.
+/// The result holds an m x k matrix u, k singular values and an n x k matrix v,
+/// only k columns of U and V are back-transformed and no m x m or n x n factor is stored.
+pub fn svd_truncated<T: Real>(a: &Matrix<Complex<T>>, k: usize) -> Result<SvdResult<T>, CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+    let min = m.min(n);
+
+    if k > min {
+        return Err(CsvdError::InvalidArgument { name: "k", value: k, max: min });
+    }
+
+    // csvd overwrites its input
+    let mut work = a.clone();
+
+    let mut s: Vec<T> = Vec::with_capacity(min);
+    s.resize(min, T::zero());
+    // the QR sweeps rotate all min(m, n) columns, so those are still needed
+    let mut u_full = Matrix::zeros(m, min);
+    let mut v_full = Matrix::zeros(n, min);
+
+    csvd(&mut work, 0, k, k, &mut s, &mut u_full, &mut v_full)?;
+
+    let mut u = Matrix::zeros(m, k);
+    for i in 0..m {
+        u.row_mut(i).copy_from_slice(&u_full.row(i)[..k]);
+    }
+    let mut v = Matrix::zeros(n, k);
+    for i in 0..n {
+        v.row_mut(i).copy_from_slice(&v_full.row(i)[..k]);
+    }
+    s.truncate(k);
+
+    Ok(SvdResult { u, s, v })
+}
+
+/// Computes the Tikhonov-regularized pseudo-inverse V x (S^2 + lambda I)^-1 x S x U* of a,
+/// see `SvdResult::pinv_regularized`. lambda must not be negative.
+pub fn pinv_regularized<T: Real>(a: &Matrix<Complex<T>>, lambda: T) -> Result<Matrix<Complex<T>>, CsvdError> {
+    Ok(svd(a)?.pinv_regularized(lambda))
+}
+
+/// Numerical rank of a, the number of singular values larger than tol.
+/// None uses the default tolerance max(m, n) * eps * sigma_max, as NumPy's matrix_rank.
+pub fn rank<T: Real>(a: &Matrix<Complex<T>>, tol: Option<T>) -> Result<usize, CsvdError> {
+    let s = singular_values(a)?;
+    let tol = rank_tol(&s, a.rows(), a.cols(), tol);
+
+    Ok(s.iter().filter(|&&s| s > tol).count())
+}
+
+/// A numerical rank from `rank_by_gap` together with the gap that revealed it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GapRank<T> {
+    /// the number of singular values above the gap
+    pub rank: usize,
+    /// sigma_rank / sigma_rank+1, the ratio of the singular values on either side of the
+    /// gap, infinite if the ones below it are zero. For a matrix of full rank, the largest
+    /// ratio of consecutive singular values, which fell short of min_gap, and one for a
+    /// zero matrix.
+    pub gap: T,
+}
+
+/// Numerical rank of a from the largest relative gap in its singular values, rather than
+/// from a threshold on their size. The rank is r for the largest ratio sigma_r / sigma_r+1
+/// of two consecutive singular values, as long as it is at least min_gap, e.g. 10, and
+/// the full rank min(m, n) otherwise. Singular values up to the default tolerance of
+/// `rank` count as zero and open an infinite gap.
+///
+/// A noisy channel matrix of rank r has n - r singular values at the noise level, which
+/// no fixed threshold separates from the signal for every SNR, while the gap after
+/// sigma_r stays apparent. The returned gap tells how clear the decision was.
+pub fn rank_by_gap<T: Real>(a: &Matrix<Complex<T>>, min_gap: T) -> Result<GapRank<T>, CsvdError> {
+    let s = singular_values(a)?;
+    Ok(gap_rank(&s, a.rows(), a.cols(), min_gap))
+}
+
+/// The rank of `rank_by_gap` from the singular values s of an m x n matrix
+fn gap_rank<T: Real>(s: &[T], m: usize, n: usize, min_gap: T) -> GapRank<T> {
+    let p = s.len();
+    if p == 0 || s[0] == T::zero() {
+        return GapRank { rank: 0, gap: T::one() };
+    }
+
+    let tol = rank_tol(s, m, n, None);
+    let mut best = GapRank { rank: p, gap: T::one() };
+    for r in 1..p {
+        let gap = if s[r] > tol { s[r - 1] / s[r] } else { T::infinity() };
+        if gap > best.gap {
+            best = GapRank { rank: r, gap };
+        }
+        if s[r] <= tol {
+            break;
+        }
+    }
+
+    if best.gap < min_gap {
+        best.rank = p;
+    }
+    best
+}
+
+/// tol, or the default tolerance max(m, n) * eps * sigma_max if it is None
+fn rank_tol<T: Real>(s: &[T], m: usize, n: usize, tol: Option<T>) -> T {
+    match tol {
+        Some(tol) => tol,
+        None => PinvOptions::default().cutoff(s[0], m, n),
+    }
+}
+
+/// Orthonormal basis of the null space of a, as the n x (n - r) matrix of the right singular
+/// vectors whose singular values are not larger than tol, where r is the rank of a.
+/// None uses the same default tolerance as `rank`.
+pub fn null_space<T: Real>(a: &Matrix<Complex<T>>, tol: Option<T>) -> Result<Matrix<Complex<T>>, CsvdError> {
+    let res = svd(a)?;
+    let n = a.cols();
+    let r = res.rank(rank_tol(&res.s, a.rows(), n, tol));
+
+    // V is n x n, so its columns past min(m, n) belong to the null space as well
+    let mut basis = Matrix::zeros(n, n - r);
+    for i in 0..n {
+        basis.row_mut(i).copy_from_slice(&res.v.row(i)[r..]);
+    }
+
+    Ok(basis)
+}
+
+/// Orthonormal basis of the column space (range) of a, as the m x r matrix of the left singular
+/// vectors whose singular values are larger than tol, where r is the rank of a.
+/// None uses the same default tolerance as `rank`.
+pub fn column_space<T: Real>(a: &Matrix<Complex<T>>, tol: Option<T>) -> Result<Matrix<Complex<T>>, CsvdError> {
+    let res = svd(a)?;
+    let m = a.rows();
+    let r = res.rank(rank_tol(&res.s, m, a.cols(), tol));
+
+    let mut basis = Matrix::zeros(m, r);
+    for i in 0..m {
+        basis.row_mut(i).copy_from_slice(&res.u.row(i)[..r]);
+    }
+
+    Ok(basis)
+}
+
+/// 2-norm condition number sigma_max / sigma_min of a,
+/// infinite if the smallest singular value is zero
+pub fn cond<T: Real>(a: &Matrix<Complex<T>>) -> Result<T, CsvdError> {
+    let s = singular_values(a)?;
+    let s_min = s[s.len() - 1];
+
+    if s_min == T::zero() {
+        Ok(T::infinity())
+    }
+    else {
+        Ok(s[0] / s_min)
+    }
+}
+
+/// Refines the singular vectors u and v of the m x n matrix a that belong to the converged
+/// singular value sigma by inverse iteration, and returns the residual
+/// sqrt(||A v - sigma u||^2 + ||A* u - sigma v||^2) of the refined vectors.
+///
+/// [u; v] is an eigenvector of the Hermitian matrix [0 A; A* 0] for the eigenvalue sigma,
+/// so each of the iterations solves with [-sigma I A; A* -sigma I] and normalizes. As its
+/// LU decomposition is computed once, every iteration costs O((m + n)^2), after the
+/// O((m + n)^3) of the decomposition. With an accurate sigma the error of the vectors
+/// shrinks by the ratio of sigma's distance to the shift and to the nearest other singular
+/// value every iteration, so one or two suffice, e.g. for the null vector of the smallest
+/// singular value in direction-of-arrival estimation. The vectors keep their phase.
+pub fn refine_singular_vectors<T: Real>(a: &Matrix<Complex<T>>, sigma: T, u: &mut [Complex<T>], v: &mut [Complex<T>],
+        iterations: usize) -> Result<T, CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+    let zero = Complex::new(T::zero(), T::zero());
+
+    if u.len() != m {
+        return Err(CsvdError::DimensionMismatch { operand: "u", expected: (m, 1), found: (u.len(), 1) });
+    }
+    if v.len() != n {
+        return Err(CsvdError::DimensionMismatch { operand: "v", expected: (n, 1), found: (v.len(), 1) });
+    }
+
+    let augmented = |shift: T| {
+        let mut k = Matrix::zeros(m + n, m + n);
+        for i in 0..m {
+            k[(i, i)] = Complex::new(-shift, T::zero());
+            for j in 0..n {
+                k[(i, m + j)] = a[(i, j)];
+                k[(m + j, i)] = a[(i, j)].conj();
+            }
+        }
+        for j in 0..n {
+            k[(m + j, m + j)] = Complex::new(-shift, T::zero());
+        }
+        k
+    };
+    // a shift that is exactly a singular value leaves a zero pivot, moving it by a few ulps
+    // of ||A|| keeps the solves finite without slowing the convergence
+    let f = match lu(&augmented(sigma)) {
+        Err(CsvdError::Singular { .. }) => {
+            let a_norm = norm2((0..m).flat_map(|i| (0..n).map(move |j| a[(i, j)])));
+            lu(&augmented(sigma + T::epsilon() * a_norm.max(T::min_positive_value())))?
+        }
+        f => f?,
+    };
+
+    let mut w = Matrix::zeros(m + n, 1);
+    for i in 0..m {
+        w[(i, 0)] = u[i];
+    }
+    for j in 0..n {
+        w[(m + j, 0)] = v[j];
+    }
+    for _ in 0..iterations {
+        w = f.solve(&w)?;
+        let norm = norm2((0..m + n).map(|i| w[(i, 0)]));
+        if !(norm > T::zero() && norm.is_finite()) {
+            break;
+        }
+        for i in 0..m + n {
+            w[(i, 0)] = w[(i, 0)] / norm;
+        }
+    }
+
+    // each part to unit length, turned back to the phase v had
+    let phase = (0..n).fold(zero, |acc, j| acc + w[(m + j, 0)].conj() * v[j]);
+    let phase = if phase != zero { phase / cabs(&phase) } else { Complex::new(T::one(), T::zero()) };
+    let u_norm = norm2((0..m).map(|i| w[(i, 0)]));
+    let v_norm = norm2((0..n).map(|j| w[(m + j, 0)]));
+    if u_norm > T::zero() {
+        for i in 0..m {
+            u[i] = w[(i, 0)] * phase / u_norm;
+        }
+    }
+    if v_norm > T::zero() {
+        for j in 0..n {
+            v[j] = w[(m + j, 0)] * phase / v_norm;
+        }
+    }
+
+    let av = (0..m).map(|i| (0..n).fold(zero, |acc, j| acc + a[(i, j)] * v[j]) - u[i] * sigma);
+    let au = (0..n).map(|j| (0..m).fold(zero, |acc, i| acc + a[(i, j)].conj() * u[i]) - v[j] * sigma);
+    Ok(norm2(av.chain(au)))
+}
+
+/// The best rank-k approximation of a matrix computed by `low_rank_approx`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LowRankApprox<T> {
+    /// m x n matrix of rank at most k
+    pub approx: Matrix<Complex<T>>,
+    /// the 2-norm error ||A - approx|| = sigma_{k+1}, zero if k = min(m, n)
+    pub error: T,
+}
+
+/// Computes the best rank-k approximation U_k S_k V_k* of a in the 2-norm and the Frobenius norm
+/// (Eckart-Young), e.g. for compressing or denoising a channel matrix.
+/// Only the leading k + 1 singular triplets are computed, see `svd_truncated`.
+pub fn low_rank_approx<T: Real>(a: &Matrix<Complex<T>>, k: usize) -> Result<LowRankApprox<T>, CsvdError> {
+    let min = a.rows().min(a.cols());
+
+    if k > min {
+        return Err(CsvdError::InvalidArgument { name: "k", value: k, max: min });
+    }
+
+    // one more singular value than kept gives the error bound
+    let mut res = svd_truncated(a, (k + 1).min(min))?;
+    let error = if k < min { res.s[k] } else { T::zero() };
+    for s in res.s.iter_mut().skip(k) {
+        *s = T::zero();
+    }
+
+    Ok(LowRankApprox { approx: res.reconstruct(), error })
+}
+
+/// Computes only the singular values of a, in descending order.
+/// U and V are not requested from csvd (NU = NV = 0), so none of the work of
+/// accumulating the rotations and back-transforming the Householder reflections is done.
+pub fn singular_values<T: Real>(a: &Matrix<Complex<T>>) -> Result<Vec<T>, CsvdError> {
+    let min = a.rows().min(a.cols());
+
+    // csvd overwrites its input
+    let mut work = a.clone();
+
+    let mut s: Vec<T> = Vec::with_capacity(min);
+    s.resize(min, T::zero());
+    let mut u = Matrix::zeros(0, 0);
+    let mut v = Matrix::zeros(0, 0);
+
+    csvd(&mut work, 0, 0, 0, &mut s, &mut u, &mut v)?;
+
+    Ok(s)
+}
+
+impl<T: Real> SvdResult<T> {
+    /// Number of rows of the decomposed matrix
+    pub fn rows(&self) -> usize {
+        self.u.rows()
+    }
+
+    /// Number of columns of the decomposed matrix
+    pub fn cols(&self) -> usize {
+        self.v.rows()
+    }
+
+    /// Multiplies the factors back together, A = U x S x V*, e.g. to verify a decomposition.
+    /// For a truncated decomposition with k columns of U and V, this is the rank-k
+    /// approximation of A, see `low_rank_approx`.
+    pub fn reconstruct(&self) -> Matrix<Complex<T>> {
+        let m = self.rows();
+        let n = self.cols();
+        let mut a = Matrix::zeros(m, n);
+
+        for i in 0..m {
+            for j in 0..n {
+                for k in 0..self.s.len() {
+                    a[(i, j)] = a[(i, j)] + self.u[(i, k)] * self.s[k] * self.v[(j, k)].conj();
+                }
+            }
+        }
+
+        a
+    }
+
+    /// Pseudo-inverse of the decomposed matrix, INV = V x S+ x U*
+    /// with the same cut-off for zero singular values as `find_pinv_from_svd`
+    pub fn pinv(&self) -> Matrix<Complex<T>> {
+        let mut s = self.s.clone();
+        let mut inv = Matrix::zeros(self.cols(), self.rows());
+
+        find_pinv_from_svd(&mut s, &self.u, &self.v, &mut inv);
+
+        inv
+    }
+
+    /// Same as `pinv`, with the cut-off for zero singular values given explicitly
+    pub fn pinv_with_options(&self, options: &PinvOptions<T>) -> Matrix<Complex<T>> {
+        let mut s = self.s.clone();
+        let mut inv = Matrix::zeros(self.cols(), self.rows());
+
+        find_pinv_from_svd_with_options(&mut s, &self.u, &self.v, &mut inv, options);
+
+        inv
+    }
+
+    /// Computes X = V x S+ x U* x B for the m x nrhs matrix b, one column at a time,
+    /// with the singular values up to the cut-off of options treated as zero
+    pub fn solve(&self, b: &Matrix<Complex<T>>, options: &PinvOptions<T>) -> Result<Matrix<Complex<T>>, CsvdError> {
+        let m = self.rows();
+        let n = self.cols();
+        let nrhs = b.cols();
+        let zero = Complex::new(T::zero(), T::zero());
+
+        if b.rows() != m {
+            return Err(CsvdError::DimensionMismatch { operand: "b", expected: (m, nrhs), found: (b.rows(), nrhs) });
+        }
+
+        let rank = self.rank(options.cutoff(self.s.first().map_or(T::zero(), |&s0| s0), m, n));
+
+        let mut x = Matrix::zeros(n, nrhs);
+        let mut c: Vec<Complex<T>> = Vec::with_capacity(rank);
+        for j in 0..nrhs {
+            // c = S+ U* b, restricted to the first rank singular vectors
+            c.clear();
+            for k in 0..rank {
+                let mut sum = zero;
+                for i in 0..m {
+                    sum = sum + self.u[(i, k)].conj() * b[(i, j)];
+                }
+                c.push(sum / self.s[k]);
+            }
+
+            // x = V c
+            for i in 0..n {
+                let mut sum = zero;
+                for k in 0..rank {
+                    sum = sum + self.v[(i, k)] * c[k];
+                }
+                x[(i, j)] = sum;
+            }
+        }
+
+        Ok(x)
+    }
+
+    /// Tikhonov-regularized pseudo-inverse INV = V x (S^2 + lambda I)^-1 x S x U*,
+    /// the MMSE (ridge) equalizer for noise variance lambda.
+    /// Each singular value s is replaced by s / (s^2 + lambda), so no cut-off is needed
+    /// for lambda > 0, and lambda = 0 gives the plain pseudo-inverse.
+    pub fn pinv_regularized(&self, lambda: T) -> Matrix<Complex<T>> {
+        let m = self.rows();
+        let n = self.cols();
+        let zero = T::zero();
+
+        let s_reg: Vec<T> = self.s.iter().map(|&s| {
+            let d = s * s + lambda;
+            if d > zero { s / d } else { zero }
+        }).collect();
+
+        let mut inv = Matrix::zeros(n, m);
+        for i in 0..n {
+            for j in 0..m {
+                for k in 0..s_reg.len() {
+                    inv[(i, j)] = inv[(i, j)] + self.v[(i, k)] * s_reg[k] * self.u[(j, k)].conj();
+                }
+            }
+        }
+
+        inv
+    }
+
+    /// Number of singular values larger than tol
+    pub fn rank(&self, tol: T) -> usize {
+        self.s.iter().filter(|&&s| s > tol).count()
+    }
+
+    /// Numerical rank from the largest relative gap in the singular values, see `rank_by_gap`
+    pub fn rank_by_gap(&self, min_gap: T) -> GapRank<T> {
+        gap_rank(&self.s, self.rows(), self.cols(), min_gap)
+    }
+
+    /// 2-norm condition number sigma_max / sigma_min of the decomposed matrix,
+    /// infinite if the smallest singular value is zero, NaN if there are no singular
+    /// values, as for `svd_truncated` or `partial_svd` with k = 0
+    pub fn cond(&self) -> T {
+        let s_min = match self.s.last() {
+            Some(&s_min) => s_min,
+            None => return T::nan(),
+        };
+
+        if s_min == T::zero() {
+            T::infinity()
+        }
+        else {
+            self.s[0] / s_min
+        }
+    }
+
+    /// Restores orthonormal columns of U and V, which the accumulated reflections and
+    /// rotations lose in single precision for large or ill-conditioned matrices, with
+    /// `orthonormalize`. The columns move by about as much as they were off, so U S V*
+    /// stays as close to A as before. Opt-in, as the O(m^3 + n^3) work is about that of
+    /// the decomposition itself.
+    pub fn reorthogonalize(&mut self) -> Reorthogonalization<T> {
+        let (u_before, v_before) = (orthogonality(&self.u), orthogonality(&self.v));
+        reorthonormalize(&mut self.u);
+        reorthonormalize(&mut self.v);
+        let report = Reorthogonalization { u_before, u_after: orthogonality(&self.u), v_before, v_after: orthogonality(&self.v) };
+
+        debug!(target: "lin_alg::csvd", "reorthogonalization of U from {:?} to {:?}, of V from {:?} to {:?}",
+               report.u_before, report.u_after, report.v_before, report.v_after);
+        report
+    }
+
+    /// Refines the singular vectors of the k-th singular value, column k of U and V, with
+    /// `refine_singular_vectors` and returns their residual. a is the decomposed matrix.
+    pub fn refine(&mut self, a: &Matrix<Complex<T>>, k: usize, iterations: usize) -> Result<T, CsvdError> {
+        let m = self.rows();
+        let n = self.cols();
+
+        if a.rows() != m || a.cols() != n {
+            return Err(CsvdError::DimensionMismatch { operand: "a", expected: (m, n), found: (a.rows(), a.cols()) });
+        }
+        if k >= self.s.len() {
+            return Err(CsvdError::InvalidArgument { name: "k", value: k, max: self.s.len().saturating_sub(1) });
+        }
+
+        let mut u: Vec<Complex<T>> = (0..m).map(|i| self.u[(i, k)]).collect();
+        let mut v: Vec<Complex<T>> = (0..n).map(|j| self.v[(j, k)]).collect();
+        let residual = refine_singular_vectors(a, self.s[k], &mut u, &mut v, iterations)?;
+        for i in 0..m {
+            self.u[(i, k)] = u[i];
+        }
+        for j in 0..n {
+            self.v[(j, k)] = v[j];
+        }
+
+        Ok(residual)
+    }
+
+    /// The SNR sigma_i^2 snr of every eigenmode of the decomposed channel matrix when each
+    /// stream is sent along its singular vectors with the linear SNR snr, see
+    /// `eigen_beamforming`, strongest stream first
+    pub fn stream_snrs(&self, snr: T) -> Vec<T> {
+        self.s.iter().map(|&s| s * s * snr).collect()
+    }
+
+    /// Shannon capacity sum log2(1 + sigma_i^2 snr) in bit/s/Hz of the decomposed channel
+    /// matrix with the linear SNR snr on every one of its min(m, n) eigenmodes, i.e. with
+    /// equal power and no water-filling
+    pub fn capacity(&self, snr: T) -> T {
+        self.stream_snrs(snr).iter().fold(T::zero(), |acc, &g| acc + g.ln_1p()) / T::LN_2()
+    }
+
+    /// The effective SNR 2^(C / r) - 1 of the capacity C spread evenly over the r = min(m, n)
+    /// eigenmodes, the SNR of r equal streams with the same total rate, e.g. for choosing
+    /// one modulation and coding scheme for all streams. Zero for an empty decomposition.
+    pub fn effective_snr(&self, snr: T) -> T {
+        let r = self.s.len();
+        if r == 0 {
+            return T::zero();
+        }
+        // the geometric mean of 1 + sigma_i^2 snr, minus one
+        (self.stream_snrs(snr).iter().fold(T::zero(), |acc, &g| acc + g.ln_1p()) / T::from(r).unwrap()).exp_m1()
+    }
+}
+
+/// Orthonormalizes the columns of q in place, see `orthonormalize`
+fn reorthonormalize<T: Real>(q: &mut Matrix<Complex<T>>) {
+    let (m, k, ld) = (q.rows(), q.cols(), q.ld());
+    let (row_stride, col_stride) = match q.layout() {
+        Layout::RowMajor => (ld, 1),
+        Layout::ColMajor => (1, ld),
+    };
+    gram_schmidt(q.as_mut_slice(), m, k, row_stride, col_stride);
+}
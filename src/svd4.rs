@@ -0,0 +1,189 @@
+//! An unrolled singular value decomposition of 4 x 4 matrices.
+//!
+//! The per-subcarrier channel matrices of 4 x 4 MIMO receivers are small enough that the
+//! Householder bidiagonalization and the QR sweeps of `csvd` spend most of their time on
+//! loop control and index arithmetic. This kernel runs one-sided Jacobi sweeps, see
+//! Demmel and Veselic, "Jacobi's method is more accurate than QR", on fixed-size arrays
+//! whose loop bounds are all constants, so the compiler unrolls them and keeps the
+//! columns in registers. Every 4 x 4 decomposition of `csvd_in_slices`, and with it of
+//! `svd_fixed` and `BatchedSvd`, takes this path.
+
+use num_complex::Complex;
+use super::csvd::{cabs, normalize_phases};
+use super::error::CsvdError;
+use super::fixed::FixedSvd;
+use super::scalar::Real;
+use super::PinvOptions;
+
+/// The column pairs of one sweep, in an order that touches every pair once
+const PAIRS: [(usize, usize); 6] = [(0, 1), (2, 3), (0, 2), (1, 3), (0, 3), (1, 2)];
+
+/// Sweeps after which the kernel gives up, a handful suffice as the convergence is quadratic
+const MAX_SWEEPS: usize = 30;
+
+/// Computes the singular value decomposition of a 4 x 4 matrix with the unrolled one-sided
+/// Jacobi kernel, which gives the small singular values to high relative accuracy and
+/// follows the phase convention of `csvd`. a is left untouched.
+pub fn svd4<T: Real>(a: &[[Complex<T>; 4]; 4]) -> Result<FixedSvd<T, 4, 4>, CsvdError> {
+    let zero = Complex::new(T::zero(), T::zero());
+    let mut res = FixedSvd { u: [[zero; 4]; 4], s: [T::zero(); 4], v: [[zero; 4]; 4] };
+
+    svd4_in_slices(a.as_flattened(), &mut res.s, res.u.as_flattened_mut(), res.v.as_flattened_mut())?;
+    Ok(res)
+}
+
+/// The pseudo-inverse V S+ U* of a 4 x 4 matrix from `svd4`, with the singular values up to
+/// the cut-off of options treated as zero, e.g. the zero-forcing weights of one subcarrier
+/// of a 4 x 4 MIMO channel, without touching the heap. a is left untouched.
+pub fn pinv4<T: Real>(a: &[[Complex<T>; 4]; 4], options: &PinvOptions<T>) -> Result<[[Complex<T>; 4]; 4], CsvdError> {
+    let res = svd4(a)?;
+    let cutoff = options.cutoff(res.s[0], 4, 4);
+    let mut inv = [[Complex::new(T::zero(), T::zero()); 4]; 4];
+
+    for k in 0..4 {
+        if res.s[k] > cutoff {
+            let s_inv = T::one() / res.s[k];
+            for i in 0..4 {
+                for j in 0..4 {
+                    inv[i][j] = inv[i][j] + res.v[i][k] * s_inv * res.u[j][k].conj();
+                }
+            }
+        }
+    }
+
+    Ok(inv)
+}
+
+/// `svd4` on the tightly packed, row-major 4 x 4 matrices a, u and v, for `csvd_in_slices`
+pub(crate) fn svd4_in_slices<T: Real>(a: &[Complex<T>], s: &mut [T], u: &mut [Complex<T>], v: &mut [Complex<T>])
+        -> Result<(), CsvdError> {
+    let zero = Complex::new(T::zero(), T::zero());
+    let one = Complex::new(T::one(), T::zero());
+
+    // w holds the columns of A V, which the sweeps make orthogonal, w[j] is column j
+    let mut w = [[zero; 4]; 4];
+    let mut amax = T::zero();
+    for i in 0..4 {
+        for j in 0..4 {
+            let x = a[i * 4 + j];
+            if !(x.re.is_finite() && x.im.is_finite()) {
+                return Err(CsvdError::NonFiniteInput { row: i, col: j });
+            }
+            amax = amax.max(x.re.abs()).max(x.im.abs());
+            w[j][i] = x;
+        }
+    }
+    // scaled to entries of at most one, the column norms neither overflow nor underflow
+    if amax > T::zero() {
+        for col in w.iter_mut() {
+            for x in col.iter_mut() {
+                *x = *x / amax;
+            }
+        }
+    }
+
+    // rows of vt are the columns of V
+    let mut vt = [[zero; 4]; 4];
+    for j in 0..4 {
+        vt[j][j] = one;
+    }
+
+    let mut converged = false;
+    for _ in 0..MAX_SWEEPS {
+        let mut rotated = false;
+        for &(p, q) in &PAIRS {
+            let (mut alpha, mut beta, mut gamma) = (T::zero(), T::zero(), zero);
+            for i in 0..4 {
+                alpha = alpha + w[p][i].norm_sqr();
+                beta = beta + w[q][i].norm_sqr();
+                gamma = gamma + w[p][i].conj() * w[q][i];
+            }
+            let g = cabs(&gamma);
+            if g <= T::epsilon() * (alpha * beta).sqrt() {
+                continue;
+            }
+            rotated = true;
+
+            // the real rotation of column p against column q times conj(gamma) / |gamma|
+            // makes them orthogonal
+            let phase = gamma.conj() / g;
+            let zeta = (beta - alpha) / (g + g);
+            let t = T::one().copysign(zeta) / (zeta.abs() + (T::one() + zeta * zeta).sqrt());
+            let c = T::one() / (T::one() + t * t).sqrt();
+            let sn = c * t;
+            for i in 0..4 {
+                let (x, y) = (w[p][i], w[q][i] * phase);
+                w[p][i] = x * c - y * sn;
+                w[q][i] = x * sn + y * c;
+                let (x, y) = (vt[p][i], vt[q][i] * phase);
+                vt[p][i] = x * c - y * sn;
+                vt[q][i] = x * sn + y * c;
+            }
+        }
+        if !rotated {
+            converged = true;
+            break;
+        }
+    }
+    if !converged {
+        debug!("4 x 4 Jacobi sweeps did not converge");
+        return Err(CsvdError::NotConverged { index: 0, iterations: MAX_SWEEPS });
+    }
+
+    // the singular values are the column norms, in descending order
+    let mut norms = [T::zero(); 4];
+    for j in 0..4 {
+        norms[j] = w[j].iter().fold(T::zero(), |acc, x| acc + x.norm_sqr()).sqrt();
+    }
+    let mut order = [0, 1, 2, 3];
+    order.sort_by(|&i, &j| norms[j].partial_cmp(&norms[i]).unwrap_or(core::cmp::Ordering::Equal));
+
+    let mut ut = [[zero; 4]; 4];
+    for (k, &j) in order.iter().enumerate() {
+        s[k] = norms[j] * amax;
+        for i in 0..4 {
+            v[i * 4 + k] = vt[j][i];
+        }
+        if norms[j] > T::zero() {
+            for i in 0..4 {
+                ut[k][i] = w[j][i] / norms[j];
+            }
+        }
+    }
+
+    // the left singular vectors of zero singular values complete U to a unitary matrix,
+    // each from the unit vector with the largest part orthogonal to the columns so far
+    for k in 0..4 {
+        if s[k] > T::zero() {
+            continue;
+        }
+        let mut best = ([zero; 4], T::zero());
+        for e in 0..4 {
+            let mut x = [zero; 4];
+            x[e] = one;
+            for _ in 0..2 {
+                for l in (0..4).filter(|&l| l != k && (s[l] > T::zero() || l < k)) {
+                    let p = (0..4).fold(zero, |acc, i| acc + ut[l][i].conj() * x[i]);
+                    for i in 0..4 {
+                        x[i] = x[i] - ut[l][i] * p;
+                    }
+                }
+            }
+            let norm = x.iter().fold(T::zero(), |acc, x| acc + x.norm_sqr()).sqrt();
+            if norm > best.1 {
+                best = (x, norm);
+            }
+        }
+        for i in 0..4 {
+            ut[k][i] = best.0[i] / best.1;
+        }
+    }
+    for k in 0..4 {
+        for i in 0..4 {
+            u[i * 4 + k] = ut[k][i];
+        }
+    }
+
+    normalize_phases(u, 4, 4, 4, v, 4, 4, 4);
+    Ok(())
+}
@@ -1,120 +1,67 @@
-use num_complex::Complex32;
-use libm::F32Ext;
+use num_complex::{Complex, Complex32, Complex64};
+use num_traits::Float;
 use alloc::vec::Vec;
-// use rand::Rng;
+use core::ops::ControlFlow;
 
-use super::csvd::csvd;
+use super::csvd::{cabs, csvd, norm2};
+use super::scalar::Real;
 // use super::pinv;
 use super::*;
 
-// fn print_matrix(mat: &Vec<Complex32>, rows: usize, cols: usize) {
-//     for i in 0..rows {
-//         for j in 0..cols{
-//             print!("{} + {}i, ", mat[i*rows + j].re, mat[i*rows + j].im);
-//         }
-//         println!("");
-//     }
-//     println!("");
-// }
-
-// fn print_vector(mat: &Vec<f32>, rows: usize) {
-//     for i in 0..rows {
-//         println!("{}", mat[i]);
-//     }
-//     println!("");
-// }
-
 /// Finds the original matrix from the singular value decompositions
 /// A = U x S x V*
 /// stores the new matrix in a
-fn find_orig_matrix_from_svd(mut a: &mut Vec<Complex32>, m: usize, n: usize) {
-    //create S vector with dimension n
-    let mut s: Vec<f32> = Vec::new(); //with_capacity(n);
-    let _ = aligned_alloc_f32_32(n, &mut s);
-    // for _ in 0..n {
-    //     s.push(0.0);
-    // }
+fn find_orig_matrix_from_svd<T: Real>(a: &mut Matrix<Complex<T>>) {
+    let m = a.rows();
+    let n = a.cols();
+
+    //create S vector with dimension min(m, n)
+    let mut s: Vec<T> = Vec::with_capacity(m.min(n));
+    for _ in 0..m.min(n) {
+        s.push(T::zero());
+    }
 
     //create U matrix dimension mxm
-    let mut u: Vec<Complex32> = Vec::new(); //with_capacity(m*m);
-    let _ = aligned_alloc(32, m*m, &mut u);
-    // for _ in 0..m*m {
-    //     u.push(Complex32{re: 0.0, im: 0.0});
-    // }
+    let mut u = Matrix::zeros(m, m);
 
     //create v matrix with dimension nxn
-    let mut v: Vec<Complex32> = Vec::new(); //with_capacity(n*n);
-    let _ = aligned_alloc(32, n*n, &mut v);
-    // for _ in 0..n*n {
-    //     v.push(Complex32{re: 0.0, im: 0.0});
-    // }
+    let mut v = Matrix::zeros(n, n);
 
-    let _ = csvd(&mut a, m, n, n, m, 0, m, n, &mut s, &mut u, &mut v);
+    let _ = csvd(a, 0, m, n, &mut s, &mut u, &mut v);
 
-    let min = m.min(n);
-    for i in 0..m {
-        for j in 0..n {
-            a[i*m + j].re = 0.0;
-            a[i*m + j].im = 0.0;
-            for k in 0..min {
-                a[i*m + j] = a[i*m + j] + u[i*m + k] * s[k] * v[j*n + k].conj();
-            }
-        }
-    }
+    *a = SvdResult { u, s, v }.reconstruct();
 }
+
 /// Verifies pinv function
-/// Checks if A*Ainv*A = A where A is a mxn matrix
-fn check_pinv(mut a: &mut Vec<Complex32>, m: usize, n: usize) -> bool {  
+/// Checks the four Moore-Penrose conditions, e.g. A*Ainv*A = A where A is a mxn matrix
+fn check_pinv<T: Real>(a: &mut Matrix<Complex<T>>) -> bool {  
+    let m = a.rows();
+    let n = a.cols();
     let a_orig = a.clone();
 
     //create inverse matrix with dimension nxm
-    let mut inv: Vec<Complex32> = Vec::new();
-    aligned_alloc_32(n*m, &mut inv);
-    // for _ in 0..n*m {
-    //     inv.push(Complex32{re: 0.0, im: 0.0});
-    // }
-
-    let _ = pinv(&mut a, &mut inv, m, n);
-    
-    //create I matrix dimension mxm
-    let mut I: Vec<Complex32> = Vec::with_capacity(m*m);
-    for _ in 0..m*m {
-        I.push(Complex32{re: 0.0, im: 0.0});
-    }
-
-    // I = A x Ainv
-    for i in 0..m {
-        for j in 0..m {
-            for k in 0..n{
-                I[i*m + j] = I[i*m + j] + (a_orig[i*m + k] * inv[k*n + j]); 
-            }
-        }
-    }
+    let mut inv = Matrix::zeros(n, m);
 
-    // I x A
-    for i in 0..m {
-        for j in 0..n {
-            a[i*m + j].re = 0.0;
-            a[i*m + j].im = 0.0;
-            for k in 0..m{
-                a[i*m + j] = a[i*m + j] + (I[i*m +k] * a_orig[k*n + j]); 
-            }
-        }
-    }
+    let _ = pinv(a, &mut inv);
 
-    check_matrix_equality(&a_orig, &a, m, n)
+    // relative to the size of A, the entries of the products scale with it
+    let tol = T::from(0.0001).unwrap() * T::from(m.max(n)).unwrap();
+    verify_pinv(&a_orig, &inv, tol).is_ok_and(|report| report.passed())
 
 }
 
 /// checks that 2 complex matrices are equal by taking the square of the euclidean distance between the elements
-fn check_matrix_equality(a: &Vec<Complex32>, b: &Vec<Complex32>, m: usize, n:usize) -> bool {
+// the negated comparisons make NaN entries compare unequal
+#[allow(clippy::neg_cmp_op_on_partial_ord)]
+fn check_matrix_equality<T: Real>(a: &Matrix<Complex<T>>, b: &Matrix<Complex<T>>) -> bool {
     let mut equal = true;
 
-    let eps = 0.0001;
+    let eps = T::from(0.0001).unwrap();
 
-    for i in 0..m {
-        for j in 0..n {
-            if F32Ext::powf(a[i*m + j].re - b[i*m + j].re, 2.0) + F32Ext::powf(a[i*m + j].im - b[i*m + j].im, 2.0) > eps {
+    for i in 0..a.rows() {
+        for j in 0..a.cols() {
+            // written so that NaN entries compare unequal
+            if !((a[(i, j)] - b[(i, j)]).norm_sqr() <= eps) {
                 equal = false;
             }
         }
@@ -126,14 +73,14 @@ fn check_matrix_equality(a: &Vec<Complex32>, b: &Vec<Complex32>, m: usize, n:usi
 /// Checks the correctness of svd function in 2 ways
 /// 1. multiplies decomposed matrices together to see if equal to original matrix 
 /// 2. finds inverse of matrix using svd and then verifies the correctness of the inverse
-/// a has dimensions m x n
-fn check_svd(mut a: &mut Vec<Complex32>, m: usize, n: usize) {
+fn check_svd<T: Real>(a: &mut Matrix<Complex<T>>) -> bool {
     
     let a_orig  = a.clone(); 
 
-    find_orig_matrix_from_svd(&mut a, m, n);
+    find_orig_matrix_from_svd(a);
 
-    if check_matrix_equality(&a_orig, &a, m, n){
+    let reconstructed = check_matrix_equality(&a_orig, a);
+    if reconstructed {
         debug!("svd successful");
     }
     else {
@@ -142,33 +89,4227 @@ fn check_svd(mut a: &mut Vec<Complex32>, m: usize, n: usize) {
 
     *a = a_orig.clone();
 
-    if check_pinv(&mut a, m, n) {
+    let inverted = check_pinv(a);
+    if inverted {
         debug!("pseudo-inverse successful");
     }
 
     else {
         debug!("pseudo-inverse failed");
     }
-    
+
+    reconstructed && inverted
 }
 
-/// A basic example to test with: https://math.stackexchange.com/questions/647321/moore-penrose-inverse-of-complex-square-matrices
-pub fn test() {
+/// Checks the owned SvdResult returned by svd, a is left untouched
+fn check_svd_result<T: Real>(a: &Matrix<Complex<T>>) -> bool {
+    let res = match svd(a) {
+        Ok(res) => res,
+        Err(e) => {
+            debug!("svd failed: {}", e);
+            return false;
+        }
+    };
 
-    let m = 8;
-    let n = 8;
+    let mut equal = verify_svd(a, &res, T::from(0.0001).unwrap()).is_ok_and(|report| report.passed());
+    if equal {
+        debug!("svd result reconstruction successful");
+    }
+    else {
+        debug!("svd result reconstruction failed");
+    }
 
-    let mut a: Vec<Complex32> = Vec::new();
-    aligned_alloc_32(m*n, &mut a);
-    a.clear();
+    // a corrupted decomposition must be reported
+    let mut corrupted = res.clone();
+    corrupted.s.reverse();
+    if verify_svd(a, &corrupted, T::from(0.0001).unwrap()).map_or(true, |report| report.passed() || report.sorted) {
+        debug!("svd report failed");
+        equal = false;
+    }
 
-    // let mut rng = rand::thread_rng();
+    // the k largest terms of the reconstruction are the best rank-k approximation, and
+    // reconstructing the exact factors of a diagonal matrix gives it back exactly
+    let k = res.s.len() / 2;
+    let approx = svd_truncated(a, k).map(|t| t.reconstruct());
+    if !approx.is_ok_and(|x| low_rank_approx(a, k).is_ok_and(|l| x.rows() == a.rows() && x.cols() == a.cols() && check_matrix_equality(&l.approx, &x))) {
+        debug!("svd result truncated reconstruction failed");
+        equal = false;
+    }
+    let d = [T::from(3.0).unwrap(), T::one(), T::from(0.5).unwrap()];
+    let exact = SvdResult { u: Matrix::identity(3), s: d.to_vec(), v: Matrix::identity(4) };
+    if exact.reconstruct() != Matrix::from_diag_rect(&d.map(|x| Complex::new(x, T::zero())), 3, 4).unwrap() {
+        debug!("svd result exact reconstruction failed");
+        equal = false;
+    }
 
-    for _ in 0..m*n {
-        // a.push(Complex32{re: rng.gen(), im: rng.gen()})
-        a.push(Complex32{re: 2.1, im: 1.3});
+    // the singular values only fast path must agree with the full decomposition
+    let eps = T::from(0.0001).unwrap();
+    match singular_values(a) {
+        Ok(s) if s.iter().zip(res.s.iter()).all(|(x, y)| (*x - *y).abs() < eps) => debug!("singular values successful"),
+        _ => {
+            debug!("singular values failed");
+            equal = false;
+        }
     }
-    
-    check_svd(&mut a, m, n) ;
-  
-}
\ No newline at end of file
+
+    equal
+}
+
+/// Checks that compensated summation recovers what plain f32 sums lose: a term absorbed
+/// by a much larger one, and digits of the singular values of a matrix with long columns
+fn check_compensated_summation() -> bool {
+    let mut equal = true;
+
+    // 1e8 + 1 rounds to 1e8 in f32
+    let x = [Complex32::new(1.0, 0.0); 3];
+    let y = [Complex32::new(1e8, 0.0), Complex32::new(1.0, 0.0), Complex32::new(-1e8, 0.0)];
+    equal &= super::kernels::dot_conj_compensated(x.iter().cloned().zip(y.iter().cloned())) == Complex32::new(1.0, 0.0);
+
+    let (m, n) = (5000, 8);
+    let a = lcg_matrix::<f32>(m, n, 89);
+    let reference = singular_values(&Matrix::from_vec(a.as_slice().iter().map(|x| Complex64::new(x.re as f64, x.im as f64)).collect(), m, n).unwrap()).unwrap();
+    let mut errors = [0.0f64; 2];
+    for (error, summation) in errors.iter_mut().zip([Summation::Plain, Summation::Compensated]) {
+        let mut work = a.clone();
+        let mut s = alloc::vec![0.0f32; n];
+        let options = SvdOptions { summation, ..SvdOptions::default() };
+        if super::csvd::csvd_with_options(&mut work, 0, 0, 0, &mut s, &mut Matrix::zeros(0, 0), &mut Matrix::zeros(0, 0), &options).is_err() {
+            return false;
+        }
+        *error = s.iter().zip(&reference).fold(0.0, |acc, (&x, &y)| acc.max((x as f64 - y).abs() / reference[0]));
+    }
+    equal &= errors[1] < errors[0];
+
+    // the factors are as valid as with plain sums
+    let a = lcg_matrix::<f32>(9, 6, 97);
+    let options = SvdOptions { summation: Summation::Compensated, ..SvdOptions::default() };
+    equal &= svd_with_options(&a, &options).is_ok_and(|res| check_matrix_equality(&res.reconstruct(), &a));
+
+    if equal {
+        debug!("compensated summation successful");
+    }
+    else {
+        debug!("compensated summation failed");
+    }
+    equal
+}
+
+/// Checks that NaN and infinite entries are reported at their position in A on every path
+/// into the decomposition, and that the unchecked variants skip the scan
+fn check_non_finite() -> bool {
+    let mut equal = true;
+
+    let mut tall = lcg_matrix::<f64>(5, 3, 101);
+    tall[(2, 1)] = Complex64::new(0.5, f64::NAN);
+    let mut wide = lcg_matrix::<f64>(3, 5, 103);
+    wide[(1, 4)] = Complex64::new(f64::INFINITY, 0.0);
+
+    for (a, row, col) in [(&tall, 2, 1), (&wide, 1, 4)] {
+        let expected = Err(CsvdError::NonFiniteInput { row, col });
+        let (m, n) = (a.rows(), a.cols());
+        equal &= svd(a).map(|_| ()) == expected;
+        equal &= svd(&a.to_layout(Layout::ColMajor)).map(|_| ()) == expected;
+
+        let mut work = a.as_slice().to_vec();
+        let (mut s, mut u, mut v, mut scratch) = (alloc::vec![0.0; m.min(n)], alloc::vec![Complex64::new(0.0, 0.0); m * m],
+            alloc::vec![Complex64::new(0.0, 0.0); n * n], alloc::vec![0.0; 3 * m.min(n)]);
+        equal &= csvd_in_slices(&mut work, &mut s, &mut u, &mut v, &mut scratch, m, n) == expected;
+
+        let mut ws = SvdWorkspace::new(m, n);
+        equal &= csvd_with_workspace(a, &mut ws) == expected;
+
+        // without the scan the decomposition runs, but cannot report where the problem is
+        equal &= !matches!(svd_unchecked(a), Err(CsvdError::NonFiniteInput { .. }));
+    }
+
+    let mut real = alloc::vec![1.0f32; 12];
+    real[7] = f32::NEG_INFINITY;
+    let (mut s, mut u, mut v, mut scratch) = ([0.0; 3], [0.0; 16], [0.0; 9], [0.0; 9]);
+    equal &= svd_real(&mut real, &mut s, &mut u, &mut v, &mut scratch, 4, 3) == Err(CsvdError::NonFiniteInput { row: 2, col: 1 });
+
+    if equal {
+        debug!("non-finite input successful");
+    }
+    else {
+        debug!("non-finite input failed");
+    }
+    equal
+}
+
+/// Whether the first entry above eps of every column of u is real and positive
+fn follows_phase_convention<T: Real>(u: &Matrix<Complex<T>>) -> bool {
+    let eps = T::from(0.0001).unwrap();
+    (0..u.cols()).all(|k| {
+        (0..u.rows()).map(|i| u[(i, k)]).find(|x| x.norm_sqr().sqrt() > T::epsilon())
+            .is_none_or(|x| x.re > T::zero() && x.im.abs() < eps)
+    })
+}
+
+/// Checks the phase convention on every path into the decomposition, that repeated runs
+/// agree bitwise, and that paths through different algorithms give the same factors
+fn check_phase_convention() -> bool {
+    let mut equal = true;
+
+    for &(m, n) in &[(7, 4), (4, 7), (5, 5)] {
+        let a = lcg_matrix::<f64>(m, n, 107);
+        let res = svd(&a).unwrap();
+        equal &= follows_phase_convention(&res.u) && check_matrix_equality(&res.reconstruct(), &a);
+        equal &= svd(&a).unwrap() == res;
+
+        let col_major = svd(&a.to_layout(Layout::ColMajor)).unwrap();
+        equal &= check_matrix_equality(&col_major.u, &res.u) && check_matrix_equality(&col_major.v, &res.v);
+
+        let mut work = a.as_slice().to_vec();
+        let (mut s, mut u, mut v, mut scratch) = (alloc::vec![0.0; m.min(n)], alloc::vec![Complex64::new(0.0, 0.0); m * m],
+            alloc::vec![Complex64::new(0.0, 0.0); n * n], alloc::vec![0.0; 3 * m.min(n)]);
+        equal &= csvd_in_slices(&mut work, &mut s, &mut u, &mut v, &mut scratch, m, n).is_ok();
+        equal &= check_matrix_equality(&Matrix::from_vec(u, m, m).unwrap(), &res.u);
+        equal &= check_matrix_equality(&Matrix::from_vec(v, n, n).unwrap(), &res.v);
+
+        let mut ws = SvdWorkspace::new(m, n);
+        equal &= csvd_with_workspace(&a, &mut ws).is_ok();
+        equal &= check_matrix_equality(&ws.u().to_matrix(), &res.u);
+
+        // a real matrix through svd_real
+        let real = Matrix::from_vec(a.as_slice().iter().map(|x| Complex64::new(x.re, 0.0)).collect(), m, n).unwrap();
+        let auto = svd_auto(&real).unwrap();
+        let reference = svd(&real).unwrap();
+        equal &= auto.structure == Structure::Real && check_matrix_equality(&auto.svd.u, &reference.u);
+        equal &= check_matrix_equality(&auto.svd.v, &reference.v);
+    }
+
+    // a Hermitian matrix through eigh, whose eigenvalues have distinct magnitudes
+    let square = lcg_matrix::<f64>(5, 5, 109);
+    let hermitian = square.add(&square.conj_transpose()).unwrap();
+    let auto = svd_auto(&hermitian).unwrap();
+    let reference = svd(&hermitian).unwrap();
+    equal &= auto.structure == Structure::Hermitian && follows_phase_convention(&auto.svd.u);
+    equal &= check_matrix_equality(&auto.svd.u, &reference.u) && check_matrix_equality(&auto.svd.v, &reference.v);
+
+    // the extra columns B of A come back as U* B for the normalized U, also for the U
+    // that is not computed, whose columns are A V / S
+    let (m, n, p) = (7, 4, 2);
+    let a = lcg_matrix::<f64>(m, n, 113);
+    let b = lcg_matrix::<f64>(m, p, 127);
+    let ab = Matrix::from_vec((0..m).flat_map(|i| a.row(i).iter().chain(b.row(i)).cloned()).collect(), m, n + p).unwrap();
+    for (nu, layout) in [(m, Layout::RowMajor), (0, Layout::RowMajor), (m, Layout::ColMajor)] {
+        let mut work = ab.to_layout(layout);
+        let (mut s, mut u, mut v) = (alloc::vec![0.0; n], Matrix::zeros(m, m), Matrix::zeros(n, n));
+        equal &= csvd(&mut work, p, nu, n, &mut s, &mut u, &mut v).is_ok();
+        if nu == 0 {
+            let mut av = Matrix::zeros(m, n);
+            equal &= matrix_mult(&a, &v, &mut av).is_ok();
+            (0..m).for_each(|i| (0..n).for_each(|k| u[(i, k)] = av[(i, k)] / s[k]));
+        }
+        for k in 0..n {
+            for j in 0..p {
+                let expected = (0..m).fold(Complex64::new(0.0, 0.0), |acc, i| acc + u[(i, k)].conj() * b[(i, j)]);
+                equal &= (work[(k, n + j)] - expected).norm_sqr() < 1e-20;
+            }
+        }
+    }
+
+    if equal {
+        debug!("phase convention successful");
+    }
+    else {
+        debug!("phase convention failed");
+    }
+    equal
+}
+
+/// Checks the statistics of the QR diagonalization: the sweeps of a general matrix, none
+/// for a diagonal one, a split for a block diagonal one, the tolerance in the units of A
+/// and the residual
+fn check_svd_stats() -> bool {
+    let mut equal = true;
+    let options = SvdOptions::default();
+
+    let a = lcg_matrix::<f64>(12, 5, 3);
+    let (res, stats) = svd_with_stats(&a, &options).unwrap();
+    equal &= res == svd(&a).unwrap();
+    equal &= 0 < stats.sweeps && stats.max_sweeps <= stats.sweeps && stats.max_sweeps <= options.max_iterations;
+    equal &= stats.residual.is_some_and(|r| r < 1e-12);
+
+    // the tolerance scales with A
+    let mut scaled = a.clone();
+    scaled.scale(Complex64::new(1e3, 0.0));
+    let (_, scaled_stats) = svd_with_stats(&scaled, &options).unwrap();
+    equal &= (scaled_stats.tolerance / stats.tolerance - 1e3).abs() < 1e-6;
+
+    let (_, stats) = svd_with_stats(&Matrix::from_diag(&[3.0, 1.0, 2.0]), &options).unwrap();
+    equal &= stats.sweeps == 0 && stats.residual == Some(0.0);
+
+    let block = lcg_matrix::<f64>(3, 3, 9);
+    let mut a = Matrix::zeros(6, 6);
+    for i in 0..3 {
+        for j in 0..3 {
+            a[(i, j)] = block[(i, j)];
+            a[(i + 3, j + 3)] = block[(j, i)];
+        }
+    }
+    let mut work = a.clone();
+    let (mut s, mut u, mut v) = (alloc::vec![0.0; 6], Matrix::zeros(6, 6), Matrix::zeros(6, 6));
+    equal &= csvd_with_stats(&mut work, 0, 6, 6, &mut s, &mut u, &mut v, &options).is_ok_and(|stats| stats.splits > 0 && stats.residual.is_none());
+
+    if equal {
+        debug!("svd stats successful");
+    }
+    else {
+        debug!("svd stats failed");
+    }
+    equal
+}
+
+/// Checks that the reorthogonalization restores orthonormal singular vectors after they
+/// were perturbed and keeps a single precision decomposition accurate
+fn check_reorthogonalize() -> bool {
+    let mut equal = true;
+
+    let a = lcg_matrix::<f32>(40, 30, 17);
+    let mut res = svd(&a).unwrap();
+    let report = res.reorthogonalize();
+    equal &= report.u_after <= report.u_before.max(1e-5) && report.v_after <= report.v_before.max(1e-5);
+    equal &= verify_svd(&a, &res, 1e-4).is_ok_and(|r| r.passed());
+
+    // columns tilted towards their neighbours
+    let mut res = svd(&a).unwrap();
+    for i in 0..40 {
+        let x = res.u[(i, 0)];
+        res.u[(i, 1)] += x * 1e-3;
+    }
+    for i in 0..30 {
+        let x = res.v[(i, 3)];
+        res.v[(i, 2)] += x * 1e-3;
+    }
+    let report = res.reorthogonalize();
+    equal &= report.u_before > 1e-3 && report.v_before > 1e-3;
+    equal &= report.u_after < 1e-5 && report.v_after < 1e-5;
+
+    if equal {
+        debug!("reorthogonalization successful");
+    }
+    else {
+        debug!("reorthogonalization failed");
+    }
+    equal
+}
+
+/// Checks that inverse iteration restores perturbed singular vectors, including the null
+/// vectors of a zero singular value, and keeps their phase up to the perturbation
+fn check_refine() -> bool {
+    let mut equal = true;
+
+    let a = lcg_matrix::<f64>(8, 5, 229);
+    let exact = svd(&a).unwrap();
+    for k in [0, 2, 4] {
+        let mut res = exact.clone();
+        for i in 0..8 {
+            res.u[(i, k)] += Complex64::new(1e-3 * (i as f64).sin(), 1e-3);
+        }
+        for j in 0..5 {
+            res.v[(j, k)] += Complex64::new(-1e-3, 1e-3 * j as f64);
+        }
+        equal &= res.refine(&a, k, 3).is_ok_and(|r| r < 1e-12);
+        // the same directions, with the phase off by no more than the perturbation
+        let dot_u = (0..8).fold(Complex64::new(0.0, 0.0), |acc, i| acc + exact.u[(i, k)].conj() * res.u[(i, k)]);
+        let dot_v = (0..5).fold(Complex64::new(0.0, 0.0), |acc, j| acc + exact.v[(j, k)].conj() * res.v[(j, k)]);
+        equal &= (1.0 - cabs(&dot_u)).abs() < 1e-12 && (1.0 - cabs(&dot_v)).abs() < 1e-12;
+        equal &= (dot_v - 1.0).norm_sqr() < 1e-4 && (dot_u - dot_v).norm_sqr() < 1e-24;
+    }
+
+    // the smallest singular value of a rank deficient matrix is zero up to rounding, which
+    // the decomposition in single precision gets to about eps ||A||
+    let a = gallery::rank_deficient::<f64>(6, 6, 5, 233).unwrap();
+    let single = Matrix::from_vec(a.as_slice().iter().map(|x| Complex32::new(x.re as f32, x.im as f32)).collect(), 6, 6).unwrap();
+    let rough = svd(&single).unwrap();
+    let mut u: Vec<Complex64> = (0..6).map(|i| Complex64::new(rough.u[(i, 5)].re as f64, rough.u[(i, 5)].im as f64)).collect();
+    let mut v: Vec<Complex64> = (0..6).map(|j| Complex64::new(rough.v[(j, 5)].re as f64, rough.v[(j, 5)].im as f64)).collect();
+    equal &= refine_singular_vectors(&a, 0.0, &mut u, &mut v, 2).is_ok_and(|r| r < 1e-13);
+
+    equal &= refine_singular_vectors(&a, 0.0, &mut u[..5], &mut v, 2) == Err(CsvdError::DimensionMismatch { operand: "u", expected: (6, 1), found: (5, 1) });
+    equal &= exact.clone().refine(&lcg_matrix::<f64>(8, 5, 229), 5, 1) == Err(CsvdError::InvalidArgument { name: "k", value: 5, max: 4 });
+
+    if equal {
+        debug!("singular vector refinement successful");
+    }
+    else {
+        debug!("singular vector refinement failed");
+    }
+    equal
+}
+
+/// Checks that the progress callback sees every sweep and can cancel the decomposition
+fn check_progress() -> bool {
+    let mut equal = true;
+    let options = SvdOptions::default();
+    let a = lcg_matrix::<f64>(9, 6, 17);
+
+    let mut seen = Vec::new();
+    let res = svd_with_progress(&a, &options, |p| {
+        seen.push(p);
+        ControlFlow::Continue(())
+    }).unwrap();
+    equal &= res == svd(&a).unwrap();
+    let (_, stats) = svd_with_stats(&a, &options).unwrap();
+    equal &= seen.len() == stats.sweeps;
+    equal &= seen.iter().enumerate().all(|(i, p)| p.sweeps == i && p.total == 6 && p.converged + p.index + 1 == 6);
+    equal &= seen.windows(2).all(|w| w[1].converged >= w[0].converged);
+
+    let result = svd_with_progress(&a, &options, |p| if p.sweeps < 3 { ControlFlow::Continue(()) } else { ControlFlow::Break(()) });
+    equal &= matches!(result, Err(CsvdError::Cancelled { sweeps: 3, .. }));
+
+    // the sweeps of a wide matrix run on its transpose
+    let result = svd_with_progress(&a.conj_transpose(), &options, |_| ControlFlow::Break(()));
+    equal &= matches!(result, Err(CsvdError::Cancelled { sweeps: 0, index: 5 }));
+
+    if equal {
+        debug!("progress successful");
+    }
+    else {
+        debug!("progress failed");
+    }
+    equal
+}
+
+/// Checks that the QR iteration gives up with NotConverged once the iteration cap is exhausted
+fn check_iteration_cap<T: Real>(a: &Matrix<Complex<T>>) -> bool {
+    let options = SvdOptions { max_iterations: 0, ..SvdOptions::default() };
+
+    match svd_with_options(a, &options) {
+        Err(CsvdError::NotConverged { .. }) => {
+            debug!("iteration cap successful");
+            true
+        }
+        _ => {
+            debug!("iteration cap failed");
+            false
+        }
+    }
+}
+
+/// Checks that a matrix whose entries are all around `magnitude` is decomposed without
+/// overflow or underflow, by comparing against the decomposition of the unscaled matrix
+#[allow(clippy::neg_cmp_op_on_partial_ord)]
+fn check_extreme_scaling<T: Real>(magnitude: T) -> bool {
+    let a = lcg_matrix::<T>(6, 4, 5);
+    let mut scaled = a.clone();
+    for i in 0..a.rows() {
+        for j in 0..a.cols() {
+            scaled[(i, j)] = a[(i, j)] * magnitude;
+        }
+    }
+
+    let eps = T::from(0.001).unwrap();
+    let (res, s) = match (svd(&scaled), singular_values(&a)) {
+        (Ok(res), Ok(s)) => (res, s),
+        _ => {
+            debug!("extreme scaling failed");
+            return false;
+        }
+    };
+
+    // relative errors, dividing by magnitude first so nothing overflows
+    let reconstructed = res.reconstruct();
+    let mut equal = true;
+    for i in 0..a.rows() {
+        for j in 0..a.cols() {
+            let d = reconstructed[(i, j)] / magnitude - a[(i, j)];
+            if !(d.re.abs() < eps && d.im.abs() < eps) {
+                equal = false;
+            }
+        }
+    }
+    for k in 0..s.len() {
+        if !((res.s[k] / magnitude - s[k]).abs() < eps) {
+            equal = false;
+        }
+    }
+
+    if equal {
+        debug!("extreme scaling successful");
+    }
+    else {
+        debug!("extreme scaling failed");
+    }
+
+    equal
+}
+
+/// Checks that one workspace can be reused for tall, wide and square matrices of different sizes
+/// and gives the same factors as svd
+fn check_workspace() -> bool {
+    let mut ws = SvdWorkspace::new(9, 9);
+    let mut equal = true;
+
+    for &(m, n) in &[(9, 4), (3, 9), (6, 6), (2, 2)] {
+        let a = lcg_matrix::<f64>(m, n, 17);
+        let res = svd(&a).unwrap();
+
+        if csvd_with_workspace(&a, &mut ws).is_err() {
+            equal = false;
+            continue;
+        }
+
+        equal &= ws.s().len() == res.s.len();
+        equal &= check_matrix_equality(&ws.u().to_matrix(), &res.u);
+        equal &= check_matrix_equality(&ws.v().to_matrix(), &res.v);
+        for k in 0..res.s.len() {
+            equal &= (ws.s()[k] - res.s[k]).abs() < 0.0001;
+        }
+    }
+
+    // larger than the workspace was created for
+    equal &= csvd_with_workspace(&lcg_matrix::<f64>(10, 2, 1), &mut ws).is_err();
+
+    // an exhausted heap is reported instead of aborting, both for sizes that overflow
+    // and for ones far beyond the address space
+    equal &= SvdWorkspace::<f64>::try_new(1 << 40, 1 << 40).err() == Some(CsvdError::AllocFailed);
+    equal &= SvdWorkspace::<f64>::try_new(1 << 26, 1 << 26).err() == Some(CsvdError::AllocFailed);
+    equal &= Matrix::<Complex32>::try_zeros(1 << 30, 1 << 30).err() == Some(CsvdError::AllocFailed);
+    equal &= BatchedSvd::<f32>::try_new(1 << 20, 1 << 20, 1 << 30, BatchLayout::Contiguous).err() == Some(CsvdError::AllocFailed);
+    equal &= aligned_vec::<u64>(1 << 58, 64).err() == Some(CsvdError::AllocFailed);
+    equal &= SvdWorkspace::<f32>::try_new(5, 3).is_ok_and(|ws| ws.m_max() == 5 && ws.n_max() == 3);
+
+    if equal {
+        debug!("workspace successful");
+    }
+    else {
+        debug!("workspace failed");
+    }
+
+    equal
+}
+
+/// Decomposes a matrix per iteration of a loop and hands out its pseudo-inverse in pooled
+/// matrices, which must match pinv and never grow the pool beyond the matrices in flight
+fn check_buffer_pool() -> bool {
+    let mut ws = SvdWorkspace::new(6, 4);
+    let mut pool = match BufferPool::<Complex64>::new(4, 6, 64, 2) {
+        Ok(pool) => pool,
+        Err(_) => return false,
+    };
+    let options = PinvOptions::absolute(0.0001);
+    let mut equal = true;
+
+    let mut in_flight = Vec::new();
+    for slot in 0..6 {
+        let a = lcg_matrix::<f64>(6, 4, 401 + slot);
+        equal &= csvd_with_workspace(&a, &mut ws).is_ok();
+        let inv = match ws.pinv_pooled(&mut pool, &options) {
+            Ok(inv) => inv,
+            Err(_) => return false,
+        };
+        equal &= (inv.as_slice().as_ptr() as usize).is_multiple_of(64);
+
+        let mut expected = Matrix::zeros(4, 6);
+        equal &= pinv(&mut a.clone(), &mut expected).is_ok();
+        equal &= (0..4).all(|i| (0..6).all(|j| (inv[(i, j)] - expected[(i, j)]).norm_sqr() < 1e-20));
+
+        // the consumer returns a matrix one slot later
+        in_flight.push(inv);
+        if in_flight.len() > 1 {
+            equal &= pool.give(in_flight.remove(0)).is_ok();
+        }
+    }
+    equal &= pool.allocated() == 2 && pool.available() == 1;
+
+    // a third matrix in flight grows the pool once
+    let extra = [pool.take(), pool.take()];
+    equal &= pool.allocated() == 3 && pool.available() == 0;
+    for m in extra.into_iter().chain(in_flight.drain(..).map(Ok)) {
+        equal &= m.is_ok_and(|m| pool.give(m).is_ok());
+    }
+    equal &= pool.available() == 3;
+
+    // matrices of another pool or more than were taken are refused
+    let mut other = BufferPool::<Complex64>::new(4, 6, 16, 1).unwrap();
+    equal &= pool.give(other.take().unwrap()).is_err();
+    let mut same = BufferPool::<Complex64>::new(4, 6, 64, 1).unwrap();
+    equal &= pool.give(same.take().unwrap()).is_err();
+    equal &= ws.pinv_pooled(&mut other, &options).is_ok();
+    equal &= ws.pinv_pooled(&mut BufferPool::new(6, 4, 64, 0).unwrap(), &options).is_err();
+    equal &= BufferPool::<f32>::new(2, 2, 24, 1).is_err();
+
+    if equal {
+        debug!("buffer pool successful");
+    }
+    else {
+        debug!("buffer pool failed");
+    }
+    equal
+}
+
+/// Checks that the allocation-free slice interface gives the same factors as svd,
+/// for a tall and a wide matrix
+fn check_in_slices() -> bool {
+    let mut equal = true;
+
+    for &(m, n) in &[(7, 3), (3, 7), (4, 6)] {
+        let a = lcg_matrix::<f32>(m, n, 23);
+        let res = svd(&a).unwrap();
+
+        let mut a_slice = a.clone().into_vec();
+        let mut s = [0.0f32; 3 * 7];
+        let mut u = [Complex32::new(0.0, 0.0); 7 * 7];
+        let mut v = [Complex32::new(0.0, 0.0); 7 * 7];
+        let mut scratch = [0.0f32; 3 * 7];
+
+        if csvd_in_slices(&mut a_slice, &mut s, &mut u[..m * m], &mut v[..n * n], &mut scratch, m, n).is_err() {
+            equal = false;
+            continue;
+        }
+
+        let u = Matrix::from_vec(u[..m * m].to_vec(), m, m).unwrap();
+        let v = Matrix::from_vec(v[..n * n].to_vec(), n, n).unwrap();
+        equal &= check_matrix_equality(&u, &res.u);
+        equal &= check_matrix_equality(&v, &res.v);
+        for k in 0..res.s.len() {
+            equal &= (s[k] - res.s[k]).abs() < 0.0001;
+        }
+    }
+
+    // scratch too small for a 7 x 3 matrix
+    let mut a_slice = lcg_matrix::<f32>(7, 3, 1).into_vec();
+    let mut s = [0.0f32; 3];
+    let mut u = [Complex32::new(0.0, 0.0); 49];
+    let mut v = [Complex32::new(0.0, 0.0); 9];
+    let mut scratch = [0.0f32; 8];
+    equal &= csvd_in_slices(&mut a_slice, &mut s, &mut u, &mut v, &mut scratch, 7, 3).is_err();
+
+    if equal {
+        debug!("slice interface successful");
+    }
+    else {
+        debug!("slice interface failed");
+    }
+
+    equal
+}
+
+/// Decomposes blocks of a larger matrix and of a padded DMA buffer through strided views,
+/// which must give the factors of the copied block and leave everything around it alone
+fn check_views() -> bool {
+    let mut equal = true;
+    let options = SvdOptions::default();
+    let sentinel = Complex64::new(7.0, -7.0);
+
+    for &(row, col, m, n) in &[(2, 1, 6, 4), (1, 2, 3, 5), (0, 0, 9, 8)] {
+        let mut big = lcg_matrix::<f64>(9, 8, 31);
+        let original = big.clone();
+
+        let mut a = big.view().submatrix(row, col, m, n).unwrap().to_matrix();
+        let min = m.min(n);
+        let (mut s, mut u, mut v) = (alloc::vec![0.0; min], Matrix::zeros(m, m), Matrix::zeros(n, n));
+        equal &= csvd(&mut a, 0, m, n, &mut s, &mut u, &mut v).is_ok();
+
+        // U and V in buffers with padded rows
+        let mut u_buf = alloc::vec![sentinel; m * (m + 3)];
+        let mut v_buf = alloc::vec![sentinel; n * (n + 1)];
+        let mut s_view = alloc::vec![0.0; min];
+        {
+            let mut whole = big.view_mut();
+            let block = whole.submatrix_mut(row, col, m, n).unwrap();
+            let u_view = MatrixViewMut::new(&mut u_buf, m, m, m + 3).unwrap();
+            let v_view = MatrixViewMut::new(&mut v_buf, n, n, n + 1).unwrap();
+            equal &= csvd_view(block, 0, m, n, &mut s_view, u_view, v_view, &options).is_ok();
+        }
+
+        equal &= s.iter().zip(&s_view).all(|(x, y)| (x - y).abs() < 1e-12);
+        let u_view = MatrixView::new(&u_buf, m, m, m + 3).unwrap();
+        let v_view = MatrixView::new(&v_buf, n, n, n + 1).unwrap();
+        equal &= check_matrix_equality(&u_view.to_matrix(), &u);
+        equal &= check_matrix_equality(&v_view.to_matrix(), &v);
+        equal &= (0..m).all(|i| u_buf[i * (m + 3) + m..(i + 1) * (m + 3)].iter().all(|&x| x == sentinel));
+        equal &= (0..n).all(|i| v_buf[i * (n + 1) + n] == sentinel);
+
+        // a tall block is overwritten, a wide one is not, the rest of the matrix never
+        for i in 0..9 {
+            for j in 0..8 {
+                let inside = (row..row + m).contains(&i) && (col..col + n).contains(&j);
+                equal &= (inside && m >= n) || big[(i, j)] == original[(i, j)];
+            }
+        }
+    }
+
+    // a 6 x 4 channel matrix in a page-aligned buffer with rows padded to 8 elements
+    let mut dma = dma_buffer::<Complex32>(6 * 8).unwrap();
+    let packed = lcg_matrix::<f32>(6, 4, 37);
+    for i in 0..6 {
+        dma[i * 8..i * 8 + 4].copy_from_slice(packed.row(i));
+    }
+    let reference = svd(&packed).unwrap();
+    let (mut s, mut u, mut v) = ([0.0f32; 4], Matrix::zeros(6, 6), Matrix::zeros(4, 4));
+    let a = MatrixViewMut::new(&mut dma, 6, 4, 8).unwrap();
+    equal &= csvd_view(a, 0, 6, 4, &mut s, u.view_mut(), v.view_mut(), &options).is_ok();
+    equal &= s.iter().zip(&reference.s).all(|(x, y)| (x - y).abs() < 0.0001);
+    equal &= check_matrix_equality(&u, &reference.u) && check_matrix_equality(&v, &reference.v);
+
+    let data = [0u8; 20];
+    equal &= MatrixView::new(&data, 3, 5, 4).is_err_and(|e| e == CsvdError::InvalidLeadingDimension { ld: 4, cols: 5 });
+    equal &= MatrixView::new(&data, 4, 5, 6).is_err_and(|e| e == CsvdError::BufferTooSmall { operand: "data", required: 23, len: 20 });
+    let view = MatrixView::new(&data, 3, 5, 6).unwrap();
+    equal &= view.submatrix(1, 1, 2, 4).is_ok_and(|b| b.rows() == 2 && b.cols() == 4 && b.row_stride() == 6);
+    equal &= view.submatrix(2, 0, 2, 1).is_err() && view.submatrix(0, 3, 1, 3).is_err();
+    equal &= view.submatrix(3, 5, 0, 0).is_ok_and(|b| b.to_matrix().as_slice().is_empty());
+
+    if equal {
+        debug!("matrix views successful");
+    }
+    else {
+        debug!("matrix views failed");
+    }
+    equal
+}
+
+/// Verifies that products and QR decompositions on split planes match the interleaved ones
+fn check_soa() -> bool {
+    let mut equal = true;
+    let one = Complex64::new(1.0, 0.0);
+
+    let a = lcg_matrix::<f64>(5, 3, 91);
+    let soa = SoaMatrix::from_matrix(&a);
+    equal &= soa.rows() == 5 && soa.cols() == 3 && soa.get(4, 1) == a[(4, 1)];
+    equal &= soa.re()[3] == a[(1, 0)].re && soa.im()[3] == a[(1, 0)].im;
+    equal &= soa.to_matrix() == a;
+    equal &= SoaMatrix::from_matrix(&a.to_layout(Layout::ColMajor)) == soa;
+    equal &= SoaMatrix::from_planes(alloc::vec![1.0; 6], alloc::vec![0.0; 5], 2, 3)
+        == Err(CsvdError::DimensionMismatch { operand: "im", expected: (6, 1), found: (5, 1) });
+
+    // every combination of operations, accumulating into a nonzero C
+    let b = lcg_matrix::<f64>(3, 4, 92);
+    let c0 = lcg_matrix::<f64>(5, 4, 93);
+    let (alpha, beta) = (Complex64::new(0.5, -1.5), Complex64::new(-1.0, 0.25));
+    for &op_a in [Op::None, Op::Transpose, Op::ConjugateTranspose].iter() {
+        for &op_b in [Op::None, Op::Transpose, Op::ConjugateTranspose].iter() {
+            // stored so that op(a) and op(b) are the matrices above
+            let stored = |x: &Matrix<Complex64>, op| match op {
+                Op::None => x.clone(),
+                Op::Transpose => x.transpose(),
+                Op::ConjugateTranspose => x.conj_transpose(),
+            };
+            let (a, b) = (stored(&a, op_a), stored(&b, op_b));
+            let mut expected = c0.clone();
+            equal &= gemm(alpha, op_a, &a, op_b, &b, beta, &mut expected).is_ok();
+            let mut c = SoaMatrix::from_matrix(&c0);
+            equal &= gemm_soa(alpha, op_a, &SoaMatrix::from_matrix(&a), op_b, &SoaMatrix::from_matrix(&b), beta, &mut c).is_ok();
+            equal &= check_matrix_equality(&c.to_matrix(), &expected);
+        }
+    }
+    let mut c = SoaMatrix::zeros(5, 3);
+    equal &= gemm_soa(one, Op::None, &soa, Op::None, &soa, Complex64::new(0.0, 0.0), &mut c)
+        == Err(CsvdError::DimensionMismatch { operand: "b", expected: (3, 3), found: (5, 3) });
+
+    // tall, wide and rank deficient matrices factor as with the interleaved kernels
+    for &(m, n) in [(5, 3), (3, 6), (8, 8)].iter() {
+        let a = lcg_matrix::<f64>(m, n, 94 + m as u64);
+        let (reference, res) = (qr(&a).unwrap(), qr_soa(&SoaMatrix::from_matrix(&a)).unwrap());
+        equal &= check_matrix_equality(&res.r(), &reference.r()) && check_matrix_equality(&res.q(), &reference.q());
+        equal &= res.tau().iter().zip(reference.tau()).all(|(x, y)| (x - y).norm_sqr() < 1e-20);
+    }
+    let a = gallery::rank_deficient::<f64>(6, 4, 2, 95).unwrap();
+    let res = qr_soa(&SoaMatrix::from_matrix(&a)).unwrap();
+    let mut qr_prod = Matrix::zeros(6, 4);
+    equal &= gemm(one, Op::None, &res.q(), Op::None, &res.r(), Complex64::new(0.0, 0.0), &mut qr_prod).is_ok();
+    equal &= check_matrix_equality(&qr_prod, &a);
+    equal &= qr_soa(&SoaMatrix::<f32>::zeros(0, 3)).err() == Some(CsvdError::EmptyMatrix);
+
+    if equal {
+        debug!("structure of arrays successful");
+    }
+    else {
+        debug!("structure of arrays failed");
+    }
+    equal
+}
+
+/// Multiplies two matrices block by block into the blocks of a third one and decomposes
+/// a block in place, which must match the product and decomposition of whole copies
+fn check_blocks() -> bool {
+    let mut equal = true;
+    let zero = Complex64::new(0.0, 0.0);
+    let one = Complex64::new(1.0, 0.0);
+
+    let a = lcg_matrix::<f64>(8, 6, 51);
+    let b = lcg_matrix::<f64>(6, 10, 53);
+    let mut expected = Matrix::zeros(8, 10);
+    equal &= gemm(one, Op::None, &a, Op::None, &b, zero, &mut expected).is_ok();
+
+    let mut c = Matrix::zeros(8, 10);
+    for (rows, cols) in [(0..4, 0..5), (0..4, 5..10), (4..8, 0..5), (4..8, 5..10)] {
+        for (l, inner) in [0..3, 3..6].into_iter().enumerate() {
+            let beta = if l == 0 { zero } else { one };
+            let a_block = a.block(rows.clone(), inner.clone()).unwrap();
+            let b_block = b.block(inner, cols.clone()).unwrap();
+            equal &= gemm_view(one, Op::None, a_block, Op::None, b_block, beta, c.block_mut(rows.clone(), cols.clone()).unwrap()).is_ok();
+        }
+    }
+    equal &= check_matrix_equality(&c, &expected);
+
+    // the Gram matrix of a block, with B transposed
+    let block = a.block(2..7, 1..=4).unwrap();
+    let copy = block.to_matrix();
+    let mut gram = Matrix::zeros(4, 4);
+    equal &= gemm(one, Op::ConjugateTranspose, &copy, Op::None, &copy, zero, &mut gram).is_ok();
+    let mut out = alloc::vec![one; 4 * 6];
+    let mut gram_view = MatrixViewMut::new(&mut out, 4, 4, 6).unwrap();
+    let copy_h = copy.conj_transpose();
+    equal &= gemm_view(one, Op::None, copy_h.view(), Op::ConjugateTranspose, copy_h.view(), zero, gram_view.reborrow()).is_ok();
+    equal &= check_matrix_equality(&gram_view.as_view().to_matrix(), &gram);
+    equal &= gemm_view(one, Op::None, block, Op::None, block, zero, gram_view).is_err();
+    equal &= (0..4).all(|i| out[i * 6 + 4] == one && out[i * 6 + 5] == one);
+
+    // a block decomposed in place
+    let mut big = lcg_matrix::<f64>(9, 8, 57);
+    let res = svd(&big.block(1..7, 2..6).unwrap().to_matrix()).unwrap();
+    let (mut s, mut u, mut v) = ([0.0; 4], Matrix::zeros(6, 6), Matrix::zeros(4, 4));
+    equal &= csvd_view(big.block_mut(1..7, 2..6).unwrap(), 0, 6, 4, &mut s, u.view_mut(), v.view_mut(), &SvdOptions::default()).is_ok();
+    equal &= s.iter().zip(&res.s).all(|(x, y)| (x - y).abs() < 1e-12);
+
+    equal &= big.block(.., ..).is_ok_and(|b| b.rows() == 9 && b.cols() == 8);
+    equal &= big.block(8.., 3..3).is_ok_and(|b| b.rows() == 1 && b.cols() == 0);
+    equal &= big.block(0..10, ..).is_err_and(|e| e == CsvdError::InvalidArgument { name: "rows", value: 10, max: 9 });
+    equal &= big.block_mut(.., 5..=8).is_err();
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = big.block(.., 4..2);
+    equal &= reversed.is_err();
+
+    if equal {
+        debug!("blocks successful");
+    }
+    else {
+        debug!("blocks failed");
+    }
+    equal
+}
+
+/// Decomposes, inverts and multiplies column-major matrices, which must give the results
+/// of their row-major copies
+fn check_col_major() -> bool {
+    let mut equal = true;
+    let opts = SvdOptions::default();
+
+    for &(m, n) in &[(6, 4), (3, 5), (4, 4)] {
+        let a = lcg_matrix::<f64>(m, n, 41);
+        let col = a.to_layout(Layout::ColMajor);
+        equal &= col.layout() == Layout::ColMajor && col.ld() == m && col.to_layout(Layout::RowMajor) == a;
+        equal &= (0..n).all(|j| col.col(j).iter().enumerate().all(|(i, &x)| x == a[(i, j)]));
+
+        let min = m.min(n);
+        let (mut s, mut u, mut v) = (alloc::vec![0.0; min], Matrix::zeros(m, m), Matrix::zeros(n, n));
+        equal &= csvd(&mut a.clone(), 0, m, n, &mut s, &mut u, &mut v).is_ok();
+
+        // every combination of layouts for A, U and V
+        for &layouts in &[(Layout::ColMajor, Layout::RowMajor), (Layout::RowMajor, Layout::ColMajor), (Layout::ColMajor, Layout::ColMajor)] {
+            let mut a_col = col.clone();
+            let mut s_col = alloc::vec![0.0; min];
+            let mut u_col = Matrix::zeros_with_layout(m, m, layouts.0);
+            let mut v_col = Matrix::zeros_with_layout(n, n, layouts.1);
+            equal &= super::csvd::csvd_with_options(&mut a_col, 0, m, n, &mut s_col, &mut u_col, &mut v_col, &opts).is_ok();
+            equal &= s.iter().zip(&s_col).all(|(x, y)| (x - y).abs() < 1e-12);
+            // the phases of the singular vectors may differ with the path, their products may not
+            for k in 0..min {
+                for i in 0..m {
+                    for j in 0..n {
+                        let x = u[(i, k)] * v[(j, k)].conj();
+                        let y = u_col[(i, k)] * v_col[(j, k)].conj();
+                        equal &= (x - y).norm_sqr() < 1e-20;
+                    }
+                }
+            }
+        }
+
+        let mut inv = Matrix::zeros(n, m);
+        equal &= pinv(&mut a.clone(), &mut inv).is_ok();
+        let mut inv_col = Matrix::zeros_with_layout(n, m, Layout::ColMajor);
+        equal &= pinv(&mut col.clone(), &mut inv_col).is_ok();
+        equal &= check_matrix_equality(&inv, &inv_col);
+
+        let b = lcg_matrix::<f64>(n, 3, 43);
+        let mut c = Matrix::zeros(m, 3);
+        let mut c_col = Matrix::zeros_with_layout(m, 3, Layout::ColMajor);
+        equal &= matrix_mult(&a, &b, &mut c).is_ok() && matrix_mult(&col, &b.to_layout(Layout::ColMajor), &mut c_col).is_ok();
+        equal &= check_matrix_equality(&c, &c_col);
+        let one = Complex64::new(1.0, 0.0);
+        let mut h = Matrix::zeros(n, 3);
+        equal &= gemm(one, Op::ConjugateTranspose, &col, Op::None, &c_col, Complex64::new(0.0, 0.0), &mut h).is_ok();
+        let mut expected = Matrix::zeros(n, 3);
+        equal &= gemm(one, Op::ConjugateTranspose, &a, Op::None, &c, Complex64::new(0.0, 0.0), &mut expected).is_ok();
+        equal &= check_matrix_equality(&h, &expected);
+
+        let x = [Complex64::new(0.5, -1.0); 3];
+        let (mut y, mut y_col) = ([one; 6], [one; 6]);
+        equal &= gemv(one, Op::None, &c, &x, one, &mut y[..m]).is_ok() && gemv(one, Op::None, &c_col, &x, one, &mut y_col[..m]).is_ok();
+        equal &= y.iter().zip(&y_col).all(|(p, q)| (p - q).norm_sqr() < 1e-20);
+        equal &= alloc::format!("{:.3}", col) == alloc::format!("{:.3}", a);
+    }
+
+    let a = lcg_matrix::<f64>(4, 4, 47);
+    let col = a.to_layout(Layout::ColMajor);
+    equal &= det(&a).is_ok_and(|d| det(&col).is_ok_and(|e| (d - e).norm_sqr() < 1e-20));
+    equal &= qr(&col).is_ok();
+
+    // a Fortran array, column by column with a leading dimension of 3
+    let data = alloc::vec![Complex64::new(1.0, 0.0), Complex64::new(2.0, 0.0), Complex64::new(9.0, 9.0),
+        Complex64::new(3.0, 0.0), Complex64::new(4.0, 0.0), Complex64::new(9.0, 9.0)];
+    let fortran = Matrix::from_vec_with_layout(data.clone(), 2, 2, 3, Layout::ColMajor).unwrap();
+    equal &= fortran[(0, 1)] == Complex64::new(3.0, 0.0) && fortran[(1, 0)] == Complex64::new(2.0, 0.0);
+    equal &= Matrix::from_vec_with_layout(data.clone(), 4, 2, 3, Layout::ColMajor) == Err(CsvdError::InvalidLeadingDimension { ld: 3, cols: 4 });
+    equal &= Matrix::from_vec_col_major(data, 3, 3).is_err();
+
+    if equal {
+        debug!("column-major matrices successful");
+    }
+    else {
+        debug!("column-major matrices failed");
+    }
+    equal
+}
+
+/// Decomposes real matrices in real arithmetic, which must reconstruct them with
+/// orthogonal factors and give the singular values of the complex decomposition
+fn check_svd_real<T: Real>() -> bool {
+    let mut equal = true;
+    let eps = T::from(0.0001).unwrap();
+
+    for &(m, n) in &[(7, 3), (3, 7), (5, 5), (1, 4), (6, 1)] {
+        let a: Vec<T> = lcg_matrix::<T>(m, n, 61).as_slice().iter().map(|x| x.re).collect();
+        let complex = Matrix::from_vec(a.iter().map(|&x| Complex::new(x, T::zero())).collect(), m, n).unwrap();
+        let reference = svd(&complex).unwrap();
+
+        let min = m.min(n);
+        let mut work = a.clone();
+        let (mut s, mut u, mut v, mut scratch) = (alloc::vec![T::zero(); min], alloc::vec![T::zero(); m * m],
+            alloc::vec![T::zero(); n * n], alloc::vec![T::zero(); 3 * min]);
+        if svd_real(&mut work, &mut s, &mut u, &mut v, &mut scratch, m, n).is_err() {
+            equal = false;
+            continue;
+        }
+        equal &= s.iter().zip(&reference.s).all(|(&x, &y)| (x - y).abs() < eps);
+
+        // A = U S V^T, U^T U = I and V^T V = I
+        for i in 0..m {
+            for j in 0..n {
+                let x = (0..min).fold(T::zero(), |acc, k| acc + u[i * m + k] * s[k] * v[j * n + k]);
+                equal &= (x - a[i * n + j]).abs() < eps;
+            }
+        }
+        for (q, d) in [(&u, m), (&v, n)] {
+            for i in 0..d {
+                for j in 0..d {
+                    let x = (0..d).fold(T::zero(), |acc, k| acc + q[k * d + i] * q[k * d + j]);
+                    let expected = if i == j { T::one() } else { T::zero() };
+                    equal &= (x - expected).abs() < eps;
+                }
+            }
+        }
+    }
+
+    // rank one, and buffers that are too small
+    let mut ones = alloc::vec![T::one(); 12];
+    let (mut s, mut u, mut v, mut scratch) = ([T::zero(); 3], [T::zero(); 16], [T::zero(); 9], [T::zero(); 9]);
+    equal &= svd_real(&mut ones, &mut s, &mut u, &mut v, &mut scratch, 4, 3).is_ok();
+    equal &= (s[0] - T::from(12.0).unwrap().sqrt()).abs() < eps && s[1].abs() < eps && s[2].abs() < eps;
+    equal &= svd_real(&mut ones, &mut s, &mut u[..15], &mut v, &mut scratch, 4, 3).is_err();
+    equal &= svd_real(&mut ones, &mut s, &mut u, &mut v, &mut scratch[..8], 4, 3).is_err();
+    equal &= svd_real(&mut ones, &mut s, &mut u, &mut v, &mut scratch, 0, 3) == Err(CsvdError::EmptyMatrix);
+
+    if equal {
+        debug!("real svd successful");
+    }
+    else {
+        debug!("real svd failed");
+    }
+    equal
+}
+
+/// Checks that svd_auto detects real and Hermitian matrices and that every path
+/// reproduces the matrix with the singular values of svd, and checks eigh on its own
+fn check_svd_auto<T: Real>() -> bool {
+    let mut equal = true;
+    let eps = T::from(0.0001).unwrap();
+
+    let general = lcg_matrix::<T>(5, 4, 67);
+    let real = Matrix::from_vec(general.as_slice().iter().map(|x| Complex::new(x.re, T::zero())).collect(), 5, 4).unwrap();
+    let square = lcg_matrix::<T>(5, 5, 71);
+    let mut hermitian = Matrix::zeros(5, 5);
+    for i in 0..5 {
+        for j in 0..5 {
+            hermitian[(i, j)] = square[(i, j)] + square[(j, i)].conj();
+        }
+    }
+
+    for (a, expected) in [(&general, Structure::General), (&real, Structure::Real), (&hermitian, Structure::Hermitian)] {
+        let res = match svd_auto(a) {
+            Ok(res) => res,
+            Err(_) => return false,
+        };
+        let reference = svd(a).unwrap();
+        equal &= res.structure == expected;
+        equal &= res.svd.s.iter().zip(&reference.s).all(|(&x, &y)| (x - y).abs() < eps);
+        equal &= check_matrix_equality(&res.svd.reconstruct(), a);
+    }
+
+    // A Q = Q L, with the eigenvalues in ascending order and Q unitary
+    let eig = eigh(&hermitian).unwrap();
+    equal &= eig.values.windows(2).all(|w| w[0] <= w[1]);
+    for i in 0..5 {
+        for j in 0..5 {
+            let aq = (0..5).fold(Complex::new(T::zero(), T::zero()), |acc, k| acc + hermitian[(i, k)] * eig.vectors[(k, j)]);
+            equal &= (aq - eig.vectors[(i, j)] * eig.values[j]).norm_sqr() < eps;
+            let qq = (0..5).fold(Complex::new(T::zero(), T::zero()), |acc, k| acc + eig.vectors[(k, i)].conj() * eig.vectors[(k, j)]);
+            let expected = if i == j { T::one() } else { T::zero() };
+            equal &= (qq - Complex::new(expected, T::zero())).norm_sqr() < eps;
+        }
+    }
+    equal &= eigh(&general) == Err(CsvdError::DimensionMismatch { operand: "a", expected: (5, 5), found: (5, 4) });
+
+    if equal {
+        debug!("svd auto successful");
+    }
+    else {
+        debug!("svd auto failed");
+    }
+    equal
+}
+
+/// Checks the fixed-size decomposition of a square and a wide matrix against svd
+fn check_fixed() -> bool {
+    let mut equal = true;
+
+    let a = lcg_matrix::<f32>(4, 4, 29);
+    let res = svd(&a).unwrap();
+    let mut fixed = [[Complex32::new(0.0, 0.0); 4]; 4];
+    for i in 0..4 {
+        fixed[i].copy_from_slice(a.row(i));
+    }
+    match svd_fixed(&mut fixed) {
+        Ok(f) => {
+            equal &= check_matrix_equality(&Matrix::from_vec(f.u.concat(), 4, 4).unwrap(), &res.u);
+            equal &= check_matrix_equality(&Matrix::from_vec(f.v.concat(), 4, 4).unwrap(), &res.v);
+            equal &= f.s.iter().zip(res.s.iter()).all(|(x, y)| (x - y).abs() < 0.0001);
+        }
+        Err(_) => equal = false,
+    }
+
+    let a = lcg_matrix::<f32>(2, 3, 31);
+    let res = svd(&a).unwrap();
+    let mut fixed = [[Complex32::new(0.0, 0.0); 3]; 2];
+    for i in 0..2 {
+        fixed[i].copy_from_slice(a.row(i));
+    }
+    match svd_fixed(&mut fixed) {
+        Ok(f) => {
+            equal &= check_matrix_equality(&Matrix::from_vec(f.u.concat(), 2, 2).unwrap(), &res.u);
+            equal &= check_matrix_equality(&Matrix::from_vec(f.v.concat(), 3, 3).unwrap(), &res.v);
+            equal &= f.s[..2].iter().zip(res.s.iter()).all(|(x, y)| (x - y).abs() < 0.0001);
+            equal &= f.s[2] == 0.0;
+        }
+        Err(_) => equal = false,
+    }
+
+    if equal {
+        debug!("fixed size successful");
+    }
+    else {
+        debug!("fixed size failed");
+    }
+
+    equal
+}
+
+/// Checks that the truncated decomposition gives the leading singular triplets of svd
+fn check_truncated() -> bool {
+    let mut equal = true;
+
+    for &(m, n, k) in &[(8, 5, 2), (4, 7, 3), (6, 6, 6), (5, 3, 0)] {
+        let a = lcg_matrix::<f64>(m, n, 37);
+        let res = svd(&a).unwrap();
+
+        let trunc = match svd_truncated(&a, k) {
+            Ok(trunc) => trunc,
+            Err(_) => {
+                equal = false;
+                continue;
+            }
+        };
+
+        equal &= trunc.s.len() == k && trunc.u.cols() == k && trunc.v.cols() == k;
+        equal &= verify_svd(&a, &trunc, 1e6).is_ok_and(|report| report.u_orthogonality < 1e-8 && report.v_orthogonality < 1e-8);
+        for j in 0..k {
+            equal &= (trunc.s[j] - res.s[j]).abs() < 0.0001;
+            for i in 0..m {
+                equal &= (trunc.u[(i, j)] - res.u[(i, j)]).norm_sqr() < 0.0001;
+            }
+            for i in 0..n {
+                equal &= (trunc.v[(i, j)] - res.v[(i, j)]).norm_sqr() < 0.0001;
+            }
+        }
+    }
+
+    equal &= svd_truncated(&lcg_matrix::<f64>(4, 3, 1), 4).is_err();
+
+    if equal {
+        debug!("truncated svd successful");
+    }
+    else {
+        debug!("truncated svd failed");
+    }
+
+    equal
+}
+
+/// Checks that orthonormalize gives orthonormal vectors spanning the same space, drops
+/// a dependent vector, and stays orthonormal for nearly dependent ones
+fn check_orthonormalize() -> bool {
+    let mut equal = true;
+
+    // the columns of an 8 x 6 matrix, column 3 a combination of columns 0 and 1
+    let mut a = lcg_matrix::<f64>(8, 6, 71);
+    for i in 0..8 {
+        a[(i, 3)] = a[(i, 0)] * Complex64::new(2.0, -1.0) - a[(i, 1)];
+    }
+    // ||Q* Q - I||, in the Frobenius norm
+    let orthogonality = |q: &Matrix<Complex64>| {
+        let mut gram = Matrix::identity(q.cols());
+        gemm(Complex64::new(1.0, 0.0), Op::ConjugateTranspose, q, Op::None, q, Complex64::new(-1.0, 0.0), &mut gram).unwrap();
+        norm2(gram.as_slice().iter().copied())
+    };
+
+    let mut q = a.clone();
+    equal &= orthonormalize(q.as_mut_slice(), 8, 6) == Ok(5);
+    equal &= (0..8).all(|i| q[(i, 3)] == Complex64::new(0.0, 0.0));
+    let kept = Matrix::from_vec((0..8).flat_map(|i| [0, 1, 2, 4, 5].map(|j| q[(i, j)])).collect(), 8, 5).unwrap();
+    equal &= orthogonality(&kept) < 1e-14;
+    // the first vector keeps its direction and the projection Q Q* reproduces every vector
+    let norm = norm2((0..8).map(|i| a[(i, 0)]));
+    equal &= (0..8).all(|i| (q[(i, 0)] * norm - a[(i, 0)]).norm_sqr() < 1e-24);
+    let mut projector = Matrix::zeros(8, 8);
+    equal &= gemm(Complex64::new(1.0, 0.0), Op::None, &kept, Op::ConjugateTranspose, &kept, Complex64::new(0.0, 0.0), &mut projector).is_ok();
+    let mut projected = Matrix::zeros(8, 6);
+    equal &= matrix_mult(&projector, &a, &mut projected).is_ok();
+    equal &= check_matrix_equality(&projected, &a);
+
+    // the columns of the Hilbert matrix are nearly dependent
+    let mut h = gallery::hilbert::<f64>(7);
+    equal &= orthonormalize(h.as_mut_slice(), 7, 7) == Ok(7);
+    equal &= orthogonality(&h) < 1e-14;
+
+    equal &= orthonormalize(&mut [Complex64::new(1.0, 0.0); 5], 3, 2) == Err(CsvdError::BufferTooSmall { operand: "vectors", required: 6, len: 5 });
+
+    if equal {
+        debug!("orthonormalize successful");
+    }
+    else {
+        debug!("orthonormalize failed");
+    }
+    equal
+}
+
+/// Checks the QR decomposition of a tall and a wide matrix:
+/// Q R must give back A, Q must have orthonormal columns and Q* A must give R
+fn check_qr() -> bool {
+    let mut equal = true;
+
+    for &(m, n) in &[(6, 4), (3, 5), (4, 4)] {
+        let a = lcg_matrix::<f64>(m, n, 41);
+        let f = match qr(&a) {
+            Ok(f) => f,
+            Err(_) => {
+                equal = false;
+                continue;
+            }
+        };
+        let k = m.min(n);
+        let q = f.q();
+        let r = f.r();
+
+        let mut qr_prod = Matrix::zeros(m, n);
+        equal &= matrix_mult(&q, &r, &mut qr_prod).is_ok();
+        equal &= check_matrix_equality(&a, &qr_prod);
+
+        let mut q_h = Matrix::zeros(k, m);
+        for i in 0..m {
+            for j in 0..k {
+                q_h[(j, i)] = q[(i, j)].conj();
+            }
+        }
+        let mut identity = Matrix::zeros(k, k);
+        equal &= matrix_mult(&q_h, &q, &mut identity).is_ok();
+        for i in 0..k {
+            identity[(i, i)] -= Complex64::new(1.0, 0.0);
+        }
+        equal &= check_matrix_equality(&identity, &Matrix::zeros(k, k));
+
+        let mut b = a.clone();
+        equal &= f.apply_qt(&mut b).is_ok();
+        for i in 0..m {
+            for j in 0..n {
+                let expected = if i < k { r[(i, j)] } else { Complex64::new(0.0, 0.0) };
+                equal &= (b[(i, j)] - expected).norm_sqr() < 0.0001;
+            }
+        }
+        equal &= f.apply_q(&mut b).is_ok();
+        equal &= check_matrix_equality(&a, &b);
+    }
+
+    if equal {
+        debug!("qr successful");
+    }
+    else {
+        debug!("qr failed");
+    }
+
+    equal
+}
+
+/// Checks the pivoted QR decomposition of a rank deficient matrix:
+/// Q R must give back A P, the diagonal of R must not increase and reveal the rank
+fn check_qr_pivoted() -> bool {
+    let mut equal = true;
+
+    // a 7 x 5 matrix of rank 3, the product of a 7 x 3 and a 3 x 5 matrix
+    let mut a = Matrix::zeros(7, 5);
+    equal &= matrix_mult(&lcg_matrix::<f64>(7, 3, 43), &lcg_matrix::<f64>(3, 5, 47), &mut a).is_ok();
+
+    match qr_pivoted(&a) {
+        Ok(f) => {
+            let mut qr_prod = Matrix::zeros(7, 5);
+            equal &= matrix_mult(&f.q(), &f.r(), &mut qr_prod).is_ok();
+
+            let mut a_p = Matrix::zeros(7, 5);
+            for i in 0..7 {
+                for j in 0..5 {
+                    a_p[(i, j)] = a[(i, f.perm()[j])];
+                }
+            }
+            equal &= check_matrix_equality(&a_p, &qr_prod);
+
+            let r = f.r();
+            for i in 1..5 {
+                equal &= r[(i, i)].norm_sqr() <= r[(i - 1, i - 1)].norm_sqr() + 1e-12;
+            }
+            equal &= f.rank(1e-8) == 3;
+        }
+        Err(_) => equal = false,
+    }
+
+    if equal {
+        debug!("pivoted qr successful");
+    }
+    else {
+        debug!("pivoted qr failed");
+    }
+
+    equal
+}
+
+/// Checks the LU decomposition and inverse of a square matrix against pinv,
+/// that a singular matrix is rejected, and the condition number estimate
+fn check_lu() -> bool {
+    let mut equal = true;
+
+    let a = lcg_matrix::<f64>(8, 8, 53);
+    match (lu(&a), inv(&a)) {
+        (Ok(f), Ok(inverse)) => {
+            let mut lu_prod = Matrix::zeros(8, 8);
+            equal &= matrix_mult(&f.l(), &f.u(), &mut lu_prod).is_ok();
+            let mut p_a = Matrix::zeros(8, 8);
+            for i in 0..8 {
+                p_a.row_mut(i).copy_from_slice(a.row(f.perm()[i]));
+            }
+            equal &= check_matrix_equality(&p_a, &lu_prod);
+
+            equal &= check_matrix_equality(&inverse, &svd(&a).unwrap().pinv());
+        }
+        _ => equal = false,
+    }
+
+    // two equal rows
+    let mut singular = lcg_matrix::<f64>(3, 3, 59);
+    for j in 0..3 {
+        singular[(2, j)] = singular[(0, j)];
+    }
+    match lu(&singular) {
+        Ok(f) => equal &= f.u()[(2, 2)].norm_sqr() < 1e-20,
+        Err(CsvdError::Singular { .. }) => {}
+        Err(_) => equal = false,
+    }
+
+    // the estimate is a lower bound within a factor of 3 of ||A||_1 ||A^-1||_1
+    let norm1 = |a: &Matrix<Complex64>| (0..a.cols()).map(|j| (0..a.rows()).map(|i| cabs(&a[(i, j)])).sum::<f64>()).fold(0.0, f64::max);
+    let mut ill = lcg_matrix::<f64>(8, 8, 61);
+    for j in 0..8 {
+        ill[(7, j)] = ill[(6, j)] + Complex64::new(1e-9 * j as f64, 0.0);
+    }
+    for a in [&a, &ill, &Matrix::identity(5)] {
+        let exact = norm1(a) * norm1(&inv(a).unwrap());
+        equal &= cond_estimate(a).is_ok_and(|c| c <= exact * (1.0 + 1e-10) && 3.0 * c >= exact);
+    }
+    equal &= cond_estimate(&ill).is_ok_and(|c| c > 1e8);
+    equal &= cond_estimate(&Matrix::<Complex64>::zeros(3, 3)) == Ok(f64::INFINITY);
+    equal &= cond_estimate(&lcg_matrix::<f64>(3, 4, 67)) == Err(CsvdError::DimensionMismatch { operand: "a", expected: (3, 3), found: (3, 4) });
+
+    if equal {
+        debug!("lu successful");
+    }
+    else {
+        debug!("lu failed");
+    }
+
+    equal
+}
+
+/// Checks the least-squares solver against the pseudo-inverse for an overdetermined
+/// and a rank deficient system, and that a consistent system has no residual, and that
+/// the backward errors of the solutions tell them from wrong ones
+fn check_lstsq() -> bool {
+    let mut equal = true;
+
+    let a = lcg_matrix::<f64>(9, 4, 61);
+    let b = lcg_matrix::<f64>(9, 2, 67);
+    match lstsq(&a, &b, 1e-10) {
+        Ok(sol) => {
+            let mut x = Matrix::zeros(4, 2);
+            equal &= matrix_mult(&svd(&a).unwrap().pinv(), &b, &mut x).is_ok();
+            equal &= check_matrix_equality(&x, &sol.x);
+            equal &= sol.rank == 4 && sol.residuals.len() == 2 && sol.residuals[0] > 0.01;
+
+            // the system is inconsistent, so only the normal residual is small
+            let report = verify_lstsq(&a, &sol.x, &b, 1e-10).unwrap();
+            equal &= report.passed() && report.normal_residual < 1e-12 && report.backward_error > 0.01;
+            equal &= (report.residual - norm2(sol.residuals.iter().map(|&r| Complex64::new(r, 0.0)))).abs() < 1e-12;
+            let mut wrong = sol.x.clone();
+            wrong[(1, 0)] += Complex64::new(0.01, 0.0);
+            equal &= verify_lstsq(&a, &wrong, &b, 1e-10).is_ok_and(|r| !r.passed() && r.normal_residual > 1e-4);
+        }
+        Err(_) => equal = false,
+    }
+    equal &= verify_lstsq(&a, &Matrix::zeros(4, 3), &b, 1e-10)
+        == Err(CsvdError::DimensionMismatch { operand: "x", expected: (4, 2), found: (4, 3) });
+
+    // b in the range of a rank 2 matrix
+    let mut a = Matrix::zeros(6, 5);
+    equal &= matrix_mult(&lcg_matrix::<f64>(6, 2, 71), &lcg_matrix::<f64>(2, 5, 73), &mut a).is_ok();
+    let mut b = Matrix::zeros(6, 1);
+    equal &= matrix_mult(&a, &lcg_matrix::<f64>(5, 1, 79), &mut b).is_ok();
+    match lstsq(&a, &b, 1e-10) {
+        Ok(sol) => {
+            equal &= sol.rank == 2 && sol.residuals[0] < 1e-8;
+            equal &= verify_lstsq(&a, &sol.x, &b, 1e-12).is_ok_and(|r| r.passed() && r.backward_error < 1e-14);
+        }
+        Err(_) => equal = false,
+    }
+    // a zero right-hand side is solved exactly by zero
+    equal &= verify_lstsq(&a, &Matrix::zeros(5, 1), &Matrix::zeros(6, 1), 0.0)
+        .is_ok_and(|r| r.passed() && r.residual == 0.0 && r.normal_residual == 0.0);
+
+    if equal {
+        debug!("lstsq successful");
+    }
+    else {
+        debug!("lstsq failed");
+    }
+
+    equal
+}
+
+/// Checks that applying the factored pseudo-inverse to the columns of b
+/// gives the same vectors as multiplying by the dense pseudo-inverse
+fn check_pinv_operator() -> bool {
+    let mut equal = true;
+
+    for &(m, n) in &[(6, 4), (3, 5)] {
+        let a = lcg_matrix::<f64>(m, n, 83);
+        let b = lcg_matrix::<f64>(m, 3, 89);
+
+        let op = match PinvOperator::new(&a) {
+            Ok(op) => op,
+            Err(_) => {
+                equal = false;
+                continue;
+            }
+        };
+
+        let mut x = Matrix::zeros(n, 3);
+        equal &= matrix_mult(&svd(&a).unwrap().pinv(), &b, &mut x).is_ok();
+
+        let mut col = Vec::new();
+        let mut out = Vec::new();
+        out.resize(n, Complex64::new(0.0, 0.0));
+        for j in 0..3 {
+            col.clear();
+            col.extend((0..m).map(|i| b[(i, j)]));
+
+            equal &= op.apply(&col, &mut out).is_ok();
+            for i in 0..n {
+                equal &= (out[i] - x[(i, j)]).norm_sqr() < 0.0001;
+            }
+        }
+
+        equal &= op.apply(&out, &mut col).is_err() || m == n;
+    }
+
+    // a singular value far below 1e-4 but above max(m, n) eps: the relative default keeps
+    // it like pinv_with_options does, the absolute legacy cut-off drops it
+    let a = gallery::with_singular_values::<f64>(6, 4, &[2.0, 1.0, 0.5, 1e-7], 91).unwrap();
+    let res = svd(&a).unwrap();
+    let b: Vec<Complex64> = (0..6).map(|i| Complex64::new(i as f64, 1.0)).collect();
+    for (options, rank) in [(PinvOptions::default(), 4), (PinvOptions::absolute(0.0001), 3)] {
+        let op = PinvOperator::new_with_options(&a, &options).unwrap();
+        let inv = res.pinv_with_options(&options);
+        let mut out = alloc::vec![Complex64::new(0.0, 0.0); 4];
+        equal &= op.rank() == rank && op == PinvOperator::from_svd_with_options(&res, &options);
+        equal &= op.apply(&b, &mut out).is_ok();
+        for i in 0..4 {
+            let expected = (0..6).fold(Complex64::new(0.0, 0.0), |acc, j| acc + inv[(i, j)] * b[j]);
+            equal &= cabs(&(out[i] - expected)) <= 1e-8 * cabs(&expected).max(1.0);
+        }
+    }
+    equal &= PinvOperator::new(&a).unwrap() == PinvOperator::from_svd_with_options(&res, &PinvOptions::absolute(0.0001));
+
+    if equal {
+        debug!("pinv operator successful");
+    }
+    else {
+        debug!("pinv operator failed");
+    }
+
+    equal
+}
+
+/// Checks the regularized pseudo-inverse against the MMSE formula (A* A + lambda I)^-1 A*
+/// and that lambda = 0 gives the plain pseudo-inverse
+fn check_pinv_regularized() -> bool {
+    let mut equal = true;
+    let lambda = 0.3;
+
+    let a = lcg_matrix::<f64>(7, 4, 97);
+    let mut a_h = Matrix::zeros(4, 7);
+    for i in 0..7 {
+        for j in 0..4 {
+            a_h[(j, i)] = a[(i, j)].conj();
+        }
+    }
+
+    let mut gram = Matrix::zeros(4, 4);
+    equal &= matrix_mult(&a_h, &a, &mut gram).is_ok();
+    for i in 0..4 {
+        gram[(i, i)] += Complex64::new(lambda, 0.0);
+    }
+    let mut mmse = Matrix::zeros(4, 7);
+    equal &= matrix_mult(&inv(&gram).unwrap(), &a_h, &mut mmse).is_ok();
+
+    match pinv_regularized(&a, lambda) {
+        Ok(reg) => equal &= check_matrix_equality(&mmse, &reg),
+        Err(_) => equal = false,
+    }
+
+    let res = svd(&a).unwrap();
+    equal &= check_matrix_equality(&res.pinv(), &res.pinv_regularized(0.0));
+
+    if equal {
+        debug!("regularized pinv successful");
+    }
+    else {
+        debug!("regularized pinv failed");
+    }
+
+    equal
+}
+
+/// Checks that the mixed-precision pinv and least-squares solution agree with the
+/// double-precision ones to far more digits than a single-precision decomposition has
+fn check_mixed_precision() -> bool {
+    let mut equal = true;
+    let max_diff = |a: &Matrix<Complex64>, b: &Matrix<Complex64>| {
+        a.as_slice().iter().zip(b.as_slice()).fold(0.0f64, |acc, (x, y)| acc.max((x - y).norm_sqr()))
+    };
+
+    for &(m, n) in &[(8, 4), (5, 5), (3, 6)] {
+        let a = lcg_matrix::<f64>(m, n, 73);
+        let reference = svd(&a).unwrap().pinv_with_options(&PinvOptions::default());
+
+        let single = svd(&lcg_matrix::<f32>(m, n, 73)).unwrap().pinv_with_options(&PinvOptions::default());
+        let single = Matrix::from_vec(single.as_slice().iter().map(|x| Complex64::new(x.re as f64, x.im as f64)).collect(), n, m).unwrap();
+
+        match pinv_mixed(&a) {
+            Ok(inv) => {
+                equal &= max_diff(&inv, &reference) < 1e-24;
+                equal &= max_diff(&single, &reference) > 1e-20;
+            }
+            Err(_) => equal = false,
+        }
+    }
+
+    // an inconsistent system, whose residual stays large
+    let a = lcg_matrix::<f64>(9, 3, 79);
+    let b = lcg_matrix::<f64>(9, 2, 83);
+    let reference = lstsq(&a, &b, 1e-12).unwrap().x;
+    equal &= lstsq_mixed(&a, &b).is_ok_and(|x| max_diff(&x, &reference) < 1e-24);
+    equal &= lstsq_mixed(&a, &lcg_matrix::<f64>(8, 2, 83)).is_err();
+
+    if equal {
+        debug!("mixed precision successful");
+    }
+    else {
+        debug!("mixed precision failed");
+    }
+    equal
+}
+
+/// Checks the relative cut-off of the pseudo-inverse on a matrix whose singular values
+/// are all far below the old absolute threshold of 1e-4
+fn check_pinv_options() -> bool {
+    let mut equal = true;
+
+    let mut a = lcg_matrix::<f64>(6, 4, 101);
+    for i in 0..6 {
+        for j in 0..4 {
+            a[(i, j)] *= 1e-7;
+        }
+    }
+    let res = svd(&a).unwrap();
+
+    // the default relative cut-off keeps every singular value, so A+ A = I
+    let inverse = res.pinv_with_options(&PinvOptions::default());
+    let mut identity = Matrix::zeros(4, 4);
+    equal &= matrix_mult(&inverse, &a, &mut identity).is_ok();
+    for i in 0..4 {
+        identity[(i, i)] -= Complex64::new(1.0, 0.0);
+    }
+    equal &= check_matrix_equality(&identity, &Matrix::zeros(4, 4));
+
+    // the absolute threshold discards all of them
+    equal &= check_matrix_equality(&res.pinv(), &Matrix::zeros(4, 6));
+
+    // a relative cut-off above the condition number discards all but the largest one
+    let options = PinvOptions { atol: 0.0, rcond: Some(res.s[1] / res.s[0] * 1.01) };
+    let mut expected = Matrix::zeros(4, 6);
+    for i in 0..4 {
+        for j in 0..6 {
+            expected[(i, j)] = res.v[(i, 0)] * (1.0 / res.s[0]) * res.u[(j, 0)].conj();
+        }
+    }
+    let inverse = res.pinv_with_options(&options);
+    for i in 0..4 {
+        for j in 0..6 {
+            // relative comparison, the entries are around 1e7
+            equal &= (inverse[(i, j)] - expected[(i, j)]).norm_sqr() < 1e-4 * expected[(i, j)].norm_sqr() + 1e-20;
+        }
+    }
+
+    if equal {
+        debug!("pinv options successful");
+    }
+    else {
+        debug!("pinv options failed");
+    }
+
+    equal
+}
+
+/// Checks the rank with the default and an explicit tolerance, and the condition number
+fn check_rank_cond() -> bool {
+    let mut equal = true;
+
+    // a 6 x 5 matrix of rank 2
+    let mut a = Matrix::zeros(6, 5);
+    equal &= matrix_mult(&lcg_matrix::<f64>(6, 2, 103), &lcg_matrix::<f64>(2, 5, 107), &mut a).is_ok();
+    equal &= rank(&a, None) == Ok(2);
+    equal &= rank(&a, Some(1e3)) == Ok(0);
+    equal &= cond(&a).is_ok_and(|c| c > 1e10);
+
+    let a = lcg_matrix::<f64>(5, 5, 109);
+    let s = singular_values(&a).unwrap();
+    equal &= rank(&a, None) == Ok(5);
+    equal &= cond(&a).is_ok_and(|c| (c - s[0] / s[4]).abs() < 1e-8);
+
+    // a unitary matrix has condition number 1
+    equal &= cond(&svd(&a).unwrap().u).is_ok_and(|c| (c - 1.0).abs() < 1e-8);
+    equal &= svd(&a).is_ok_and(|res| (res.cond() - s[0] / s[4]).abs() < 1e-8);
+
+    // without singular values the condition number is undefined
+    equal &= svd_truncated(&a, 0).is_ok_and(|res| res.cond().is_nan());
+
+    if equal {
+        debug!("rank and cond successful");
+    }
+    else {
+        debug!("rank and cond failed");
+    }
+
+    equal
+}
+
+/// Checks the rank from the largest gap in the singular values for a noisy low-rank
+/// matrix, for which the threshold of rank fails, and for full rank and zero matrices
+fn check_rank_by_gap() -> bool {
+    let mut equal = true;
+
+    // a rank 2 channel with noise 1e-6 below it
+    let mut a = Matrix::zeros(6, 4);
+    equal &= matrix_mult(&lcg_matrix::<f64>(6, 2, 263), &lcg_matrix::<f64>(2, 4, 269), &mut a).is_ok();
+    let mut noise = lcg_matrix::<f64>(6, 4, 271);
+    noise.scale(Complex64::new(1e-6, 0.0));
+    let noisy = a.add(&noise).unwrap();
+    equal &= rank(&noisy, None) == Ok(4);
+    equal &= rank_by_gap(&noisy, 10.0).is_ok_and(|r| r.rank == 2 && r.gap > 1e4 && r.gap.is_finite());
+    equal &= rank_by_gap(&a, 10.0).is_ok_and(|r| r.rank == 2 && r.gap == f64::INFINITY);
+    equal &= svd(&noisy).unwrap().rank_by_gap(10.0) == rank_by_gap(&noisy, 10.0).unwrap();
+
+    let a = gallery::with_singular_values::<f64>(6, 5, &[5.0, 4.0, 3.0, 1e-3, 5e-4], 277).unwrap();
+    equal &= rank_by_gap(&a, 10.0).is_ok_and(|r| r.rank == 3 && (r.gap - 3e3).abs() < 1e-6);
+    // no gap of at least min_gap leaves the full rank
+    equal &= rank_by_gap(&a, 1e4).is_ok_and(|r| r.rank == 5 && (r.gap - 3e3).abs() < 1e-6);
+    equal &= rank_by_gap(&Matrix::<Complex64>::identity(4), 10.0) == Ok(GapRank { rank: 4, gap: 1.0 });
+    equal &= rank_by_gap(&Matrix::<Complex64>::zeros(3, 4), 10.0) == Ok(GapRank { rank: 0, gap: 1.0 });
+
+    if equal {
+        debug!("rank by gap successful");
+    }
+    else {
+        debug!("rank by gap failed");
+    }
+
+    equal
+}
+
+/// Checks the null space and column space bases of a rank deficient wide matrix:
+/// A N must vanish and the column space basis must reproduce A when projected onto
+fn check_subspaces() -> bool {
+    let mut equal = true;
+
+    // a 4 x 6 matrix of rank 3
+    let mut a = Matrix::zeros(4, 6);
+    equal &= matrix_mult(&lcg_matrix::<f64>(4, 3, 113), &lcg_matrix::<f64>(3, 6, 127), &mut a).is_ok();
+
+    match (null_space(&a, None), column_space(&a, None)) {
+        (Ok(null), Ok(range)) => {
+            equal &= null.rows() == 6 && null.cols() == 3;
+            equal &= range.rows() == 4 && range.cols() == 3;
+
+            let mut prod = Matrix::zeros(4, 3);
+            equal &= matrix_mult(&a, &null, &mut prod).is_ok();
+            equal &= check_matrix_equality(&prod, &Matrix::zeros(4, 3));
+
+            // A = Q Q* A for the orthonormal basis Q of the range
+            let mut q_h = Matrix::zeros(3, 4);
+            for i in 0..4 {
+                for j in 0..3 {
+                    q_h[(j, i)] = range[(i, j)].conj();
+                }
+            }
+            let mut q_h_a = Matrix::zeros(3, 6);
+            equal &= matrix_mult(&q_h, &a, &mut q_h_a).is_ok();
+            let mut projected = Matrix::zeros(4, 6);
+            equal &= matrix_mult(&range, &q_h_a, &mut projected).is_ok();
+            equal &= check_matrix_equality(&a, &projected);
+        }
+        _ => equal = false,
+    }
+
+    if equal {
+        debug!("subspaces successful");
+    }
+    else {
+        debug!("subspaces failed");
+    }
+
+    equal
+}
+
+/// Checks the rank-k approximation: it must have rank k, its error must be sigma_{k+1},
+/// and k = min(m, n) must give back the matrix itself
+fn check_low_rank_approx() -> bool {
+    let mut equal = true;
+
+    let a = lcg_matrix::<f64>(7, 5, 131);
+    let s = singular_values(&a).unwrap();
+
+    match low_rank_approx(&a, 2) {
+        Ok(approx) => {
+            equal &= (approx.error - s[2]).abs() < 1e-8;
+            equal &= rank(&approx.approx, None) == Ok(2);
+
+            let mut diff = a.clone();
+            for i in 0..7 {
+                for j in 0..5 {
+                    diff[(i, j)] -= approx.approx[(i, j)];
+                }
+            }
+            equal &= singular_values(&diff).is_ok_and(|d| (d[0] - s[2]).abs() < 1e-8);
+        }
+        Err(_) => equal = false,
+    }
+
+    match low_rank_approx(&a, 5) {
+        Ok(approx) => equal &= approx.error == 0.0 && check_matrix_equality(&a, &approx.approx),
+        Err(_) => equal = false,
+    }
+    equal &= low_rank_approx(&a, 6).is_err();
+
+    if equal {
+        debug!("low rank approximation successful");
+    }
+    else {
+        debug!("low rank approximation failed");
+    }
+
+    equal
+}
+
+/// Checks rank-one updates of tall, wide and square decompositions against a new
+/// decomposition of the updated matrix, including a matrix built up from zero
+fn check_rank_one_update() -> bool {
+    let mut equal = true;
+
+    for (m, n, seed) in [(7, 4, 151), (4, 7, 157), (5, 5, 163)] {
+        let a = lcg_matrix::<f64>(m, n, seed);
+        let x = lcg_matrix::<f64>(m, 1, seed + 1).into_vec();
+        let y = lcg_matrix::<f64>(n, 1, seed + 2).into_vec();
+
+        let mut res = svd(&a).unwrap();
+        equal &= res.rank_one_update(&x, &y).is_ok();
+        let updated = a.add(&outer(&x, &y.iter().map(|y| y.conj()).collect::<Vec<_>>())).unwrap();
+        equal &= verify_svd(&updated, &res, 1e-10).is_ok_and(|report| report.passed());
+
+        let expected = svd(&updated).unwrap();
+        equal &= res.s.iter().zip(&expected.s).all(|(s, e)| (s - e).abs() < 1e-10);
+        // distinct singular values leave only the phases open, which both fix the same way
+        for k in 0..m.min(n) {
+            equal &= (0..m).all(|i| (res.u[(i, k)] - expected.u[(i, k)]).norm_sqr() < 1e-16);
+            equal &= (0..n).all(|i| (res.v[(i, k)] - expected.v[(i, k)]).norm_sqr() < 1e-16);
+        }
+    }
+
+    // a rank-three matrix as the sum of three outer products, starting from zero
+    let mut res = svd(&Matrix::zeros(6, 4)).unwrap();
+    let mut sum = Matrix::zeros(6, 4);
+    for seed in 0..3 {
+        let x = lcg_matrix::<f64>(6, 1, 167 + seed).into_vec();
+        let y = lcg_matrix::<f64>(4, 1, 173 + seed).into_vec();
+        equal &= res.rank_one_update(&x, &y).is_ok();
+        sum = sum.add(&outer(&x, &y.iter().map(|y| y.conj()).collect::<Vec<_>>())).unwrap();
+    }
+    equal &= verify_svd(&sum, &res, 1e-10).is_ok_and(|report| report.passed());
+    equal &= res.s[3] < 1e-12 && res.s[2] > 1e-3;
+
+    equal &= res.rank_one_update(&[Complex64::new(1.0, 0.0); 5], &[Complex64::new(1.0, 0.0); 4]).is_err();
+    let mut truncated = svd_truncated(&lcg_matrix::<f64>(6, 4, 179), 2).unwrap();
+    equal &= truncated.rank_one_update(&[Complex64::new(1.0, 0.0); 6], &[Complex64::new(1.0, 0.0); 4]).is_err();
+
+    if equal {
+        debug!("rank-one update successful");
+    }
+    else {
+        debug!("rank-one update failed");
+    }
+
+    equal
+}
+
+/// Checks the streaming decomposition of a rank-three matrix row by row against the
+/// decomposition of the whole matrix, with and without the left singular vectors
+fn check_incremental_svd() -> bool {
+    let mut equal = true;
+
+    let a = gallery::rank_deficient::<f64>(40, 6, 3, 181).unwrap();
+    let expected = svd(&a).unwrap();
+
+    let mut inc = IncrementalSvd::new(6, 3, true).unwrap();
+    equal &= inc.push_rows(&a).is_ok();
+    equal &= inc.rows() == 40 && inc.rank() == 3;
+    equal &= inc.singular_values().iter().zip(&expected.s).all(|(s, e)| (s - e).abs() < 1e-10);
+    let res = SvdResult { u: inc.u().unwrap().clone(), s: inc.singular_values().to_vec(), v: inc.v().clone() };
+    equal &= check_matrix_equality(&res.reconstruct(), &a);
+    for k in 0..3 {
+        equal &= (0..40).all(|i| (res.u[(i, k)] - expected.u[(i, k)]).norm_sqr() < 1e-16);
+    }
+
+    // without U the right singular vectors span the same subspace
+    let mut subspace = IncrementalSvd::new(6, 3, false).unwrap();
+    for i in 0..40 {
+        equal &= subspace.push_row(a.row(i)).is_ok();
+    }
+    equal &= subspace.u().is_none();
+    for k in 0..3 {
+        let overlap: f64 = (0..3).map(|l| (0..6).fold(Complex64::new(0.0, 0.0), |acc, i| acc + subspace.v()[(i, l)].conj() * expected.v[(i, k)]).norm_sqr()).sum();
+        equal &= (overlap - 1.0).abs() < 1e-10;
+    }
+
+    // keeping all singular values of a full-rank matrix gives its decomposition
+    let b = lcg_matrix::<f64>(9, 4, 191);
+    let mut full = IncrementalSvd::new(4, 4, true).unwrap();
+    equal &= full.push_rows(&b).is_ok();
+    equal &= singular_values(&b).is_ok_and(|s| s.iter().zip(full.singular_values()).all(|(s, e)| (s - e).abs() < 1e-10));
+
+    equal &= full.push_row(&[Complex64::new(1.0, 0.0); 3]).is_err();
+    equal &= matches!(full.push_row(&[Complex64::new(f64::NAN, 0.0); 4]), Err(CsvdError::NonFiniteInput { row: 9, col: 0 }));
+    equal &= IncrementalSvd::<f64>::new(4, 5, false).is_err();
+
+    if equal {
+        debug!("incremental svd successful");
+    }
+    else {
+        debug!("incremental svd failed");
+    }
+
+    equal
+}
+
+/// Checks the smallest singular value from inverse iteration against the full decomposition,
+/// for tall, wide, rank deficient and nearly degenerate matrices
+fn check_sigma_min() -> bool {
+    let mut equal = true;
+
+    let row_major = |a: &Matrix<Complex64>| a.to_layout(Layout::RowMajor).into_vec();
+    for (m, n) in [(8, 5), (5, 8), (6, 6)] {
+        let a = gallery::with_singular_values::<f64>(m, n, &[4.0, 2.0, 1.0, 0.5, 0.1], 239).unwrap();
+        equal &= sigma_min(&row_major(&a), m, n).is_ok_and(|s| (s - if m == n { 0.0 } else { 0.1 }).abs() < 1e-12);
+    }
+
+    let a = lcg_matrix::<f64>(9, 7, 241);
+    let s = singular_values(&a).unwrap();
+    equal &= sigma_min(&row_major(&a), 9, 7).is_ok_and(|x| (x - s[6]).abs() < 1e-12);
+    let a = gallery::rank_deficient::<f64>(7, 5, 3, 251).unwrap();
+    equal &= sigma_min(&row_major(&a), 7, 5).is_ok_and(|x| x < 1e-12);
+
+    // two smallest singular values 1e-9 apart, which the iteration cannot tell apart in time
+    let a = gallery::with_singular_values::<f64>(6, 4, &[3.0, 2.0, 1.0 + 1e-9, 1.0], 257).unwrap();
+    equal &= sigma_min(&row_major(&a), 6, 4).is_ok_and(|x| (x - 1.0).abs() < 1e-12);
+
+    equal &= sigma_min(&[Complex64::new(1.0, 0.0); 5], 2, 3) == Err(CsvdError::BufferTooSmall { operand: "A", required: 6, len: 5 });
+    equal &= sigma_min::<f64>(&[], 0, 3) == Err(CsvdError::EmptyMatrix);
+
+    if equal {
+        debug!("sigma min successful");
+    }
+    else {
+        debug!("sigma min failed");
+    }
+    equal
+}
+
+/// Checks the power iteration against the largest singular triplet of the full
+/// decomposition, for tall, wide and zero matrices
+fn check_dominant_triplet() -> bool {
+    let mut equal = true;
+
+    for (m, n) in [(8, 5), (5, 8)] {
+        let a = gallery::with_singular_values::<f64>(m, n, &[4.0, 2.0, 1.0, 0.5, 0.1], 193).unwrap();
+        let expected = svd(&a).unwrap();
+        let data = a.to_layout(Layout::RowMajor).into_vec();
+
+        match dominant_singular_triplet(&data, m, n, 100, 1e-15) {
+            Ok(t) => {
+                equal &= t.converged && t.iterations < 100;
+                equal &= (t.sigma - 4.0).abs() < 1e-12;
+                equal &= (0..m).all(|i| (t.u[i] - expected.u[(i, 0)]).norm_sqr() < 1e-12);
+                equal &= (0..n).all(|i| (t.v[i] - expected.v[(i, 0)]).norm_sqr() < 1e-12);
+            }
+            Err(_) => equal = false,
+        }
+
+        // every iteration tightens the lower bound on sigma_max
+        match (dominant_singular_triplet(&data, m, n, 2, 0.0), dominant_singular_triplet(&data, m, n, 5, 0.0)) {
+            (Ok(t2), Ok(t5)) => equal &= !t2.converged && t2.sigma <= t5.sigma && t5.sigma <= 4.0 + 1e-12,
+            _ => equal = false,
+        }
+    }
+
+    let zeros = alloc::vec![Complex64::new(0.0, 0.0); 12];
+    equal &= dominant_singular_triplet(&zeros, 3, 4, 10, 1e-12).is_ok_and(|t| t.sigma == 0.0 && t.converged);
+    equal &= dominant_singular_triplet(&zeros, 4, 4, 10, 1e-12).is_err();
+
+    if equal {
+        debug!("dominant singular triplet successful");
+    }
+    else {
+        debug!("dominant singular triplet failed");
+    }
+
+    equal
+}
+
+/// Checks the Lanczos partial decomposition against the full one for tall and wide
+/// matrices, a repeated and a zero singular value and a step limit that is too low
+fn check_partial_svd() -> bool {
+    let mut equal = true;
+    let options = LanczosOptions::default();
+    let s: Vec<f64> = (0..30).map(|i| 10.0 * Float::powi(0.8, i)).collect();
+
+    for (m, n) in [(60, 30), (30, 50)] {
+        let a = gallery::with_singular_values(m, n, &s, 197).unwrap();
+        let expected = svd(&a).unwrap();
+        match partial_svd(&a, 4, &options) {
+            Ok(res) => {
+                equal &= res.u.rows() == m && res.u.cols() == 4 && res.v.rows() == n && res.v.cols() == 4;
+                equal &= res.s.iter().zip(&expected.s).all(|(s, e)| (s - e).abs() < 1e-10);
+                for k in 0..4 {
+                    equal &= (0..m).all(|i| (res.u[(i, k)] - expected.u[(i, k)]).norm_sqr() < 1e-12);
+                    equal &= (0..n).all(|i| (res.v[(i, k)] - expected.v[(i, k)]).norm_sqr() < 1e-12);
+                }
+            }
+            Err(_) => equal = false,
+        }
+    }
+
+    // a single Krylov space holds every distinct singular value only once
+    let repeated = Matrix::from_diag(&[3.0, 1.0, 3.0, 1.0]);
+    equal &= partial_svd(&repeated, 2, &options).is_ok_and(|res| (res.s[0] - 3.0).abs() < 1e-12 && (res.s[1] - 3.0).abs() < 1e-12);
+
+    let low_rank = gallery::rank_deficient::<f64>(50, 30, 3, 199).unwrap();
+    equal &= partial_svd(&low_rank, 5, &options).is_ok_and(|res| {
+        res.s.iter().zip(&[3.0, 2.0, 1.0, 0.0, 0.0]).all(|(s, e)| (s - e).abs() < 1e-10)
+    });
+
+    let a = gallery::with_singular_values(60, 30, &s, 197).unwrap();
+    equal &= matches!(partial_svd(&a, 4, &LanczosOptions { max_steps: 5, ..options }), Err(CsvdError::NotConverged { .. }));
+    equal &= partial_svd(&a, 31, &options).is_err();
+    // the step budget is honored, not raised to k
+    equal &= partial_svd(&a, 4, &LanczosOptions { max_steps: 3, ..options }) == Err(CsvdError::InvalidArgument { name: "k", value: 4, max: 3 });
+    equal &= partial_svd(&a, 0, &options).is_ok_and(|res| res.s.is_empty() && res.cond().is_nan());
+
+    if equal {
+        debug!("partial svd successful");
+    }
+    else {
+        debug!("partial svd failed");
+    }
+
+    equal
+}
+
+/// Checks the CSR assembly against the dense matrix, `spmv` against `gemv` for all three
+/// operations, the validation of the CSR arrays, and the partial decomposition and the
+/// power iteration of a sparse matrix against those of its dense copy
+fn check_sparse() -> bool {
+    let mut equal = true;
+    let c = |re: f64, im: f64| Complex::new(re, im);
+
+    // a tridiagonal matrix assembled from triplets, with a repeated entry adding up
+    let (m, n): (usize, usize) = (40, 30);
+    let mut triplets = Vec::new();
+    for i in 0..m {
+        for j in i.saturating_sub(1)..(i + 2).min(n) {
+            triplets.push((i, j, c(1.0 + (i * j % 7) as f64, (i as f64 - j as f64) * 0.5)));
+        }
+    }
+    triplets.push((3, 2, c(0.25, -1.0)));
+    triplets.reverse();
+    let a = CsrMatrix::from_triplets(m, n, &triplets).unwrap();
+    let dense = a.to_dense();
+    equal &= a.nnz() == triplets.len() - 1 && a.row_ptr().len() == m + 1;
+    equal &= (dense[(3, 2)] - c(1.0 + 6.0, 0.5) - c(0.25, -1.0)).norm_sqr() < 1e-24;
+    equal &= CsrMatrix::from_dense(&dense) == a;
+
+    let x: Vec<Complex64> = (0..m).map(|i| c(i as f64 * 0.1, 1.0 - i as f64 * 0.05)).collect();
+    for op in [Op::None, Op::Transpose, Op::ConjugateTranspose] {
+        let len = if op == Op::None { (n, m) } else { (m, n) };
+        let (alpha, beta) = (c(0.5, -2.0), c(-1.0, 0.25));
+        let mut y = alloc::vec![c(1.0, 1.0); len.1];
+        let mut expected = y.clone();
+        equal &= spmv(alpha, op, &a, &x[..len.0], beta, &mut y).is_ok();
+        equal &= gemv(alpha, op, &dense, &x[..len.0], beta, &mut expected).is_ok();
+        equal &= y.iter().zip(&expected).all(|(y, e)| (y - e).norm_sqr() < 1e-20);
+    }
+    equal &= spmv(c(1.0, 0.0), Op::None, &a, &x, c(0.0, 0.0), &mut alloc::vec![c(0.0, 0.0); m]).is_err();
+
+    let values = alloc::vec![c(1.0, 0.0); 3];
+    equal &= CsrMatrix::new(2, 3, alloc::vec![0, 2, 3], alloc::vec![0, 2, 1], values.clone()).is_ok();
+    equal &= matches!(CsrMatrix::new(2, 3, alloc::vec![0, 2, 1], alloc::vec![0, 2, 1], values.clone()), Err(CsvdError::InvalidArgument { name: "row_ptr", .. }));
+    equal &= matches!(CsrMatrix::new(2, 3, alloc::vec![0, 2, 3], alloc::vec![0, 3, 1], values.clone()), Err(CsvdError::InvalidArgument { name: "col_idx", .. }));
+    equal &= matches!(CsrMatrix::new(2, 3, alloc::vec![0, 2, 4], alloc::vec![0, 2, 1], values), Err(CsvdError::DimensionMismatch { operand: "col_idx", .. }));
+    equal &= CsrMatrix::<f64>::from_triplets(2, 3, &[(2, 0, c(1.0, 0.0))]).is_err();
+
+    let expected = svd(&dense).unwrap();
+    match partial_svd(&a, 3, &LanczosOptions::default()) {
+        Ok(res) => {
+            equal &= res.s.iter().zip(&expected.s).all(|(s, e)| (s - e).abs() < 1e-10 * e);
+            for k in 0..3 {
+                equal &= (0..n).all(|i| (res.v[(i, k)] - expected.v[(i, k)]).norm_sqr() < 1e-12);
+            }
+        }
+        Err(_) => equal = false,
+    }
+    // the same start vector gives the same iterates as for the dense matrix
+    let dominant = dominant_singular_triplet(dense.as_slice(), m, n, 20, 0.0).unwrap();
+    equal &= dominant_singular_triplet_of(&a, 20, 0.0).is_ok_and(|t| {
+        t.iterations == 20 && (t.sigma - dominant.sigma).abs() < 1e-12 * t.sigma
+            && t.v.iter().zip(&dominant.v).all(|(v, e)| (v - e).norm_sqr() < 1e-20)
+    });
+    equal &= dominant_singular_triplet_of(&CsrMatrix::<f64>::from_triplets(0, 3, &[]).unwrap(), 10, 1e-6).is_err();
+
+    if equal {
+        debug!("sparse matrix successful");
+    }
+    else {
+        debug!("sparse matrix failed");
+    }
+
+    equal
+}
+
+/// Checks LSQR on a consistent sparse system, on an overdetermined dense one against
+/// `lstsq`, on a rank deficient one for the minimum norm solution, and its argument checks
+fn check_lsqr() -> bool {
+    let mut equal = true;
+    let options = SolverOptions::default();
+    let c = |re: f64, im: f64| Complex::new(re, im);
+
+    // a diagonally dominant tridiagonal system with a known solution
+    let n = 50;
+    let mut triplets = Vec::new();
+    for i in 0..n {
+        triplets.push((i, i, c(4.0, 1.0)));
+        if i + 1 < n {
+            triplets.push((i, i + 1, c(-1.0, 0.5)));
+            triplets.push((i + 1, i, c(0.5, -1.0)));
+        }
+    }
+    let a = CsrMatrix::from_triplets(n, n, &triplets).unwrap();
+    let solution: Vec<Complex64> = (0..n).map(|i| c((i % 5) as f64, 1.0 / (1.0 + i as f64))).collect();
+    let mut b = alloc::vec![c(0.0, 0.0); n];
+    a.apply(&solution, &mut b);
+    equal &= lsqr(&a, &b, &options).is_ok_and(|res| {
+        res.converged && res.iterations < n && res.residual < 1e-7 * norm2(b.iter().copied())
+            && res.x.iter().zip(&solution).all(|(x, s)| (x - s).norm_sqr() < 1e-12)
+    });
+
+    let s: Vec<f64> = (0..20).map(|i| 1.0 + i as f64 * 0.1).collect();
+    let a = gallery::with_singular_values(40, 20, &s, 211).unwrap();
+    let b = lcg_matrix::<f64>(40, 1, 213);
+    let expected = lstsq(&a, &b, 1e-12).unwrap();
+    let b: Vec<Complex64> = (0..40).map(|i| b[(i, 0)]).collect();
+    equal &= lsqr(&a, &b, &SolverOptions { tol: 1e-12, ..options }).is_ok_and(|res| {
+        res.converged && (res.residual - expected.residuals[0]).abs() < 1e-10
+            && (0..20).all(|i| (res.x[i] - expected.x[(i, 0)]).norm_sqr() < 1e-18)
+    });
+
+    // x = 0 at the start keeps the iterates in the row space of A
+    let low_rank = gallery::rank_deficient::<f64>(30, 20, 4, 217).unwrap();
+    let b = lcg_matrix::<f64>(30, 1, 219);
+    let expected = lstsq(&low_rank, &b, 1e-10).unwrap();
+    let b: Vec<Complex64> = (0..30).map(|i| b[(i, 0)]).collect();
+    equal &= lsqr(&low_rank, &b, &SolverOptions { tol: 1e-12, ..options })
+        .is_ok_and(|res| (0..20).all(|i| (res.x[i] - expected.x[(i, 0)]).norm_sqr() < 1e-18));
+
+    equal &= lsqr(&a, &b[..20], &options).is_err();
+    equal &= lsqr(&a, &alloc::vec![c(0.0, 0.0); 40], &options).is_ok_and(|res| res.iterations == 0 && res.x.iter().all(|x| x.norm_sqr() == 0.0));
+    let mut b = b;
+    b.resize(40, c(1.0, 0.0));
+    equal &= lsqr(&a, &b, &SolverOptions { max_iterations: 2, ..options }).is_ok_and(|res| !res.converged && res.iterations == 2);
+
+    if equal {
+        debug!("lsqr successful");
+    }
+    else {
+        debug!("lsqr failed");
+    }
+
+    equal
+}
+
+/// Checks CG on a Hermitian positive definite sparse system and its stop on an indefinite
+/// one, restarted and full GMRES on a non-Hermitian system, and both with an operator
+/// given by closures
+fn check_krylov_solvers() -> bool {
+    let mut equal = true;
+    let options = SolverOptions::default();
+    let c = |re: f64, im: f64| Complex::new(re, im);
+
+    let n = 60;
+    let mut hermitian = Vec::new();
+    let mut general = Vec::new();
+    for i in 0..n {
+        hermitian.push((i, i, c(4.0 + (i % 3) as f64, 0.0)));
+        general.push((i, i, c(4.0, 1.0)));
+        if i + 1 < n {
+            hermitian.push((i, i + 1, c(-1.0, 0.5)));
+            hermitian.push((i + 1, i, c(-1.0, -0.5)));
+            general.push((i, i + 1, c(-1.0, 0.5)));
+            general.push((i + 1, i, c(0.5, -1.0)));
+        }
+    }
+    let hermitian = CsrMatrix::from_triplets(n, n, &hermitian).unwrap();
+    let general = CsrMatrix::from_triplets(n, n, &general).unwrap();
+    let solution: Vec<Complex64> = (0..n).map(|i| c((i % 4) as f64 - 1.5, 2.0 / (1.0 + i as f64))).collect();
+    let close = |x: &[Complex64]| x.iter().zip(&solution).all(|(x, s)| (x - s).norm_sqr() < 1e-12);
+
+    let mut b = alloc::vec![c(0.0, 0.0); n];
+    hermitian.apply(&solution, &mut b);
+    equal &= cg(&hermitian, &b, &options).is_ok_and(|res| res.converged && res.iterations < n && close(&res.x));
+    equal &= gmres(&hermitian, &b, 20, &options).is_ok_and(|res| res.converged && close(&res.x));
+
+    general.apply(&solution, &mut b);
+    equal &= gmres(&general, &b, 10, &options).is_ok_and(|res| res.converged && close(&res.x));
+    equal &= gmres(&general, &b, n, &options).is_ok_and(|res| {
+        res.converged && res.iterations < n && res.residual < 1e-7 * norm2(b.iter().copied()) && close(&res.x)
+    });
+    equal &= gmres(&general, &b, 5, &SolverOptions { max_iterations: 3, ..options }).is_ok_and(|res| !res.converged && res.iterations == 3);
+
+    // a diagonal with a negative entry stops CG at the first direction with p* A p <= 0
+    let indefinite = Matrix::from_diag(&[1.0, -1.0]);
+    equal &= cg(&indefinite, &[c(1.0, 0.0), c(1.0, 0.0)], &options).is_ok_and(|res| !res.converged);
+    equal &= gmres(&indefinite, &[c(1.0, 0.0), c(1.0, 0.0)], 2, &options)
+        .is_ok_and(|res| res.converged && (res.x[0] - c(1.0, 0.0)).norm_sqr() < 1e-20 && (res.x[1] - c(-1.0, 0.0)).norm_sqr() < 1e-20);
+
+    // a circular convolution with the kernel (3, 1, 0, ..., 0, i), never stored as a matrix
+    let convolve = FnOperator::new(n, n,
+        |x: &[Complex64], y: &mut [Complex64]| for i in 0..n {
+            y[i] = x[i] * 3.0 + x[(i + n - 1) % n] + x[(i + 1) % n] * c(0.0, 1.0);
+        },
+        |y: &[Complex64], x: &mut [Complex64]| for i in 0..n {
+            x[i] = y[i] * 3.0 + y[(i + 1) % n] + y[(i + n - 1) % n] * c(0.0, -1.0);
+        });
+    equal &= convolve.dims() == (n, n);
+    convolve.apply(&solution, &mut b);
+    equal &= gmres(&convolve, &b, 20, &options).is_ok_and(|res| res.converged && close(&res.x));
+    equal &= lsqr(&convolve, &b, &options).is_ok_and(|res| res.converged && close(&res.x));
+
+    equal &= matches!(cg(&lcg_matrix::<f64>(4, 3, 223), &b[..4], &options), Err(CsvdError::DimensionMismatch { operand: "A", .. }));
+    equal &= matches!(gmres(&general, &b[..4], 10, &options), Err(CsvdError::DimensionMismatch { operand: "b", .. }));
+    equal &= gmres(&general, &b, 0, &options).is_err();
+
+    if equal {
+        debug!("krylov solvers successful");
+    }
+    else {
+        debug!("krylov solvers failed");
+    }
+
+    equal
+}
+
+/// Checks BiCGSTAB against the known solution of a general sparse system, and that the
+/// Jacobi preconditioner cuts the iterations of GMRES and BiCGSTAB on a badly scaled one
+fn check_preconditioners() -> bool {
+    let mut equal = true;
+    let options = SolverOptions::default();
+    let c = |re: f64, im: f64| Complex::new(re, im);
+
+    // a tridiagonal system whose rows are scaled over four orders of magnitude
+    let n = 80;
+    let mut triplets = Vec::new();
+    for i in 0..n {
+        let scale = Float::powf(10.0, i as f64 / 20.0);
+        triplets.push((i, i, c(4.0, 1.0) * scale));
+        if i + 1 < n {
+            triplets.push((i, i + 1, c(-1.0, 0.5) * scale));
+        }
+        if i > 0 {
+            triplets.push((i, i - 1, c(0.5, -1.0) * scale));
+        }
+    }
+    let a = CsrMatrix::from_triplets(n, n, &triplets).unwrap();
+    let solution: Vec<Complex64> = (0..n).map(|i| c(1.0 - (i % 3) as f64, 1.0 / (1.0 + i as f64))).collect();
+    let close = |x: &[Complex64]| x.iter().zip(&solution).all(|(x, s)| (x - s).norm_sqr() < 1e-12);
+    let mut b = alloc::vec![c(0.0, 0.0); n];
+    a.apply(&solution, &mut b);
+    let tight = SolverOptions { tol: 1e-12, ..options };
+
+    let jacobi = JacobiPreconditioner::new(&a.diagonal()).unwrap();
+    match (gmres(&a, &b, 10, &tight), gmres_preconditioned(&a, &b, &jacobi, 10, &tight)) {
+        (Ok(plain), Ok(preconditioned)) =>
+            equal &= preconditioned.converged && close(&preconditioned.x) && preconditioned.iterations < plain.iterations,
+        _ => equal = false,
+    }
+    match (bicgstab(&a, &b, &tight), bicgstab_preconditioned(&a, &b, &jacobi, &tight)) {
+        (Ok(plain), Ok(preconditioned)) =>
+            equal &= preconditioned.converged && close(&preconditioned.x) && preconditioned.iterations <= plain.iterations,
+        _ => equal = false,
+    }
+
+    // the same preconditioner as a closure
+    let diagonal = a.diagonal();
+    let divide = |r: &[Complex64], z: &mut [Complex64]| for ((z, r), d) in z.iter_mut().zip(r).zip(&diagonal) {
+        *z = r / d;
+    };
+    equal &= bicgstab_preconditioned(&a, &b, &divide, &tight)
+        .is_ok_and(|res| res.converged && close(&res.x) && res.residual < 1e-10 * norm2(b.iter().copied()));
+
+    // an unscaled general system without a preconditioner
+    let general = CsrMatrix::from_triplets(n, n, &triplets.iter().map(|&(i, j, x)| (i, j, x / Float::powf(10.0, i as f64 / 20.0))).collect::<Vec<_>>()).unwrap();
+    general.apply(&solution, &mut b);
+    equal &= bicgstab(&general, &b, &options).is_ok_and(|res| res.converged && res.iterations < n && close(&res.x));
+    equal &= bicgstab(&general, &b, &SolverOptions { max_iterations: 2, ..options }).is_ok_and(|res| !res.converged && res.iterations == 2);
+    equal &= bicgstab(&general, &b[..3], &options).is_err();
+
+    equal &= matches!(JacobiPreconditioner::new(&[c(1.0, 0.0), c(0.0, 0.0)]), Err(CsvdError::Singular { index: 1 }));
+
+    if equal {
+        debug!("preconditioners successful");
+    }
+    else {
+        debug!("preconditioners failed");
+    }
+
+    equal
+}
+
+/// Checks CGLS against `lstsq` on an overdetermined and a rank deficient problem, and the
+/// residual history and the cancellation of `cg_with_progress` and `cgls_with_progress`
+fn check_cgls() -> bool {
+    let mut equal = true;
+    let options = SolverOptions { tol: 1e-12, ..SolverOptions::default() };
+
+    let s: Vec<f64> = (0..20).map(|i| 1.0 + i as f64 * 0.1).collect();
+    let a = gallery::with_singular_values(40, 20, &s, 227).unwrap();
+    let b = lcg_matrix::<f64>(40, 1, 229);
+    let expected = lstsq(&a, &b, 1e-12).unwrap();
+    let b: Vec<Complex64> = (0..40).map(|i| b[(i, 0)]).collect();
+    equal &= cgls(&a, &b, &options).is_ok_and(|res| {
+        res.converged && (res.residual - expected.residuals[0]).abs() < 1e-10
+            && (0..20).all(|i| (res.x[i] - expected.x[(i, 0)]).norm_sqr() < 1e-18)
+    });
+
+    let low_rank = gallery::rank_deficient::<f64>(30, 20, 4, 231).unwrap();
+    let rhs = lcg_matrix::<f64>(30, 1, 233);
+    let expected = lstsq(&low_rank, &rhs, 1e-10).unwrap();
+    let rhs: Vec<Complex64> = (0..30).map(|i| rhs[(i, 0)]).collect();
+    equal &= cgls(&low_rank, &rhs, &options).is_ok_and(|res| (0..20).all(|i| (res.x[i] - expected.x[(i, 0)]).norm_sqr() < 1e-18));
+
+    // the history goes into a fixed buffer, one residual per iteration
+    let mut history = [0.0; 64];
+    let mut len = 0;
+    let res = cgls_with_progress(&a, &b, &options, |p| {
+        history[len] = p.residual;
+        len += 1;
+        equal &= p.iteration == len;
+        ControlFlow::Continue(())
+    });
+    // the residual of CGLS never grows
+    equal &= res.is_ok_and(|res| res.iterations == len && (res.residual - history[len - 1]).abs() < 1e-10);
+    equal &= history[..len].windows(2).all(|w| w[1] <= w[0] * (1.0 + 1e-12));
+
+    let mut hermitian = Matrix::zeros(20, 20);
+    equal &= gemm(Complex64::new(1.0, 0.0), Op::ConjugateTranspose, &a, Op::None, &a, Complex64::new(0.0, 0.0), &mut hermitian).is_ok();
+    let mut hb = alloc::vec![Complex64::new(0.0, 0.0); 20];
+    a.apply_adjoint(&b, &mut hb);
+    let mut calls = 0;
+    equal &= cg_with_progress(&hermitian, &hb, &options, |p| {
+        calls += 1;
+        if p.iteration == 3 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+    }).is_ok_and(|res| !res.converged && res.iterations == 3);
+    equal &= calls == 3;
+    // the normal equations have the same solution
+    let normal = cgls(&a, &b, &options).unwrap();
+    equal &= cg(&hermitian, &hb, &options).is_ok_and(|res| res.converged && (0..20).all(|i| (res.x[i] - normal.x[i]).norm_sqr() < 1e-16));
+
+    equal &= cgls(&a, &b[..20], &options).is_err();
+
+    if equal {
+        debug!("cgls successful");
+    }
+    else {
+        debug!("cgls failed");
+    }
+
+    equal
+}
+
+/// Checks that the zero-forcing weights of a 4 x 2 channel recover the sent symbols, that
+/// they give the minimum norm weights for a rank one channel, and that the batched weights
+/// of every subcarrier match `zf_weights` in both layouts
+fn check_zf_weights() -> bool {
+    let mut equal = true;
+    let (n_rx, n_tx) = (4, 2);
+    let c = |re: f64, im: f64| Complex64::new(re, im);
+
+    let h = lcg_matrix::<f64>(n_rx, n_tx, 239);
+    match zf_weights(h.as_slice(), n_rx, n_tx) {
+        Ok(w) => {
+            equal &= w.rows() == n_tx && w.cols() == n_rx;
+            // W H = I, so W y gives back the QPSK symbols of y = H x
+            let x = [c(1.0, -1.0), c(-1.0, -1.0)];
+            let y: Vec<Complex64> = (0..n_rx).map(|i| h[(i, 0)] * x[0] + h[(i, 1)] * x[1]).collect();
+            for t in 0..n_tx {
+                let estimate = (0..n_rx).fold(c(0.0, 0.0), |acc, r| acc + w[(t, r)] * y[r]);
+                equal &= (estimate - x[t]).norm_sqr() < 1e-20;
+            }
+        }
+        Err(_) => equal = false,
+    }
+
+    // both streams see the same channel, the weights split the energy between them
+    let g = [c(1.0, 0.5), c(-0.5, 2.0), c(0.0, 1.0), c(0.25, 0.0)];
+    let same: Vec<Complex64> = g.iter().flat_map(|&g| [g, g]).collect();
+    let norm = g.iter().map(|g| g.norm_sqr()).sum::<f64>();
+    equal &= zf_weights(&same, n_rx, n_tx).is_ok_and(|w| {
+        (0..n_tx).all(|t| (0..n_rx).all(|r| (w[(t, r)] - g[r].conj() / (2.0 * norm)).norm_sqr() < 1e-20))
+    });
+
+    let subcarriers = 6;
+    let channels: Vec<Matrix<Complex64>> = (0..subcarriers).map(|k| lcg_matrix::<f64>(n_rx, n_tx, 241 + k as u64)).collect();
+    for layout in [BatchLayout::Contiguous, BatchLayout::Interleaved] {
+        let index = |k: usize, e: usize| match layout {
+            BatchLayout::Contiguous => k * n_rx * n_tx + e,
+            BatchLayout::Interleaved => e * subcarriers + k,
+        };
+        let mut h = alloc::vec![c(0.0, 0.0); subcarriers * n_rx * n_tx];
+        for (k, channel) in channels.iter().enumerate() {
+            for e in 0..n_rx * n_tx {
+                h[index(k, e)] = channel.as_slice()[e];
+            }
+        }
+
+        let mut w = alloc::vec![c(0.0, 0.0); subcarriers * n_rx * n_tx];
+        equal &= zf_weights_batched(&h, &mut w, n_rx, n_tx, subcarriers, layout).is_ok();
+        for (k, channel) in channels.iter().enumerate() {
+            let expected = zf_weights(channel.as_slice(), n_rx, n_tx).unwrap();
+            equal &= (0..n_tx * n_rx).all(|e| (w[index(k, e)] - expected.as_slice()[e]).norm_sqr() < 1e-20);
+        }
+        equal &= zf_weights_batched(&h, &mut w[..5], n_rx, n_tx, subcarriers, layout).is_err();
+    }
+
+    equal &= matches!(zf_weights(&h.as_slice()[..7], n_rx, n_tx), Err(CsvdError::BufferTooSmall { .. }));
+    equal &= matches!(zf_weights(&[c(f64::NAN, 0.0)], 1, 1), Err(CsvdError::NonFiniteInput { row: 0, col: 0 }));
+
+    // sizes whose product wraps to zero must not pass the length checks
+    let huge = usize::MAX / 2 + 1;
+    let wrapped = Err(CsvdError::BufferTooSmall { operand: "H", required: usize::MAX, len: 8 });
+    equal &= zf_weights(&h.as_slice()[..8], huge, 2).map(|_| ()) == wrapped;
+    equal &= eigen_beamforming(&h.as_slice()[..8], huge, 2, 1).map(|_| ()) == wrapped;
+    let mut w = alloc::vec![c(0.0, 0.0); 8];
+    equal &= zf_weights_batched(&h.as_slice()[..8], &mut w, 2, 2, huge / 2, BatchLayout::Contiguous) == wrapped;
+
+    if equal {
+        debug!("zero-forcing weights successful");
+    }
+    else {
+        debug!("zero-forcing weights failed");
+    }
+
+    equal
+}
+
+/// Checks that the eigen-beamforming precoder and combiner of a 4 x 3 channel have
+/// orthonormal columns and diagonalize the channel with its largest singular values
+fn check_eigen_beamforming() -> bool {
+    let mut equal = true;
+    let (n_rx, n_tx) = (4, 3);
+    let zero = Complex64::new(0.0, 0.0);
+
+    let h = lcg_matrix::<f64>(n_rx, n_tx, 251);
+    let s = singular_values(&h).unwrap();
+    for k in 1..=3 {
+        match eigen_beamforming(h.as_slice(), n_rx, n_tx, k) {
+            Ok(bf) => {
+                equal &= bf.precoder.rows() == n_tx && bf.precoder.cols() == k;
+                equal &= bf.combiner.rows() == n_rx && bf.combiner.cols() == k;
+                equal &= bf.gains.iter().zip(&s).all(|(g, s)| (g - s).abs() < 1e-12);
+                for a in 0..k {
+                    for b in 0..k {
+                        let delta = if a == b { 1.0 } else { 0.0 };
+                        let ff = (0..n_tx).fold(zero, |acc, i| acc + bf.precoder[(i, a)].conj() * bf.precoder[(i, b)]);
+                        equal &= (ff - delta).norm_sqr() < 1e-24;
+                        // G* H F = diag(gains)
+                        let ghf = (0..n_rx).fold(zero, |acc, i| {
+                            acc + bf.combiner[(i, a)].conj() * (0..n_tx).fold(zero, |acc, j| acc + h[(i, j)] * bf.precoder[(j, b)])
+                        });
+                        equal &= (ghf - delta * bf.gains[a]).norm_sqr() < 1e-20;
+                    }
+                }
+            }
+            Err(_) => equal = false,
+        }
+    }
+
+    equal &= matches!(eigen_beamforming(h.as_slice(), n_rx, n_tx, 4), Err(CsvdError::InvalidArgument { name: "k", .. }));
+    equal &= eigen_beamforming(h.as_slice(), n_rx, n_tx, 0).is_err();
+    equal &= eigen_beamforming(&h.as_slice()[..11], n_rx, n_tx, 1).is_err();
+
+    if equal {
+        debug!("eigen-beamforming successful");
+    }
+    else {
+        debug!("eigen-beamforming failed");
+    }
+
+    equal
+}
+
+/// Checks the stream SNRs, the capacity and the effective SNR of a channel with known
+/// singular values against the closed forms, and the capacity of a random channel against
+/// log2 det(I + snr H* H)
+fn check_capacity() -> bool {
+    let mut equal = true;
+    let snr = 10.0;
+
+    let h = gallery::with_singular_values::<f64>(4, 2, &[2.0, 0.5], 257).unwrap();
+    let res = svd(&h).unwrap();
+    let snrs = res.stream_snrs(snr);
+    equal &= snrs.len() == 2 && (snrs[0] - 40.0).abs() < 1e-12 && (snrs[1] - 2.5).abs() < 1e-12;
+    let capacity = Float::log2(41.0) + Float::log2(3.5);
+    equal &= (res.capacity(snr) - capacity).abs() < 1e-12;
+    equal &= (res.effective_snr(snr) - (Float::sqrt(41.0 * 3.5) - 1.0)).abs() < 1e-12;
+    // two equal streams carry the total rate at the effective SNR
+    equal &= (2.0 * Float::log2(1.0 + res.effective_snr(snr)) - capacity).abs() < 1e-12;
+    equal &= res.capacity(0.0) == 0.0 && res.effective_snr(0.0) == 0.0;
+
+    // C = log2 det(I + snr H* H), with the determinant from the LU factorization
+    let h = lcg_matrix::<f64>(3, 3, 259);
+    let mut g = Matrix::identity(3);
+    equal &= gemm(Complex64::new(snr, 0.0), Op::ConjugateTranspose, &h, Op::None, &h, Complex64::new(1.0, 0.0), &mut g).is_ok();
+    equal &= det(&g).is_ok_and(|d| (svd(&h).unwrap().capacity(snr) - Float::log2(d.re)).abs() < 1e-10);
+
+    equal &= svd_truncated(&h, 0).is_ok_and(|res| res.capacity(snr) == 0.0 && res.effective_snr(snr) == 0.0);
+
+    if equal {
+        debug!("capacity successful");
+    }
+    else {
+        debug!("capacity failed");
+    }
+
+    equal
+}
+
+/// Checks the unrolled 4 x 4 kernel against `svd` on a random, a rank deficient, a zero and
+/// a huge matrix, that `svd_fixed` selects it, and `pinv4` against `SvdResult::pinv_with_options`
+fn check_svd4() -> bool {
+    let mut equal = true;
+    let zero = Complex64::new(0.0, 0.0);
+    let to_array = |a: &Matrix<Complex64>| {
+        let mut x = [[zero; 4]; 4];
+        for i in 0..4 {
+            for j in 0..4 {
+                x[i][j] = a[(i, j)];
+            }
+        }
+        x
+    };
+    let to_matrix = |x: &[[Complex64; 4]; 4]| Matrix::from_vec(x.concat(), 4, 4).unwrap();
+
+    // svd itself takes the kernel for 4 x 4 matrices, svd_with_stats the general algorithm
+    let a = lcg_matrix::<f64>(4, 4, 263);
+    let expected = svd_with_stats(&a, &SvdOptions::default()).unwrap().0;
+    match svd4(&to_array(&a)) {
+        Ok(res) => {
+            equal &= res.s.iter().zip(&expected.s).all(|(s, e)| (s - e).abs() < 1e-12);
+            equal &= (0..4).all(|i| (0..4).all(|j| {
+                (res.u[i][j] - expected.u[(i, j)]).norm_sqr() < 1e-20 && (res.v[i][j] - expected.v[(i, j)]).norm_sqr() < 1e-20
+            }));
+            let mut fixed = to_array(&a);
+            equal &= svd_fixed(&mut fixed).is_ok_and(|f| f == res);
+        }
+        Err(_) => equal = false,
+    }
+
+    // U is completed to a unitary matrix for the two zero singular values
+    for a in [gallery::rank_deficient::<f64>(4, 4, 2, 269).unwrap(), Matrix::zeros(4, 4)] {
+        equal &= svd4(&to_array(&a)).is_ok_and(|res| {
+            let res = SvdResult { u: to_matrix(&res.u), s: res.s.to_vec(), v: to_matrix(&res.v) };
+            verify_svd(&a, &res, 1e-12).is_ok_and(|r| r.passed())
+        });
+    }
+
+    // entries near the overflow threshold keep their singular values
+    let mut huge = a.clone();
+    huge.scale(Complex64::new(1e300, 0.0));
+    equal &= svd4(&to_array(&huge)).is_ok_and(|res| res.s.iter().zip(&expected.s).all(|(s, e)| (s / 1e300 - e).abs() < 1e-12));
+
+    let options = PinvOptions::default();
+    let low_rank = gallery::rank_deficient::<f64>(4, 4, 3, 271).unwrap();
+    for a in [&a, &low_rank] {
+        let expected = svd_with_stats(a, &SvdOptions::default()).unwrap().0.pinv_with_options(&options);
+        equal &= pinv4(&to_array(a), &options).is_ok_and(|inv| check_matrix_equality(&to_matrix(&inv), &expected));
+    }
+
+    // the matrix-level drivers dispatch to the kernel, in either layout
+    let res = svd4(&to_array(&a)).unwrap();
+    let kernel = SvdResult { u: to_matrix(&res.u), s: res.s.to_vec(), v: to_matrix(&res.v) };
+    equal &= svd(&a).is_ok_and(|x| x == kernel);
+    for layout in [Layout::RowMajor, Layout::ColMajor] {
+        let (mut s, mut u, mut v) = (alloc::vec![0.0; 4], Matrix::zeros(4, 4), Matrix::zeros_with_layout(4, 4, layout));
+        equal &= csvd(&mut a.to_layout(layout), 0, 4, 4, &mut s, &mut u, &mut v).is_ok();
+        equal &= s == kernel.s && u == kernel.u && v.to_layout(Layout::RowMajor) == kernel.v;
+    }
+    let mut inv = Matrix::zeros(4, 4);
+    equal &= pinv(&mut a.clone(), &mut inv).is_ok();
+    equal &= pinv4(&to_array(&a), &PinvOptions::absolute(0.0001)).is_ok_and(|x| to_matrix(&x) == inv);
+
+    let mut nan = to_array(&a);
+    nan[2][1] = Complex64::new(f64::NAN, 0.0);
+    equal &= matches!(svd4(&nan), Err(CsvdError::NonFiniteInput { row: 2, col: 1 }));
+
+    if equal {
+        debug!("unrolled 4 x 4 svd successful");
+    }
+    else {
+        debug!("unrolled 4 x 4 svd failed");
+    }
+
+    equal
+}
+
+/// Checks the adjugate inverses of random 2 x 2 and 3 x 3 matrices against `inv`, the
+/// fallback to the pseudo-inverse for singular and nearly singular ones, and scaling
+fn check_small_inverse() -> bool {
+    let mut equal = true;
+    let c = |re: f64, im: f64| Complex64::new(re, im);
+    let options = PinvOptions::default();
+
+    let a = lcg_matrix::<f64>(2, 2, 277);
+    let expected = inv(&a).unwrap();
+    equal &= inv2(&[[a[(0, 0)], a[(0, 1)]], [a[(1, 0)], a[(1, 1)]]])
+        .is_ok_and(|x| (0..2).all(|i| (0..2).all(|j| (x[i][j] - expected[(i, j)]).norm_sqr() < 1e-24)));
+
+    let to_array = |a: &Matrix<Complex64>| {
+        let mut x = [[c(0.0, 0.0); 3]; 3];
+        for i in 0..3 {
+            for j in 0..3 {
+                x[i][j] = a[(i, j)];
+            }
+        }
+        x
+    };
+    let close = |x: &[[Complex64; 3]; 3], e: &Matrix<Complex64>, tol: f64| (0..3).all(|i| (0..3).all(|j| (x[i][j] - e[(i, j)]).norm_sqr() < tol));
+
+    let a = lcg_matrix::<f64>(3, 3, 281);
+    let expected = inv(&a).unwrap();
+    equal &= inv3(&to_array(&a)).is_ok_and(|x| close(&x, &expected, 1e-24));
+
+    // entries far beyond the square root of the overflow threshold
+    let mut huge = a.clone();
+    huge.scale(c(1e200, 0.0));
+    equal &= inv3(&to_array(&huge)).is_ok_and(|x| {
+        (0..3).all(|i| (0..3).all(|j| (x[i][j] * 1e200 - expected[(i, j)]).norm_sqr() < 1e-24))
+    });
+
+    // a singular and a nearly singular matrix get the pseudo-inverse
+    let singular = gallery::rank_deficient::<f64>(3, 3, 2, 283).unwrap();
+    equal &= inv3(&to_array(&singular)).is_ok_and(|x| close(&x, &svd(&singular).unwrap().pinv_with_options(&options), 1e-20));
+    let nearly = [[c(1.0, 0.0), c(1.0, 0.0)], [c(1.0, 0.0), c(1.0 + 1e-12, 0.0)]];
+    let expected = svd(&Matrix::from_vec(nearly.concat(), 2, 2).unwrap()).unwrap().pinv_with_options(&options);
+    equal &= inv2(&nearly).is_ok_and(|x| (0..2).all(|i| (0..2).all(|j| (x[i][j] - expected[(i, j)]).norm_sqr() < 1e-20 * expected[(i, j)].norm_sqr())));
+    equal &= inv2(&[[c(0.0, 0.0); 2]; 2]).is_ok_and(|x| x == [[c(0.0, 0.0); 2]; 2]);
+
+    equal &= matches!(inv2(&[[c(1.0, 0.0), c(f64::INFINITY, 0.0)], [c(0.0, 0.0), c(1.0, 0.0)]]), Err(CsvdError::NonFiniteInput { row: 0, col: 1 }));
+
+    if equal {
+        debug!("small inverse successful");
+    }
+    else {
+        debug!("small inverse failed");
+    }
+
+    equal
+}
+
+/// Checks the determinant against the product of the singular values, which equals |det|,
+/// the determinant of a permutation matrix and the log-determinant of a large scaled matrix
+fn check_det() -> bool {
+    let mut equal = true;
+
+    let a = lcg_matrix::<f64>(6, 6, 137);
+    let s = singular_values(&a).unwrap();
+    let abs_det = s.iter().fold(1.0, |p, s| p * s);
+    match (det(&a), logdet(&a)) {
+        (Ok(d), Ok((phase, logabs))) => {
+            equal &= (Float::sqrt(d.norm_sqr()) - abs_det).abs() < 1e-8;
+            equal &= (logabs - Float::ln(abs_det)).abs() < 1e-8;
+            equal &= (phase * Float::exp(logabs) - d).norm_sqr() < 1e-16;
+        }
+        _ => equal = false,
+    }
+
+    // swapping two rows of the identity flips the sign
+    let mut p = Matrix::zeros(3, 3);
+    p[(0, 1)] = Complex64::new(1.0, 0.0);
+    p[(1, 0)] = Complex64::new(1.0, 0.0);
+    p[(2, 2)] = Complex64::new(1.0, 0.0);
+    equal &= det(&p) == Ok(Complex64::new(-1.0, 0.0));
+
+    // det(1e3 A) of a 60 x 60 matrix overflows, its logarithm does not
+    let mut big = lcg_matrix::<f64>(60, 60, 139);
+    let (_, logabs) = logdet(&big).unwrap();
+    for i in 0..60 {
+        for j in 0..60 {
+            big[(i, j)] *= 1e6;
+        }
+    }
+    equal &= logdet(&big).is_ok_and(|(_, l)| (l - logabs - 60.0 * Float::ln(1e6f64)).abs() < 1e-6);
+
+    let mut singular = lcg_matrix::<f64>(3, 3, 149);
+    for j in 0..3 {
+        singular[(1, j)] = Complex64::new(0.0, 0.0);
+    }
+    equal &= det(&singular) == Ok(Complex64::new(0.0, 0.0));
+
+    if equal {
+        debug!("det successful");
+    }
+    else {
+        debug!("det failed");
+    }
+
+    equal
+}
+
+/// Checks all four Moore-Penrose conditions for the pseudo-inverse of tall, wide and vector shaped
+/// matrices, and that the pseudo-inverse of the pseudo-inverse gives back the full rank matrix
+fn check_penrose_rectangular() -> bool {
+    let mut equal = true;
+
+    for &(m, n) in &[(7, 4), (4, 7), (5, 1), (1, 5), (3, 3)] {
+        let a = lcg_matrix::<f64>(m, n, 151);
+
+        let mut inv = Matrix::zeros(n, m);
+        equal &= pinv(&mut a.clone(), &mut inv).is_ok();
+
+        equal &= verify_pinv(&a, &inv, 0.0001).is_ok_and(|report| report.passed());
+
+        let mut round_trip = Matrix::zeros(m, n);
+        equal &= pinv(&mut inv.clone(), &mut round_trip).is_ok();
+        equal &= check_matrix_equality(&round_trip, &a);
+    }
+
+    if equal {
+        debug!("rectangular pseudo-inverse successful");
+    }
+    else {
+        debug!("rectangular pseudo-inverse failed");
+    }
+
+    equal
+}
+
+/// Checks that solving for many right-hand sides at once matches the pseudo-inverse
+/// applied to every column, for a tall and a wide system
+fn check_solve_multi() -> bool {
+    let mut equal = true;
+
+    for &(m, n) in &[(8, 4), (4, 8)] {
+        let a = lcg_matrix::<f64>(m, n, 157);
+        let b = lcg_matrix::<f64>(m, 12, 163);
+
+        let mut expected = Matrix::zeros(n, 12);
+        equal &= matrix_mult(&svd(&a).unwrap().pinv(), &b, &mut expected).is_ok();
+
+        match solve_multi(&a, &b) {
+            Ok(x) => equal &= check_matrix_equality(&expected, &x),
+            Err(_) => equal = false,
+        }
+    }
+
+    equal &= solve_multi(&lcg_matrix::<f64>(4, 3, 1), &lcg_matrix::<f64>(3, 2, 1)).is_err();
+
+    if equal {
+        debug!("solve multi successful");
+    }
+    else {
+        debug!("solve multi failed");
+    }
+
+    equal
+}
+
+/// Checks that solve_auto takes the expected path for every shape and conditioning and
+/// agrees with solve_multi on all of them
+fn check_solve_auto() -> bool {
+    let mut equal = true;
+
+    let mut collinear = lcg_matrix::<f64>(9, 4, 173);
+    for i in 0..9 {
+        collinear[(i, 3)] = collinear[(i, 1)] * Complex64::new(0.0, 2.0);
+    }
+    let cases = [
+        (lcg_matrix::<f64>(2, 2, 179), Solver::Adjugate),
+        (lcg_matrix::<f64>(3, 3, 181), Solver::Adjugate),
+        (lcg_matrix::<f64>(1, 1, 191), Solver::Lu),
+        (lcg_matrix::<f64>(7, 7, 193), Solver::Lu),
+        (gallery::hilbert(8), Solver::Svd),
+        (gallery::rank_deficient(5, 5, 3, 197).unwrap(), Solver::Svd),
+        (lcg_matrix::<f64>(9, 4, 199), Solver::Qr),
+        (collinear, Solver::Svd),
+        (lcg_matrix::<f64>(4, 9, 211), Solver::Svd),
+    ];
+    for (a, solver) in cases.iter() {
+        let b = lcg_matrix::<f64>(a.rows(), 3, 223);
+        match solve_auto(a, &b) {
+            Ok(sol) => {
+                equal &= sol.solver == *solver;
+                equal &= check_matrix_equality(&sol.x, &solve_multi(a, &b).unwrap());
+            }
+            Err(_) => equal = false,
+        }
+    }
+
+    // QR solves the tall least-squares problem with a pivoted decomposition as well
+    let a = lcg_matrix::<f64>(9, 4, 199);
+    let b = lcg_matrix::<f64>(9, 2, 227);
+    equal &= qr_pivoted(&a).and_then(|f| f.solve(&b)).is_ok_and(|x| check_matrix_equality(&x, &solve_multi(&a, &b).unwrap()));
+    equal &= qr(&lcg_matrix::<f64>(3, 5, 1)).unwrap().solve(&b) == Err(CsvdError::WideMatrix { rows: 3, cols: 5 });
+
+    equal &= solve_auto(&a, &lcg_matrix::<f64>(4, 2, 1)) == Err(CsvdError::DimensionMismatch { operand: "b", expected: (9, 2), found: (4, 2) });
+
+    if equal {
+        debug!("solve auto successful");
+    }
+    else {
+        debug!("solve auto failed");
+    }
+
+    equal
+}
+
+/// Checks the batched decomposition in both layouts against decomposing every matrix on its own,
+/// reusing one BatchedSvd for a second batch
+fn check_batched_svd() -> bool {
+    let mut equal = true;
+    let batch = 5;
+
+    for &(m, n) in &[(4, 4), (3, 5)] {
+        let matrices: Vec<Matrix<Complex32>> = (0..batch).map(|b| lcg_matrix::<f32>(m, n, 167 + b as u64)).collect();
+
+        for &layout in &[BatchLayout::Contiguous, BatchLayout::Interleaved] {
+            let mut packed = Vec::new();
+            packed.resize(batch * m * n, Complex32::new(0.0, 0.0));
+            for b in 0..batch {
+                for e in 0..m * n {
+                    let idx = match layout {
+                        BatchLayout::Contiguous => b * m * n + e,
+                        BatchLayout::Interleaved => e * batch + b,
+                    };
+                    packed[idx] = matrices[b].as_slice()[e];
+                }
+            }
+
+            let mut res = BatchedSvd::new(m, n, batch, layout);
+            for _ in 0..2 {
+                let mut a = packed.clone();
+                equal &= res.decompose(&mut a).is_ok();
+
+                for b in 0..batch {
+                    let expected = svd(&matrices[b]).unwrap();
+                    let got = res.result(b);
+                    equal &= check_matrix_equality(&expected.u, &got.u);
+                    equal &= check_matrix_equality(&expected.v, &got.v);
+                    equal &= expected.s.iter().zip(got.s.iter()).all(|(x, y)| (x - y).abs() < 0.0001);
+                }
+            }
+        }
+    }
+
+    equal &= batched_svd::<f32>(&mut [Complex32::new(0.0, 0.0); 15], 2, 2, 4, BatchLayout::Contiguous).is_err();
+
+    if equal {
+        debug!("batched svd successful");
+    }
+    else {
+        debug!("batched svd failed");
+    }
+
+    equal
+}
+
+/// Checks the batched pseudo-inverse in both layouts against pinv of every matrix
+fn check_batched_pinv() -> bool {
+    let mut equal = true;
+    let batch = 6;
+    let (m, n) = (4, 2);
+
+    let matrices: Vec<Matrix<Complex64>> = (0..batch).map(|b| lcg_matrix::<f64>(m, n, 173 + b as u64)).collect();
+
+    for &layout in &[BatchLayout::Contiguous, BatchLayout::Interleaved] {
+        let index = |b: usize, e: usize, len: usize| match layout {
+            BatchLayout::Contiguous => b * len + e,
+            BatchLayout::Interleaved => e * batch + b,
+        };
+
+        let mut packed = Vec::new();
+        packed.resize(batch * m * n, Complex64::new(0.0, 0.0));
+        for b in 0..batch {
+            for e in 0..m * n {
+                packed[index(b, e, m * n)] = matrices[b].as_slice()[e];
+            }
+        }
+
+        let mut out = Vec::new();
+        out.resize(batch * n * m, Complex64::new(0.0, 0.0));
+        equal &= batched_pinv(&mut packed, &mut out, m, n, batch, layout).is_ok();
+
+        for b in 0..batch {
+            let expected = svd(&matrices[b]).unwrap().pinv();
+            for e in 0..n * m {
+                equal &= (out[index(b, e, n * m)] - expected.as_slice()[e]).norm_sqr() < 0.0001;
+            }
+        }
+    }
+
+    if equal {
+        debug!("batched pinv successful");
+    }
+    else {
+        debug!("batched pinv failed");
+    }
+
+    equal
+}
+
+/// Checks matrix_mult against the plain triple loop for shapes that span several of the
+/// packed panels of B, accumulating into a non-zero C
+fn check_matrix_mult() -> bool {
+    let mut equal = true;
+
+    for &(m, k, n) in [(3, 5, 2), (150, 300, 140), (1, 257, 129)].iter() {
+        let a = lcg_matrix::<f64>(m, k, 191 + m as u64);
+        let b = lcg_matrix::<f64>(k, n, 193 + n as u64);
+        let mut c = lcg_matrix::<f64>(m, n, 197);
+
+        let mut expected = c.clone();
+        for i in 0..m {
+            for j in 0..n {
+                for l in 0..k {
+                    expected[(i, j)] += a[(i, l)] * b[(l, j)];
+                }
+            }
+        }
+
+        equal &= matrix_mult(&a, &b, &mut c).is_ok();
+        equal &= check_matrix_equality(&c, &expected);
+    }
+
+    if equal {
+        debug!("matrix mult successful");
+    }
+    else {
+        debug!("matrix mult failed");
+    }
+
+    equal
+}
+
+/// Checks gemm for every combination of ops against explicitly formed transposes,
+/// with a beta of zero ignoring the NaN that C starts out with
+fn check_gemm() -> bool {
+    let mut equal = true;
+
+    let alpha = Complex64::new(0.5, -1.5);
+    let beta = Complex64::new(-0.25, 2.0);
+    let ops = [Op::None, Op::Transpose, Op::ConjugateTranspose];
+
+    let (m, k, n) = (70, 9, 75);
+    for &op_a in ops.iter() {
+        for &op_b in ops.iter() {
+            let a = if op_a == Op::None { lcg_matrix::<f64>(m, k, 211) } else { lcg_matrix::<f64>(k, m, 211) };
+            let b = if op_b == Op::None { lcg_matrix::<f64>(k, n, 223) } else { lcg_matrix::<f64>(n, k, 223) };
+            let c = lcg_matrix::<f64>(m, n, 227);
+
+            let formed = |op: Op, x: &Matrix<Complex64>| {
+                let mut y = Matrix::zeros(x.cols(), x.rows());
+                for i in 0..x.rows() {
+                    for j in 0..x.cols() {
+                        y[(j, i)] = if op == Op::ConjugateTranspose { x[(i, j)].conj() } else { x[(i, j)] };
+                    }
+                }
+                if op == Op::None { x.clone() } else { y }
+            };
+
+            let mut product = Matrix::zeros(m, n);
+            equal &= matrix_mult(&formed(op_a, &a), &formed(op_b, &b), &mut product).is_ok();
+            let mut expected = c.clone();
+            for i in 0..m {
+                for j in 0..n {
+                    expected[(i, j)] = alpha * product[(i, j)] + beta * c[(i, j)];
+                }
+            }
+
+            let mut result = c.clone();
+            equal &= gemm(alpha, op_a, &a, op_b, &b, beta, &mut result).is_ok();
+            equal &= check_matrix_equality(&result, &expected);
+
+            let mut result = Matrix::from_vec(alloc::vec![Complex64::new(f64::NAN, 0.0); m * n], m, n).unwrap();
+            equal &= gemm(Complex64::new(1.0, 0.0), op_a, &a, op_b, &b, Complex64::new(0.0, 0.0), &mut result).is_ok();
+            equal &= check_matrix_equality(&result, &product);
+
+            let mut result = c.clone();
+            equal &= gemm_3m(alpha, op_a, &a, op_b, &b, beta, &mut result).is_ok();
+            equal &= check_matrix_equality(&result, &expected);
+        }
+    }
+
+    // three real products agree with four up to rounding, also in single precision and for
+    // a column-major C, and the product of real matrices stays exactly real
+    let (a, b) = (lcg_matrix::<f32>(40, 30, 239), lcg_matrix::<f32>(30, 20, 241));
+    let (mut c4, mut c3) = (Matrix::zeros(40, 20), Matrix::zeros_with_layout(40, 20, Layout::ColMajor));
+    let one = Complex32::new(1.0, 0.0);
+    equal &= gemm(one, Op::None, &a, Op::None, &b, Complex32::new(0.0, 0.0), &mut c4).is_ok();
+    equal &= gemm_3m(one, Op::None, &a, Op::None, &b, Complex32::new(0.0, 0.0), &mut c3).is_ok();
+    equal &= (0..40).all(|i| (0..20).all(|j| (c4[(i, j)] - c3[(i, j)]).norm_sqr() < 1e-9));
+    let real = |x: &Matrix<Complex64>| Matrix::from_vec(x.as_slice().iter().map(|z| Complex64::new(z.re, 0.0)).collect(), x.rows(), x.cols()).unwrap();
+    let (a, b) = (real(&lcg_matrix::<f64>(6, 5, 251)), real(&lcg_matrix::<f64>(5, 7, 257)));
+    let (mut c4, mut c3) = (Matrix::zeros(6, 7), Matrix::zeros(6, 7));
+    equal &= gemm(Complex64::new(1.0, 0.0), Op::None, &a, Op::None, &b, Complex64::new(0.0, 0.0), &mut c4).is_ok();
+    equal &= gemm_3m(Complex64::new(1.0, 0.0), Op::None, &a, Op::None, &b, Complex64::new(0.0, 0.0), &mut c3).is_ok();
+    equal &= c3.as_slice().iter().zip(c4.as_slice()).all(|(x, y)| (x.re - y.re).abs() < 1e-14 && x.im == 0.0);
+    let mut c = Matrix::zeros(3, 4);
+    equal &= gemm_3m(Complex64::new(1.0, 0.0), Op::None, &lcg_matrix::<f64>(3, 2, 229), Op::None,
+                     &lcg_matrix::<f64>(3, 4, 233), Complex64::new(0.0, 0.0), &mut c)
+        == Err(CsvdError::DimensionMismatch { operand: "b", expected: (2, 4), found: (3, 4) });
+
+    // op(B) has to have as many rows as op(A) has columns
+    let mut c = Matrix::zeros(3, 4);
+    equal &= gemm(Complex64::new(1.0, 0.0), Op::Transpose, &lcg_matrix::<f64>(2, 3, 229), Op::ConjugateTranspose,
+                  &lcg_matrix::<f64>(4, 3, 233), Complex64::new(0.0, 0.0), &mut c).is_err();
+
+    if equal {
+        debug!("gemm successful");
+    }
+    else {
+        debug!("gemm failed");
+    }
+
+    equal
+}
+
+/// Checks gemv for every op against gemm with a single column, and the level-1 helpers
+/// against their definitions
+fn check_blas() -> bool {
+    let mut equal = true;
+    let eps = 1e-10;
+
+    let alpha = Complex64::new(0.5, -1.5);
+    let beta = Complex64::new(-0.25, 2.0);
+    let a = lcg_matrix::<f64>(6, 4, 239);
+
+    for &op in [Op::None, Op::Transpose, Op::ConjugateTranspose].iter() {
+        let (m, n) = if op == Op::None { (6, 4) } else { (4, 6) };
+        let x = lcg_matrix::<f64>(n, 1, 241);
+        let y = lcg_matrix::<f64>(m, 1, 251);
+
+        let mut expected = y.clone();
+        equal &= gemm(alpha, op, &a, Op::None, &x, beta, &mut expected).is_ok();
+
+        let mut result = y.clone().into_vec();
+        equal &= gemv(alpha, op, &a, x.as_slice(), beta, &mut result).is_ok();
+        equal &= check_matrix_equality(&Matrix::from_vec(result, m, 1).unwrap(), &expected);
+
+        equal &= gemv(alpha, op, &a, &x.as_slice()[1..], beta, &mut y.into_vec()).is_err();
+    }
+
+    let x = lcg_matrix::<f64>(1, 7, 257).into_vec();
+    let y = lcg_matrix::<f64>(1, 7, 263).into_vec();
+
+    let dotc_expected = x.iter().zip(y.iter()).fold(Complex64::new(0.0, 0.0), |sum, (x, y)| sum + x.conj() * y);
+    let dotu_expected = x.iter().zip(y.iter()).fold(Complex64::new(0.0, 0.0), |sum, (x, y)| sum + x * y);
+    equal &= dotc(&x, &y).is_ok_and(|d| (d - dotc_expected).norm_sqr() < eps);
+    equal &= dotu(&x, &y).is_ok_and(|d| (d - dotu_expected).norm_sqr() < eps);
+    equal &= dotc(&x, &y[1..]).is_err() && dotu(&x[1..], &y).is_err();
+
+    let mut z = y.clone();
+    equal &= axpy(alpha, &x, &mut z).is_ok();
+    equal &= z.iter().zip(x.iter().zip(y.iter())).all(|(z, (x, y))| (*z - (y + alpha * x)).norm_sqr() < eps);
+    equal &= axpy(alpha, &x[1..], &mut z).is_err();
+
+    let mut z = x.clone();
+    scal(alpha, &mut z);
+    equal &= z.iter().zip(x.iter()).all(|(z, x)| (*z - alpha * x).norm_sqr() < eps);
+
+    let norm = Float::sqrt(x.iter().map(|x| x.norm_sqr()).sum::<f64>());
+    equal &= (nrm2(&x) - norm).abs() < eps;
+    equal &= (nrm2(&[Complex64::new(3e200, 4e200)]) / 5e200 - 1.0).abs() < eps;
+
+    if equal {
+        debug!("blas successful");
+    }
+    else {
+        debug!("blas failed");
+    }
+
+    equal
+}
+
+/// Checks the transposes and elementwise operations of Matrix elementwise, including a
+/// matrix whose rows are padded, and its diagonal, trace and diagonal constructors
+fn check_matrix_ops() -> bool {
+    let mut equal = true;
+
+    let (m, n) = (4, 3);
+    let mut data = lcg_matrix::<f64>(m, n + 2, 269).into_vec();
+    data.truncate((m - 1) * (n + 2) + n);
+    let a = Matrix::from_vec_with_ld(data, m, n, n + 2).unwrap();
+    let b = lcg_matrix::<f64>(m, n, 271);
+    let alpha = Complex64::new(0.5, -1.5);
+
+    let t = a.transpose();
+    let t_h = a.conj_transpose();
+    equal &= t.rows() == n && t.cols() == m && t_h.rows() == n && t_h.cols() == m;
+
+    let sum = a.add(&b).unwrap();
+    let diff = a.sub(&b).unwrap();
+    let prod = a.hadamard(&b).unwrap();
+    let mut scaled = a.clone();
+    scaled.scale(alpha);
+
+    for i in 0..m {
+        for j in 0..n {
+            equal &= t[(j, i)] == a[(i, j)] && t_h[(j, i)] == a[(i, j)].conj();
+            equal &= sum[(i, j)] == a[(i, j)] + b[(i, j)] && diff[(i, j)] == a[(i, j)] - b[(i, j)];
+            equal &= prod[(i, j)] == a[(i, j)] * b[(i, j)] && scaled[(i, j)] == a[(i, j)] * alpha;
+        }
+    }
+
+    equal &= a.add(&t).is_err() && a.sub(&t).is_err() && a.hadamard(&t).is_err();
+
+    // the diagonal of the padded matrix, and of its column-major copy
+    let d = a.diagonal();
+    equal &= d.len() == 3 && (0..3).all(|i| d[i] == a[(i, i)]) && a.to_layout(Layout::ColMajor).diagonal() == d;
+    equal &= a.trace() == d[0] + d[1] + d[2] && t.trace() == a.trace();
+    let s = Matrix::from_diag_rect(&d, 3, 5).unwrap();
+    equal &= s.diagonal() == d && s.trace() == a.trace() && (0..3).all(|i| (0..5).all(|j| i == j || s[(i, j)] == Complex64::new(0.0, 0.0)));
+    equal &= Matrix::from_diag_rect(&d, 2, 5) == Err(CsvdError::InvalidArgument { name: "d", value: 3, max: 2 });
+    equal &= Matrix::<Complex64>::from_diag(&[1.0, 2.0]).trace() == Complex64::new(3.0, 0.0);
+
+    if equal {
+        debug!("matrix ops successful");
+    }
+    else {
+        debug!("matrix ops failed");
+    }
+
+    equal
+}
+
+/// Checks the unitary and Hermitian predicates on matrices that are so up to rounding,
+/// and that a tolerance too tight, a perturbation or a rectangular shape fails them
+fn check_unitary_hermitian() -> bool {
+    let mut equal = true;
+
+    let q = random_unitary::<f64>(6, 283);
+    equal &= is_unitary(&q, 1e-12);
+    equal &= is_unitary(&svd(&lcg_matrix::<f64>(7, 4, 293)).unwrap().u, 1e-12);
+    // the unnormalized DFT matrix is unitary only once scaled by 1 / sqrt(n)
+    let mut dft = gallery::dft::<f64>(8);
+    equal &= !is_unitary(&dft, 1e-12);
+    dft.scale(Complex64::new(1.0 / 8f64.sqrt(), 0.0));
+    equal &= is_unitary(&dft, 1e-12);
+    let mut tilted = q.clone();
+    tilted[(2, 3)] += Complex64::new(1e-6, 0.0);
+    equal &= !is_unitary(&tilted, 1e-8) && is_unitary(&tilted, 1e-5);
+    // orthonormal columns, but not square
+    equal &= !is_unitary(&svd_truncated(&lcg_matrix::<f64>(7, 4, 293), 2).unwrap().u, 1.0);
+
+    // A* A is Hermitian up to rounding at any scale
+    let a = lcg_matrix::<f64>(5, 4, 307);
+    for scale in [1e-20, 1.0, 1e20] {
+        let mut gram = Matrix::zeros(4, 4);
+        equal &= gemm(Complex64::new(scale, 0.0), Op::ConjugateTranspose, &a, Op::None, &a, Complex64::new(0.0, 0.0), &mut gram).is_ok();
+        equal &= is_hermitian(&gram, 1e-14);
+        let d = gram[(0, 0)];
+        gram[(0, 1)] += d * 1e-6;
+        equal &= !is_hermitian(&gram, 1e-8);
+    }
+    equal &= !is_hermitian(&lcg_matrix::<f64>(4, 4, 311), 0.1) && !is_hermitian(&a, 1.0);
+    equal &= is_hermitian(&Matrix::<Complex64>::zeros(3, 3), 0.0);
+
+    if equal {
+        debug!("unitary and hermitian predicates successful");
+    }
+    else {
+        debug!("unitary and hermitian predicates failed");
+    }
+
+    equal
+}
+
+/// Checks kron and outer elementwise, and the mixed-product property
+/// kron(A, B) kron(C, D) = kron(A C, B D)
+fn check_kron_outer() -> bool {
+    let mut equal = true;
+
+    let a = lcg_matrix::<f64>(2, 3, 277);
+    let b = lcg_matrix::<f64>(4, 2, 281);
+    let k = kron(&a, &b);
+
+    equal &= k.rows() == 8 && k.cols() == 6;
+    for i in 0..2 {
+        for j in 0..3 {
+            for p in 0..4 {
+                for q in 0..2 {
+                    equal &= k[(4 * i + p, 2 * j + q)] == a[(i, j)] * b[(p, q)];
+                }
+            }
+        }
+    }
+
+    let c = lcg_matrix::<f64>(3, 2, 283);
+    let d = lcg_matrix::<f64>(2, 5, 293);
+    let mut lhs = Matrix::zeros(8, 10);
+    let mut ac = Matrix::zeros(2, 2);
+    let mut bd = Matrix::zeros(4, 5);
+    equal &= matrix_mult(&k, &kron(&c, &d), &mut lhs).is_ok();
+    equal &= matrix_mult(&a, &c, &mut ac).is_ok() && matrix_mult(&b, &d, &mut bd).is_ok();
+    equal &= check_matrix_equality(&lhs, &kron(&ac, &bd));
+
+    let x = lcg_matrix::<f64>(1, 4, 307).into_vec();
+    let y = lcg_matrix::<f64>(1, 3, 311).into_vec();
+    let o = outer(&x, &y);
+    equal &= o.rows() == 4 && o.cols() == 3;
+    for i in 0..4 {
+        for j in 0..3 {
+            equal &= o[(i, j)] == x[i] * y[j];
+        }
+    }
+
+    if equal {
+        debug!("kron outer successful");
+    }
+    else {
+        debug!("kron outer failed");
+    }
+
+    equal
+}
+
+/// Checks the constructors, and rebuilds A = U S V* with S from from_diag
+fn check_constructors() -> bool {
+    let mut equal = true;
+
+    let one = Complex64::new(1.0, 0.0);
+    let zero = Complex64::new(0.0, 0.0);
+
+    let id = Matrix::<Complex64>::identity(3);
+    let d = Matrix::<Complex64>::from_diag(&[3.0, 2.0, 1.0]);
+    for i in 0..3 {
+        for j in 0..3 {
+            equal &= id[(i, j)] == if i == j { one } else { zero };
+            equal &= d[(i, j)] == if i == j { Complex64::new(3.0 - i as f64, 0.0) } else { zero };
+        }
+    }
+
+    let r0 = [Complex64::new(1.0, 2.0), Complex64::new(3.0, 4.0)];
+    let r1 = [Complex64::new(5.0, 6.0), Complex64::new(7.0, 8.0)];
+    let a = Matrix::from_rows(&[&r0[..], &r1[..]]).unwrap();
+    equal &= a.rows() == 2 && a.cols() == 2 && a.row(0) == &r0[..] && a.row(1) == &r1[..];
+    equal &= Matrix::from_rows(&[&r0[..], &r1[..1]]).is_err();
+    equal &= Matrix::<Complex64>::from_rows(&[]).is_ok_and(|a| a.rows() == 0 && a.cols() == 0);
+
+    let a = lcg_matrix::<f64>(4, 4, 313);
+    let res = svd(&a).unwrap();
+    let mut us = Matrix::zeros(4, 4);
+    let mut usv = Matrix::zeros(4, 4);
+    equal &= matrix_mult(&res.u, &Matrix::from_diag(&res.s), &mut us).is_ok();
+    equal &= gemm(one, Op::None, &us, Op::ConjugateTranspose, &res.v, zero, &mut usv).is_ok();
+    equal &= check_matrix_equality(&usv, &a);
+
+    if equal {
+        debug!("constructors successful");
+    }
+    else {
+        debug!("constructors failed");
+    }
+
+    equal
+}
+
+/// Checks that the generators are deterministic, the moments of the Gaussian entries,
+/// and that random_unitary is unitary
+fn check_random() -> bool {
+    let mut equal = true;
+
+    let a = random_complex_matrix::<f64>(64, 64, 317);
+    equal &= a == random_complex_matrix::<f64>(64, 64, 317);
+    equal &= a != random_complex_matrix::<f64>(64, 64, 318);
+
+    // mean 0 and E|x|^2 = 1, within a few standard errors of the 4096 samples
+    let count = 64.0 * 64.0;
+    let mean = a.as_slice().iter().fold(Complex64::new(0.0, 0.0), |sum, x| sum + x) / count;
+    let power = a.as_slice().iter().map(|x| x.norm_sqr()).sum::<f64>() / count;
+    equal &= mean.norm_sqr() < 0.01 && (power - 1.0).abs() < 0.1;
+
+    let mut rng = Xoshiro256::new(331);
+    equal &= (0..1000).map(|_| rng.next_f64()).all(|x| (0.0..1.0).contains(&x));
+
+    for &n in [1, 5, 16].iter() {
+        let q = random_unitary::<f64>(n, 337);
+        let mut q_h_q = Matrix::zeros(n, n);
+        equal &= gemm(Complex64::new(1.0, 0.0), Op::ConjugateTranspose, &q, Op::None, &q, Complex64::new(0.0, 0.0), &mut q_h_q).is_ok();
+        equal &= check_matrix_equality(&q_h_q, &Matrix::identity(n));
+
+        // all singular values of a unitary matrix are 1
+        equal &= singular_values(&q).is_ok_and(|s| s.iter().all(|&s| (s - 1.0).abs() < 1e-10));
+    }
+    equal &= random_unitary::<f32>(0, 1).rows() == 0;
+
+    if equal {
+        debug!("random successful");
+    }
+    else {
+        debug!("random failed");
+    }
+
+    equal
+}
+
+/// Checks the computed singular values of the gallery matrices against their exact spectra
+fn check_gallery() -> bool {
+    let mut equal = true;
+
+    let close = |s: &[f64], expected: &[f64], rtol: f64| {
+        s.len() == expected.len() && s.iter().zip(expected.iter()).all(|(s, e)| (s - e).abs() <= rtol * e.max(1.0))
+    };
+
+    let s = singular_values(&gallery::dft::<f64>(8)).unwrap();
+    equal &= close(&s, &[Float::sqrt(8.0); 8], 1e-12);
+
+    let roots: Vec<Complex64> = (0..8).map(|k| {
+        let angle = 2.0 * core::f64::consts::PI * k as f64 / 8.0;
+        Complex64::new(Float::cos(angle), Float::sin(angle))
+    }).collect();
+    let s = singular_values(&gallery::vandermonde(&roots, 5)).unwrap();
+    equal &= close(&s, &[Float::sqrt(8.0); 5], 1e-12);
+
+    // det(H) = c(n)^4 / c(2n), with the superfactorial c(k) = 0! 1! ... (k-1)!
+    let n = 6;
+    let c = |k: usize| (0..k).map(|i| (1..=i).product::<usize>() as f64).product::<f64>();
+    let det = Float::powi(c(n), 4) / c(2 * n);
+    let s = singular_values(&gallery::hilbert::<f64>(n)).unwrap();
+    equal &= (s.iter().product::<f64>() / det - 1.0).abs() < 1e-6;
+
+    let s = singular_values(&gallery::rank_deficient::<f64>(7, 5, 3, 347).unwrap()).unwrap();
+    equal &= close(&s, &[3.0, 2.0, 1.0, 0.0, 0.0], 1e-10);
+
+    let s = singular_values(&gallery::graded::<f64>(6, 0.1, 349)).unwrap();
+    let expected: Vec<f64> = (0..6).map(|i| Float::powi(0.1, i)).collect();
+    equal &= s.iter().zip(expected.iter()).all(|(s, e)| (s / e - 1.0).abs() < 1e-8);
+
+    equal &= gallery::with_singular_values::<f64>(3, 4, &[1.0; 4], 353).is_err();
+
+    if equal {
+        debug!("gallery successful");
+    }
+    else {
+        debug!("gallery failed");
+    }
+
+    equal
+}
+
+/// Checks that matrices and decompositions survive encoding, also across precisions,
+/// and that short buffers and corrupted bytes are reported
+fn check_codec() -> bool {
+    use super::codec::{decode_matrix, decode_svd, encode_matrix, encode_svd, encoded_len_matrix, encoded_len_svd};
+
+    let mut equal = true;
+    let mut buf = [0u8; 4096];
+
+    let a = lcg_matrix::<f64>(4, 3, 359);
+    let res = svd(&a).unwrap();
+    let pinv = res.pinv();
+
+    let len = encode_matrix(&pinv, &mut buf).unwrap_or(0);
+    equal &= Some(len) == encoded_len_matrix(&pinv);
+    equal &= decode_matrix::<f64>(&buf[..len]).is_ok_and(|(x, read)| x == pinv && read == len);
+
+    let len = encode_svd(&res, &mut buf).unwrap_or(0);
+    equal &= Some(len) == encoded_len_svd(&res);
+    equal &= decode_svd::<f64>(&buf[..len]).is_ok_and(|(x, read)| x == res && read == len);
+
+    // f32 weights read back as f64
+    let a32 = lcg_matrix::<f32>(2, 5, 367);
+    let len = encode_matrix(&a32, &mut buf).unwrap_or(0);
+    equal &= len == 8 + 8 + 2 * 5 * 8;
+    equal &= decode_matrix::<f64>(&buf[..len]).is_ok_and(|(x, _)| {
+        (0..2).all(|i| (0..5).all(|j| x[(i, j)].re == a32[(i, j)].re as f64 && x[(i, j)].im == a32[(i, j)].im as f64))
+    });
+
+    equal &= encode_svd(&res, &mut buf[..10]).is_err();
+    equal &= decode_svd::<f64>(&buf[..len]).is_err();
+    equal &= decode_matrix::<f64>(&buf[..len - 1]).is_err();
+    buf[0] = b'X';
+    equal &= decode_matrix::<f64>(&buf[..len]).is_err();
+
+    // a count of singular values whose size in bytes overflows, and factors that do not
+    // fit the singular values, which would panic in reconstruct and pinv
+    let mut huge = [0u8; 12];
+    huge[..8].copy_from_slice(&[b'C', b'S', b'V', b'D', 1, 2, 8, 0]);
+    huge[8..].copy_from_slice(&u32::MAX.to_le_bytes());
+    equal &= decode_svd::<f64>(&huge).is_err();
+    let truncated = svd_truncated(&a, 2).unwrap();
+    let short = SvdResult { u: res.u.clone(), s: alloc::vec![1.0; 4], v: res.v.clone() };
+    for bad in [truncated, short] {
+        let len = encode_svd(&bad, &mut buf).unwrap_or(0);
+        equal &= decode_svd::<f64>(&buf[..len]) == Err(CsvdError::InvalidData { reason: "inconsistent factor shapes" });
+    }
+
+    if equal {
+        debug!("codec successful");
+    }
+    else {
+        debug!("codec failed");
+    }
+
+    equal
+}
+
+/// Checks the vector kernels of every supported instruction set against their scalar
+/// versions for odd and even lengths, and that unsupported ones are refused
+fn check_kernels<T: Real>() -> bool {
+    use super::kernels::{axpy, axpy_scalar, dot_conj, dot_conj_scalar, is_supported, isa, rot, rot_scalar, set_isa, Isa};
+
+    let mut equal = true;
+    let eps = T::from(0.0001).unwrap();
+    let selected = isa();
+
+    for &level in [Isa::Scalar, Isa::Sse2, Isa::Avx2Fma, Isa::Neon].iter() {
+        if !is_supported(level) {
+            equal &= !set_isa(level) && isa() == selected;
+            continue;
+        }
+        equal &= set_isa(level) && isa() == level;
+
+        for len in 0..10 {
+            let x = lcg_matrix::<T>(1, len + 1, 179 + len as u64).into_vec();
+            let y = lcg_matrix::<T>(1, len + 1, 181 + len as u64).into_vec();
+            let alpha = Complex::new(T::from(0.7).unwrap(), T::from(-1.3).unwrap());
+
+            equal &= (dot_conj(&x[..len], &y) - dot_conj_scalar(&x[..len], &y)).norm_sqr() < eps;
+
+            let mut y1 = y.clone();
+            let mut y2 = y.clone();
+            axpy(alpha, &x[..len], &mut y1);
+            axpy_scalar(alpha, &x[..len], &mut y2);
+            equal &= y1.iter().zip(y2.iter()).all(|(a, b)| (*a - *b).norm_sqr() < eps);
+
+            let (c, s) = (T::from(0.6).unwrap(), Complex::new(T::from(0.64).unwrap(), T::from(-0.48).unwrap()));
+            let (mut x1, mut y1, mut x2, mut y2) = (x.clone(), y.clone(), x.clone(), y.clone());
+            rot(c, s, &mut x1[..len], &mut y1);
+            rot_scalar(c, s, &mut x2[..len], &mut y2);
+            equal &= x1.iter().chain(&y1).zip(x2.iter().chain(&y2)).all(|(a, b)| (*a - *b).norm_sqr() < eps);
+            // a rotation keeps the norms of the pairs, and the last elements are not touched
+            let norm = |v: &[Complex<T>]| v.iter().fold(T::zero(), |acc, z| acc + z.norm_sqr());
+            equal &= (norm(&x1[..len]) + norm(&y1[..len]) - norm(&x[..len]) - norm(&y[..len])).abs() < eps;
+            equal &= x1[len] == x[len] && y1[len] == y[len];
+        }
+    }
+
+    set_isa(selected);
+
+    if equal {
+        debug!("kernels successful");
+    }
+    else {
+        debug!("kernels failed");
+    }
+
+    equal
+}
+
+/// Fills an m x n matrix with pseudo-random entries in [-1, 1) from a linear congruential generator
+fn lcg_matrix<T: Real>(m: usize, n: usize, seed: u64) -> Matrix<Complex<T>> {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 11) as f64) / ((1u64 << 52) as f64) - 1.0
+    };
+
+    let mut a: Vec<Complex<T>> = Vec::with_capacity(m*n);
+    for _ in 0..m*n {
+        let re = T::from(next()).unwrap();
+        let im = T::from(next()).unwrap();
+        a.push(Complex::new(re, im));
+    }
+
+    Matrix::from_vec(a, m, n).unwrap()
+}
+
+/// Verifies that AlignedVec and aligned_vec honor the requested alignment, and that
+/// AlignedVec drops its elements exactly once
+fn check_aligned_vec() -> bool {
+    use alloc::rc::Rc;
+
+    let mut equal = true;
+
+    for &alignment in [1, 16, 32, 64, 4096].iter() {
+        for &len in [0, 1, 7, 64].iter() {
+            match AlignedVec::<Complex32>::zeroed(len, alignment) {
+                Ok(v) => {
+                    equal &= v.len() == len && (v.as_ptr() as usize).is_multiple_of(alignment);
+                    equal &= v.alignment() == alignment.max(core::mem::align_of::<Complex32>());
+                    equal &= v.iter().all(|x| x.re == 0.0 && x.im == 0.0);
+                }
+                Err(_) => equal = false,
+            }
+        }
+    }
+
+    let mut v = AlignedVec::from_elem(1.5f64, 10, 64).unwrap();
+    v[3] = -2.0;
+    let copy = v.clone();
+    equal &= copy == v && (copy.as_ptr() as usize).is_multiple_of(64) && copy[3] == -2.0;
+
+    equal &= AlignedVec::<f32>::zeroed(4, 48) == Err(CsvdError::InvalidAlignment { alignment: 48 });
+    equal &= AlignedVec::<f32>::zeroed(4, 0).is_err();
+
+    // zeroed buffers of plain data straight from the allocator, up to page alignment
+    for &alignment in [1, 2, 8, 16, 32, 64, 128, 4096].iter() {
+        let bytes = aligned_vec::<u8>(100, alignment).unwrap();
+        let reals = aligned_vec::<f32>(33, alignment).unwrap();
+        let complex = aligned_vec::<Complex64>(9, alignment).unwrap();
+        equal &= (bytes.as_ptr() as usize).is_multiple_of(alignment) && bytes.len() == 100 && bytes.iter().all(|&x| x == 0);
+        equal &= (reals.as_ptr() as usize).is_multiple_of(alignment) && reals.iter().all(|&x| x == 0.0);
+        equal &= (complex.as_ptr() as usize).is_multiple_of(alignment) && complex.iter().all(|x| x.re == 0.0 && x.im == 0.0);
+        equal &= (aligned_vec::<u64>(0, alignment).unwrap().as_ptr() as usize).is_multiple_of(alignment);
+    }
+    equal &= aligned_vec::<f64>(8, 96).is_err();
+    equal &= matches!(aligned_vec::<u8>(usize::MAX, 64), Err(CsvdError::InvalidArgument { name: "len", .. }));
+
+    // uninitialized buffers, filled before they are read
+    let u = aligned_vec_uninit::<Complex32>(64, 4096).unwrap();
+    equal &= (u.as_ptr() as usize).is_multiple_of(4096) && u.len() == 64;
+    let u = u.init_with(|i| Complex32::new(i as f32, -(i as f32)));
+    equal &= (u.as_ptr() as usize).is_multiple_of(4096) && u.iter().enumerate().all(|(i, x)| x.re == i as f32 && x.im == -x.re);
+    let mut v = aligned_vec_uninit::<f64>(3, 32).unwrap();
+    for x in v.iter_mut() {
+        x.write(2.5);
+    }
+    equal &= unsafe { v.assume_init() }.iter().all(|&x| x == 2.5);
+
+    // DMA buffers fill whole pages, and a matrix in one decomposes in place
+    for &len in [1, 100, 512, 513, 1000].iter() {
+        let v = dma_buffer::<Complex64>(len).unwrap();
+        equal &= (v.as_ptr() as usize).is_multiple_of(PAGE_SIZE) && v.len() == len;
+        equal &= v.byte_capacity().is_multiple_of(PAGE_SIZE) && v.byte_capacity() >= len * 16 && v.byte_capacity() < len * 16 + PAGE_SIZE;
+        equal &= v.capacity() == v.byte_capacity() / 16;
+        equal &= unsafe { core::slice::from_raw_parts(v.as_ptr(), v.capacity()) }.iter().all(|x| x.re == 0.0 && x.im == 0.0);
+    }
+    equal &= dma_buffer::<u8>(0).is_ok_and(|v| v.is_empty() && v.byte_capacity() == 0);
+    equal &= dma_buffer::<f32>(3).is_ok_and(|v| v.capacity() == 1024);
+    equal &= AlignedVec::<f32>::zeroed(3, 64).is_ok_and(|v| v.capacity() == 3 && v.byte_capacity() == 12);
+    let mut channel = dma_buffer::<Complex32>(6 * 4).unwrap();
+    for (i, x) in channel.iter_mut().enumerate() {
+        *x = Complex32::new((i % 5) as f32 - 2.0, (i % 3) as f32);
+    }
+    let (mut s, mut u, mut w, mut scratch) = ([0.0; 4], [Complex32::new(0.0, 0.0); 36], [Complex32::new(0.0, 0.0); 16], [0.0; 12]);
+    equal &= csvd_in_slices(&mut channel, &mut s, &mut u, &mut w, &mut scratch, 6, 4).is_ok() && s[0] > 0.0;
+
+    let shared = Rc::new(0u8);
+    let v = AlignedVec::from_elem(shared.clone(), 5, 128).unwrap();
+    equal &= Rc::strong_count(&shared) == 6;
+    drop(v);
+    equal &= Rc::strong_count(&shared) == 1;
+    let v = aligned_vec_uninit::<Rc<u8>>(4, 64).unwrap().init_with(|_| shared.clone());
+    equal &= Rc::strong_count(&shared) == 5;
+    drop(v);
+    equal &= Rc::strong_count(&shared) == 1;
+
+    if equal {
+        debug!("aligned vec successful");
+    }
+    else {
+        debug!("aligned vec failed");
+    }
+    equal
+}
+
+/// An allocator over the global one that counts the blocks it has handed out
+#[derive(Clone)]
+struct CountingAlloc<'a> {
+    live: &'a core::cell::Cell<usize>,
+    total: &'a core::cell::Cell<usize>,
+}
+
+unsafe impl Alloc for CountingAlloc<'_> {
+    fn alloc(&self, layout: core::alloc::Layout) -> Option<core::ptr::NonNull<u8>> {
+        self.live.set(self.live.get() + 1);
+        self.total.set(self.total.get() + 1);
+        Global.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: core::ptr::NonNull<u8>, layout: core::alloc::Layout) {
+        self.live.set(self.live.get() - 1);
+        Global.dealloc(ptr, layout)
+    }
+}
+
+/// Buffers and pooled matrices from a custom allocator, which must be returned to it
+fn check_custom_alloc() -> bool {
+    let live = core::cell::Cell::new(0);
+    let total = core::cell::Cell::new(0);
+    let counting = CountingAlloc { live: &live, total: &total };
+    let mut equal = true;
+
+    {
+        let v = aligned_vec_in::<Complex32, _>(16, 64, counting.clone()).unwrap();
+        equal &= (v.as_ptr() as usize).is_multiple_of(64) && v.iter().all(|x| x.re == 0.0 && x.im == 0.0);
+        let w = AlignedVec::from_elem_in(0.5f64, 5, 32, counting.clone()).unwrap();
+        let copy = w.try_clone().unwrap();
+        equal &= copy == w && live.get() == 3;
+        let u = aligned_vec_uninit_in::<u16, _>(8, 16, counting.clone()).unwrap().init_with(|i| i as u16);
+        equal &= u[7] == 7 && live.get() == 4;
+        // empty buffers need no block
+        equal &= AlignedVec::<f32, _>::zeroed_in(0, 64, counting.clone()).is_ok_and(|e| e.is_empty()) && live.get() == 4;
+    }
+    equal &= live.get() == 0;
+
+    // a pool takes all its matrices from the allocator, also when it grows
+    let mut ws = SvdWorkspace::new(5, 3);
+    let a = lcg_matrix::<f64>(5, 3, 77);
+    equal &= csvd_with_workspace(&a, &mut ws).is_ok();
+    {
+        let mut pool = BufferPool::<Complex64, _>::new_in(3, 5, 64, 1, counting.clone()).unwrap();
+        let before = total.get();
+        let options = PinvOptions::absolute(0.0001);
+        let first = ws.pinv_pooled(&mut pool, &options).unwrap();
+        let second = ws.pinv_pooled(&mut pool, &options).unwrap();
+        equal &= total.get() == before + 1 && live.get() == 2;
+        equal &= first.as_slice() == second.as_slice();
+        equal &= pool.give(first).is_ok() && pool.give(second).is_ok();
+        equal &= ws.pinv_pooled(&mut pool, &options).is_ok_and(|m| pool.give(m).is_ok());
+        equal &= total.get() == before + 1;
+    }
+    equal &= live.get() == 0;
+
+    // a workspace takes its five buffers from the allocator and decomposes a matrix that
+    // lives there too, to the factors of the one on the heap
+    {
+        let mut local = SvdWorkspace::<f64, _>::try_new_in(5, 3, counting.clone()).unwrap();
+        equal &= live.get() == 5;
+        let mut pool = BufferPool::<Complex64, _>::new_in(5, 3, 64, 1, counting.clone()).unwrap();
+        let mut b = pool.take().unwrap();
+        b.as_mut_slice().copy_from_slice(a.as_slice());
+        let before = total.get();
+        equal &= csvd_with_workspace(&b, &mut local).is_ok() && total.get() == before;
+        equal &= local.s() == ws.s();
+        equal &= check_matrix_equality(&local.u().to_matrix(), &ws.u().to_matrix());
+        equal &= check_matrix_equality(&local.v().to_matrix(), &ws.v().to_matrix());
+        equal &= pool.give(b).is_ok();
+    }
+    equal &= live.get() == 0;
+
+    if equal {
+        debug!("custom allocator successful");
+    }
+    else {
+        debug!("custom allocator failed");
+    }
+    equal
+}
+
+/// Verifies the conversion of interleaved IQ buffers into complex samples
+fn check_iq() -> bool {
+    let mut equal = true;
+
+    let raw: [i16; 8] = [16384, -16384, 0, 32767, -32768, 1, 8192, 0];
+    let expected = [Complex32::new(0.5, -0.5), Complex32::new(0.0, 32767.0 / 32768.0),
+                    Complex32::new(-1.0, 1.0 / 32768.0), Complex32::new(0.25, 0.0)];
+    let mut out = [Complex32::new(9.0, 9.0); 5];
+    equal &= iq_into(&raw, 1.0 / i16::FULL_SCALE, &mut out) == Ok(4);
+    equal &= out[..4] == expected && out[4] == Complex32::new(9.0, 9.0);
+    equal &= iq_into(&raw[..7], 1.0, &mut out).is_err_and(|e| matches!(e, CsvdError::InvalidData { .. }));
+    equal &= iq_into(&raw, 1.0, &mut out[..3]) == Err(CsvdError::BufferTooSmall { operand: "out", required: 4, len: 3 });
+
+    let bytes: [i8; 4] = [-128, 64, 127, 0];
+    equal &= iq_into(&bytes, 2.0 / i8::FULL_SCALE, &mut out) == Ok(2);
+    equal &= out[0] == Complex32::new(-2.0, 1.0) && out[1] == Complex32::new(254.0 / 128.0, 0.0);
+
+    // a 2 x 2 channel estimate, with a trailing sample that is not part of it
+    let a = iq_matrix(&raw, 2, 2, 1.0 / i16::FULL_SCALE).unwrap();
+    equal &= a.rows() == 2 && a.cols() == 2 && a.as_slice() == expected;
+    equal &= iq_matrix(&raw[..7], 1, 3, 1.0).is_ok_and(|a| a.as_slice().len() == 3);
+    equal &= iq_matrix(&raw, 3, 2, 1.0) == Err(CsvdError::BufferTooSmall { operand: "raw", required: 12, len: 8 });
+    // shapes whose sample count wraps around must not pass the length check
+    equal &= iq_matrix(&raw, usize::MAX / 2 + 1, 2, 1.0) == Err(CsvdError::BufferTooSmall { operand: "raw", required: usize::MAX, len: 8 });
+    equal &= iq_matrix(&raw, usize::MAX / 4 + 1, 2, 1.0).is_err();
+
+    let aligned = iq_aligned(&raw, 64, 1.0 / i16::FULL_SCALE).unwrap();
+    equal &= (aligned.as_ptr() as usize).is_multiple_of(64) && aligned[..] == expected;
+    equal &= iq_aligned(&raw[..7], 64, 1.0).is_err();
+
+    // f32 pairs are reinterpreted in place, or scaled while copying
+    let mut pairs = [[1.0f32, -2.0], [0.5, 4.0], [-3.0, 0.0]];
+    equal &= pairs_as_complex(&pairs) == [Complex32::new(1.0, -2.0), Complex32::new(0.5, 4.0), Complex32::new(-3.0, 0.0)];
+    scal(Complex32::new(2.0, 0.0), pairs_as_complex_mut(&mut pairs));
+    equal &= pairs == [[2.0, -4.0], [1.0, 8.0], [-6.0, 0.0]];
+    equal &= iq_into(pairs.as_flattened(), 0.5, &mut out) == Ok(3) && out[1] == Complex32::new(0.5, 4.0);
+
+    // samples straight from the front-end decompose like any other matrix
+    let raw: Vec<i16> = (0..2 * 4 * 3).map(|k| ((k * 7919) % 4001) as i16 - 2000).collect();
+    let mut channel = iq_aligned(&raw, 32, 1.0 / i16::FULL_SCALE).unwrap();
+    let reference = svd(&iq_matrix(&raw, 4, 3, 1.0 / i16::FULL_SCALE).unwrap()).unwrap();
+    let (mut s, mut u, mut v, mut scratch) = ([0.0; 3], [Complex32::new(0.0, 0.0); 16], [Complex32::new(0.0, 0.0); 9], [0.0; 9]);
+    equal &= csvd_in_slices(&mut channel, &mut s, &mut u, &mut v, &mut scratch, 4, 3).is_ok();
+    equal &= s.iter().zip(&reference.s).all(|(x, y)| (x - y).abs() < 0.0001);
+
+    if equal {
+        debug!("IQ ingestion successful");
+    }
+    else {
+        debug!("IQ ingestion failed");
+    }
+    equal
+}
+
+/// Verifies the decomposition of Q15 fixed-point matrices against their f32 values
+fn check_q15() -> bool {
+    let mut equal = true;
+
+    let q = [Complex::new(16384i16, -8192), Complex::new(0, 32767), Complex::new(-32768, 0)];
+    let mut out = [Complex32::new(0.0, 0.0); 3];
+    equal &= q15_into(&q, 2.0, &mut out) == Ok(3);
+    equal &= out == [Complex32::new(1.0, -0.5), Complex32::new(0.0, 32767.0 / 16384.0), Complex32::new(-2.0, 0.0)];
+    equal &= q15_into(&q, 1.0, &mut out[..2]) == Err(CsvdError::BufferTooSmall { operand: "out", required: 3, len: 2 });
+
+    // a 5 x 3 channel estimate with a scale factor of 4 from the front-end's AGC
+    let a: Vec<Complex<i16>> = (0..15).map(|k: i32| Complex::new(((k * 7919) % 60001 - 30000) as i16, ((k * 104729) % 50021 - 25010) as i16)).collect();
+    let reference = svd(&Matrix::from_vec(a.iter().map(|q| Complex32::new(q.re as f32 / 8192.0, q.im as f32 / 8192.0)).collect(), 5, 3).unwrap()).unwrap();
+    let res = svd_q15(&a, 5, 3, 4.0).unwrap();
+    equal &= res.s.iter().zip(&reference.s).all(|(x, y)| (x - y).abs() < 0.0001 * reference.s[0]);
+    equal &= check_matrix_equality(&res.u, &reference.u) && check_matrix_equality(&res.v, &reference.v);
+    equal &= svd_q15(&a, 3, 5, 4.0).is_ok_and(|res| res.u.rows() == 3 && res.v.rows() == 5);
+
+    equal &= svd_q15(&a, 4, 4, 1.0) == Err(CsvdError::BufferTooSmall { operand: "A", required: 16, len: 15 });
+    equal &= svd_q15(&a, 0, 3, 1.0) == Err(CsvdError::EmptyMatrix);
+    equal &= matches!(svd_q15(&a, 5, 3, f32::INFINITY), Err(CsvdError::NonFiniteInput { .. }));
+
+    if equal {
+        debug!("Q15 input successful");
+    }
+    else {
+        debug!("Q15 input failed");
+    }
+    equal
+}
+
+/// A basic example to test with: https://math.stackexchange.com/questions/647321/moore-penrose-inverse-of-complex-square-matrices
+pub fn test() {
+
+    let m = 8;
+    let n = 8;
+
+    let a = AlignedVec::from_elem(Complex32{re: 2.1, im: 1.3}, m*n, 32).unwrap();
+    let mut a = Matrix::from_vec(a.to_vec(), m, n).unwrap();
+    
+    check_svd(&mut a) ;
+
+    // same matrix in double precision
+    let mut a: Vec<Complex64> = Vec::with_capacity(m*n);
+    for _ in 0..m*n {
+        a.push(Complex64{re: 2.1, im: 1.3});
+    }
+    let mut a = Matrix::from_vec(a, m, n).unwrap();
+
+    check_svd(&mut a);
+
+    // a tall matrix through the owned result api
+    let a = lcg_matrix::<f64>(12, 5, 3);
+
+    check_svd_result(&a);
+
+    check_iteration_cap(&a);
+
+    check_compensated_summation();
+
+    check_non_finite();
+
+    check_phase_convention();
+
+    check_svd_stats();
+
+    check_reorthogonalize();
+
+    check_refine();
+
+    check_progress();
+
+    // a wide matrix, decomposed through its conjugate transpose
+    let mut a = lcg_matrix::<f64>(5, 12, 11);
+
+    check_svd(&mut a);
+
+    let a = lcg_matrix::<f64>(5, 12, 13);
+
+    check_svd_result(&a);
+
+    // entries close to the largest and smallest normal f32
+    check_extreme_scaling(f32::MAX / 8.0);
+    check_extreme_scaling(f32::MIN_POSITIVE * 4.0);
+
+    check_workspace();
+
+    check_in_slices();
+    check_views();
+    check_col_major();
+    check_svd_real::<f32>();
+    check_svd_real::<f64>();
+    check_svd_auto::<f32>();
+    check_svd_auto::<f64>();
+    check_blocks();
+
+    check_soa();
+
+    check_buffer_pool();
+    check_custom_alloc();
+
+    check_fixed();
+    check_svd4();
+    check_small_inverse();
+
+    check_truncated();
+
+    check_qr();
+
+    check_qr_pivoted();
+
+    check_orthonormalize();
+
+    check_lu();
+
+    check_lstsq();
+
+    check_pinv_operator();
+
+    check_pinv_regularized();
+
+    check_pinv_options();
+
+    check_mixed_precision();
+
+    check_rank_cond();
+
+    check_rank_by_gap();
+
+    check_subspaces();
+
+    check_low_rank_approx();
+    check_rank_one_update();
+    check_incremental_svd();
+    check_dominant_triplet();
+    check_sigma_min();
+    check_partial_svd();
+    check_sparse();
+    check_lsqr();
+    check_krylov_solvers();
+    check_preconditioners();
+    check_cgls();
+
+    check_det();
+
+    check_penrose_rectangular();
+
+    check_solve_multi();
+
+    check_solve_auto();
+
+    check_batched_svd();
+
+    check_batched_pinv();
+    check_zf_weights();
+    check_eigen_beamforming();
+    check_capacity();
+
+    check_matrix_mult();
+
+    check_gemm();
+
+    check_blas();
+
+    check_matrix_ops();
+
+    check_kron_outer();
+
+    check_constructors();
+
+    check_random();
+
+    check_gallery();
+
+    check_codec();
+
+    check_aligned_vec();
+
+    check_iq();
+
+    check_q15();
+
+    check_unitary_hermitian();
+
+    check_kernels::<f32>();
+    check_kernels::<f64>();
+
+    // a matrix with more columns than the old NBIG = 150 limit
+    let mut a = lcg_matrix::<f64>(170, 160, 7);
+
+    check_svd(&mut a);
+  
+}
+#[cfg(test)]
+mod golden;
+// the generated references round 1/sqrt(2) and the like to the nearest double
+#[cfg(test)]
+#[allow(clippy::approx_constant)]
+mod golden_data;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that the SVD of a reconstructs a and has unitary factors to a tolerance
+    /// relative to ||A||, that its singular values match expected if given, and that the
+    /// pseudo-inverse satisfies the Moore-Penrose conditions
+    fn assert_decomposition(a: &Matrix<Complex64>, expected: Option<&[f64]>) {
+        let m = a.rows();
+        let n = a.cols();
+        let norm = Float::sqrt(a.as_slice().iter().map(|x| x.norm_sqr()).sum::<f64>());
+        let tol = 1e-12 * (m.max(n) as f64) * norm.max(1.0);
+
+        let res = svd(a).unwrap();
+        let report = verify_svd(a, &res, tol).unwrap();
+        assert!(report.passed(), "{} x {}: {:?}", m, n, report);
+
+        if let Some(expected) = expected {
+            assert_eq!(res.s.len(), expected.len());
+            for (s, e) in res.s.iter().zip(expected.iter()) {
+                assert!((s - e).abs() <= tol, "{} x {}: singular value {} instead of {}", m, n, s, e);
+            }
+        }
+
+        let pinv = res.pinv();
+        let report = verify_pinv(a, &pinv, 1e-4 * m.max(n) as f64).unwrap();
+        assert!(report.passed(), "{} x {}: {:?}", m, n, report);
+    }
+
+    #[test]
+    fn square() {
+        assert_decomposition(&random_complex_matrix(8, 8, 1), None);
+        assert_decomposition(&gallery::dft(16), Some(&[4.0; 16]));
+    }
+
+    #[test]
+    fn tall() {
+        assert_decomposition(&random_complex_matrix(12, 5, 2), None);
+        assert_decomposition(&gallery::with_singular_values(9, 4, &[4.0, 3.0, 2.0, 1.0], 3).unwrap(), Some(&[4.0, 3.0, 2.0, 1.0]));
+    }
+
+    #[test]
+    fn wide() {
+        assert_decomposition(&random_complex_matrix(5, 12, 4), None);
+        assert_decomposition(&gallery::with_singular_values(3, 7, &[2.0, 1.0, 0.5], 5).unwrap(), Some(&[2.0, 1.0, 0.5]));
+    }
+
+    #[test]
+    fn rank_deficient() {
+        assert_decomposition(&gallery::rank_deficient(7, 5, 3, 6).unwrap(), Some(&[3.0, 2.0, 1.0, 0.0, 0.0]));
+        assert_decomposition(&gallery::rank_deficient(4, 9, 1, 7).unwrap(), Some(&[1.0, 0.0, 0.0, 0.0]));
+        assert_eq!(rank(&gallery::rank_deficient::<f64>(7, 5, 3, 8).unwrap(), None).unwrap(), 3);
+    }
+
+    #[test]
+    fn near_singular() {
+        let s = [1.0, 0.5, 0.1, 1e-3, 1e-6, 1e-9];
+        assert_decomposition(&gallery::with_singular_values(6, 6, &s, 9).unwrap(), Some(&s));
+        assert_decomposition(&gallery::hilbert(8), None);
+    }
+
+    #[test]
+    fn real() {
+        assert!(check_svd_real::<f32>());
+        assert!(check_svd_real::<f64>());
+        assert!(check_svd_auto::<f32>());
+        assert!(check_svd_auto::<f64>());
+    }
+
+    #[test]
+    fn single_precision() {
+        let a = random_complex_matrix::<f32>(10, 6, 10);
+        let res = svd(&a).unwrap();
+        assert!(verify_svd(&a, &res, 1e-4).unwrap().passed());
+        assert!(verify_pinv(&a, &res.pinv(), 1e-3).unwrap().passed());
+    }
+
+    #[test]
+    fn constant_matrices() {
+        // rank one, so all but one singular value are exactly zero
+        let mut a = Matrix::from_vec(alloc::vec![Complex32::new(2.1, 1.3); 64], 8, 8).unwrap();
+        assert!(check_svd(&mut a));
+        let mut a = Matrix::from_vec(alloc::vec![Complex64::new(2.1, 1.3); 64], 8, 8).unwrap();
+        assert!(check_svd(&mut a));
+    }
+
+    #[test]
+    fn svd_checks() {
+        assert!(check_svd(&mut lcg_matrix::<f64>(5, 12, 11)));
+        assert!(check_svd(&mut lcg_matrix::<f64>(170, 160, 7)));
+        assert!(check_svd_result(&lcg_matrix::<f64>(12, 5, 3)));
+        assert!(check_svd_result(&lcg_matrix::<f64>(5, 12, 13)));
+        assert!(check_iteration_cap(&lcg_matrix::<f64>(12, 5, 3)));
+        assert!(check_compensated_summation());
+        assert!(check_non_finite());
+        assert!(check_phase_convention());
+        assert!(check_svd_stats());
+        assert!(check_reorthogonalize());
+        assert!(check_refine());
+        assert!(check_progress());
+    }
+
+    #[test]
+    fn extreme_scaling() {
+        assert!(check_extreme_scaling(f32::MAX / 8.0));
+        assert!(check_extreme_scaling(f32::MIN_POSITIVE * 4.0));
+    }
+
+    #[test]
+    fn workspace() {
+        assert!(check_workspace());
+        assert!(check_in_slices());
+        assert!(check_views());
+        assert!(check_buffer_pool());
+        assert!(check_custom_alloc());
+        assert!(check_fixed());
+        assert!(check_svd4());
+        assert!(check_small_inverse());
+    }
+
+    // a column past the last one would otherwise read the next row or its padding
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn matrix_index_out_of_bounds() {
+        let a = Matrix::<f64>::zeros(2, 3);
+        let _ = a[(0, 3)];
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn view_index_out_of_bounds() {
+        let data = [0.0f64; 8];
+        let view = MatrixView::new(&data, 2, 3, 4).unwrap();
+        let _ = view[(0, 3)];
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn pooled_index_out_of_bounds() {
+        let mut pool = BufferPool::<f64>::new(2, 3, 64, 1).unwrap();
+        let mut b = pool.take().unwrap();
+        b[(0, 3)] = 1.0;
+    }
+
+    #[test]
+    fn truncated() {
+        assert!(check_truncated());
+        assert!(check_low_rank_approx());
+    }
+
+    #[test]
+    fn updates() {
+        assert!(check_rank_one_update());
+        assert!(check_incremental_svd());
+    }
+
+    #[test]
+    fn iterative() {
+        assert!(check_dominant_triplet());
+        assert!(check_sigma_min());
+        assert!(check_partial_svd());
+        assert!(check_sparse());
+        assert!(check_lsqr());
+        assert!(check_krylov_solvers());
+        assert!(check_preconditioners());
+        assert!(check_cgls());
+    }
+
+    #[test]
+    fn qr() {
+        assert!(check_qr());
+        assert!(check_qr_pivoted());
+        assert!(check_orthonormalize());
+    }
+
+    #[test]
+    fn lu() {
+        assert!(check_lu());
+        assert!(check_det());
+    }
+
+    #[test]
+    fn solve() {
+        assert!(check_lstsq());
+        assert!(check_solve_multi());
+        assert!(check_solve_auto());
+    }
+
+    #[test]
+    fn pinv() {
+        assert!(check_pinv_operator());
+        assert!(check_pinv_regularized());
+        assert!(check_pinv_options());
+        assert!(check_mixed_precision());
+        assert!(check_penrose_rectangular());
+    }
+
+    #[test]
+    fn rank_and_subspaces() {
+        assert!(check_rank_cond());
+        assert!(check_rank_by_gap());
+        assert!(check_subspaces());
+    }
+
+    #[test]
+    fn batched() {
+        assert!(check_batched_svd());
+        assert!(check_batched_pinv());
+    }
+
+    #[test]
+    fn mimo() {
+        assert!(check_zf_weights());
+        assert!(check_eigen_beamforming());
+        assert!(check_capacity());
+    }
+
+    #[test]
+    fn products() {
+        assert!(check_matrix_mult());
+        assert!(check_gemm());
+        assert!(check_blas());
+        assert!(check_kron_outer());
+        assert!(check_col_major());
+        assert!(check_blocks());
+        assert!(check_soa());
+    }
+
+    #[test]
+    fn matrices() {
+        assert!(check_matrix_ops());
+        assert!(check_constructors());
+        assert!(check_random());
+        assert!(check_gallery());
+        assert!(check_codec());
+        assert!(check_aligned_vec());
+        assert!(check_iq());
+        assert!(check_q15());
+        assert!(check_unitary_hermitian());
+    }
+
+    #[test]
+    fn display() {
+        let a = Matrix::from_rows(&[&[Complex64::new(1.0, 0.5), Complex64::new(-2.0, 0.0)][..], &[Complex64::new(0.0, -1.0), Complex64::new(3.25, 1.0)][..]]).unwrap();
+        assert_eq!(alloc::format!("{}", a), "1+0.5i, -2+0i\n0-1i, 3.25+1i");
+        assert_eq!(alloc::format!("{:.2}", a), "1.00+0.50i, -2.00+0.00i\n0.00-1.00i, 3.25+1.00i");
+        assert_eq!(alloc::format!("{}", Matrix::<Complex64>::zeros(0, 3)), "");
+
+        #[cfg(feature = "std")]
+        {
+            print_matrix(&a);
+            print_svd(&svd(&a).unwrap());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn npy() {
+        use npy::{read_npy, write_npy, NpyError};
+
+        let a = random_complex_matrix::<f64>(3, 5, 11);
+        let mut bytes = Vec::new();
+        write_npy(&mut bytes, &a).unwrap();
+        // the data starts 64-byte aligned, as numpy writes it
+        assert_eq!(bytes.iter().position(|&b| b == b'\n').unwrap() % 64, 63);
+        assert_eq!(read_npy::<f64, _>(&bytes[..]).unwrap(), a);
+
+        let a = random_complex_matrix::<f32>(4, 2, 12);
+        let mut bytes = Vec::new();
+        write_npy(&mut bytes, &a).unwrap();
+        assert!(bytes.windows(5).any(|w| w == b"'<c8'"));
+        assert_eq!(read_npy::<f32, _>(&bytes[..]).unwrap(), a);
+
+        let with_header = |header: &str| {
+            let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+            bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(header.as_bytes());
+            bytes
+        };
+
+        // a big-endian complex64 array in Fortran order, [[1+2j, 3+4j], [5+6j, 7+8j]] stored by columns
+        let mut bytes = with_header("{'descr': '>c8', 'fortran_order': True, 'shape': (2, 2), }\n");
+        for &x in &[1.0f32, 2.0, 5.0, 6.0, 3.0, 4.0, 7.0, 8.0] {
+            bytes.extend_from_slice(&x.to_be_bytes());
+        }
+        let a = read_npy::<f64, _>(&bytes[..]).unwrap();
+        assert_eq!(a[(0, 1)], Complex64::new(3.0, 4.0));
+        assert_eq!(a[(1, 0)], Complex64::new(5.0, 6.0));
+
+        // a vector is read as a column
+        let mut bytes = with_header("{'descr': '<c16', 'fortran_order': False, 'shape': (3,), }\n");
+        bytes.extend((0..6).flat_map(|x| (x as f64).to_le_bytes().to_vec()));
+        let a = read_npy::<f64, _>(&bytes[..]).unwrap();
+        assert_eq!((a.rows(), a.cols()), (3, 1));
+        assert_eq!(a[(2, 0)], Complex64::new(4.0, 5.0));
+
+        match read_npy::<f64, _>(&with_header("{'descr': '<f8', 'fortran_order': False, 'shape': (1,), }\n")[..]) {
+            Err(NpyError::UnsupportedDtype(descr)) => assert_eq!(descr, "<f8"),
+            other => panic!("{:?}", other),
+        }
+        assert!(matches!(read_npy::<f64, _>(&b"PK\x03\x04 not npy"[..]), Err(NpyError::InvalidFormat(_))));
+        assert!(matches!(read_npy::<f64, _>(&bytes[..20]), Err(NpyError::Io(_))));
+
+        // shapes from a malicious header neither overflow nor allocate before the data is there
+        let huge = with_header("{'descr': '<c16', 'fortran_order': False, 'shape': (4294967296, 4294967296), }\n");
+        assert!(matches!(read_npy::<f64, _>(&huge[..]), Err(NpyError::InvalidFormat("shape too large"))));
+        let mut truncated = with_header("{'descr': '<c16', 'fortran_order': False, 'shape': (100000, 100000), }\n");
+        truncated.extend_from_slice(&[0; 64]);
+        assert!(matches!(read_npy::<f64, _>(&truncated[..]), Err(NpyError::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn csv() {
+        use csv::{from_csv, to_csv, CsvError, CsvFormat};
+
+        for &format in &[CsvFormat::Complex, CsvFormat::Pairs] {
+            let mut a = random_complex_matrix::<f64>(3, 4, 13);
+            a[(0, 0)] = Complex64::new(-0.0, -1e-300);
+            let mut text = Vec::new();
+            to_csv(&mut text, &a, format).unwrap();
+            assert_eq!(from_csv::<f64, _>(&text[..], format).unwrap(), a);
+
+            let a = random_complex_matrix::<f32>(2, 2, 14);
+            let mut text = Vec::new();
+            to_csv(&mut text, &a, format).unwrap();
+            assert_eq!(from_csv::<f32, _>(&text[..], format).unwrap(), a);
+        }
+
+        let text = "# written by hand\n1, -2.5j, (3-4i)\n\n1e-3+2E+2j, 1-j, 7\n";
+        let a = from_csv::<f64, _>(text.as_bytes(), CsvFormat::Complex).unwrap();
+        assert_eq!((a.rows(), a.cols()), (2, 3));
+        assert_eq!(a.row(0), &[Complex64::new(1.0, 0.0), Complex64::new(0.0, -2.5), Complex64::new(3.0, -4.0)][..]);
+        assert_eq!(a.row(1), &[Complex64::new(1e-3, 200.0), Complex64::new(1.0, -1.0), Complex64::new(7.0, 0.0)][..]);
+
+        let a = from_csv::<f64, _>("1,2,3,4\n5,6,7,8\n".as_bytes(), CsvFormat::Pairs).unwrap();
+        assert_eq!(a[(1, 1)], Complex64::new(7.0, 8.0));
+
+        assert!(matches!(from_csv::<f64, _>("1,2\n3\n".as_bytes(), CsvFormat::Complex), Err(CsvError::RaggedRow { line: 2, expected: 2, found: 1 })));
+        assert!(matches!(from_csv::<f64, _>("1,2\n3,x+j\n".as_bytes(), CsvFormat::Complex), Err(CsvError::Parse { line: 2, column: 2 })));
+        assert!(matches!(from_csv::<f64, _>("1,2,3\n".as_bytes(), CsvFormat::Pairs), Err(CsvError::OddColumns { line: 1 })));
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn nalgebra() {
+        use nalgebra::{DMatrix, Dynamic, SVD};
+
+        let a = random_complex_matrix::<f32>(4, 6, 15);
+        let d = DMatrix::from(&a);
+        assert_eq!((d.nrows(), d.ncols()), (4, 6));
+        assert_eq!(d[(3, 1)], a[(3, 1)]);
+        assert_eq!(Matrix::from(d), a);
+
+        // nalgebra's recompose multiplies the converted thin factors back together
+        let a = random_complex_matrix::<f64>(7, 3, 16);
+        let res = svd(&a).unwrap();
+        let thin: SVD<Complex64, Dynamic, Dynamic> = res.clone().into();
+        assert_eq!(thin.singular_values.as_slice(), &res.s[..]);
+        assert!(check_matrix_equality(&Matrix::from(thin.recompose().unwrap()), &a));
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn ffi() {
+        use ffi::*;
+
+        let (m, n) = (5, 3);
+        let a = random_complex_matrix::<f64>(m, n, 17);
+        let mut a_raw = a.as_slice().to_vec();
+        let mut s = alloc::vec![0.0; n];
+        let mut u = alloc::vec![Complex64::new(0.0, 0.0); m * m];
+        let mut v = alloc::vec![Complex64::new(0.0, 0.0); n * n];
+        let mut scratch = alloc::vec![0.0; csvd_scratch_len(m, n)];
+        let code = unsafe {
+            csvd_svd_c64(a_raw.as_mut_ptr(), m, n, s.as_mut_ptr(), u.as_mut_ptr(), v.as_mut_ptr(), scratch.as_mut_ptr())
+        };
+        assert_eq!(code, CSVD_OK);
+        assert_eq!(s, svd(&a).unwrap().s);
+
+        let mut inv = alloc::vec![Complex64::new(0.0, 0.0); m * n];
+        assert_eq!(unsafe { csvd_pinv_c64(a.as_slice().as_ptr(), m, n, inv.as_mut_ptr()) }, CSVD_OK);
+        let mut expected = Matrix::zeros(n, m);
+        super::pinv(&mut a.clone(), &mut expected).unwrap();
+        assert_eq!(&inv[..], expected.as_slice());
+
+        // every pointer is validated before it is used
+        let null = core::ptr::null_mut();
+        assert_eq!(unsafe { csvd_pinv_c64(a.as_slice().as_ptr(), m, n, null) }, CSVD_ERR_NULL_POINTER);
+        assert_eq!(unsafe { csvd_pinv_c64(a.as_slice().as_ptr(), 0, n, inv.as_mut_ptr()) }, CSVD_ERR_EMPTY_MATRIX);
+        assert_eq!(unsafe { csvd_pinv_c64(a.as_slice().as_ptr(), usize::MAX, 2, inv.as_mut_ptr()) }, CSVD_ERR_TOO_LARGE);
+        let misaligned = (inv.as_mut_ptr() as usize + 4) as *mut Complex64;
+        assert_eq!(unsafe { csvd_pinv_c64(a.as_slice().as_ptr(), m, n, misaligned) }, CSVD_ERR_MISALIGNED);
+        let code = unsafe {
+            csvd_svd_c64(a_raw.as_mut_ptr(), m, n, s.as_mut_ptr(), a_raw.as_mut_ptr().add(1), v.as_mut_ptr(), scratch.as_mut_ptr())
+        };
+        assert_eq!(code, CSVD_ERR_OVERLAP);
+
+        assert_eq!(error_code(&CsvdError::Singular { index: 2 }), CSVD_ERR_SINGULAR);
+        let message = unsafe { core::slice::from_raw_parts(csvd_error_message(CSVD_ERR_OVERLAP), 38) };
+        assert_eq!(message, &b"output buffer overlaps another buffer\0"[..]);
+    }
+
+    // one test, as the instruction set is switched for the whole process
+    #[test]
+    fn kernels() {
+        assert!(check_kernels::<f32>());
+        assert!(check_kernels::<f64>());
+    }
+}
+
+/// Property tests over random shapes and entries, run with `cargo test --features proptest`
+#[cfg(all(test, feature = "proptest"))]
+mod properties {
+    use proptest::prelude::*;
+    use super::*;
+
+    /// Matrices of up to 12 x 12 with entries of magnitude up to 10^exponent, and every
+    /// second one with a repeated column so that rank-deficient inputs are common
+    fn matrix() -> impl Strategy<Value = Matrix<Complex64>> {
+        (1usize..13, 1usize..13, -30i32..30, any::<bool>()).prop_flat_map(|(m, n, exponent, repeat)| {
+            proptest::collection::vec((-1.0..1.0f64, -1.0..1.0f64), m * n).prop_map(move |entries| {
+                let scale = Float::powi(10.0, exponent);
+                let mut a = Matrix::from_vec(entries.into_iter().map(|(re, im)| Complex64::new(re, im) * scale).collect(), m, n).unwrap();
+                if repeat && n > 1 {
+                    for i in 0..m {
+                        a[(i, n - 1)] = a[(i, 0)];
+                    }
+                }
+                a
+            })
+        })
+    }
+
+    fn frobenius(a: &Matrix<Complex64>) -> f64 {
+        nrm2(a.as_slice())
+    }
+
+    proptest! {
+        #[test]
+        fn svd_invariants(a in matrix()) {
+            let m = a.rows();
+            let n = a.cols();
+            let res = svd(&a).unwrap();
+
+            prop_assert_eq!(res.s.len(), m.min(n));
+            prop_assert!(res.s.iter().all(|&s| s >= 0.0));
+            prop_assert!(res.s.windows(2).all(|w| w[0] >= w[1]));
+
+            // U and V are unitary independently of the scale of A, the reconstruction
+            // error is relative to it
+            let report = verify_svd(&a, &res, f64::INFINITY).unwrap();
+            let tol = 1e-12 * m.max(n) as f64;
+            prop_assert!(report.u_orthogonality <= tol, "U* U - I = {}", report.u_orthogonality);
+            prop_assert!(report.v_orthogonality <= tol, "V* V - I = {}", report.v_orthogonality);
+            prop_assert!(report.reconstruction <= tol * frobenius(&a), "A - U S V* = {}", report.reconstruction);
+        }
+
+        #[test]
+        fn conj_transpose_has_same_singular_values(a in matrix()) {
+            let s = singular_values(&a).unwrap();
+            let s_h = singular_values(&a.conj_transpose()).unwrap();
+            let tol = 1e-12 * a.rows().max(a.cols()) as f64 * frobenius(&a);
+
+            prop_assert_eq!(s.len(), s_h.len());
+            prop_assert!(s.iter().zip(s_h.iter()).all(|(x, y)| (x - y).abs() <= tol));
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Compares the decompositions against the reference factors of golden_data.rs,
+//! which scripts/golden.py generates. Singular vectors are only unique up to a phase,
+//! and those of a repeated singular value only up to a unitary mix, so the singular
+//! subspaces are compared through their orthogonal projectors instead.
+
+use alloc::vec::Vec;
+use num_complex::Complex64;
+use num_traits::Float;
+use super::{svd, Matrix};
+
+/// A matrix with its thin reference factors, all row-major (re, im) pairs
+pub(super) struct Case {
+    pub name: &'static str,
+    pub m: usize,
+    pub n: usize,
+    pub a: &'static [[f64; 2]],
+    pub s: &'static [f64],
+    /// m x min(m, n)
+    pub u: &'static [[f64; 2]],
+    /// n x min(m, n)
+    pub v: &'static [[f64; 2]],
+}
+
+fn matrix(data: &[[f64; 2]], rows: usize, cols: usize) -> Matrix<Complex64> {
+    Matrix::from_vec(data.iter().map(|x| Complex64::new(x[0], x[1])).collect(), rows, cols).unwrap()
+}
+
+/// Frobenius norm of the difference of the projectors onto the spans of
+/// columns cols of q and of q_ref
+fn projector_distance(q: &Matrix<Complex64>, q_ref: &Matrix<Complex64>, cols: &[usize]) -> f64 {
+    let rows = q.rows();
+    let mut sum = 0.0;
+
+    for i in 0..rows {
+        for j in 0..rows {
+            let mut d = Complex64::new(0.0, 0.0);
+            for &k in cols {
+                d = d + q[(i, k)] * q[(j, k)].conj() - q_ref[(i, k)] * q_ref[(j, k)].conj();
+            }
+            sum += d.norm_sqr();
+        }
+    }
+
+    Float::sqrt(sum)
+}
+
+#[test]
+fn golden() {
+    use super::golden_data::CASES;
+
+    for case in CASES {
+        let (m, n) = (case.m, case.n);
+        let k = m.min(n);
+        let res = svd(&matrix(case.a, m, n)).unwrap();
+
+        // singular values to n eps relative to the largest one
+        let s_max = case.s.iter().cloned().fold(0.0, f64::max);
+        let tol = m.max(n) as f64 * f64::EPSILON * s_max;
+        for (s, s_ref) in res.s.iter().zip(case.s.iter()) {
+            assert!((s - s_ref).abs() <= tol, "{}: singular value {} instead of {}", case.name, s, s_ref);
+        }
+
+        // the subspaces of equal singular values, whose accuracy depends on the gap to the others
+        let u_ref = matrix(case.u, m, k);
+        let v_ref = matrix(case.v, n, k);
+        let mut start = 0;
+        while start < k {
+            let mut end = start + 1;
+            while end < k && (case.s[end] - case.s[start]).abs() <= 1e-8 * s_max {
+                end += 1;
+            }
+            let cols: Vec<usize> = (start..end).collect();
+
+            assert!(projector_distance(&res.u, &u_ref, &cols) < 1e-10, "{}: left subspace {:?}", case.name, cols);
+            assert!(projector_distance(&res.v, &v_ref, &cols) < 1e-10, "{}: right subspace {:?}", case.name, cols);
+            start = end;
+        }
+    }
+}
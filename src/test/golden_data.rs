@@ -0,0 +1,96 @@
+// Generated by scripts/golden.py --mpmath from mpmath svd_c in 50 digits, do not edit.
+
+use super::golden::Case;
+
+pub(super) static CASES: &[Case] = &[
+    Case {
+        name: "permuted_phases_3x3",
+        m: 3,
+        n: 3,
+        a: &[[0.0, 0.0], [0.0, 2.0], [0.0, 0.0], [0.0, 0.0], [0.0, 0.0], [-1.0, 0.0], [5.0, 0.0], [0.0, 0.0], [0.0, 0.0]],
+        s: &[5.0, 2.0, 1.0],
+        u: &[[0.0, 0.0], [0.0, -1.0], [0.0, 0.0], [0.0, 0.0], [0.0, 0.0], [1.0, 0.0], [-1.0, 0.0], [0.0, 0.0], [0.0, 0.0]],
+        v: &[[-1.0, 0.0], [0.0, 0.0], [0.0, 0.0], [0.0, 0.0], [-1.0, 0.0], [0.0, 0.0], [0.0, 0.0], [0.0, 0.0], [-1.0, 0.0]],
+    },
+    Case {
+        name: "rotations_2x2",
+        m: 2,
+        n: 2,
+        a: &[[1.2727922061357853, 0.565685424949238], [-1.2727922061357853, 0.565685424949238], [0.42426406871192845, 1.6970562748477143], [0.42426406871192845, -1.6970562748477143]],
+        s: &[3.0, 0.9999999999999999],
+        u: &[[-0.5999999999999999, 2.355138688025662e-17], [-3.14018491736755e-17, -0.8000000000000002], [-3.14018491736755e-17, -0.8000000000000002], [-0.5999999999999999, 2.355138688025662e-17]],
+        v: &[[-0.7071067811865476, 0.0], [-0.7071067811865476, 0.0], [0.7071067811865476, -5.551115123125782e-17], [-0.7071067811865476, 5.551115123125782e-17]],
+    },
+    Case {
+        name: "tall_4x2",
+        m: 4,
+        n: 2,
+        a: &[[0.6, -0.2], [0.15, -0.8], [0.6, 0.2], [-0.15, -0.8], [0.6, -0.2], [0.15, -0.8], [0.6, 0.2], [-0.15, -0.8]],
+        s: &[2.0, 0.5],
+        u: &[[-0.5, -1.3363823550460973e-52], [-1.3363823550460973e-52, 0.5], [-0.5, 4.677338242661342e-52], [-1.4700205905507074e-51, -0.5], [-0.5, -1.3363823550460973e-52], [-8.018294130276587e-52, 0.5], [-0.5, 4.677338242661342e-52], [-1.4700205905507074e-51, -0.5]],
+        v: &[[-0.6, 0.0], [-0.8, 0.0], [0.0, -0.8], [0.0, 0.6]],
+    },
+    Case {
+        name: "wide_2x4",
+        m: 2,
+        n: 4,
+        a: &[[1.2, 0.1], [1.2, -0.1], [1.2, 0.1], [1.2, -0.1], [0.075, 1.6], [-0.075, 1.6], [0.075, 1.6], [-0.075, 1.6]],
+        s: &[4.0, 0.25],
+        u: &[[-0.6, 2.6714705919619826e-52], [1.2308421282779101e-50, 0.8], [1.1368759453263482e-52, -0.8], [0.6, -8.669909940174581e-51]],
+        v: &[[-0.5, 0.0], [0.5, 0.0], [-0.5, 6.306245473389945e-53], [-0.5, 5.432068949058015e-51], [-0.5, -8.332783284167245e-53], [0.5, -1.0620005744647905e-50], [-0.5, 6.292645704308119e-53], [-0.5, 5.397253540208541e-51]],
+    },
+    Case {
+        name: "rank_one_3x3",
+        m: 3,
+        n: 3,
+        a: &[[0.0, 0.0], [0.0, 0.0], [2.3094010767585034, 0.0], [0.0, 0.0], [0.0, 0.0], [2.3094010767585034, 0.0], [0.0, 0.0], [0.0, 0.0], [2.3094010767585034, 0.0]],
+        s: &[4.000000000000001, 0.0, 0.0],
+        u: &[[-0.5773502691896257, 0.0], [0.816496580927726, 0.0], [0.0, 0.0], [-0.5773502691896257, 0.0], [-0.408248290463863, 0.0], [-0.7071067811865476, 0.0], [-0.5773502691896257, 0.0], [-0.408248290463863, 0.0], [0.7071067811865476, 0.0]],
+        v: &[[0.0, 0.0], [1.0, 0.0], [0.0, 0.0], [0.0, 0.0], [0.0, 0.0], [-1.0, 0.0], [-1.0, 0.0], [0.0, 0.0], [0.0, 0.0]],
+    },
+    Case {
+        name: "repeated_3x3",
+        m: 3,
+        n: 3,
+        a: &[[1.1547005383792517, 0.0], [1.1547005383792517, 0.0], [0.5773502691896258, 0.0], [1.1547005383792517, 0.0], [-0.5773502691896255, -1.0000000000000002], [-0.28867513459481314, 0.4999999999999999], [1.1547005383792517, 0.0], [-0.5773502691896263, 0.9999999999999998], [-0.2886751345948125, -0.5000000000000003]],
+        s: &[2.0000000000000004, 2.0, 1.0000000000000002],
+        u: &[[0.397122624446995, 0.3165971874614359], [-0.44185381929199086, 0.46205163249098785], [-0.2581988897471609, -0.5163977794943223], [0.7900277768533409, -0.08975329195364094], [0.13156399448939649, -0.1309887033461771], [0.5763130403735386, 0.034592091997182405], [0.24166536263672592, -0.22684389550779496], [-0.668732908705137, -0.3310629291448107], [-0.31811415062637755, 0.48180568749713987]],
+        v: &[[0.8249271659314434, 0.0], [-0.565239038733452, 0.0], [1.9110556821996702e-16, 0.0], [-0.13709060355415378, 0.5483624142166152], [-0.20007422579863338, 0.8002969031945335], [3.715467157317925e-17, -3.7015718847597306e-17], [4.546246096956932e-17, 1.251361317937538e-16], [-8.485222341911783e-17, -1.1977555931537297e-16], [-0.447213595499958, -0.8944271909999159]],
+    },
+    Case {
+        name: "random_5x5",
+        m: 5,
+        n: 5,
+        a: &[[0.8272859451097823, -0.7250439608255377], [0.11214027252682701, -0.033955024334235695], [0.9179608298470616, -0.23922049672899326], [-0.10699918374312611, 0.1547744383110674], [-0.23580587094563144, 0.8238095190865289], [0.7571137578606488, 0.44747802913247137], [-0.8879859075596246, 0.9795089930244083], [0.09779775817461123, 0.1953771127905759], [0.08423315495412886, 0.031508271295886736], [0.8614213826479724, -0.45369995319911016], [-0.7151710078603468, 0.9993356711499843], [-0.47769226214353555, -0.44390869164136126], [-0.12447685619203464, 0.795244813517751], [-0.2480353612124071, -0.9747281616603012], [-0.4683908373055481, -0.5414485524899781], [-0.958857191054729, 0.7848602370444082], [0.4672325086868889, -0.7271342221060029], [-0.5144980856257215, 0.9612791691106579], [0.4008973569874712, -0.10626170962348924], [0.15825692082753062, 0.9657745873806298], [-0.5218051188511335, 0.26346414680704244], [-0.9679359846285627, -0.03002528016300121], [-0.5295325992523934, -0.3199589257473361], [-0.2133710470902841, -0.14584156292237194], [0.7886876901714202, -0.2709368385190254]],
+        s: &[3.1376885810139914, 2.369225153126719, 1.3416517589797357, 0.5219578844237842, 0.14377094859276626],
+        u: &[[-0.2767255500689002, 0.2445124156667007], [0.5062184348471428, -0.00037571768445570367], [-0.016586352558002223, 0.08537519325568071], [-0.11729404689575633, -0.5739957517857787], [0.11032062629832158, 0.4943841783211187], [-0.35607067170821566, -0.2713881728204123], [-0.25753217044901583, -0.3756855746144805], [-0.3000736095761555, -0.2853107358281457], [-0.10915812042921108, -0.5157235105595322], [-0.08214847058627574, -0.3688070954453745], [0.20608686996075323, -0.4831095984449775], [-0.18558499295144018, 0.30773508789399096], [0.3215234480055768, 0.4826131248416366], [-0.2689458623914433, -0.38713371937193636], [0.1734762095822602, -0.07999687018968465], [0.5097696012607459, -0.2386208636222013], [0.2499150226672571, 0.279678056244977], [-0.4169587563056935, -0.4339165828657957], [-0.3798585148251639, -0.041875067348076014], [-0.15329889430047833, 0.10408138394860376], [0.1477322253823326, -0.22068604284074722], [-0.2110857149578834, -0.4710659174453231], [0.34297127760359997, -0.07020914220204254], [-0.10161257619630384, 0.017584855164250655], [-0.491193640039792, 0.5371670380275213]],
+        v: &[[-0.7134953568587429, 0.0], [0.19505755718364398, 0.0], [-0.2357959388786359, 0.0], [0.38355788992853795, 0.0], [-0.5001605196419193, 0.0], [0.12823314496074684, 0.4485063953772347], [0.0005868110302928005, 0.4793296349336858], [-0.44330051433374523, 0.03041390300174369], [-0.288854500134681, 0.47173098238862404], [-0.1952238318490406, -0.10545753907033921], [-0.41733915657258563, -0.033656773000921396], [0.43760480543314845, 0.017466172115845967], [-0.10338479828244367, 0.41765070500294627], [-0.29278366004007883, 0.04380380932678356], [0.5902223305172205, -0.10848153977217921], [0.216426327235054, 0.11246293538060066], [-0.06646379861831746, -0.06358999441407362], [-0.5615709364865757, 0.014373615997990856], [0.40924077935524666, -0.38754112664047063], [0.24392215187540783, -0.4892013236745319], [0.08753706037068831, -0.17568977121460652], [0.008456245557474734, -0.7292870773576433], [-0.4936019054980634, -0.05126438475232048], [-0.29720647704880326, 0.2308190361676379], [-0.1167912973993191, 0.16738881444633277]],
+    },
+    Case {
+        name: "random_8x3",
+        m: 8,
+        n: 3,
+        a: &[[-0.9620567880699267, 0.674565516025263], [0.8458343591699602, 0.68560936243873], [-0.4100465742361292, -0.2904508488776949], [0.5321636838813588, -0.8375499711854244], [-0.8829237869975632, 0.011411152000470715], [-0.5401717945291831, -0.07389938969415044], [-0.6799089472056643, 0.9228481319160724], [-0.007288625966019868, -0.3074612989702701], [-0.3904390585570445, -0.4519077424743605], [0.5643731639587062, 0.5942482190607787], [0.5807005918635848, 0.8622442796414196], [0.20924023269713166, -0.23152075110566162], [-0.15771993398744844, -0.7341450537753127], [-0.2681961757380129, -0.030148693377882285], [0.7315555773039115, 0.15562271755567036], [-0.804326740789155, 0.0729474955464895], [-0.32092297879431997, 0.6118813594744763], [0.6751309670678944, 0.19061973576832525], [0.31613926786236757, -0.16024864698827201], [-0.44225182070679203, 0.6042290348701584], [0.5460102484499927, 0.11371758138060506], [0.8599141960435805, 0.6377687348964174], [0.39824811052673503, -0.7884681453389553], [0.9213387785972666, -0.8110176624085497]],
+        s: &[3.0616751366263095, 1.9463276094645552, 1.6066648928003326],
+        u: &[[-0.3609891909155593, 0.3254593967952184], [0.36076431036347456, -0.0043716977523790175], [0.1668438336539089, -0.1231834556800205], [0.18064233482458092, -0.3985270962708024], [0.14318817026781497, 0.001991621084011624], [0.23502663775435903, 0.13446172215536645], [-0.07677663378114181, 0.25015218681129], [0.4042319162118349, -0.0519640303813194], [-0.26723335658541075, 0.34640803197744874], [-0.038057805675808166, 0.26781371515263813], [-0.2051919587813075, 0.09080261969870573], [0.6136291441667228, 0.020433182279759658], [-0.07422243535942354, -0.2222057870565334], [-0.31872297415202383, 0.18999148810281066], [-0.2316256502342748, -0.09135270678131757], [-0.3604082503093539, -0.04262370131036288], [-0.2376690094288349, 0.022606354424820067], [-0.12512000313063598, 0.22512134359865618], [-0.06239490237979563, -0.12545286970003675], [-0.36603097356441944, 0.06137239966462896], [0.2595208487199161, 0.21350947266583614], [0.34161660770246627, 0.32823073179596907], [-0.31919633271062975, 0.44560113864867784], [-0.20011554139015045, 0.18435583550843176]],
+        v: &[[0.7781899542899112, 0.0], [-0.3454592892542592, 0.0], [0.5244790505923071, 0.0], [-0.05504936501147737, 0.5945478356331719], [0.019665234901530746, 0.15274923593461986], [0.09463180818761012, -0.7815421990478472], [-0.16585295610469922, 0.10195896502683047], [-0.837760733340852, 0.3938220189346926], [-0.3057264594153212, 0.10811877491797037]],
+    },
+    Case {
+        name: "random_3x8",
+        m: 3,
+        n: 8,
+        a: &[[-0.5973041565357371, -0.7540494925919563], [-0.6442039475720598, 0.3985360748369191], [0.11681278458463562, -0.7432646723049623], [-0.5971395428578608, 0.4871501525159565], [-0.3651707571780807, -0.5676392141203619], [0.6081997517857454, -0.48025458180196945], [-0.8277208215166338, 0.039776084241817555], [0.47210013922825467, -0.9915185716702715], [0.25612814415450247, -0.6308704867930717], [0.7394393304245563, -0.06116543874922553], [-0.31855844788135435, -0.3282948908048091], [-0.8461578305438731, -0.9282286332327787], [0.007012581591399725, -0.010762156796894518], [-0.6832656828445147, -0.7696580676789373], [-0.10691428537373704, 0.8402847288945183], [-0.888209113631468, 0.3392999109652215], [0.8642944579913956, -0.4002325275060068], [-0.2582054579125508, 0.1018121786033992], [-0.5334562452443965, -0.9420489310205082], [-0.7025132116337474, 0.7843719202943655], [-0.14444267724302895, 0.756430582438022], [-0.6582303709765174, 0.5036825057655858], [-0.5463236959517004, -0.2721090515283766], [0.49109378475771703, 0.9977350550114656]],
+        s: &[2.93709257174418, 2.3575852303614537, 1.7491009453083202],
+        u: &[[0.39995324052924497, 0.16499343947378298], [0.20873830331763038, -0.7478755542141428], [-0.4581234543115263, -0.006924382095732551], [-0.3583100729159809, -0.4825842867215614], [-0.13311595616459088, -0.38323138736943857], [0.08866770543803529, -0.6828456500689026], [-0.5288633293713463, 0.4145412488805043], [-0.01340334346868712, -0.4820214439940491], [0.4635631798865565, 0.31787417005800095]],
+        v: &[[-0.26340286522716944, 0.0], [0.35131954924400494, 0.0], [0.5750321845447884, 0.0], [-0.08462993506242157, -0.2375258125167333], [-0.23461757147920395, 0.09878810410649143], [0.17858613802982115, -0.25254878381919416], [0.03005210656957548, -0.12485488652781175], [0.5131134890847391, 0.16571084153017382], [-0.2282217231868431, 0.09859168329924643], [0.43899607206637153, -0.03200707566124147], [-0.1651173346771123, 0.3795201102256324], [0.4303197847944259, 0.17179802286726537], [0.052070237805535116, 0.1701372868879733], [-0.004746581083592556, 0.19818305333916617], [0.20163842386969566, -0.3761489437656931], [0.4552698018182704, 0.11572701495041138], [0.27064669829993565, 0.05463941153414207], [0.02552468628279562, -0.07554828581663849], [-0.17553291825951353, -0.05794221476700308], [-0.15771693815913712, 0.4340246721070271], [-0.31107050126318636, -0.014332395210176188], [0.11358791778441603, 0.5978391555304596], [0.1445421010365216, 0.0068319245533752515], [0.014263198276870113, -0.10719183236548124]],
+    },
+    Case {
+        name: "random_12x12",
+        m: 12,
+        n: 12,
+        a: &[[0.9683783526292802, 0.6106865894381075], [-0.7734347359976199, 0.27461412408455477], [0.4543804805774352, -0.45409797370736515], [0.10176400895208282, -0.3451961989999175], [0.3631449352834377, 0.5220308090568055], [0.37043504054777676, -0.5834154418799182], [-0.976555278831398, -0.4902413437528359], [-0.02734449582481835, -0.8791536444824726], [0.4577397017556002, 0.5301941152455545], [0.39535576937493544, 0.02302652866825805], [0.9364405771786228, -0.09520722936494108], [-0.2574396554155334, 0.6802638008570894], [0.1759536222273721, 0.0833658789740499], [-0.13016168985695264, -0.590180984349765], [-0.46142248652940676, -0.21443489144899575], [0.6017732244762055, -0.15304666895840757], [-0.37974015068991807, -0.7090115267231696], [0.6389249144138094, -0.3112535698221428], [-0.22141719097235968, -0.9088875885232877], [0.42483827908090754, -0.2915024592273092], [-0.3852483797765318, -0.2668864339561001], [-0.46430754815979713, -0.2744283976487645], [-0.35852766961134863, -0.40066852693915345], [0.5868823119633755, 0.8876369204333534], [0.057202234646374084, -0.6747589947067076], [-0.6760140765591081, -0.32699502159384286], [0.8007986543092762, 0.9070223888148778], [-0.7891662358921465, -0.78860540243659], [0.8301566481424736, 0.4384275092496053], [-0.025098815901583693, 0.35057434599350223], [0.10204673657615082, -0.6873476344776921], [0.8331215531636866, -0.9260587450789848], [-0.2908042294825155, -0.9283873445489323], [-0.8918637373728846, -0.6178233090662504], [-0.653627597747493, 0.8594787265349191], [0.11637786537538863, 0.2550362358711489], [0.6807383046711803, 0.1738120435989523], [-0.5836689350295619, -0.5219294234317555], [-0.8224911125243588, -0.1223369974965891], [0.6078677428724579, 0.9338922733217214], [0.32663081643203706, -0.5640173377403854], [0.6631093244346775, 0.2781364786781644], [-0.024113954233492052, 0.6592936214866538], [-0.21929033549711408, 0.6453804396640734], [-0.15281622878077372, 0.14846293683943523], [-0.42327359085135075, -0.561839678011641], [0.4649505941985046, 0.7028008586035233], [-0.8500217695754262, -0.5172559782791171], [0.6380163694691958, 0.9241205654307689], [0.2013381824616407, 0.9025702378064697], [0.12881521354690095, 0.9482550762892903], [0.035431457165601365, -0.15364797280775533], [0.7172600517953196, 0.9778029383824585], [-0.6404592764185106, -0.681143693939203], [-0.3092902989235444, -0.7206369751178952], [-0.6635047084454353, -0.0002816100820728362], [0.9510206643281685, 0.6386816883399185], [-0.3991920077926949, -0.4064155734272805], [-0.4078689496404586, 0.7030544387538307], [-0.37865662646667375, -0.7571958492482744], [0.8130009174934021, 0.7264641699590404], [0.1319295875494224, -0.14969533510061606], [-0.6826077012920413, -0.04451514344564278], [-0.320684108829864, 0.6029257070918426], [-0.6225419382096573, 0.6819844978897649], [0.8697708743242649, 0.9023214614390769], [0.6662850887298917, -0.8015378172791385], [0.3757142575659844, -0.11789997755123727], [-0.9155486786789317, -0.8354550865053436], [0.1567569505939277, 0.6298467588193915], [-0.015690919256890368, -0.6190779898814063], [-0.8980992493351634, 0.14698368924926797], [-0.29419682864501584, -0.9221221171640501], [0.726674050291165, -0.06788350546355915], [0.3538623572039965, -0.7849716489447576], [0.9546844390726763, 0.26620251817381346], [-0.9215526283612525, 0.3695801432981147], [-0.8907884057620337, -0.41514207032862993], [0.20956176193698206, 0.8242063260094685], [0.14497608241993665, -0.9222243636468801], [0.6741731131615638, -0.2765660966407104], [0.6644282389288583, 0.049311310069463765], [0.1699579992722644, -0.8160104850177652], [0.4530894400678396, -0.5178923132919817], [0.47179718298240947, 0.10829287229864892], [0.5144368670286223, 0.7600359256050131], [-0.6795938770000132, 0.6975794848023287], [-0.6116581213525281, -0.9086034048155174], [-0.5152826624507731, 0.2783867506578337], [-0.6210075149264598, 0.24843484817934258], [-0.4196801673978996, 0.917243008365219], [0.3304055526657854, 0.4164587541443985], [-0.9002159857929626, -0.087001219295334], [0.6404841896434046, 0.7058103034694139], [-0.7023941051591878, 0.7348753000919501], [-0.3726619010206116, 0.7823758295602656], [-0.12615993477594634, 0.2809620366091128], [0.45070079928375284, 0.6824023876650571], [0.11608513637081996, -0.2519626019525254], [0.9648240478798198, 0.7268062972380163], [-0.0825050629979629, 0.6115180799346935], [0.7526512583467151, 0.5982276576448549], [0.6303650678603299, 0.37090328753560775], [-0.4344389204221859, 0.08218886749967202], [-0.9120913691627655, -0.06115949255381814], [0.6646510937371934, 0.21683798878547011], [0.6292926564866406, 0.7644247947628586], [0.20244434970038583, 0.42665825125351264], [0.6607506474518485, 0.7070540919109161], [-0.2959311334459316, -0.2059607197810216], [-0.7884823598697648, -0.8291618398246414], [-0.5876227855893306, -0.11378218335062384], [-0.2763479949868126, -0.34683008853104136], [-0.8018767074504145, -0.3248504952693694], [0.6902781732916738, 0.5252716566048585], [-0.29420910070631234, -0.6142176782139377], [-0.40593025531622895, 0.6057672428333676], [0.608122703758927, -0.1733821493526828], [-0.7334027458937458, 0.1665265488062595], [-0.651781895654082, 0.2515066960042329], [-0.6795624990314484, -0.4500140348549422], [-0.8442274335653155, -0.6497717688703202], [0.12625840079624617, -0.02944675743102043], [-0.5954806598527986, 0.2208942138282015], [0.4058652022560889, -0.35325443905320175], [-0.24154738021364164, -0.405344803324895], [0.09439239777825748, -0.05665354830453939], [-0.33820244908404207, -0.33041879638110583], [-0.15231671180049955, 0.6504554489431458], [0.6442336146500445, 0.6188896121425072], [-0.029019105945646606, -0.8219893272579506], [-0.8562905563379879, 0.17017084040606734], [0.6881738691656794, 0.7945260451121507], [0.8844690203731627, 0.7434625070245124], [0.25078381214325485, -0.48772562011131515], [-0.26924090383087496, -0.41663986691151367], [-0.22324577125125322, -0.2932115227772414], [0.39934222048490575, 0.7023160780197788], [0.867504548702851, -0.40275458292897626], [0.9258609462200547, -0.6164919965306765], [-0.5705461614363792, -0.4045093076998856], [-0.1813837236158724, 0.586784816398914], [-0.695829528643954, -0.8507601888106557], [0.9992915056281704, 0.21786776596073132]],
+        s: &[4.4145089759041864, 4.047403976326002, 3.8462924377683256, 3.591534415613361, 3.110736176494447, 2.799256263615853, 2.420634750064596, 2.0312762173284473, 1.6166137908094185, 0.7977076275580205, 0.4624922328988746, 0.2562678869223818],
+        u: &[[-0.2407643368419711, 0.22796501594067065], [-0.07067741686119798, -0.17573815504348628], [-0.10910681338685312, 0.011493226173650879], [0.08362419553764468, 0.22410834172768923], [-0.11336926082902787, -0.2555883452009222], [0.1798688986046491, 0.3692185405762161], [-0.06063326438354349, 0.281247161550264], [0.38588622353819435, 0.15921688007232979], [0.2106328726295662, -0.3667654613501082], [0.08074315614405328, -0.2153126652882584], [0.18406238113226717, -0.06784304147872004], [-0.10317084574825233, -0.012477010309878281], [-0.1406284678166219, -0.15068741445303885], [-0.10998157648763672, -0.19876948073199652], [-0.2241147345728249, 0.14873513061073074], [-0.10812067928964293, 0.14131171267490977], [-0.0165523299619423, 0.10246667556406233], [0.25625658446432703, -0.3271351445447604], [0.06650936683015685, -0.09225308187769346], [0.0442546751993638, 0.1967254300959789], [0.29714585190057724, 0.052688810724320904], [-0.19158520287500638, 0.4401068076704066], [0.127088654543488, -0.3306570184273626], [-0.14175808737946044, 0.31272944547091275], [-0.24708398997120734, 0.0014673060657563537], [0.3942365675467349, 0.18572499598854617], [0.021944794739702376, 0.48896171556378315], [-0.25263126967005434, -0.04302022750915607], [0.17634145408542, 0.1542374513518996], [0.12235132989565012, 0.005686232608082396], [0.029179277607885815, 0.13955395354599603], [0.3173289217262989, 0.1333572141495344], [-0.1914530128132009, 0.13269475471327583], [-0.13263360272667046, -0.15392476074764003], [-0.1896645061580589, 0.2227162997514339], [-0.017769629009191712, 0.23164384343513322], [-0.028701783809651184, 0.03408684983607728], [0.1410414741203502, 0.002124197649895395], [-0.06539440741861954, -0.5138697317678435], [-0.1391575761401553, 0.11393267209672829], [-0.22092930940508243, 0.16332938823423412], [-0.04924948107209095, -0.22559775856706546], [-0.05788690580905611, -0.17529395992844146], [0.31938402639588803, 0.2012196148302785], [-0.37610874586158416, 0.020488913110233035], [0.2425470059999448, -0.05375683286944594], [0.28533637355953606, 0.09030798418493664], [0.07305700787801761, 0.2711945230453534], [-0.2669720070512998, 0.24817956642878297], [0.018669186376211875, 0.43487686232125605], [-0.06309329253386338, -0.0375770490848895], [0.06702616225957334, 0.010547312491888632], [-0.10891404307286834, -0.5290515084175081], [-0.1012129347036941, 0.10289582112565952], [-0.13774623966014632, 0.05827705663337532], [-0.1209886896314276, -0.1269007622738694], [-0.1889082644397092, 0.3024577023565928], [-0.10976793345035106, 0.25638016262263014], [0.106756017145469, -0.1751191246155752], [-0.033134575919579475, 0.23207747819535693], [-0.10820729966221751, -0.5232591486506695], [0.14935841291704433, -0.12472461578130513], [-0.005504971444133857, -0.1088575437967582], [0.0022856728743248554, 0.17069405856563172], [-0.24395610903338216, -0.0195251438889633], [0.0057950791645521174, 0.08539140642298251], [-0.2610771678906161, 0.3409562674421025], [-0.09870130105082582, -0.21358023770982476], [-0.1537790644025878, -0.26617904138153486], [-0.2272222148163044, 0.18126876880659726], [-0.1530207227272107, 0.07914124006537285], [0.32605757437377253, 0.11676087168830553], [0.30569936553275595, 0.050760712859461835], [-0.4043574166996156, -0.10916357837458819], [0.05877257291402891, 0.10187503453848826], [0.07724256207020075, -0.3888644325578064], [0.0017472958389991122, 0.03394825572367576], [-0.0713468271310286, 0.004828171411031715], [-0.2522672732395035, 0.425519927008674], [0.1769015175792922, 0.18230381875933707], [-0.007803931999652237, 0.1992179469550413], [-0.01612844326084435, 0.184746555280519], [0.18585167172349293, 0.2884632194731753], [0.0812624788285542, 0.20865123565583676], [0.006079620989808928, -0.15853377673463248], [0.1107839815202403, 0.24232734331045505], [0.0801849785663041, 0.13771267869973627], [0.5780845389252247, 0.16720493882420262], [-0.1065234770777335, 0.24693865345658964], [-0.30475535386383257, 0.13680129217588652], [0.24221067589941733, -0.0922587775833692], [-0.025949835510410416, 0.06628394330861011], [0.1731755256858594, -0.06079202549951581], [-0.15012113122828402, -0.03299250788778249], [0.3264984230315754, 0.1738035737022361], [-0.05695873818892288, 0.25055198847562493], [-0.01942300027448954, -0.17958144979697943], [0.12640136923832448, -0.22760507969067584], [0.0541372513670546, -0.33185328312050344], [0.24454477910953792, -0.1560286945170349], [0.1431525495649721, -0.18097279131360247], [-0.18713173961229132, -0.05071649213204391], [0.3628585855097125, 0.28841475658734406], [0.17849640118812926, 0.042722089303601246], [0.013636153886332257, 0.19042454098567413], [0.046744340486927806, -0.07812759086257325], [-0.40539320717172833, -0.19200003788047876], [-0.267723977733421, 0.2130664326406222], [-0.007898846749436732, -0.1634142259274584], [-0.2266688386326388, 0.17897276153268882], [0.22300681592465157, -0.1358862840107222], [0.16793959130983285, 0.20670736654961294], [-0.10120179831788031, 0.12459908544779279], [0.2738102847028524, 0.30246457211111233], [-0.10678639141611965, -0.24881251925150452], [0.4044403401200144, 0.08166232750299986], [0.03129505960858802, 0.3550479800731811], [-0.06824938713081632, 0.19799278900442813], [-0.3043088929499124, 0.018973097939746296], [0.034225759241110286, -0.22335266887480634], [-0.0010805310077727456, 0.09342948077823578], [-0.15971287382106655, -0.0804658183290972], [0.15197674238561396, -0.056667817253647154], [-0.17180325642548383, 0.2025258439879974], [0.15659287556349033, 0.34603763716823627], [0.11345742229105611, 0.42374390796175737], [-0.0580791844227862, 0.06032251164603501], [-0.2921648604528618, -0.2563534386148408], [-0.05354610250671517, 0.0625266841773049], [0.286112785730122, -0.0014544660154985416], [-0.04619054121355578, -0.059136384012401205], [-0.22125635206167446, 0.4737476677352078], [0.0485785761814403, -0.41771380526113333], [0.026493345602341584, -0.21231587101290786], [0.005260346182482673, 0.3789719853010839], [0.18303701133589967, -0.030390593774642493], [-0.12619569871580374, -0.3410901976764321], [0.2202787701567714, -0.021340598897541356], [-0.01840384300823942, -0.1889613733991501], [0.0008291077677186153, -0.11577519083844014], [-0.18059324255835937, 0.18954786024435313], [0.4963485623722267, -0.11428812904226458], [0.18537757448259307, 0.020374979423350717], [-0.0654225731545178, 0.039749214328802816]],
+        v: &[[-0.27831868451593367, 0.0], [0.1027410388054162, 0.0], [-0.16302828451132512, 0.0], [0.352266312233151, 0.0], [-0.6333376423899718, 0.0], [0.21566720872878964, 0.0], [-0.25592462558373247, 0.0], [0.2744721063862953, 0.0], [-0.138510496144262, 0.0], [0.25021191176888324, 0.0], [-0.2523840194630627, 0.0], [0.16541794202170304, 0.0], [0.09842873240675334, -0.14026121160922186], [0.02503164286241023, -0.0814077457826423], [0.06918348830380709, 0.08098513735709449], [0.19577125810377466, -0.5141827834885077], [-0.43094443577917146, -0.1611445691001581], [-0.11253808859693863, 0.01104815559825077], [0.12714684161177403, -0.11068008713967305], [-0.2914052657723637, -0.022935606954179386], [0.1726678737756105, 0.23543303988617004], [-0.30545803369340246, 0.1765114810700213], [0.2923373607801475, -0.11902606550724326], [0.030981655142220805, -0.026654214033968046], [0.08078872660515163, 0.2839596004741211], [0.2615106280158637, -0.2677966748670283], [0.10549555788471246, 0.2727573368676632], [-0.08971060793066632, -0.1434412115732432], [0.1756367984766683, -0.23295252422922635], [-0.007372417525027978, 0.2641432493318558], [-0.024982410456949055, 0.12460292867473804], [-0.14610562414670727, 0.25911266360891994], [-0.03960211852834722, 0.18593960041584934], [0.15691779454156513, -0.04965866791098819], [-0.26725926707369874, -0.09504998262997534], [0.47608882599097646, -0.16928370583764973], [0.04915195793216192, -0.008363595190466042], [-0.25112955780292157, -0.12644433830343257], [-0.4714695255837199, -0.22298938912542887], [-0.06182016021167285, -0.08047058764960426], [0.004830121372820193, -0.11118107881499018], [-0.2351062528280173, -0.24158291103267399], [0.20030253009391133, 0.3182310987098824], [0.10837290242176983, 0.2804092175050166], [0.05840628042634945, 0.36137141836488673], [0.3344826862046407, 0.0958697667695662], [0.058715777026766065, 0.030984229359898773], [-0.0066886073138040965, 0.13727467712435606], [-0.12707170586012098, 0.28167702831259644], [0.24534189908528878, 0.043346988535167275], [0.005797602811821544, -0.0022134928746550256], [-0.26446679125311817, -0.054427127852313885], [-0.18701532986489702, -0.07586855779995846], [0.21809486217995977, 0.27116136214870407], [0.49560823957224004, -0.049822415024147214], [-0.050293278788477155, -0.08782738616643752], [-0.3258460241029832, 0.1303415334412043], [0.1702748363603525, 0.031527268268323354], [0.039903619411000416, 0.05620796162973759], [-0.41693996514592296, 0.13257309079219778], [-0.3495407034644436, -0.18519463717462353], [0.08367136589796044, -0.4493528925391052], [-0.1136703259633322, -0.13906340839316916], [-0.13950546898264093, 0.020737700703309197], [-0.03294945310741682, -0.0345185906663947], [-0.08333726974082721, -0.1696338944624178], [0.128546180581898, -0.0432921297795465], [-0.04387076575454865, 0.009856260443222983], [-0.21770126075711269, -0.17295807287192264], [-0.12038728316880769, -0.5004433835751012], [0.16158030191405828, -0.3625107774767543], [0.045488038700394556, -0.14899467149868162], [0.12056091390023371, -0.457642652753294], [0.045197276216120706, 0.04018505487505257], [-0.12310433081319186, -0.14459247589285404], [-0.08543302075082179, -0.29493526715823526], [0.2510401320844159, -0.007041506765471162], [0.09911162232956011, 0.21909085524548602], [-0.1525145871030544, -0.1528179603387364], [0.1970344970882123, -0.3300925016582338], [-0.12157508454280301, 0.4156833111774602], [0.019190181093936366, -0.10109564198533046], [-0.30755747387266735, -0.1519826029910635], [-0.09564787772241594, -0.04159926027641788], [0.02523312112436103, -0.21408852148516083], [0.160990999203557, -0.17969329266751735], [-0.2966079033149662, 0.3350807246479469], [0.027185986987830764, -0.04811796307048003], [0.05490870996362194, 0.09680253543540032], [-0.111882581255776, 0.17196118741019473], [-0.281185165878955, -0.19780280218975718], [-0.015229124047426832, 0.43091043068341733], [-0.1963253756254006, -0.035539317909953315], [-0.05392186891115097, -0.035063381815902045], [0.15666946438301452, 0.4127830102849351], [-0.3052097105261252, -0.0374061348663543], [0.2177610335899567, 0.3527328810131124], [-0.07334860490193164, 0.2642820500432036], [-0.2607170171821957, -0.025550587960942225], [-0.06337610233353073, -0.17488161682776918], [-0.023204820321608907, -0.20196088305303703], [0.24750528732706129, 0.019257995606653967], [-0.41030287890632583, 0.09261639790927528], [-0.11835154163062525, 0.07424862678140233], [0.11482490467133585, -0.09312070867285246], [0.044961637556968456, -0.3236252193489784], [0.16644706061245523, -0.3290336686207533], [-0.27790627999888046, -0.09216826966806545], [-0.05417505817970316, -0.10734271080670514], [-0.28198537906202337, -0.035545157318813546], [0.15241113742331638, -0.20065440634318468], [0.292990702618485, -0.1766621059689568], [0.033895439217466725, 0.11886099421687557], [0.09088283801913852, 0.37689345876897684], [0.12826649035121668, 0.15522605740385187], [-0.2366262118565498, 0.2438141206464945], [0.09169860512347627, -0.1424125184482634], [-0.0027233287840870233, -0.3743469217379005], [-0.21741556279323698, 0.0997941805380126], [-0.038199093115261706, 0.41848358256137935], [0.13810378408461488, 0.2672338457078634], [0.21416751034026874, -0.41772933228117537], [-0.18701598024159868, -0.26698915079708063], [-0.05926087795002439, -0.14285471800409133], [-0.01111671771936348, -0.09971822556731452], [-0.18524918234837698, 0.22595851017014953], [-0.10169610998956334, -0.0040084941432183985], [0.3297713403742633, -0.25555920157828854], [0.2565873615490105, -0.307907055306909], [-0.17626789128002568, 0.1125232509425947], [-0.02878128840570126, 0.11791291010927425], [-0.02676744656140617, 0.243488761293175], [-0.05518706368165014, 0.035977013023300995], [-0.15139439547398623, -0.1757481920923328], [0.05614517434665607, 0.3072459598756906], [0.17726654145130272, -0.370330580926719], [0.24024554473396917, -0.21644837792493052], [0.15045692168712818, -0.4249243691457125], [0.14145854539013514, -0.2634169263802166], [0.07129028986804044, -0.0172723918864962], [0.019186335511786463, -0.29328936374433917], [0.22496967500787854, 0.11782779653415708], [-0.2558353445216394, -0.05937446839623195], [-0.21131224855459307, 0.09313932121570136]],
+    },
+];
@@ -0,0 +1,452 @@
+//! Updates of an existing singular value decomposition by a rank-one matrix.
+//!
+//! For A = U S V* the update A + x y* equals U (S + a b*) V* with a = U* x and b = V* y,
+//! so only the decomposition of the diagonal-plus-rank-one core K = S + a b* is needed.
+//! Its Gram matrix K* K = S* S + c b* + b c* + |a|^2 b b*, with c = S* a, is the diagonal
+//! S* S modified by two Hermitian rank-one terms, whose eigendecompositions follow from the
+//! roots of secular equations without any iteration over the whole matrix. The right
+//! singular vectors of K are the eigenvectors of K* K, the singular values and the left
+//! singular vectors follow from K v, after a few sweeps of one-sided Jacobi that restore
+//! the accuracy of the small singular values the Gram matrix cannot resolve.
+//!
+//! The eigenvectors are computed from the roots as in Gu and Eisenstat, "A stable and
+//! efficient algorithm for the rank-one modification of the symmetric eigenproblem",
+//! which keeps them orthogonal to working precision even for close eigenvalues.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use num_complex::Complex;
+use super::csvd::{cabs, norm2, normalize_phases};
+use super::error::CsvdError;
+use super::kernels::rot;
+use super::matrix::{Layout, Matrix};
+use super::scalar::Real;
+use super::svd::SvdResult;
+
+/// Sweeps of one-sided Jacobi after the secular equations. Columns of distinct singular
+/// values are nearly orthogonal from the start and need one or two, a cluster of tiny
+/// singular values, e.g. of a rank-deficient matrix, starts out mixed and needs up to ten.
+const MAX_POLISH_SWEEPS: usize = 30;
+
+impl<T: Real> SvdResult<T> {
+    /// Updates the decomposition of A to the decomposition of A + x y* for x with m and
+    /// y with n elements, e.g. for an adaptive beamformer or a recursive least-squares
+    /// filter that tracks a slowly changing matrix sample by sample.
+    ///
+    /// The singular values come from secular equations in O(min(m, n)^2), rotating the
+    /// full factors U and V costs about two products with min(m, n) + 1 columns, instead
+    /// of the Householder reduction and QR iteration of a new decomposition. The result
+    /// follows the same phase convention as `svd`. Needs the full factors, a truncated
+    /// decomposition from `svd_truncated` is rejected with `DimensionMismatch`.
+    pub fn rank_one_update(&mut self, x: &[Complex<T>], y: &[Complex<T>]) -> Result<(), CsvdError> {
+        let m = self.rows();
+        let n = self.cols();
+
+        if x.len() != m {
+            return Err(CsvdError::DimensionMismatch { operand: "x", expected: (m, 1), found: (x.len(), 1) });
+        }
+        if y.len() != n {
+            return Err(CsvdError::DimensionMismatch { operand: "y", expected: (n, 1), found: (y.len(), 1) });
+        }
+        if self.u.cols() != m {
+            return Err(CsvdError::DimensionMismatch { operand: "u", expected: (m, m), found: (m, self.u.cols()) });
+        }
+        if self.v.cols() != n {
+            return Err(CsvdError::DimensionMismatch { operand: "v", expected: (n, n), found: (n, self.v.cols()) });
+        }
+        // the bisection for the secular equations would never end on a NaN
+        if let Some(i) = x.iter().position(|x| !(x.re.is_finite() && x.im.is_finite())) {
+            return Err(CsvdError::NonFiniteInput { row: i, col: 0 });
+        }
+        if let Some(j) = y.iter().position(|y| !(y.re.is_finite() && y.im.is_finite())) {
+            return Err(CsvdError::NonFiniteInput { row: 0, col: j });
+        }
+
+        // (A + x y*)* = A* + y x* has the factors of A swapped
+        if m >= n {
+            update_tall(&mut self.u, &mut self.s, &mut self.v, x, y);
+        }
+        else {
+            update_tall(&mut self.v, &mut self.s, &mut self.u, y, x);
+        }
+
+        let (u_layout, v_layout) = (self.u.layout(), self.v.layout());
+        let mut u = self.u.to_layout(Layout::RowMajor);
+        let mut v = self.v.to_layout(Layout::RowMajor);
+        normalize_phases(u.as_mut_slice(), m, m, m, v.as_mut_slice(), n, n, n);
+        self.u = u.to_layout(u_layout);
+        self.v = v.to_layout(v_layout);
+        Ok(())
+    }
+}
+
+/// Replaces the full factors u (M x M), s and v (N x N) of a matrix with M >= N by those of
+/// the matrix plus x y*
+fn update_tall<T: Real>(u: &mut Matrix<Complex<T>>, s: &mut [T], v: &mut Matrix<Complex<T>>,
+        x: &[Complex<T>], y: &[Complex<T>]) {
+    let mm = u.rows();
+    let nn = v.rows();
+    let zero = Complex::new(T::zero(), T::zero());
+
+    let mut a: Vec<Complex<T>> = (0..mm).map(|k| (0..mm).fold(zero, |acc, i| acc + u[(i, k)].conj() * x[i])).collect();
+    let b: Vec<Complex<T>> = (0..nn).map(|k| (0..nn).fold(zero, |acc, i| acc + v[(i, k)].conj() * y[i])).collect();
+
+    // the rows N.. of S are zero, a reflector on the columns N.. of U gathers the part of a
+    // in them into its first entry, leaving an (N + 1) x N core
+    let q = if mm > nn { nn + 1 } else { nn };
+    if mm > nn {
+        let tail_norm = norm2(a[nn..].iter().copied());
+        if tail_norm > T::zero() {
+            let head = a[nn];
+            let phase = if cabs(&head) > T::zero() { head / cabs(&head) } else { Complex::new(T::one(), T::zero()) };
+            let gamma = -phase * tail_norm;
+
+            let mut w = a[nn..].to_vec();
+            w[0] = w[0] - gamma;
+            let w_norm = norm2(w.iter().copied());
+            let tau = (T::one() + T::one()) / (w_norm * w_norm);
+
+            for i in 0..mm {
+                let t = w.iter().enumerate().fold(zero, |acc, (l, w)| acc + u[(i, nn + l)] * w) * tau;
+                for (l, w) in w.iter().enumerate() {
+                    u[(i, nn + l)] = u[(i, nn + l)] - t * w.conj();
+                }
+            }
+            a[nn] = gamma;
+        }
+    }
+
+    let (uk, sigma, vk) = core_svd(s, &a[..q], &b);
+
+    let mut row = alloc::vec![zero; q];
+    for i in 0..mm {
+        for (j, row) in row.iter_mut().enumerate() {
+            *row = (0..q).fold(zero, |acc, l| acc + u[(i, l)] * uk[(l, j)]);
+        }
+        for j in 0..q {
+            u[(i, j)] = row[j];
+        }
+    }
+    for i in 0..nn {
+        for (j, row) in row.iter_mut().enumerate().take(nn) {
+            *row = (0..nn).fold(zero, |acc, l| acc + v[(i, l)] * vk[(l, j)]);
+        }
+        for j in 0..nn {
+            v[(i, j)] = row[j];
+        }
+    }
+    s.copy_from_slice(&sigma[..s.len()]);
+}
+
+/// The decomposition K = Uk diag(sigma) Vk* of the q x r matrix K = S + a b*, q >= r, where S
+/// holds s on its diagonal and zeros everywhere else. The r singular values are descending.
+pub(crate) fn core_svd<T: Real>(s: &[T], a: &[Complex<T>], b: &[Complex<T>]) -> (Matrix<Complex<T>>, Vec<T>, Matrix<Complex<T>>) {
+    let q = a.len();
+    let r = b.len();
+    let zero = T::zero();
+    let one = T::one();
+    let two = one + one;
+    let czero = Complex::new(zero, zero);
+
+    // a b* = (a t) (b / t)*, balanced so that neither Gram term below is much larger than
+    // the product whose rounding errors it would otherwise carry
+    let (a_norm, b_norm) = (norm2(a.iter().copied()), norm2(b.iter().copied()));
+    let t = if a_norm > zero && b_norm > zero { (b_norm / a_norm).sqrt() } else { one };
+    let a: Vec<Complex<T>> = a.iter().map(|&a| a * t).collect();
+    let b: Vec<Complex<T>> = b.iter().map(|&b| b / t).collect();
+
+    // K* K = D + c b* + b c* + alpha b b* = D + [b c] [[alpha, 1], [1, 0]] [b c]*, and the
+    // 2 x 2 matrix has the eigenvalues mu+ > 0 > mu- with eigenvectors (mu, 1)
+    let d: Vec<T> = (0..r).map(|i| if i < s.len() { s[i] * s[i] } else { zero }).collect();
+    let c: Vec<Complex<T>> = (0..r).map(|i| if i < s.len() { a[i] * s[i] } else { czero }).collect();
+    let alpha = a_norm * b_norm;
+    let root = (alpha * alpha + two + two).sqrt();
+    let mu_plus = (alpha + root) / two;
+    let mu_minus = -two / (alpha + root);
+    let w = |mu: T| -> Vec<Complex<T>> {
+        let norm = (mu * mu + one).sqrt();
+        b.iter().zip(&c).map(|(&b, &c)| (b * mu + c) / norm).collect()
+    };
+
+    let (values, q1) = diag_rank_one(&d, &w(mu_plus), mu_plus);
+    let w_minus = w(mu_minus);
+    let z: Vec<Complex<T>> = (0..r).map(|j| (0..r).fold(czero, |acc, i| acc + q1[(i, j)].conj() * w_minus[i])).collect();
+    let (_, q2) = diag_rank_one(&values, &z, mu_minus);
+
+    let mut vk = Matrix::zeros(r, r);
+    for i in 0..r {
+        for j in 0..r {
+            vk[(i, j)] = (0..r).fold(czero, |acc, l| acc + q1[(i, l)] * q2[(l, j)]);
+        }
+    }
+
+    // K v = S v + a (b* v), with columns made orthogonal to working precision by polish
+    let mut kv: Vec<Vec<Complex<T>>> = (0..r).map(|j| {
+        let bv = (0..r).fold(czero, |acc, i| acc + b[i].conj() * vk[(i, j)]);
+        (0..q).map(|i| a[i] * bv + if i < s.len() && i < r { vk[(i, j)] * s[i] } else { czero }).collect()
+    }).collect();
+    polish(&mut kv, &mut vk);
+    let mut sigma: Vec<T> = kv.iter().map(|kv| norm2(kv.iter().copied())).collect();
+
+    let mut order: Vec<usize> = (0..r).collect();
+    order.sort_by(|&i, &j| sigma[j].partial_cmp(&sigma[i]).unwrap_or(Ordering::Equal));
+    let mut sorted = Matrix::zeros(r, r);
+    for i in 0..r {
+        for (j, &k) in order.iter().enumerate() {
+            sorted[(i, j)] = vk[(i, k)];
+        }
+    }
+    kv = order.iter().map(|&j| core::mem::take(&mut kv[j])).collect();
+    sigma = order.iter().map(|&j| sigma[j]).collect();
+
+    // the left singular vectors of the nonzero singular values, orthogonalized against the
+    // larger ones, and a completion to a unitary matrix for the rest
+    let tol = T::epsilon() * T::from(q).unwrap() * sigma.first().copied().unwrap_or(zero);
+    let mut columns: Vec<Option<Vec<Complex<T>>>> = (0..q).map(|_| None).collect();
+    for j in 0..r {
+        if sigma[j] > tol {
+            let mut col: Vec<Complex<T>> = kv[j].iter().map(|&x| x / sigma[j]).collect();
+            // a column that was mostly along the larger ones is left to the completion
+            if project_out(&mut col, &columns) > one / two {
+                normalize(&mut col);
+                columns[j] = Some(col);
+            }
+        }
+    }
+    for j in 0..q {
+        if columns[j].is_none() {
+            // the unit vector with the largest part outside the columns found so far
+            let mut best = (zero, Vec::new());
+            for k in 0..q {
+                let mut col = alloc::vec![czero; q];
+                col[k] = Complex::new(one, zero);
+                let norm = project_out(&mut col, &columns);
+                if norm > best.0 {
+                    best = (norm, col);
+                }
+            }
+            normalize(&mut best.1);
+            columns[j] = Some(best.1);
+        }
+    }
+
+    let mut uk = Matrix::zeros(q, q);
+    for (j, col) in columns.iter().flatten().enumerate() {
+        for i in 0..q {
+            uk[(i, j)] = col[i];
+        }
+    }
+    (uk, sigma, sorted)
+}
+
+/// One-sided Jacobi on the columns of B = K V, rotating the same pairs of columns of V
+/// until all columns of B are orthogonal, which gives the singular values as their norms.
+/// The eigenvectors of the Gram matrix K* K leave the columns of small singular values
+/// mixed up to about eps ||K||^2 / sigma.
+fn polish<T: Real>(b: &mut [Vec<Complex<T>>], v: &mut Matrix<Complex<T>>) {
+    let r = b.len();
+    let zero = T::zero();
+    let one = T::one();
+    let two = one + one;
+    let eps = T::epsilon();
+
+    for _ in 0..MAX_POLISH_SWEEPS {
+        let mut rotated = false;
+        for i in 0..r {
+            for j in i + 1..r {
+                let alpha = b[i].iter().fold(zero, |acc, x| acc + x.norm_sqr());
+                let beta = b[j].iter().fold(zero, |acc, x| acc + x.norm_sqr());
+                let gamma = b[i].iter().zip(&b[j]).fold(Complex::new(zero, zero), |acc, (x, y)| acc + x.conj() * y);
+                let g = cabs(&gamma);
+                if g <= eps * (alpha * beta).sqrt() || g == zero {
+                    continue;
+                }
+                rotated = true;
+
+                // with the phase of gamma moved into column j the Gram matrix of the pair is
+                // [[alpha, g], [g, beta]], diagonalized by the same rotation as in eigh
+                let phase = gamma.conj() / g;
+                let theta = (beta - alpha) / (two * g);
+                let t = if theta >= zero { one / (theta + (one + theta * theta).sqrt()) }
+                    else { -one / (-theta + (one + theta * theta).sqrt()) };
+                let c = one / (one + t * t).sqrt();
+                let s = c * t;
+
+                let (left, right) = b.split_at_mut(j);
+                for y in right[0].iter_mut() {
+                    *y = *y * phase;
+                }
+                rot(c, Complex::new(-s, zero), &mut left[i], &mut right[0]);
+                for k in 0..v.rows() {
+                    let (vi, vj) = (v[(k, i)], v[(k, j)] * phase);
+                    v[(k, i)] = vi * c - vj * s;
+                    v[(k, j)] = vi * s + vj * c;
+                }
+            }
+        }
+        if !rotated {
+            return;
+        }
+    }
+}
+
+/// Removes the components along the orthonormal columns found so far from the unit vector
+/// x, twice for orthogonality to working precision, and returns the norm of what is left
+fn project_out<T: Real>(x: &mut [Complex<T>], columns: &[Option<Vec<Complex<T>>>]) -> T {
+    for _ in 0..2 {
+        for col in columns.iter().flatten() {
+            let p = col.iter().zip(x.iter()).fold(Complex::new(T::zero(), T::zero()), |acc, (c, x)| acc + c.conj() * x);
+            for (x, c) in x.iter_mut().zip(col) {
+                *x = *x - *c * p;
+            }
+        }
+    }
+    norm2(x.iter().copied())
+}
+
+/// Scales x to unit length
+fn normalize<T: Real>(x: &mut [Complex<T>]) {
+    let norm = norm2(x.iter().copied());
+    for x in x.iter_mut() {
+        *x = *x / norm;
+    }
+}
+
+/// The eigendecomposition D + rho z z* = Q diag(values) Q* of the real diagonal matrix
+/// D = diag(d) with a Hermitian rank-one modification. The eigenvalues are ascending.
+pub(crate) fn diag_rank_one<T: Real>(d: &[T], z: &[Complex<T>], rho: T) -> (Vec<T>, Matrix<Complex<T>>) {
+    let n = d.len();
+
+    // D + rho z z* = -(-D + |rho| z z*), with the order of the eigenvalues reversed
+    if rho < T::zero() {
+        let negated: Vec<T> = d.iter().map(|&d| -d).collect();
+        let (values, q) = diag_rank_one(&negated, z, -rho);
+        let mut reversed = Matrix::zeros(n, n);
+        for i in 0..n {
+            for j in 0..n {
+                reversed[(i, j)] = q[(i, n - 1 - j)];
+            }
+        }
+        return (values.iter().rev().map(|&v| -v).collect(), reversed);
+    }
+
+    let zero = T::zero();
+    let one = T::one();
+    let eps = T::epsilon();
+
+    // with the phases of z in a unitary diagonal P, D + rho z z* = P (D + rho |z| |z|^T) P*,
+    // a real problem in the order of ascending d
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| d[i].partial_cmp(&d[j]).unwrap_or(Ordering::Equal));
+    let dd: Vec<T> = order.iter().map(|&i| d[i]).collect();
+    let mut zz: Vec<T> = order.iter().map(|&i| cabs(&z[i])).collect();
+
+    let z_norm = zz.iter().fold(zero, |acc, &z| acc.hypot(z));
+    let d_max = dd.iter().fold(zero, |acc, &d| acc.max(d.abs()));
+    let tol = T::from(8.0).unwrap() * eps * d_max.max(rho * z_norm * z_norm);
+
+    // deflation: an entry of z below the tolerance leaves d_i and e_i as an eigenpair, and of
+    // two close d_i a rotation moves all of z into one of them, leaving the other as is
+    let mut active: Vec<usize> = Vec::with_capacity(n);
+    let mut rotations: Vec<(usize, usize, T, T)> = Vec::new();
+    for i in 0..n {
+        if rho * zz[i] * z_norm <= tol {
+            zz[i] = zero;
+            continue;
+        }
+        if let Some(&j) = active.last() {
+            if dd[i] - dd[j] <= tol {
+                let r = zz[j].hypot(zz[i]);
+                rotations.push((j, i, zz[j] / r, zz[i] / r));
+                zz[j] = r;
+                zz[i] = zero;
+                continue;
+            }
+        }
+        active.push(i);
+    }
+
+    let k = active.len();
+    let dk: Vec<T> = active.iter().map(|&i| dd[i]).collect();
+    let zk: Vec<T> = active.iter().map(|&i| zz[i]).collect();
+
+    // every root is stored as lambda = d[origin] + tau relative to its closer pole, so that
+    // the differences lambda - d_i below carry no cancellation
+    let secular = |origin: usize, tau: T| {
+        one + rho * dk.iter().zip(&zk).fold(zero, |acc, (&d, &z)| acc + z * z / ((d - dk[origin]) - tau))
+    };
+    let mut roots: Vec<(usize, T)> = Vec::with_capacity(k);
+    for j in 0..k {
+        // the secular function grows from -inf to +inf between neighboring poles
+        let (origin, mut lo, mut hi) = if j + 1 < k {
+            let half = (dk[j + 1] - dk[j]) / (one + one);
+            if secular(j, half) >= zero { (j, zero, half) } else { (j + 1, -half, zero) }
+        }
+        else {
+            (j, zero, rho * zk.iter().fold(zero, |acc, &z| acc + z * z))
+        };
+
+        loop {
+            let mid = (lo + hi) / (one + one);
+            if mid <= lo || mid >= hi || hi - lo <= eps * lo.abs().max(hi.abs()) {
+                break;
+            }
+            if secular(origin, mid) >= zero {
+                hi = mid;
+            }
+            else {
+                lo = mid;
+            }
+        }
+        roots.push((origin, (lo + hi) / (one + one)));
+    }
+    let diff = |j: usize, i: usize| (dk[roots[j].0] - dk[i]) + roots[j].1;
+
+    // z recomputed from the roots, for which they are the exact eigenvalues (Loewner)
+    let z_hat: Vec<T> = (0..k).map(|i| {
+        let mut prod = diff(k - 1, i) / rho;
+        for j in 0..k - 1 {
+            let pole = if j < i { dk[j] } else { dk[j + 1] };
+            prod = prod * diff(j, i) / (pole - dk[i]);
+        }
+        prod.max(zero).sqrt()
+    }).collect();
+
+    // the eigenvectors in the sorted order, identity columns for the deflated eigenvalues
+    let mut values = dd.clone();
+    let mut q = alloc::vec![zero; n * n];
+    for i in 0..n {
+        q[i * n + i] = one;
+    }
+    for (j, &col) in active.iter().enumerate() {
+        values[col] = dk[roots[j].0] + roots[j].1;
+        let entries: Vec<T> = (0..k).map(|i| z_hat[i] / -diff(j, i)).collect();
+        let norm = entries.iter().fold(zero, |acc, &x| acc.hypot(x));
+        for (i, &row) in active.iter().enumerate() {
+            q[row * n + col] = entries[i] / norm;
+        }
+    }
+    for &(j, i, c, s) in rotations.iter().rev() {
+        for col in 0..n {
+            let (qj, qi) = (q[j * n + col], q[i * n + col]);
+            q[j * n + col] = c * qj - s * qi;
+            q[i * n + col] = s * qj + c * qi;
+        }
+    }
+
+    // back to the original order and phases, with the eigenvalues ascending
+    let phases: Vec<Complex<T>> = order.iter().map(|&i| {
+        let abs = cabs(&z[i]);
+        if abs > zero { z[i] / abs } else { Complex::new(one, zero) }
+    }).collect();
+    let mut by_value: Vec<usize> = (0..n).collect();
+    by_value.sort_by(|&i, &j| values[i].partial_cmp(&values[j]).unwrap_or(Ordering::Equal));
+
+    let mut vectors = Matrix::zeros(n, n);
+    for (r, &row) in order.iter().enumerate() {
+        for (c, &col) in by_value.iter().enumerate() {
+            vectors[(row, c)] = phases[r] * q[r * n + col];
+        }
+    }
+    (by_value.iter().map(|&i| values[i]).collect(), vectors)
+}
@@ -0,0 +1,213 @@
+use num_complex::Complex;
+use super::blas::{gemm, Op};
+use super::csvd::norm2;
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::matrix_mult;
+use super::scalar::Real;
+use super::svd::SvdResult;
+
+/// Residuals of the four Moore-Penrose conditions for a candidate pseudo-inverse X of A,
+/// each measured in the Frobenius norm
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PinvReport<T> {
+    /// ||A X A - A||
+    pub a_x_a: T,
+    /// ||X A X - X||
+    pub x_a_x: T,
+    /// ||(A X)* - A X||
+    pub a_x_hermitian: T,
+    /// ||(X A)* - X A||
+    pub x_a_hermitian: T,
+    /// the tolerance the residuals were compared against
+    pub tol: T,
+}
+
+impl<T: Real> PinvReport<T> {
+    /// Whether all four residuals are within the tolerance
+    pub fn passed(&self) -> bool {
+        self.a_x_a <= self.tol && self.x_a_x <= self.tol
+            && self.a_x_hermitian <= self.tol && self.x_a_hermitian <= self.tol
+    }
+}
+
+/// Checks all four Moore-Penrose conditions for the n x m matrix pinv as the
+/// pseudo-inverse of the m x n matrix a, returning the residual of every condition
+pub fn verify_pinv<T: Real>(a: &Matrix<Complex<T>>, pinv: &Matrix<Complex<T>>, tol: T) -> Result<PinvReport<T>, CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+
+    if pinv.rows() != n || pinv.cols() != m {
+        return Err(CsvdError::DimensionMismatch { operand: "pinv", expected: (n, m), found: (pinv.rows(), pinv.cols()) });
+    }
+
+    let mut a_x = Matrix::zeros(m, m);
+    matrix_mult(a, pinv, &mut a_x)?;
+    let mut x_a = Matrix::zeros(n, n);
+    matrix_mult(pinv, a, &mut x_a)?;
+
+    let mut a_x_a = Matrix::zeros(m, n);
+    matrix_mult(&a_x, a, &mut a_x_a)?;
+    let mut x_a_x = Matrix::zeros(n, m);
+    matrix_mult(&x_a, pinv, &mut x_a_x)?;
+
+    Ok(PinvReport {
+        a_x_a: distance(&a_x_a, a),
+        x_a_x: distance(&x_a_x, pinv),
+        a_x_hermitian: hermitian_residual(&a_x),
+        x_a_hermitian: hermitian_residual(&x_a),
+        tol,
+    })
+}
+
+/// Diagnostics of a singular value decomposition A = U S V*, the norms are Frobenius norms
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SvdReport<T> {
+    /// ||A - U S V*||
+    pub reconstruction: T,
+    /// ||U* U - I||
+    pub u_orthogonality: T,
+    /// ||V* V - I||
+    pub v_orthogonality: T,
+    /// whether the singular values are nonnegative and in descending order
+    pub sorted: bool,
+    /// the tolerance the norms were compared against
+    pub tol: T,
+}
+
+impl<T: Real> SvdReport<T> {
+    /// Whether all errors are within the tolerance and the singular values are sorted
+    pub fn passed(&self) -> bool {
+        self.reconstruction <= self.tol && self.u_orthogonality <= self.tol
+            && self.v_orthogonality <= self.tol && self.sorted
+    }
+}
+
+/// Checks the decomposition res of the m x n matrix a, returning the reconstruction error,
+/// how far U and V are from having orthonormal columns and whether S is sorted.
+/// This works for full and truncated decompositions alike.
+pub fn verify_svd<T: Real>(a: &Matrix<Complex<T>>, res: &SvdResult<T>, tol: T) -> Result<SvdReport<T>, CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+
+    if res.rows() != m || res.cols() != n {
+        return Err(CsvdError::DimensionMismatch { operand: "res", expected: (m, n), found: (res.rows(), res.cols()) });
+    }
+    if res.u.cols() < res.s.len() || res.v.cols() < res.s.len() {
+        return Err(CsvdError::BufferTooSmall { operand: "S", required: res.u.cols().min(res.v.cols()), len: res.s.len() });
+    }
+
+    let zero = T::zero();
+    let sorted = res.s.iter().all(|&s| s >= zero)
+        && res.s.windows(2).all(|w| w[0] >= w[1]);
+
+    Ok(SvdReport {
+        reconstruction: distance(a, &res.reconstruct()),
+        u_orthogonality: orthogonality(&res.u),
+        v_orthogonality: orthogonality(&res.v),
+        sorted,
+        tol,
+    })
+}
+
+/// How well X solves the least-squares problem min ||A X - B||, the norms are Frobenius norms.
+///
+/// A small backward error means X is the exact solution of a nearby problem (A + E) X = B + f,
+/// a small normal residual that the residual is orthogonal to the range of A as it has to be
+/// for an inconsistent system. If either is small, the solver did its job and an inaccurate
+/// X is due to the conditioning of the problem, see `cond`; if both are large, it did not.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LstsqReport<T> {
+    /// ||A X - B||
+    pub residual: T,
+    /// the normwise backward error ||A X - B|| / (||A|| ||X|| + ||B||) of Rigal and Gaches
+    pub backward_error: T,
+    /// ||A* (A X - B)|| / (||A|| ||A X - B||), zero for a zero residual
+    pub normal_residual: T,
+    /// the tolerance the backward error and the normal residual were compared against
+    pub tol: T,
+}
+
+impl<T: Real> LstsqReport<T> {
+    /// Whether the backward error or the normal residual is within the tolerance,
+    /// the stopping tests of LSQR
+    pub fn passed(&self) -> bool {
+        self.backward_error <= self.tol || self.normal_residual <= self.tol
+    }
+}
+
+/// Checks the n x nrhs solution x of min ||A X - B|| for the m x n matrix a and the
+/// m x nrhs matrix b, e.g. from `lstsq`, `solve_multi` or a pseudo-inverse applied to b,
+/// at the cost of two more products with a
+pub fn verify_lstsq<T: Real>(a: &Matrix<Complex<T>>, x: &Matrix<Complex<T>>, b: &Matrix<Complex<T>>, tol: T)
+        -> Result<LstsqReport<T>, CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+    let nrhs = b.cols();
+
+    if b.rows() != m {
+        return Err(CsvdError::DimensionMismatch { operand: "b", expected: (m, nrhs), found: (b.rows(), nrhs) });
+    }
+    if x.rows() != n || x.cols() != nrhs {
+        return Err(CsvdError::DimensionMismatch { operand: "x", expected: (n, nrhs), found: (x.rows(), x.cols()) });
+    }
+
+    let zero = Complex::new(T::zero(), T::zero());
+    let one = Complex::new(T::one(), T::zero());
+    let mut r = b.clone();
+    gemm(one, Op::None, a, Op::None, x, -one, &mut r)?;
+    let mut normal = Matrix::zeros(n, nrhs);
+    gemm(one, Op::ConjugateTranspose, a, Op::None, &r, zero, &mut normal)?;
+
+    let frobenius = |a: &Matrix<Complex<T>>| norm2((0..a.rows()).flat_map(|i| (0..a.cols()).map(move |j| a[(i, j)])));
+    let (a_norm, residual) = (frobenius(a), frobenius(&r));
+    let ratio = |num: T, den: T| if num == T::zero() { T::zero() } else { num / den };
+
+    Ok(LstsqReport {
+        residual,
+        backward_error: ratio(residual, a_norm * frobenius(x) + frobenius(b)),
+        normal_residual: ratio(frobenius(&normal), a_norm * residual),
+        tol,
+    })
+}
+
+/// Whether the matrix a is square and unitary up to ||A* A - I|| <= tol in the Frobenius
+/// norm, e.g. U or V of a decomposition or a precoder that has to keep the transmit power
+pub fn is_unitary<T: Real>(a: &Matrix<Complex<T>>, tol: T) -> bool {
+    a.rows() == a.cols() && orthogonality(a) <= tol
+}
+
+/// Whether the matrix a is square and Hermitian up to ||A* - A|| <= tol ||A|| in the
+/// Frobenius norm, relative to A so that the same tol fits a covariance matrix of any scale
+pub fn is_hermitian<T: Real>(a: &Matrix<Complex<T>>, tol: T) -> bool {
+    let a_norm = norm2((0..a.rows()).flat_map(|i| (0..a.cols()).map(move |j| a[(i, j)])));
+    a.rows() == a.cols() && hermitian_residual(a) <= tol * a_norm
+}
+
+/// Frobenius norm of q* q - I
+pub(crate) fn orthogonality<T: Real>(q: &Matrix<Complex<T>>) -> T {
+    let k = q.cols();
+
+    norm2((0..k).flat_map(|i| (0..k).map(move |j| {
+        let mut sum = Complex::new(T::zero(), T::zero());
+        for r in 0..q.rows() {
+            sum = sum + q[(r, i)].conj() * q[(r, j)];
+        }
+        if i == j {
+            sum - T::one()
+        }
+        else {
+            sum
+        }
+    })))
+}
+
+/// Frobenius norm of a - b
+fn distance<T: Real>(a: &Matrix<Complex<T>>, b: &Matrix<Complex<T>>) -> T {
+    norm2((0..a.rows()).flat_map(|i| (0..a.cols()).map(move |j| a[(i, j)] - b[(i, j)])))
+}
+
+/// Frobenius norm of a* - a for a square matrix a
+fn hermitian_residual<T: Real>(a: &Matrix<Complex<T>>) -> T {
+    norm2((0..a.rows()).flat_map(|i| (0..a.cols()).map(move |j| a[(j, i)].conj() - a[(i, j)])))
+}
@@ -0,0 +1,254 @@
+//! Borrowed matrices with an explicit row stride, for submatrices of a larger matrix or
+//! rows padded by a DMA engine, which the decomposition then works on without a copy.
+//!
+//! Element (i, j) of a view lives at `data[i * row_stride + j]`, the same layout as a
+//! `Matrix` with leading dimension `row_stride`. Nothing past column `cols` of a row is
+//! read or written through a view.
+//!
+//! `block` and `block_mut` take the rows and columns of a sub-view as ranges, so blocked
+//! algorithms can hand blocks of one buffer to `gemm_view` and `csvd_view`.
+
+use core::ops::{Bound, Index, IndexMut, RangeBounds};
+use super::error::CsvdError;
+use super::matrix::{Layout, Matrix};
+
+/// A read-only rows x cols window into row-major storage
+#[derive(Clone, Copy, Debug)]
+pub struct MatrixView<'a, T> {
+    data: &'a [T],
+    rows: usize,
+    cols: usize,
+    row_stride: usize,
+}
+
+/// A mutable rows x cols window into row-major storage
+#[derive(Debug)]
+pub struct MatrixViewMut<'a, T> {
+    data: &'a mut [T],
+    rows: usize,
+    cols: usize,
+    row_stride: usize,
+}
+
+/// Number of elements a rows x cols matrix with the given row stride spans, from its
+/// first element to its last one
+fn span(rows: usize, cols: usize, row_stride: usize, len: usize) -> Result<usize, CsvdError> {
+    if row_stride < cols {
+        return Err(CsvdError::InvalidLeadingDimension { ld: row_stride, cols });
+    }
+    if rows == 0 || cols == 0 {
+        return Ok(0);
+    }
+    let required = (rows - 1).checked_mul(row_stride).and_then(|x| x.checked_add(cols))
+        .ok_or(CsvdError::BufferTooSmall { operand: "data", required: usize::MAX, len })?;
+    if len < required {
+        return Err(CsvdError::BufferTooSmall { operand: "data", required, len });
+    }
+    Ok(required)
+}
+
+/// The offset and span of the rows x cols submatrix at (row, col)
+fn sub_span(shape: (usize, usize), row_stride: usize, row: usize, col: usize, rows: usize, cols: usize)
+        -> Result<(usize, usize), CsvdError> {
+    if row.checked_add(rows).is_none_or(|end| end > shape.0) {
+        return Err(CsvdError::InvalidArgument { name: "rows", value: row.saturating_add(rows), max: shape.0 });
+    }
+    if col.checked_add(cols).is_none_or(|end| end > shape.1) {
+        return Err(CsvdError::InvalidArgument { name: "cols", value: col.saturating_add(cols), max: shape.1 });
+    }
+    if rows == 0 || cols == 0 {
+        return Ok((0, 0));
+    }
+    Ok((row * row_stride + col, (rows - 1) * row_stride + cols))
+}
+
+/// The first index and the length of range within 0..len
+fn bounds<R: RangeBounds<usize>>(range: R, len: usize, name: &'static str) -> Result<(usize, usize), CsvdError> {
+    let invalid = |value| CsvdError::InvalidArgument { name, value, max: len };
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1).ok_or(invalid(usize::MAX))?,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1).ok_or(invalid(usize::MAX))?,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    if end > len || start > end {
+        return Err(invalid(end.max(start)));
+    }
+    Ok((start, end - start))
+}
+
+impl<'a, T> MatrixView<'a, T> {
+    /// Views data as a rows x cols matrix whose rows start `row_stride` elements apart
+    pub fn new(data: &'a [T], rows: usize, cols: usize, row_stride: usize) -> Result<MatrixView<'a, T>, CsvdError> {
+        let len = span(rows, cols, row_stride, data.len())?;
+        Ok(MatrixView { data: &data[..len], rows, cols, row_stride })
+    }
+
+    /// Number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Distance between the starts of two consecutive rows
+    pub fn row_stride(&self) -> usize {
+        self.row_stride
+    }
+
+    /// Row i, without padding
+    pub fn row(&self, i: usize) -> &'a [T] {
+        &self.data[i * self.row_stride..i * self.row_stride + self.cols]
+    }
+
+    /// The rows x cols block whose top left element is (row, col), with the same row stride
+    pub fn submatrix(&self, row: usize, col: usize, rows: usize, cols: usize) -> Result<MatrixView<'a, T>, CsvdError> {
+        let (start, len) = sub_span((self.rows, self.cols), self.row_stride, row, col, rows, cols)?;
+        Ok(MatrixView { data: &self.data[start..start + len], rows, cols, row_stride: self.row_stride })
+    }
+
+    /// The block of the given rows and columns, e.g. `a.block(2..6, ..3)`
+    pub fn block<R: RangeBounds<usize>, C: RangeBounds<usize>>(&self, rows: R, cols: C) -> Result<MatrixView<'a, T>, CsvdError> {
+        let (row, rows) = bounds(rows, self.rows, "rows")?;
+        let (col, cols) = bounds(cols, self.cols, "cols")?;
+        self.submatrix(row, col, rows, cols)
+    }
+}
+
+impl<T: Clone> MatrixView<'_, T> {
+    /// A tightly packed copy of the viewed elements
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let mut data = alloc::vec::Vec::with_capacity(self.rows * self.cols);
+        for i in 0..self.rows {
+            data.extend_from_slice(self.row(i));
+        }
+        Matrix::from_vec(data, self.rows, self.cols).unwrap()
+    }
+}
+
+impl<'a, T> MatrixViewMut<'a, T> {
+    /// Views data as a mutable rows x cols matrix whose rows start `row_stride` elements apart
+    pub fn new(data: &'a mut [T], rows: usize, cols: usize, row_stride: usize) -> Result<MatrixViewMut<'a, T>, CsvdError> {
+        let len = span(rows, cols, row_stride, data.len())?;
+        Ok(MatrixViewMut { data: &mut data[..len], rows, cols, row_stride })
+    }
+
+    /// Number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Distance between the starts of two consecutive rows
+    pub fn row_stride(&self) -> usize {
+        self.row_stride
+    }
+
+    /// Row i, without padding
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.row_stride..i * self.row_stride + self.cols]
+    }
+
+    /// Mutable row i, without padding
+    pub fn row_mut(&mut self, i: usize) -> &mut [T] {
+        &mut self.data[i * self.row_stride..i * self.row_stride + self.cols]
+    }
+
+    /// A read-only view of the same elements
+    pub fn as_view(&self) -> MatrixView<'_, T> {
+        MatrixView { data: self.data, rows: self.rows, cols: self.cols, row_stride: self.row_stride }
+    }
+
+    /// A shorter-lived mutable view of the same elements, to pass the view on and keep it
+    pub fn reborrow(&mut self) -> MatrixViewMut<'_, T> {
+        MatrixViewMut { data: self.data, rows: self.rows, cols: self.cols, row_stride: self.row_stride }
+    }
+
+    /// The mutable rows x cols block whose top left element is (row, col)
+    pub fn submatrix_mut(&mut self, row: usize, col: usize, rows: usize, cols: usize) -> Result<MatrixViewMut<'_, T>, CsvdError> {
+        self.reborrow().into_submatrix(row, col, rows, cols)
+    }
+
+    /// Same as `submatrix_mut`, keeping the lifetime of the view
+    pub fn into_submatrix(self, row: usize, col: usize, rows: usize, cols: usize) -> Result<MatrixViewMut<'a, T>, CsvdError> {
+        let (start, len) = sub_span((self.rows, self.cols), self.row_stride, row, col, rows, cols)?;
+        Ok(MatrixViewMut { data: &mut self.data[start..start + len], rows, cols, row_stride: self.row_stride })
+    }
+
+    /// The mutable block of the given rows and columns
+    pub fn block_mut<R: RangeBounds<usize>, C: RangeBounds<usize>>(&mut self, rows: R, cols: C) -> Result<MatrixViewMut<'_, T>, CsvdError> {
+        let (row, rows) = bounds(rows, self.rows, "rows")?;
+        let (col, cols) = bounds(cols, self.cols, "cols")?;
+        self.submatrix_mut(row, col, rows, cols)
+    }
+
+    /// The storage from the first to the last element of the view, including the elements
+    /// between the rows, which the view itself never touches
+    pub(crate) fn storage_mut(&mut self) -> &mut [T] {
+        self.data
+    }
+}
+
+impl<T> Matrix<T> {
+    /// The whole matrix as a view. Panics for a column-major matrix, views are row-major.
+    pub fn view(&self) -> MatrixView<'_, T> {
+        assert!(self.layout() == Layout::RowMajor, "view of a column-major matrix");
+        MatrixView::new(self.as_slice(), self.rows(), self.cols(), self.ld()).unwrap()
+    }
+
+    /// The whole matrix as a mutable view. Panics for a column-major matrix.
+    pub fn view_mut(&mut self) -> MatrixViewMut<'_, T> {
+        assert!(self.layout() == Layout::RowMajor, "view of a column-major matrix");
+        let (rows, cols, ld) = (self.rows(), self.cols(), self.ld());
+        MatrixViewMut::new(self.as_mut_slice(), rows, cols, ld).unwrap()
+    }
+
+    /// The block of the given rows and columns as a view, e.g. `a.block(1..5, 2..)`.
+    /// Panics for a column-major matrix.
+    pub fn block<R: RangeBounds<usize>, C: RangeBounds<usize>>(&self, rows: R, cols: C) -> Result<MatrixView<'_, T>, CsvdError> {
+        self.view().block(rows, cols)
+    }
+
+    /// The block of the given rows and columns as a mutable view. Panics for a column-major matrix.
+    pub fn block_mut<R: RangeBounds<usize>, C: RangeBounds<usize>>(&mut self, rows: R, cols: C) -> Result<MatrixViewMut<'_, T>, CsvdError> {
+        let (row, rows) = bounds(rows, self.rows(), "rows")?;
+        let (col, cols) = bounds(cols, self.cols(), "cols")?;
+        self.view_mut().into_submatrix(row, col, rows, cols)
+    }
+}
+
+impl<T> Index<(usize, usize)> for MatrixView<'_, T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        assert!(i < self.rows && j < self.cols, "index ({}, {}) out of bounds for a {} x {} matrix", i, j, self.rows, self.cols);
+        &self.data[i * self.row_stride + j]
+    }
+}
+
+impl<T> Index<(usize, usize)> for MatrixViewMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        assert!(i < self.rows && j < self.cols, "index ({}, {}) out of bounds for a {} x {} matrix", i, j, self.rows, self.cols);
+        &self.data[i * self.row_stride + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for MatrixViewMut<'_, T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        assert!(i < self.rows && j < self.cols, "index ({}, {}) out of bounds for a {} x {} matrix", i, j, self.rows, self.cols);
+        &mut self.data[i * self.row_stride + j]
+    }
+}
@@ -0,0 +1,245 @@
+use core::ops::Index;
+use num_complex::Complex;
+use super::csvd::{csvd_tall, no_progress, normalize_phases, swap_uv, SvdOptions};
+use super::aligned::{Alloc, AlignedVec, Global};
+use super::error::CsvdError;
+use super::matrix::Matrix;
+use super::pool::{BufferPool, PooledMatrix};
+use super::scalar::Real;
+use super::view::{MatrixView, MatrixViewMut};
+use super::PinvOptions;
+
+/// Alignment of the workspace buffers in bytes, one cache line
+const ALIGNMENT: usize = 64;
+
+/// Read access to a matrix in any storage, so that `csvd_with_workspace` decomposes a
+/// `Matrix`, a view or a `PooledMatrix` in the memory of an `Alloc` alike
+pub trait MatrixRef<T>: Index<(usize, usize), Output = T> {
+    fn rows(&self) -> usize;
+    fn cols(&self) -> usize;
+}
+
+impl<T> MatrixRef<T> for Matrix<T> {
+    fn rows(&self) -> usize {
+        Matrix::rows(self)
+    }
+
+    fn cols(&self) -> usize {
+        Matrix::cols(self)
+    }
+}
+
+impl<T> MatrixRef<T> for MatrixView<'_, T> {
+    fn rows(&self) -> usize {
+        MatrixView::rows(self)
+    }
+
+    fn cols(&self) -> usize {
+        MatrixView::cols(self)
+    }
+}
+
+impl<T> MatrixRef<T> for MatrixViewMut<'_, T> {
+    fn rows(&self) -> usize {
+        MatrixViewMut::rows(self)
+    }
+
+    fn cols(&self) -> usize {
+        MatrixViewMut::cols(self)
+    }
+}
+
+impl<T, A: Alloc> MatrixRef<T> for PooledMatrix<T, A> {
+    fn rows(&self) -> usize {
+        PooledMatrix::rows(self)
+    }
+
+    fn cols(&self) -> usize {
+        PooledMatrix::cols(self)
+    }
+}
+
+/// Pre-allocated buffers for decomposing matrices of up to m_max x n_max elements.
+///
+/// Creating the workspace is the only allocation, `csvd_with_workspace` reuses its
+/// buffers for every decomposition, so e.g. a new channel matrix can be decomposed
+/// every slot without touching the heap. All buffers come from the `Alloc` A, see
+/// `try_new_in`, and are aligned to a cache line.
+pub struct SvdWorkspace<T, A: Alloc = Global> {
+    m_max: usize,
+    n_max: usize,
+    /// options used by `csvd_with_workspace`
+    pub options: SvdOptions,
+    /// copy of the input matrix, or of its conjugate transpose, which csvd overwrites
+    a: AlignedVec<Complex<T>, A>,
+    /// the work arrays B, C and T of csvd
+    work: AlignedVec<T, A>,
+    s: AlignedVec<T, A>,
+    /// m x m, row-major
+    u: AlignedVec<Complex<T>, A>,
+    /// n x n, row-major
+    v: AlignedVec<Complex<T>, A>,
+    /// shape of the last decomposed matrix
+    m: usize,
+    n: usize,
+    /// min(m, n) of the last decomposed matrix, 0 before the first one
+    min: usize,
+}
+
+impl<T: Real> SvdWorkspace<T> {
+    /// Allocates all of the buffers needed to decompose matrices with at most
+    /// m_max rows and n_max columns. Panics if the heap is exhausted, see `try_new`.
+    pub fn new(m_max: usize, n_max: usize) -> SvdWorkspace<T> {
+        match SvdWorkspace::try_new(m_max, n_max) {
+            Ok(ws) => ws,
+            Err(e) => panic!("cannot allocate a workspace for {} x {} matrices: {}", m_max, n_max, e),
+        }
+    }
+
+    /// Same as `new`, but returns `AllocFailed` instead of panicking when the heap is exhausted
+    pub fn try_new(m_max: usize, n_max: usize) -> Result<SvdWorkspace<T>, CsvdError> {
+        SvdWorkspace::try_new_in(m_max, n_max, Global)
+    }
+}
+
+impl<T: Real, A: Alloc> SvdWorkspace<T, A> {
+    /// Same as `try_new`, with all buffers from alloc, e.g. DMA-capable or per-core memory
+    pub fn try_new_in(m_max: usize, n_max: usize, alloc: A) -> Result<SvdWorkspace<T, A>, CsvdError>
+            where A: Clone {
+        let min_max = m_max.min(n_max);
+        let zero = Complex::new(T::zero(), T::zero());
+        let len = |rows: usize, cols: usize| rows.checked_mul(cols).ok_or(CsvdError::AllocFailed);
+
+        Ok(SvdWorkspace {
+            m_max,
+            n_max,
+            options: SvdOptions::default(),
+            a: AlignedVec::from_elem_in(zero, len(m_max, n_max)?, ALIGNMENT, alloc.clone())?,
+            work: AlignedVec::from_elem_in(T::zero(), len(min_max, 3)?, ALIGNMENT, alloc.clone())?,
+            s: AlignedVec::from_elem_in(T::zero(), min_max, ALIGNMENT, alloc.clone())?,
+            u: AlignedVec::from_elem_in(zero, len(m_max, m_max)?, ALIGNMENT, alloc.clone())?,
+            v: AlignedVec::from_elem_in(zero, len(n_max, n_max)?, ALIGNMENT, alloc)?,
+            m: m_max,
+            n: n_max,
+            min: 0,
+        })
+    }
+
+    /// The largest number of rows this workspace can handle
+    pub fn m_max(&self) -> usize {
+        self.m_max
+    }
+
+    /// The largest number of columns this workspace can handle
+    pub fn n_max(&self) -> usize {
+        self.n_max
+    }
+
+    /// Singular values of the last decomposed matrix, in descending order
+    pub fn s(&self) -> &[T] {
+        &self.s[..self.min]
+    }
+
+    /// m x m matrix of left singular vectors of the last decomposed matrix
+    pub fn u(&self) -> MatrixView<'_, Complex<T>> {
+        MatrixView::new(&self.u, self.m, self.m, self.m).unwrap()
+    }
+
+    /// n x n matrix of right singular vectors of the last decomposed matrix
+    pub fn v(&self) -> MatrixView<'_, Complex<T>> {
+        MatrixView::new(&self.v, self.n, self.n, self.n).unwrap()
+    }
+
+    /// Writes the n x m pseudo-inverse INV = V x S+ x U* of the last decomposed m x n matrix
+    /// into out, row by row and without any allocation.
+    /// options decide which singular values are treated as zero.
+    pub fn pinv_into(&self, out: &mut [Complex<T>], options: &PinvOptions<T>) -> Result<(), CsvdError> {
+        let (m, n) = (self.m, self.n);
+        let (u, v) = (self.u(), self.v());
+        let min = self.min;
+
+        if out.len() < n * m {
+            return Err(CsvdError::BufferTooSmall { operand: "out", required: n * m, len: out.len() });
+        }
+
+        let cutoff = if min > 0 { options.cutoff(self.s[0], m, n) } else { T::zero() };
+        for i in 0..n {
+            for j in 0..m {
+                let mut sum = Complex::new(T::zero(), T::zero());
+                for k in 0..min {
+                    if self.s[k] > cutoff {
+                        sum = sum + v[(i, k)] * u[(j, k)].conj() / self.s[k];
+                    }
+                }
+                out[i * m + j] = sum;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Same as `pinv_into`, with the pseudo-inverse written to a matrix taken from pool,
+    /// which has to hold n x m matrices. After warm-up of the pool, nothing is allocated.
+    pub fn pinv_pooled<P: Alloc + Clone>(&self, pool: &mut BufferPool<Complex<T>, P>, options: &PinvOptions<T>)
+            -> Result<PooledMatrix<Complex<T>, P>, CsvdError> {
+        let shape = (self.n, self.m);
+        if pool.shape() != shape {
+            return Err(CsvdError::DimensionMismatch { operand: "pool", expected: shape, found: pool.shape() });
+        }
+
+        let mut inv = pool.take()?;
+        if let Err(e) = self.pinv_into(inv.as_mut_slice(), options) {
+            pool.give(inv)?;
+            return Err(e);
+        }
+        Ok(inv)
+    }
+}
+
+/// Computes the full singular value decomposition of a into the buffers of ws,
+/// without any heap allocation. a is left untouched, can have any shape up to the
+/// m_max x n_max the workspace was created for and live in any storage, see `MatrixRef`.
+/// The factors are read back with `ws.s()`, `ws.u()` and `ws.v()`.
+pub fn csvd_with_workspace<T: Real, A: Alloc, M: MatrixRef<Complex<T>> + ?Sized>(a: &M, ws: &mut SvdWorkspace<T, A>)
+        -> Result<(), CsvdError> {
+    let m = a.rows();
+    let n = a.cols();
+
+    if m < 1 || n < 1 {
+        return Err(CsvdError::EmptyMatrix);
+    }
+    if m > ws.m_max || n > ws.n_max {
+        return Err(CsvdError::WorkspaceTooSmall { required: (m, n), capacity: (ws.m_max, ws.n_max) });
+    }
+
+    ws.min = 0;
+    ws.m = m;
+    ws.n = n;
+    let (u, v) = (&mut ws.u[..m * m], &mut ws.v[..n * n]);
+
+    if m < n {
+        // decompose the conjugate transpose and swap the roles of U and V, as csvd does
+        for i in 0..m {
+            for j in 0..n {
+                ws.a[j * m + i] = a[(i, j)].conj();
+            }
+        }
+
+        csvd_tall(&mut ws.a[..n * m], m, n, m, 0, n, m, &mut ws.s, v, n, u, m,
+            &mut ws.work, &ws.options, &mut no_progress).map_err(swap_uv)?;
+    }
+    else {
+        for i in 0..m {
+            for j in 0..n {
+                ws.a[i * n + j] = a[(i, j)];
+            }
+        }
+
+        csvd_tall(&mut ws.a[..m * n], n, m, n, 0, m, n, &mut ws.s, u, m, v, n,
+            &mut ws.work, &ws.options, &mut no_progress)?;
+    }
+
+    normalize_phases(&mut ws.u[..m * m], m, m, m, &mut ws.v[..n * n], n, n, n);
+    ws.min = m.min(n);
+    Ok(())
+}